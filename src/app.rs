@@ -1,34 +1,79 @@
 use {
     crate::{
+        audio::{metronome::Metronome, tone::TonePlayer},
         message::states::{
-            AppMessage, AppState, GameMessage, MainMenuMessage, SelectionMessage, SettingsMessage,
+            AppMessage, AppState, CalibrationMessage, CaptureMessage, EarTrainingMessage,
+            EditorMessage, FlashcardMessage, GameMessage, LatencyMessage, LessonMessage,
+            MainMenuMessage, ReplayMessage, ResultsMessage, RhythmMessage, ScaleMessage,
+            SelectionMessage, SettingsMessage, SightReadingMessage, TrophyMessage,
+        },
+        midi::{
+            input::{MidiInput, input_ports},
+            output::{MidiOutput, output_events, output_ports},
+            smf::{loaded_metadata, partitures_to_smf, save_smf, take_to_smf},
         },
         models::{
+            calibration::{BEAT_SECONDS, CUE_BEATS, CalibrationWizard},
+            capture::{CAPTURE_PIECE, CaptureConfig, CaptureSession},
+            curriculum::{Curriculum, Lesson, LessonProgress},
+            ear_training::{EarAnswer, EarCategory, EarTrainingSession, EarTrainingStats},
+            editor::{NEW_PIECE, ScoreEditor},
+            flashcards::{Clef, FlashcardSession, FlashcardStats, Guess},
+            input::{InputAction, TimedInput},
+            latency::{TAP_BEAT_SECONDS, TAP_COUNT_IN, TapTest},
+            markings::Markings,
             note::Note,
             partiture::{Hand, Partiture, PieceMetadata},
+            playback::PlaybackClock,
+            progress::{ProgressHistory, RunRecord, load_take, save_take, unix_now},
+            results::{BarAccuracy, SongResults},
+            rewards::{PlayerProgress, PracticeEvent, Reward, RewardRules, RunResult},
+            rhythm::{RhythmConfig, RhythmLine, RhythmRun, RhythmSource},
+            score::{Piece, ScoreFile, ScoreMetadata},
+            score_error::ScoreError,
             settings::CustomSettings,
+            take::{InputSource, JudgedNote, Take, TakeRecorder},
         },
-        utils::helper_json::{
-            get_metadata_and_section, get_price_metdata_compas, load_notes_from_file,
-            load_partiture, sanitize_data,
+        utils::{
+            difficulty::{DifficultyRating, rate_library},
+            frecuency::{MICROPHONE_VELOCITY, MicrophoneInput, MicrophoneListener},
+            helper_json::{build_partitures, load_library, load_piece_hands, store_piece},
+            keyboard_input::{capture_event, editor_event, tap_event, timed_event},
+            performance::{evenness, expected_notes, judge_take, summarize},
+            scales::{self, SCALE_EXERCISE, ScaleConfig},
+            sight_reading::{GENERATED_PIECE, SeededRng, SightReadingConfig, generate_piece},
         },
         views::{
+            calibration::calibration_view,
+            capture::capture_view,
+            ear_training::ear_training_view,
+            editor::editor_view,
+            flashcards::flashcards_view,
             game::game_view,
+            latency::latency_view,
+            lessons::{lesson_map_view, lesson_view},
             menu::main_menu_view,
+            replay::replay_view,
+            results::results_view,
+            rhythm::rhythm_view,
+            scales::scales_view,
             selection::select_partiture_view,
-            settings::{paused_view, settings_view},
+            settings::{NO_MIDI_DEVICE, paused_view, settings_view},
+            sight_reading::sight_reading_view,
+            trophies::trophies_view,
         },
     },
     iced::{
         Element, Event, Length, Subscription, Theme,
-        event::listen,
-        keyboard::{self, Key},
+        event::{listen, listen_with},
+        keyboard::{self, Key, key::Named},
         time::every,
         widget::{Container, Text},
     },
-    serde_json::Value,
     std::{
+        collections::BTreeMap,
         error, fs,
+        path::Path,
         process::exit,
         sync::{
             Arc,
@@ -46,32 +91,118 @@ macro_rules! asset_path {
     };
 }
 
+// Tiempo que se ve el aviso de una recompensa sobre la partitura
+const REWARD_NOTICE: Duration = Duration::from_secs(4);
+
+// Máximo que cuenta como práctica entre dos ticks, por si la ventana se queda parada
+const MAX_PRACTICE_GAP: f32 = 1.0;
+
 /// Estructura de la aplicación
 pub struct MyApp {
     state: AppState,                                    // Estado de la app
-    start_time: Option<Instant>,                        // Momento de inicio de la partitura
-    actual_time: Option<Instant>,                       // Tiempo actual de la partitura
-    pause_started: Option<Instant>,                     // Momento en que se pausó
-    is_paused: Arc<AtomicBool>,                         // Tiempo pausado
-    settings: CustomSettings,                           // Ajustes
-    finished: Arc<AtomicBool>,                          // Fin de la partitura
-    partiture_name: Option<&'static str>,               // Partitura selecionada
+    clock: PlaybackClock, // Reloj de la partitura, compartido con las manos
+    is_paused: Arc<AtomicBool>, // Tiempo pausado
+    settings: CustomSettings, // Ajustes
+    partiture_name: Option<String>, // Partitura selecionada
     partiture_selected: Option<(Partiture, Partiture)>, // Partitura derecha, izquierda
+    recorder: Option<TakeRecorder>, // Grabación de la partida en curso
+    last_take: Option<Take>, // Última grabación terminada
+    replay_playing: bool, // Si la repetición está avanzando
+    count_in_target: Option<f32>, // Segundo de la partitura donde acaba la cuenta previa
+    scrubbing: Option<f32>, // Posición mientras se arrastra la barra de progreso
+    metronome: Metronome, // Clicks del metrónomo y de la cuenta previa
+    microphone: Option<MicrophoneInput>, // Micrófono abierto mientras se calibra
+    calibration: Option<CalibrationWizard>, // Asistente de calibración en curso
+    listener: Option<MicrophoneListener>, // Piano acústico durante la partida o la prueba de latencia
+    tap_test: Option<TapTest>,            // Prueba de latencia en curso
+    curriculum: Curriculum,               // Cursos de `assets/courses`
+    lesson_progress: LessonProgress,      // Lecciones aprobadas y mejores intentos
+    lesson: Option<Lesson>,               // Lección abierta o en juego
+    lesson_result: Option<String>,        // Resultado del último intento de la lección
+    sight_reading: SightReadingConfig,    // Parámetros del ejercicio de lectura generado
+    scales: ScaleConfig,                  // Parámetros del ejercicio de escalas
+    exercise_report: Option<String>,      // Precisión y regularidad del último ejercicio de escalas
+    flashcards: Option<FlashcardSession>, // Tarjetas de lectura de notas en curso
+    ear_training: Option<EarTrainingSession>, // Entrenamiento auditivo en curso
+    tone_player: Option<TonePlayer>,      // Sonido de las preguntas del entrenamiento auditivo
+    rhythm: RhythmConfig,                 // Parámetros del ejercicio de ritmo
+    rhythm_run: Option<RhythmRun>,        // Ejercicio de ritmo en curso o terminado
+    reward_rules: RewardRules,            // XP, niveles y logros de `assets/rewards.json`
+    player: PlayerProgress,               // XP, racha y logros del jugador
+    practice_mark: Option<Instant>,       // Último tick contado como tiempo de práctica
+    rewards: Vec<(Reward, Instant)>,      // Recompensas de la partida en curso y cuándo llegaron
+    piece_info: Option<ScoreMetadata>,    // Título, compositor y demás de la pieza en juego
+    run_started: Option<Instant>,         // Cuándo empezó la partida, para el tiempo con pausas
+    results: Option<SongResults>,         // Resultados de la última pieza terminada
+    editor: Option<ScoreEditor>,          // Pieza abierta en el editor de partituras
+    capture: CaptureConfig,               // Parámetros de la grabación de partituras
+    capture_session: Option<CaptureSession>, // Grabación en curso o terminada
+    midi_output: Option<MidiOutput>,      // Piano digital o sintetizador externo
+    midi_ports: Vec<String>,              // Puertos de salida MIDI encontrados
+    midi_input: Option<MidiInput>,        // Teclado MIDI
+    midi_input_ports: Vec<String>,        // Puertos de entrada MIDI encontrados
+    songs: Vec<(String, Option<DifficultyRating>)>, // Piezas de la biblioteca con su dificultad
 }
 
 /// Implementar Default para MyApp
 impl Default for MyApp {
     fn default() -> Self {
+        let settings: CustomSettings = MyApp::load_settings();
+        let metronome: Metronome = Metronome::new(&settings.metronome);
+        let midi_output: Option<MidiOutput> = settings
+            .midi_output
+            .device
+            .as_deref()
+            .and_then(MyApp::open_midi_output);
+        let midi_input: Option<MidiInput> = settings
+            .midi_input
+            .as_deref()
+            .and_then(MyApp::open_midi_input);
+
         Self {
             state: AppState::MainMenu,
-            start_time: None,
-            actual_time: None,
-            pause_started: None,
+            clock: PlaybackClock::new(),
             is_paused: Arc::new(AtomicBool::new(false)),
-            settings: MyApp::load_settings(),
-            finished: Arc::new(AtomicBool::new(false)),
+            settings,
             partiture_name: None,
             partiture_selected: None,
+            recorder: None,
+            last_take: None,
+            replay_playing: false,
+            count_in_target: None,
+            scrubbing: None,
+            metronome,
+            microphone: None,
+            calibration: None,
+            listener: None,
+            tap_test: None,
+            curriculum: Curriculum::default(),
+            lesson_progress: LessonProgress::load(),
+            lesson: None,
+            lesson_result: None,
+            sight_reading: SightReadingConfig::default(),
+            scales: ScaleConfig::default(),
+            exercise_report: None,
+            flashcards: None,
+            ear_training: None,
+            tone_player: None,
+            rhythm: RhythmConfig::default(),
+            rhythm_run: None,
+            reward_rules: RewardRules::load(),
+            player: PlayerProgress::load(),
+            practice_mark: None,
+            rewards: Vec::new(),
+            piece_info: None,
+            run_started: None,
+            results: None,
+            editor: None,
+            capture: CaptureConfig::default(),
+            capture_session: None,
+            midi_output,
+            midi_ports: Vec::new(),
+            midi_input,
+            midi_input_ports: Vec::new(),
+            songs: MyApp::load_songs(),
         }
     }
 }
@@ -83,37 +214,65 @@ impl MyApp {
         match message {
             // Eventos de teclado en el juego
             AppMessage::Event(msg) => match msg {
-                Event::Keyboard(keyboard::Event::KeyPressed { key, .. }) => match key {
-                    Key::Named(keyboard::key::Named::Escape)
-                    | Key::Named(keyboard::key::Named::Space) => {
-                        // Si no estaba pausado lo pausamos, y si estaba pausado lo despausamos
-                        self.is_paused.fetch_not(Ordering::SeqCst);
-
-                        if self.is_paused.load(Ordering::SeqCst) {
-                            // Pausando: guardar el momento actual
-                            self.pause_started = Some(Instant::now());
-                            self.state = AppState::Paused;
-                        } else {
-                            self.resume_game()
-                        }
+                Event::Keyboard(keyboard::Event::KeyPressed {
+                    key: Key::Named(Named::Escape | Named::Space),
+                    ..
+                }) => {
+                    // Si no estaba pausado lo pausamos, y si estaba pausado lo despausamos
+                    self.is_paused.fetch_not(Ordering::SeqCst);
+
+                    if self.is_paused.load(Ordering::SeqCst) {
+                        // Pausando: el reloj se queda en la posición actual
+                        self.clock.pause();
+                        self.state = AppState::Paused;
+                        self.metronome.stop();
+                        self.silence_midi();
+                        self.practice_mark = None;
+                    } else {
+                        self.resume_game()
+                    }
+                }
+                // Flechas: un compás atrás o adelante, con Shift una sección. Inicio: volver a empezar
+                Event::Keyboard(keyboard::Event::KeyPressed {
+                    key: Key::Named(named @ (Named::ArrowLeft | Named::ArrowRight | Named::Home)),
+                    modifiers,
+                    ..
+                }) if self.state == AppState::Game => {
+                    let step: i32 = if named == Named::ArrowLeft { -1 } else { 1 };
+                    match named {
+                        Named::Home => self.seek_to(0.0),
+                        _ if modifiers.shift() => self.jump_section(step),
+                        _ => self.jump_bar(step),
                     }
-                    _ => {}
-                },
+                }
                 _ => {}
             },
 
+            // Notas y pulsaciones con el momento en que se capturaron
+            AppMessage::Input(input) => self.handle_input(input),
+
             // Manejar mensajes del menu
             AppMessage::MainMenu(msg) => match msg {
                 // Seleccionar partitura
                 MainMenuMessage::SelectPartiture => {
                     self.state = AppState::SelectionPartiture;
                 }
+                // Abrir el mapa de lecciones
+                MainMenuMessage::OpenLessons => self.open_lesson_map(),
+                // Abrir las tarjetas de lectura de notas
+                MainMenuMessage::OpenFlashcards => self.open_flashcards(Clef::Treble, 1),
+                // Abrir el entrenamiento auditivo
+                MainMenuMessage::OpenEarTraining => self.open_ear_training(EarCategory::Interval),
+                // Ver el nivel, la racha y los logros
+                MainMenuMessage::OpenTrophies => self.state = AppState::Trophies,
                 // Salir de la aplicación
                 MainMenuMessage::Exit => {
                     exit(0);
                 }
                 // Abrir configuración
                 MainMenuMessage::OpenSettings => {
+                    self.midi_ports = output_ports();
+                    self.midi_input_ports = input_ports();
                     self.state = AppState::Settings;
                 }
             },
@@ -121,33 +280,103 @@ impl MyApp {
             // Manejar mensajes del juego
             AppMessage::Game(msg) => match msg {
                 GameMessage::Tick(instant) => {
-                    // Tiempo transcurrido desde que inició la applicacion
-                    let elapsed: f32 = self
-                        .start_time
-                        .map(|start| instant.duration_since(start).as_secs_f32())
-                        .unwrap_or(0.0);
+                    // Mientras se arrastra la barra de progreso manda la posición elegida
+                    if self.scrubbing.is_some() {
+                        return;
+                    }
+                    self.count_practice(instant);
+
+                    // El tick solo redibuja y comprueba el final: la posición la lleva el reloj
+                    let elapsed: f32 = self.clock.position();
 
                     // Esperamos a que la partitura tenga un valor
-                    if let Some((ref mut left_partiture, ref mut right_partiture)) =
-                        self.partiture_selected
-                    {
-                        // Si el tiempo transcurrido es mayor que la duración máxima + el timer del inicio y del final, finalizar el juego
-                        if elapsed > (right_partiture.time + (self.settings.timer * 2.0)) {
-                            self.finished.store(true, Ordering::SeqCst);
-                            self.state = AppState::Paused;
+                    if let Some((_, right_partiture)) = &self.partiture_selected {
+                        // Pasada la duración más la cuenta del inicio y del final, finalizar el juego
+                        let song_ended: bool = right_partiture.has_ended();
+
+                        // Notas del teclado MIDI y del micrófono desde el último tick
+                        self.poll_midi_input();
+                        self.poll_microphone(|pitches, at| {
+                            pitches
+                                .iter()
+                                .map(|&pitch| {
+                                    TimedInput::new(
+                                        at,
+                                        InputSource::Microphone,
+                                        InputAction::NoteOn {
+                                            pitch,
+                                            velocity: MICROPHONE_VELOCITY,
+                                        },
+                                    )
+                                })
+                                .collect()
+                        });
+
+                        // La cuenta previa termina al llegar al compás elegido
+                        if self
+                            .count_in_target
+                            .is_some_and(|target| elapsed - self.timer() >= target)
+                        {
+                            self.count_in_target = None;
                         }
 
-                        // Actualizamos el tiempo elapsed
-                        right_partiture.elapsed = elapsed;
-                        left_partiture.elapsed = elapsed;
+                        if song_ended {
+                            self.clock.pause();
+                            self.metronome.stop();
+                            if let Some(output) = &self.midi_output {
+                                output.stop();
+                            }
+                            self.practice_mark = None;
+                            self.finish_run(true);
+                            self.state = AppState::Results;
+                        }
                     }
                 }
                 GameMessage::RestartGame => {
-                    if let Some(name) = self.partiture_name {
-                        self.start_game_with_partiture(name)
+                    self.finish_run(false);
+                    if let Some(name) = self.partiture_name.clone() {
+                        self.start_game_with_partiture(&name)
                     }
                 }
                 GameMessage::ResumeGame => self.resume_game(),
+                GameMessage::ExportMidi => self.export_midi(),
+                GameMessage::Seek(position) => self.seek_to(position),
+                GameMessage::JumpBar(step) => self.jump_bar(step),
+                GameMessage::JumpSection(step) => self.jump_section(step),
+                GameMessage::Scrub(position) => {
+                    self.scrubbing = Some(position);
+                    self.count_in_target = None;
+                    self.metronome.stop();
+                    self.silence_midi();
+                    self.clock.pause();
+                    self.clock.seek(position + self.timer());
+                }
+                GameMessage::ScrubRelease => {
+                    if let Some(position) = self.scrubbing.take() {
+                        self.clock.play();
+                        self.seek_to(position);
+                    }
+                }
+            },
+
+            // Manejar mensajes de la pantalla de resultados
+            AppMessage::Results(msg) => match msg {
+                ResultsMessage::Retry => {
+                    if let Some(name) = self.partiture_name.clone() {
+                        self.start_game_with_partiture(&name)
+                    }
+                }
+                ResultsMessage::ChoosePiece => {
+                    self.listener = None;
+                    self.lesson = None;
+                    self.state = AppState::SelectionPartiture;
+                }
+                ResultsMessage::FromSection(position) => {
+                    if let Some(name) = self.partiture_name.clone() {
+                        self.start_game_with_partiture(&name);
+                        self.seek_to(position);
+                    }
+                }
             },
 
             // Manejar mensajes de configuración
@@ -162,20 +391,549 @@ impl MyApp {
                     };
                     self.save_settings().unwrap_or_else(|e| {
                         log::error!("{}", e);
-                        return;
+                    });
+                }
+                SettingsMessage::ToggleMetronome(enabled) => {
+                    self.settings.metronome.enabled = enabled;
+                    self.apply_metronome_settings();
+                }
+                SettingsMessage::MetronomeVolume(volume) => {
+                    self.settings.metronome.volume = volume;
+                    self.apply_metronome_settings();
+                }
+                SettingsMessage::CountInBars(bars) => {
+                    self.settings.metronome.count_in_bars = bars;
+                    self.apply_metronome_settings();
+                }
+                SettingsMessage::ToggleVisualBeat(visible) => {
+                    self.settings.metronome.visual_beat = visible;
+                    self.apply_metronome_settings();
+                }
+                SettingsMessage::ToggleMicrophone(enabled) => {
+                    self.settings.microphone.use_in_game = enabled;
+                    self.save_settings().unwrap_or_else(|e| {
+                        log::error!("{}", e);
+                    });
+                }
+                SettingsMessage::MidiDevice(device) => {
+                    self.settings.midi_output.device = (device != NO_MIDI_DEVICE).then_some(device);
+                    self.midi_output = None;
+                    self.midi_output = self
+                        .settings
+                        .midi_output
+                        .device
+                        .as_deref()
+                        .and_then(MyApp::open_midi_output);
+                    self.save_settings().unwrap_or_else(|e| {
+                        log::error!("{}", e);
+                    });
+                }
+                SettingsMessage::MidiInputDevice(device) => {
+                    self.settings.midi_input = (device != NO_MIDI_DEVICE).then_some(device);
+                    self.midi_input = None;
+                    self.midi_input = self
+                        .settings
+                        .midi_input
+                        .as_deref()
+                        .and_then(MyApp::open_midi_input);
+                    self.save_settings().unwrap_or_else(|e| {
+                        log::error!("{}", e);
+                    });
+                }
+                SettingsMessage::RefreshMidiPorts => {
+                    self.midi_ports = output_ports();
+                    self.midi_input_ports = input_ports();
+                }
+                SettingsMessage::MidiSound(sound) => {
+                    self.settings.midi_output.sound = sound;
+                    self.save_settings().unwrap_or_else(|e| {
+                        log::error!("{}", e);
+                    });
+                }
+                SettingsMessage::MidiHands(hands) => {
+                    self.settings.midi_output.hands = hands;
+                    self.save_settings().unwrap_or_else(|e| {
+                        log::error!("{}", e);
+                    });
+                }
+                SettingsMessage::MidiChannel(channel) => {
+                    self.settings.midi_output.channel = channel;
+                    self.save_settings().unwrap_or_else(|e| {
+                        log::error!("{}", e);
+                    });
+                }
+                SettingsMessage::MidiGuide(guide) => {
+                    self.settings.midi_output.guide = guide;
+                    self.save_settings().unwrap_or_else(|e| {
+                        log::error!("{}", e);
+                    });
+                }
+                SettingsMessage::GuideChannel(hand, channel) => {
+                    match hand {
+                        Hand::Right => self.settings.midi_output.guide_right = channel,
+                        Hand::Left => self.settings.midi_output.guide_left = channel,
+                    }
+                    self.save_settings().unwrap_or_else(|e| {
+                        log::error!("{}", e);
                     });
                 }
                 SettingsMessage::BackToMenu => {
+                    self.metronome.stop();
+                    if let Some(output) = &self.midi_output {
+                        output.stop();
+                    }
+                    self.listener = None;
+                    self.finish_run(false);
                     self.state = AppState::MainMenu;
                 }
             },
 
+            // Manejar mensajes del modo repetición
+            AppMessage::Replay(msg) => match msg {
+                ReplayMessage::Open => self.open_replay(),
+                ReplayMessage::Tick(_) => {
+                    // Parar al llegar al final
+                    if self.replay_at_end() {
+                        let position: f32 = self.replay_position();
+                        self.seek_replay(position);
+                    }
+                }
+                ReplayMessage::TogglePlay => {
+                    // Si ya se llegó al final, volver a empezar
+                    if !self.replay_playing && self.replay_at_end() {
+                        self.seek_replay(0.0);
+                    }
+                    self.replay_playing = !self.replay_playing;
+                    if self.replay_playing {
+                        self.clock.play();
+                    } else {
+                        self.clock.pause();
+                    }
+                }
+                ReplayMessage::Seek(position) => self.seek_replay(position),
+                ReplayMessage::Export => self.export_take(),
+                ReplayMessage::Back => {
+                    self.replay_playing = false;
+                    self.clock.pause();
+                    if let Some((left, right)) = self.partiture_selected.as_mut() {
+                        left.replay.clear();
+                        right.replay.clear();
+                    }
+                    self.state = AppState::Results;
+                }
+            },
+
+            // Manejar mensajes del asistente de calibración
+            AppMessage::Calibration(msg) => match msg {
+                CalibrationMessage::Open => self.open_calibration(),
+                CalibrationMessage::Next => {
+                    if let Some(wizard) = self.calibration.as_mut()
+                        && wizard.start(Instant::now()).is_some()
+                    {
+                        // Clicks de la cuenta, con el último en el momento de tocar
+                        self.metronome.cue(CUE_BEATS, CUE_BEATS + 1, BEAT_SECONDS);
+                    }
+                }
+                CalibrationMessage::Tick(instant) => {
+                    if let (Some(wizard), Some(microphone)) =
+                        (self.calibration.as_mut(), self.microphone.as_ref())
+                    {
+                        for block in microphone.drain() {
+                            wizard.push(&block);
+                        }
+                        let was_waiting: bool = wizard.waiting();
+                        wizard.tick(instant);
+                        // Al terminar de escuchar la nota, parar los clicks
+                        if wizard.waiting() && !was_waiting {
+                            self.metronome.stop();
+                        }
+                    }
+                }
+                CalibrationMessage::Save => {
+                    if let Some(result) = self.calibration.as_ref().and_then(|w| w.result()) {
+                        self.settings.microphone = result;
+                        self.save_settings().unwrap_or_else(|e| {
+                            log::error!("{}", e);
+                        });
+                    }
+                    self.close_calibration();
+                }
+                CalibrationMessage::Cancel => self.close_calibration(),
+            },
+
+            // Manejar mensajes de la prueba de latencia
+            AppMessage::Latency(msg) => match msg {
+                LatencyMessage::Open => {
+                    self.tap_test = None;
+                    self.state = AppState::Latency;
+                }
+                LatencyMessage::Start(source) => self.start_tap_test(source),
+                LatencyMessage::Tick(instant) => {
+                    if let Some(test) = self.tap_test.as_mut() {
+                        test.tick(instant);
+                    }
+                    self.poll_midi_input();
+                    self.poll_microphone(|_, at| {
+                        vec![TimedInput::new(
+                            at,
+                            InputSource::Microphone,
+                            InputAction::Tap,
+                        )]
+                    });
+                    if self.tap_test.as_ref().is_some_and(TapTest::is_finished) {
+                        self.metronome.stop();
+                    }
+                }
+                LatencyMessage::Save => {
+                    if let Some(result) = self.tap_test.as_ref().and_then(TapTest::result) {
+                        self.settings
+                            .set_input_latency(result.source, result.offset);
+                        self.save_settings().unwrap_or_else(|e| {
+                            log::error!("{}", e);
+                        });
+                    }
+                }
+                LatencyMessage::Adjust(source, latency) => {
+                    self.settings.set_input_latency(source, latency);
+                    self.save_settings().unwrap_or_else(|e| {
+                        log::error!("{}", e);
+                    });
+                }
+                LatencyMessage::Back => {
+                    self.metronome.stop();
+                    self.tap_test = None;
+                    self.listener = None;
+                    self.state = AppState::Settings;
+                }
+            },
+
             // Manejar mensajes de selección de partitura
+            // Manejar mensajes de las lecciones
+            AppMessage::Lesson(msg) => match msg {
+                LessonMessage::Select(id) => {
+                    self.lesson = self.curriculum.lesson(&id).cloned();
+                    if self.lesson.is_some() {
+                        self.state = AppState::Lesson;
+                    }
+                }
+                LessonMessage::Start => {
+                    if let Some(lesson) = &self.lesson {
+                        let name: String = lesson.piece.clone();
+                        self.start_game_with_partiture(&name);
+                    }
+                }
+                LessonMessage::BackToMap => {
+                    self.metronome.stop();
+                    self.listener = None;
+                    self.finish_run(false);
+                    self.open_lesson_map();
+                }
+                LessonMessage::BackToMenu => {
+                    self.lesson = None;
+                    self.state = AppState::MainMenu;
+                }
+            },
+
             AppMessage::Selection(msg) => match msg {
                 // Manejar selección de partitura
-                SelectionMessage::StartGame(name) => self.start_game_with_partiture(name),
+                SelectionMessage::StartGame(name) => {
+                    self.lesson = None;
+                    self.start_game_with_partiture(&name)
+                }
+                SelectionMessage::OpenSightReading => self.state = AppState::SightReading,
+                SelectionMessage::OpenScales => self.state = AppState::Scales,
+                SelectionMessage::OpenRhythm => self.open_rhythm(),
+                SelectionMessage::NewPiece => self.open_editor(None),
+                SelectionMessage::EditPiece(name) => self.open_editor(Some(&name)),
+                SelectionMessage::OpenCapture => {
+                    self.capture_session = None;
+                    self.state = AppState::Capture;
+                }
                 SelectionMessage::BackToMenu => self.state = AppState::MainMenu,
             },
+
+            // Manejar mensajes del generador de lectura a primera vista
+            AppMessage::SightReading(msg) => {
+                let config: &mut SightReadingConfig = &mut self.sight_reading;
+                match msg {
+                    SightReadingMessage::Level(level) => {
+                        // El nivel cambia figuras, saltos y registros, pero no la tonalidad ni el compás
+                        *config = SightReadingConfig {
+                            key: config.key,
+                            meter: config.meter,
+                            bars: config.bars,
+                            ..SightReadingConfig::level(level, config.seed)
+                        };
+                    }
+                    SightReadingMessage::Key(key) => config.key = key,
+                    SightReadingMessage::Meter(meter) => config.meter = meter,
+                    SightReadingMessage::Bars(bars) => config.bars = bars,
+                    SightReadingMessage::ToggleChords(chords) => config.chords = chords,
+                    SightReadingMessage::ToggleAccidentals(accidentals) => {
+                        config.accidentals = accidentals
+                    }
+                    SightReadingMessage::Seed(text) => {
+                        if text.is_empty() {
+                            config.seed = 0;
+                        } else if let Ok(seed) = text.parse() {
+                            config.seed = seed;
+                        }
+                    }
+                    SightReadingMessage::NewSeed => {
+                        config.seed =
+                            SeededRng::new(unix_now() ^ config.seed).next_u64() % 1_000_000;
+                    }
+                    SightReadingMessage::Start => {
+                        self.lesson = None;
+                        self.start_game_with_partiture(GENERATED_PIECE);
+                    }
+                    SightReadingMessage::Back => self.state = AppState::SelectionPartiture,
+                }
+            }
+
+            // Manejar mensajes de las tarjetas de lectura de notas
+            AppMessage::Flashcards(msg) => match msg {
+                FlashcardMessage::Clef(clef) => {
+                    let cluster: usize = self.flashcards.as_ref().map_or(1, |s| s.cluster);
+                    self.open_flashcards(clef, cluster);
+                }
+                FlashcardMessage::Cluster(cluster) => {
+                    let clef: Clef = self.flashcards.as_ref().map_or(Clef::Treble, |s| s.clef);
+                    self.open_flashcards(clef, cluster);
+                }
+                FlashcardMessage::Answer(pitch_class) => {
+                    self.answer_flashcard(Guess::PitchClass(pitch_class), Instant::now())
+                }
+                FlashcardMessage::Typed(text) => {
+                    if let Some(session) = self.flashcards.as_mut() {
+                        session.typed = text;
+                    }
+                }
+                FlashcardMessage::Submit => {
+                    let guess: Option<Guess> = self
+                        .flashcards
+                        .as_ref()
+                        .and_then(|session| Guess::parse(&session.typed));
+                    match guess {
+                        Some(guess) => self.answer_flashcard(guess, Instant::now()),
+                        None => {
+                            if let Some(session) = self.flashcards.as_mut() {
+                                session.typed.clear();
+                            }
+                        }
+                    }
+                }
+                FlashcardMessage::Tick(_) => {
+                    self.poll_midi_input();
+                    self.poll_microphone(|pitches, at| {
+                        pitches
+                            .iter()
+                            .map(|&pitch| {
+                                TimedInput::new(
+                                    at,
+                                    InputSource::Microphone,
+                                    InputAction::NoteOn {
+                                        pitch,
+                                        velocity: MICROPHONE_VELOCITY,
+                                    },
+                                )
+                            })
+                            .collect()
+                    });
+                }
+                FlashcardMessage::Back => {
+                    self.listener = None;
+                    self.flashcards = None;
+                    self.state = AppState::MainMenu;
+                }
+            },
+
+            // Manejar mensajes del entrenamiento auditivo
+            AppMessage::EarTraining(msg) => match msg {
+                EarTrainingMessage::Category(category) => self.open_ear_training(category),
+                EarTrainingMessage::Level(level) => {
+                    if let Some(session) = self.ear_training.as_mut() {
+                        session.set_level(level);
+                        session.stats.save().unwrap_or_else(|e| {
+                            log::error!("{}", e);
+                        });
+                    }
+                    self.play_ear_question();
+                }
+                EarTrainingMessage::Play => self.play_ear_question(),
+                EarTrainingMessage::Choice(choice) => {
+                    self.answer_ear_question(EarAnswer::Choice(choice))
+                }
+                EarTrainingMessage::Note(pitch_class) => {
+                    let answered: Option<bool> = self
+                        .ear_training
+                        .as_mut()
+                        .and_then(|session| session.push_note(pitch_class));
+                    if answered.is_some() {
+                        self.save_ear_training();
+                    }
+                }
+                EarTrainingMessage::Undo => {
+                    if let Some(session) = self.ear_training.as_mut() {
+                        session.notes.pop();
+                    }
+                }
+                EarTrainingMessage::Typed(text) => {
+                    if let Some(session) = self.ear_training.as_mut() {
+                        session.typed = text;
+                    }
+                }
+                EarTrainingMessage::Submit => {
+                    let answer: Option<EarAnswer> = self
+                        .ear_training
+                        .as_ref()
+                        .and_then(|session| EarAnswer::parse(&session.typed));
+                    match answer {
+                        Some(answer) => self.answer_ear_question(answer),
+                        None => {
+                            if let Some(session) = self.ear_training.as_mut() {
+                                session.typed.clear();
+                            }
+                        }
+                    }
+                }
+                EarTrainingMessage::Back => {
+                    self.tone_player = None;
+                    self.ear_training = None;
+                    self.state = AppState::MainMenu;
+                }
+            },
+
+            // Manejar mensajes de los ejercicios de ritmo
+            AppMessage::Rhythm(msg) => match msg {
+                RhythmMessage::Source(source) => self.rhythm.source = source,
+                RhythmMessage::Meter(meter) => self.rhythm.meter = meter,
+                RhythmMessage::Tempo(tempo) => self.rhythm.tempo = tempo,
+                RhythmMessage::Bars(bars) => self.rhythm.bars = bars,
+                RhythmMessage::Level(level) => self.rhythm.level = level,
+                RhythmMessage::Start => {
+                    if let Some(line) = self.rhythm_line() {
+                        self.start_rhythm(line);
+                    }
+                }
+                RhythmMessage::Retry => {
+                    if let Some(run) = self.rhythm_run.take() {
+                        self.start_rhythm(run.line);
+                    }
+                }
+                RhythmMessage::Tick(now) => {
+                    self.poll_midi_input();
+                    if let Some(run) = self.rhythm_run.as_mut() {
+                        run.tick(now);
+                        if run.is_finished() {
+                            self.metronome.stop();
+                        }
+                    }
+                }
+                RhythmMessage::Stop => {
+                    self.metronome.stop();
+                    self.rhythm_run = None;
+                }
+                RhythmMessage::Back => {
+                    self.metronome.stop();
+                    self.rhythm_run = None;
+                    self.state = AppState::SelectionPartiture;
+                }
+            },
+
+            // Manejar mensajes del editor de partituras
+            AppMessage::Editor(msg) => match msg {
+                EditorMessage::Save => self.save_editor(),
+                EditorMessage::Back => {
+                    self.editor = None;
+                    self.state = AppState::SelectionPartiture;
+                }
+                msg => self.edit_score(msg),
+            },
+
+            // Manejar mensajes de la grabación de partituras
+            AppMessage::Capture(msg) => match msg {
+                CaptureMessage::Meter(meter) => self.capture.meter = meter,
+                CaptureMessage::Tempo(tempo) => self.capture.tempo = tempo,
+                CaptureMessage::Grid(grid) => self.capture.grid = grid,
+                CaptureMessage::Triplets(triplets) => self.capture.triplets = triplets,
+                CaptureMessage::Swing(swing) => self.capture.swing = swing,
+                CaptureMessage::Split(split) => self.capture.split = split,
+                CaptureMessage::SplitPitch(pitch) => self.capture.split_pitch = pitch,
+                CaptureMessage::Start => self.start_capture(),
+                CaptureMessage::Tick(now) => {
+                    self.poll_midi_input();
+                    if let Some(session) = self.capture_session.as_mut() {
+                        session.tick(now);
+                    }
+                }
+                CaptureMessage::Channel => {
+                    if let Some(session) = self.capture_session.as_mut() {
+                        session.toggle_channel();
+                    }
+                }
+                CaptureMessage::Stop => {
+                    self.metronome.stop();
+                    if let Some(session) = self.capture_session.as_mut() {
+                        session.stop(Instant::now());
+                    }
+                }
+                CaptureMessage::Name(name) => {
+                    if let Some(session) = self.capture_session.as_mut() {
+                        session.name = name;
+                    }
+                }
+                CaptureMessage::Title(title) => {
+                    if let Some(session) = self.capture_session.as_mut() {
+                        session.title = title;
+                    }
+                }
+                CaptureMessage::Save => self.save_capture(),
+                CaptureMessage::Edit => {
+                    if let Some(session) = self.capture_session.take() {
+                        let mut editor: ScoreEditor =
+                            ScoreEditor::unsaved(session.name.trim(), session.to_piece());
+                        editor.dirty = true;
+                        self.editor = Some(editor);
+                        self.state = AppState::Editor;
+                    }
+                }
+                CaptureMessage::Retry => {
+                    self.metronome.stop();
+                    self.capture_session = None;
+                }
+                CaptureMessage::Back => {
+                    self.metronome.stop();
+                    self.capture_session = None;
+                    self.state = AppState::SelectionPartiture;
+                }
+            },
+
+            // Manejar mensajes de la pantalla de trofeos
+            AppMessage::Trophies(msg) => match msg {
+                TrophyMessage::Back => self.state = AppState::MainMenu,
+            },
+
+            // Manejar mensajes de los ejercicios de escalas
+            AppMessage::Scales(msg) => {
+                let config: &mut ScaleConfig = &mut self.scales;
+                match msg {
+                    ScaleMessage::Key(key) => config.key = key,
+                    ScaleMessage::MinorForm(form) => config.minor_form = form,
+                    ScaleMessage::Pattern(pattern) => {
+                        config.pattern = pattern;
+                        config.octaves = config.octaves();
+                    }
+                    ScaleMessage::Octaves(octaves) => config.octaves = octaves,
+                    ScaleMessage::Hands(hands) => config.hands = hands,
+                    ScaleMessage::NoteValue(value) => config.note_value = value,
+                    ScaleMessage::Start => {
+                        self.lesson = None;
+                        self.start_game_with_partiture(SCALE_EXERCISE);
+                    }
+                    ScaleMessage::Back => self.state = AppState::SelectionPartiture,
+                }
+            }
         }
     }
 
@@ -183,16 +941,65 @@ impl MyApp {
     pub fn view(&self) -> Element<'_, AppMessage> {
         match self.state {
             AppState::MainMenu => main_menu_view(),
-            AppState::SelectionPartiture => select_partiture_view(),
+            AppState::SelectionPartiture => select_partiture_view(&self.songs),
             AppState::Game => match &self.partiture_selected {
-                Some((left, right)) => game_view((left, right), &self.settings),
+                Some((left, right)) => game_view(
+                    (left, right),
+                    &right.settings,
+                    self.count_in_target,
+                    self.recent_rewards(),
+                ),
                 _ => Container::new(Text::new("Cargando partitura..."))
                     .width(Length::Fill)
                     .height(Length::Fill)
                     .into(),
             },
-            AppState::Settings => settings_view(&self.settings.get_iced_theme()),
-            AppState::Paused => paused_view(self.finished.clone()),
+            AppState::Settings => {
+                settings_view(&self.settings, &self.midi_input_ports, &self.midi_ports)
+            }
+            AppState::Paused => paused_view(self.lesson_status(), self.reward_lines()),
+            AppState::Results => match &self.results {
+                Some(results) => results_view(
+                    results,
+                    self.last_take.is_some(),
+                    self.lesson_status(),
+                    self.exercise_report.clone(),
+                    self.reward_lines(),
+                ),
+                None => main_menu_view(),
+            },
+            AppState::Replay => match &self.partiture_selected {
+                Some((left, right)) => replay_view((left, right), self.replay_playing),
+                _ => main_menu_view(),
+            },
+            AppState::Calibration => calibration_view(self.calibration.as_ref()),
+            AppState::Latency => latency_view(
+                self.tap_test.as_ref(),
+                &self.settings,
+                self.midi_input.is_some(),
+            ),
+            AppState::SightReading => sight_reading_view(&self.sight_reading),
+            AppState::Scales => scales_view(&self.scales),
+            AppState::Flashcards => match &self.flashcards {
+                Some(session) => flashcards_view(session),
+                None => main_menu_view(),
+            },
+            AppState::Trophies => trophies_view(&self.reward_rules, &self.player, unix_now()),
+            AppState::Rhythm => rhythm_view(&self.rhythm, self.rhythm_run.as_ref()),
+            AppState::EarTraining => match &self.ear_training {
+                Some(session) => ear_training_view(session),
+                None => main_menu_view(),
+            },
+            AppState::Editor => match &self.editor {
+                Some(editor) => editor_view(editor, &self.settings),
+                None => select_partiture_view(&self.songs),
+            },
+            AppState::Capture => capture_view(&self.capture, self.capture_session.as_ref()),
+            AppState::LessonMap => lesson_map_view(&self.curriculum, &self.lesson_progress),
+            AppState::Lesson => match &self.lesson {
+                Some(lesson) => lesson_view(lesson, &self.lesson_progress),
+                None => lesson_map_view(&self.curriculum, &self.lesson_progress),
+            },
         }
     }
 
@@ -204,15 +1011,58 @@ impl MyApp {
     // Método para manejar las suscripciones de la aplicación
     pub fn subscription(&self) -> Subscription<AppMessage> {
         match self.state {
+            // Las teclas se sellan al llegar, no en el tick de 16 ms
             AppState::Game => Subscription::batch(vec![
                 every(Duration::from_millis(16))
                     .map(|instant| AppMessage::Game(GameMessage::Tick(instant))),
-                listen().map(AppMessage::Event),
+                listen_with(timed_event),
             ]),
             AppState::Paused => {
                 // Solo escuchar eventos de teclado, sin tick de tiempo
                 listen().map(AppMessage::Event)
             }
+            AppState::Replay if self.replay_playing => every(Duration::from_millis(16))
+                .map(|instant| AppMessage::Replay(ReplayMessage::Tick(instant))),
+            AppState::Calibration => every(Duration::from_millis(16))
+                .map(|instant| AppMessage::Calibration(CalibrationMessage::Tick(instant))),
+            AppState::Latency => Subscription::batch(vec![
+                every(Duration::from_millis(16))
+                    .map(|instant| AppMessage::Latency(LatencyMessage::Tick(instant))),
+                listen_with(tap_event),
+            ]),
+            // Cualquier tecla es una pulsación mientras suena la línea de ritmo
+            AppState::Rhythm
+                if self
+                    .rhythm_run
+                    .as_ref()
+                    .is_some_and(|run| !run.is_finished()) =>
+            {
+                Subscription::batch(vec![
+                    every(Duration::from_millis(16))
+                        .map(|instant| AppMessage::Rhythm(RhythmMessage::Tick(instant))),
+                    listen_with(tap_event),
+                ])
+            }
+            // Las teclas de piano se graban y el pulso avanza hasta que se para
+            AppState::Capture
+                if self
+                    .capture_session
+                    .as_ref()
+                    .is_some_and(CaptureSession::is_recording) =>
+            {
+                Subscription::batch(vec![
+                    every(Duration::from_millis(16))
+                        .map(|instant| AppMessage::Capture(CaptureMessage::Tick(instant))),
+                    listen_with(capture_event),
+                ])
+            }
+            // Los atajos del editor, salvo lo que se escribe en los campos de texto
+            AppState::Editor => listen_with(editor_event),
+            // El teclado MIDI o el micrófono, si se ha activado en los ajustes, contestan las tarjetas
+            AppState::Flashcards if self.midi_input.is_some() || self.listener.is_some() => {
+                every(Duration::from_millis(16))
+                    .map(|instant| AppMessage::Flashcards(FlashcardMessage::Tick(instant)))
+            }
             _ => Subscription::none(),
         }
     }
@@ -226,6 +1076,20 @@ impl MyApp {
             .unwrap_or_default()
     }
 
+    // Conectar con el teclado MIDI de los ajustes; sin él se sigue tocando con el ordenador
+    fn open_midi_input(device: &str) -> Option<MidiInput> {
+        MidiInput::open(device)
+            .map_err(|e| log::error!("No se pudo abrir el teclado MIDI: {}", e))
+            .ok()
+    }
+
+    // Conectar con el puerto de salida MIDI de los ajustes; sin él la partida sigue igual
+    fn open_midi_output(device: &str) -> Option<MidiOutput> {
+        MidiOutput::open(device)
+            .map_err(|e| log::error!("No se pudo abrir la salida MIDI: {}", e))
+            .ok()
+    }
+
     // Guardar la configuración
     fn save_settings(&self) -> Result<(), Box<dyn error::Error>> {
         let path: String = asset_path!("settings.json");
@@ -234,114 +1098,897 @@ impl MyApp {
         Ok(())
     }
 
+    // Apagar lo que suena en el piano digital, al pausar o al arrastrar la barra
+    fn silence_midi(&self) {
+        if let Some(output) = &self.midi_output {
+            output.silence();
+        }
+    }
+
     /// Reanudar el juego
     fn resume_game(&mut self) {
-        if let (Some(pause_start), Some(start)) = (self.pause_started, self.start_time) {
-            let pause_duration = Instant::now().duration_since(pause_start);
-            self.start_time = Some(start + pause_duration);
-        }
-        self.pause_started = None;
+        self.clock.play();
         self.state = AppState::Game;
+
+        // Lo que oyó el micrófono durante la pausa no cuenta
+        if let Some(listener) = self.listener.as_mut() {
+            listener.poll();
+        }
+
+        if let Some(time) = self.score_time() {
+            self.start_metronome(time);
+        }
     }
 
-    /// Empezar juego con partitura
-    fn start_game_with_partiture(&mut self, name: &'static str) {
-        // Iniciamos los tiempos
+    /// Abrir el micrófono y empezar el asistente de calibración
+    fn open_calibration(&mut self) {
+        self.calibration = None;
+        self.microphone = match MicrophoneInput::open() {
+            Ok(microphone) => {
+                self.calibration = Some(CalibrationWizard::new(microphone.sample_rate()));
+                Some(microphone)
+            }
+            Err(e) => {
+                log::error!("No se pudo abrir el micrófono: {}", e);
+                None
+            }
+        };
+        self.state = AppState::Calibration;
+    }
+
+    // Cerrar el micrófono y volver a los ajustes
+    fn close_calibration(&mut self) {
+        self.metronome.stop();
+        self.calibration = None;
+        self.microphone = None;
+        self.state = AppState::Settings;
+    }
+
+    // Guardar los ajustes del metrónomo y aplicarlos al momento
+    fn apply_metronome_settings(&mut self) {
+        self.metronome.apply_settings(&self.settings.metronome);
+        self.save_settings().unwrap_or_else(|e| {
+            log::error!("{}", e);
+        });
+    }
+
+    // Arrancar el metrónomo en un segundo de partitura, en tiempo real según la velocidad del reloj
+    fn start_metronome(&self, score_time: f32) {
+        self.metronome.start(score_time / self.clock.rate());
+    }
+
+    // Duración de la cuenta previa de la partitura actual
+    fn timer(&self) -> f32 {
+        self.partiture_selected
+            .as_ref()
+            .map(|(_, right)| right.settings.timer)
+            .unwrap_or(self.settings.timer)
+    }
+
+    // Posición actual en segundos de partitura, o el destino de la cuenta previa si hay una
+    fn transport_position(&self) -> f32 {
+        self.count_in_target.unwrap_or_else(|| {
+            self.partiture_selected
+                .as_ref()
+                .map(|(_, right)| right.elapsed() - self.timer())
+                .unwrap_or(0.0)
+        })
+    }
+
+    /// Saltar a un segundo de la partitura. Antes suena la misma cuenta previa que al empezar,
+    /// así que el reloj se coloca en `position` y la partitura llega allí al acabar la cuenta.
+    fn seek_to(&mut self, position: f32) {
+        let Some((_, right)) = &self.partiture_selected else {
+            return;
+        };
+        let position: f32 = position.clamp(0.0, right.time);
+        self.count_in_target = (position > 0.0).then_some(position);
+
+        // El reloj sigue sonando o parado desde la nueva posición
+        self.clock.seek(position);
+        self.start_metronome(position - self.timer());
+    }
+
+    // Saltar compases desde la posición actual
+    fn jump_bar(&mut self, step: i32) {
+        let target: Option<f32> = self
+            .partiture_selected
+            .as_ref()
+            .and_then(|(_, right)| right.metadata.as_ref())
+            .map(|metadata| metadata.bar_jump(self.transport_position(), step));
+        if let Some(target) = target {
+            self.seek_to(target);
+        }
+    }
+
+    // Saltar secciones de `structure` desde la posición actual
+    fn jump_section(&mut self, step: i32) {
+        let target: Option<f32> = self
+            .partiture_selected
+            .as_ref()
+            .and_then(|(_, right)| right.metadata.as_ref())
+            .and_then(|metadata| metadata.section_jump(self.transport_position(), step));
+        if let Some(target) = target {
+            self.seek_to(target);
+        }
+    }
+
+    /// Segundos de partitura transcurridos (sin la cuenta atrás ni las pausas)
+    fn score_time(&self) -> Option<f32> {
+        self.score_time_at(self.clock.now())
+    }
+
+    // Segundo de partitura de un momento concreto, por ejemplo el de una nota ya corregida
+    fn score_time_at(&self, at: Instant) -> Option<f32> {
+        self.partiture_selected
+            .as_ref()
+            .map(|_| self.clock.position_at(at) - self.timer())
+    }
+
+    /// Nota o pulsación de cualquier entrada. Se resta el retraso de su entrada
+    /// al momento en que se capturó para saber cuándo se tocó de verdad.
+    fn handle_input(&mut self, input: TimedInput) {
+        match self.state {
+            // La prueba mide el retraso, así que usa el momento sin corregir. Soltar la tecla
+            // no es una pulsación.
+            AppState::Latency => {
+                if let (Some(test), InputAction::Tap | InputAction::NoteOn { .. }) =
+                    (self.tap_test.as_mut(), input.action)
+                    && test.source() == input.source
+                {
+                    test.tap(input.at);
+                }
+            }
+            // En el ritmo solo cuenta cuándo empieza cada pulsación o nota
+            AppState::Rhythm => {
+                let at: Instant = input.corrected(self.settings.input_latency(input.source));
+                if let (Some(run), InputAction::Tap | InputAction::NoteOn { .. }) =
+                    (self.rhythm_run.as_mut(), input.action)
+                {
+                    run.tap(at);
+                }
+            }
+            AppState::Game => {
+                let at: Instant = input.corrected(self.settings.input_latency(input.source));
+                let (Some(time), Some(recorder)) = (self.score_time_at(at), self.recorder.as_mut())
+                else {
+                    return;
+                };
+                match input.action {
+                    InputAction::NoteOn { pitch, velocity } => {
+                        // El micrófono no oye soltar la tecla: la nota dura hasta que se repite
+                        if input.source == InputSource::Microphone {
+                            recorder.note_off(pitch, time);
+                        }
+                        recorder.note_on(pitch, time, velocity, input.source);
+                    }
+                    InputAction::NoteOff { pitch } => recorder.note_off(pitch, time),
+                    InputAction::Tap => {}
+                }
+            }
+            // Se graba lo que se toca, con el momento corregido como en el juego
+            AppState::Capture => {
+                let at: Instant = input.corrected(self.settings.input_latency(input.source));
+                let Some(session) = self.capture_session.as_mut() else {
+                    return;
+                };
+                match input.action {
+                    InputAction::NoteOn { pitch, .. } => session.note_on(pitch, at),
+                    InputAction::NoteOff { pitch } => session.note_off(pitch, at),
+                    InputAction::Tap => {}
+                }
+            }
+            // Una nota tocada contesta la tarjeta: el teclado MIDI dice el tono exacto,
+            // el micrófono solo el nombre porque puede confundir la octava
+            AppState::Flashcards => {
+                if let InputAction::NoteOn { pitch, .. } = input.action {
+                    let guess: Guess = match input.source {
+                        InputSource::Midi => Guess::Pitch(pitch),
+                        _ => Guess::PitchClass(pitch % 12),
+                    };
+                    self.answer_flashcard(guess, input.at);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Pasar las notas del teclado MIDI, selladas al llegar, a la pantalla actual
+    fn poll_midi_input(&mut self) {
+        let Some(input) = self.midi_input.as_mut() else {
+            return;
+        };
+        for input in input.poll() {
+            self.handle_input(input);
+        }
+    }
+
+    // Pasar lo que oyó el micrófono como entradas con su momento de captura
+    fn poll_microphone(&mut self, to_inputs: impl Fn(&[u8], Instant) -> Vec<TimedInput>) {
+        let Some(listener) = self.listener.as_mut() else {
+            return;
+        };
+        let inputs: Vec<TimedInput> = listener
+            .poll()
+            .iter()
+            .flat_map(|heard| to_inputs(&heard.pitches, heard.at))
+            .collect();
+        for input in inputs {
+            self.handle_input(input);
+        }
+    }
+
+    // Empezar la prueba de latencia con el pulso del metrónomo
+    fn start_tap_test(&mut self, source: InputSource) {
+        self.listener = None;
+        if source == InputSource::Microphone {
+            match MicrophoneListener::open(&self.settings.microphone) {
+                Ok(listener) => self.listener = Some(listener),
+                Err(e) => {
+                    log::error!("No se pudo abrir el micrófono: {}", e);
+                    return;
+                }
+            }
+        }
+
+        let mut test: TapTest = TapTest::new(source);
+        test.start(Instant::now());
+        self.tap_test = Some(test);
+        self.metronome.cue(TAP_COUNT_IN, 4, TAP_BEAT_SECONDS);
+    }
+
+    /// Terminar la grabación en curso y guardarla en el historial de progreso
+    fn finish_run(&mut self, finished: bool) {
+        let (Some(recorder), Some(name)) = (self.recorder.take(), self.partiture_name.clone())
+        else {
+            return;
+        };
+        let time: f32 = self.score_time().unwrap_or(0.0);
+        let take: Take = recorder.finish(time);
+
+        // Solo se guarda el archivo de la grabación si se tocó alguna nota
+        let take_file: Option<String> = if take.notes.is_empty() {
+            None
+        } else {
+            save_take(&take)
+                .map_err(|e| log::error!("No se pudo guardar la grabación: {}", e))
+                .ok()
+        };
+
+        let mut history: ProgressHistory = ProgressHistory::load();
+        history.runs.push(RunRecord {
+            piece: name,
+            started_at: take.recorded_at,
+            played_seconds: time.max(0.0),
+            finished,
+            take: take_file,
+        });
+        history.save().unwrap_or_else(|e| {
+            log::error!("{}", e);
+        });
+
+        if finished {
+            self.grade_lesson(&take);
+            self.report_exercise(&take);
+            self.reward_run(&take);
+            self.results = self.song_results(&take);
+        }
+        if finished && !take.notes.is_empty() {
+            self.last_take = Some(take);
+        }
+    }
+
+    // Resultados de la pieza terminada, con la precisión de cada compás
+    fn song_results(&self, take: &Take) -> Option<SongResults> {
+        let (Some(info), Some((left, right))) = (&self.piece_info, &self.partiture_selected) else {
+            return None;
+        };
+        let metadata: &PieceMetadata = right.metadata.as_ref()?;
+        let metric: BarAccuracy = BarAccuracy::new(
+            &judge_take(take, &right.notes, &left.notes),
+            &right.notes,
+            &left.notes,
+        );
+        let wall_seconds: f32 = self
+            .run_started
+            .map_or(0.0, |started| started.elapsed().as_secs_f32());
+
+        Some(SongResults::new(
+            info.clone(),
+            metadata,
+            right.time,
+            wall_seconds,
+            &metric,
+        ))
+    }
+
+    /// Sumar el tiempo desde el tick anterior a la práctica del jugador
+    fn count_practice(&mut self, instant: Instant) {
+        let seconds: f32 = self.practice_mark.map_or(0.0, |mark| {
+            instant
+                .saturating_duration_since(mark)
+                .as_secs_f32()
+                .min(MAX_PRACTICE_GAP)
+        });
+        self.practice_mark = Some(instant);
+        self.reward(PracticeEvent::Practiced { seconds });
+    }
+
+    // Precisión de una partida terminada contra la partitura cargada
+    fn run_accuracy(&self, take: &Take) -> Option<f32> {
+        let (left, right) = self.partiture_selected.as_ref()?;
+        let judged: Vec<JudgedNote> = judge_take(take, &right.notes, &left.notes);
+        Some(summarize(&judged, expected_notes(&right.notes, &left.notes)).accuracy)
+    }
+
+    // Dar las recompensas de una partida terminada según su precisión y su tempo
+    fn reward_run(&mut self, take: &Take) {
+        let (Some(name), Some(accuracy)) = (self.partiture_name.clone(), self.run_accuracy(take))
+        else {
+            return;
+        };
+
+        self.reward(PracticeEvent::Finished(RunResult {
+            piece: name,
+            accuracy,
+            tempo: self.clock.rate(),
+            exercise: self.piece_info.as_ref().is_some_and(|info| info.exercise),
+        }));
+    }
+
+    /// Apuntar algo que ha pasado en el progreso del jugador y avisar de lo ganado.
+    /// El tiempo de práctica solo se guarda cuando trae alguna recompensa.
+    fn reward(&mut self, event: PracticeEvent) {
+        let rewards: Vec<Reward> = self.player.record(&self.reward_rules, &event, unix_now());
+        if rewards.is_empty() && matches!(event, PracticeEvent::Practiced { .. }) {
+            return;
+        }
+
+        self.player.save().unwrap_or_else(|e| {
+            log::error!("{}", e);
+        });
         let now: Instant = Instant::now();
-        self.partiture_name = Some(name);
-        self.actual_time = Some(now);
-        self.start_time = Some(now);
-        self.pause_started = None;
+        self.rewards
+            .extend(rewards.into_iter().map(|reward| (reward, now)));
+    }
 
-        // Le decimos que no a terminado y que no esta pausado
-        self.finished.store(false, Ordering::SeqCst);
-        self.is_paused.store(false, Ordering::SeqCst);
+    // Todas las recompensas de la partida, para la pausa y los resultados
+    fn reward_lines(&self) -> Vec<String> {
+        self.rewards
+            .iter()
+            .map(|(reward, _)| reward.to_string())
+            .collect()
+    }
 
-        // Array con todas las notas
-        let file_notes: Vec<Value> = match load_partiture(&asset_path!("partitures.json")) {
-            Ok(notes) => notes,
-            Err(e) => {
-                log::error!("Partituras no encontradas -> {}", e);
-                return;
+    // Recompensas que acaban de llegar, para el aviso sobre la partitura
+    fn recent_rewards(&self) -> Vec<String> {
+        self.rewards
+            .iter()
+            .filter(|(_, at)| at.elapsed() < REWARD_NOTICE)
+            .map(|(reward, _)| reward.to_string())
+            .collect()
+    }
+
+    /// Empezar las tarjetas de lectura con una clave y un número de notas por tarjeta.
+    /// Las estadísticas siguen de una partida a otra.
+    fn open_flashcards(&mut self, clef: Clef, cluster: usize) {
+        let stats: FlashcardStats = match self.flashcards.take() {
+            Some(session) => session.stats,
+            None => {
+                // El piano acústico también contesta si se ha activado en los ajustes
+                self.listener = None;
+                if self.settings.microphone.use_in_game {
+                    match MicrophoneListener::open(&self.settings.microphone) {
+                        Ok(listener) => self.listener = Some(listener),
+                        Err(e) => log::error!("No se pudo abrir el micrófono: {}", e),
+                    }
+                }
+                FlashcardStats::load()
             }
         };
+        self.flashcards = Some(FlashcardSession::new(
+            clef,
+            cluster,
+            stats,
+            unix_now(),
+            Instant::now(),
+        ));
+        self.state = AppState::Flashcards;
+    }
+
+    // Contestar la tarjeta actual y guardar las estadísticas al terminarla
+    fn answer_flashcard(&mut self, guess: Guess, at: Instant) {
+        let Some(session) = self.flashcards.as_mut() else {
+            return;
+        };
+        if session.answer(guess, at).is_some() {
+            session.stats.save().unwrap_or_else(|e| {
+                log::error!("{}", e);
+            });
+        }
+    }
+
+    /// Abrir los ejercicios de ritmo con las piezas de la biblioteca como fuente
+    fn open_rhythm(&mut self) {
+        let mut sources: Vec<RhythmSource> = vec![RhythmSource::Generated];
+        match load_library() {
+            Ok(library) => sources.extend(
+                library
+                    .pieces()
+                    .map(|(name, _)| RhythmSource::Piece(name.clone())),
+            ),
+            Err(e) => log::error!("{}", e),
+        }
+        self.rhythm.sources = sources;
+        self.rhythm_run = None;
+        self.state = AppState::Rhythm;
+    }
 
-        // Obtenemos la metadata y la secciones
-        let (metadata, sections) = match get_metadata_and_section(&file_notes, name) {
-            Ok((m, s)) => (m, s),
+    // Línea de ritmo de la fuente elegida: generada o la mano derecha de la pieza
+    fn rhythm_line(&self) -> Option<RhythmLine> {
+        let name: &str = match &self.rhythm.source {
+            RhythmSource::Generated => {
+                return Some(RhythmLine::generate(&self.rhythm, unix_now()));
+            }
+            RhythmSource::Piece(name) => name,
+        };
+        match load_library().and_then(|library| load_piece_hands(&library, name)) {
+            Ok((left, right, metadata)) => {
+                let notes: Vec<Note> = if right.is_empty() { left } else { right };
+                Some(RhythmLine::from_notes(name, &notes, metadata))
+            }
             Err(e) => {
-                log::error!("No se pudo obtener metadata/sections: {}", e);
+                log::error!("{}", e);
+                None
+            }
+        }
+    }
+
+    // Empezar la línea tras la cuenta previa de los ajustes, con el metrónomo marcando el pulso
+    fn start_rhythm(&mut self, line: RhythmLine) {
+        let count_in: f32 = self.settings.count_in_seconds(Some(&line.metadata));
+        let (pulses, pulse_seconds) = line.metadata.pulses();
+        let count_in_pulses: u32 = (count_in / pulse_seconds.max(f32::EPSILON)).round() as u32;
+        self.metronome.cue(count_in_pulses, pulses, pulse_seconds);
+        self.rhythm_run = Some(RhythmRun::new(
+            line,
+            Instant::now() + Duration::from_secs_f32(count_in),
+        ));
+    }
+
+    /// Abrir el editor con una pieza de la biblioteca, o con una vacía si no se indica ninguna
+    fn open_editor(&mut self, name: Option<&str>) {
+        let library: ScoreFile = load_library().unwrap_or_else(|e| {
+            log::error!("{}", e);
+            ScoreFile::default()
+        });
+        let editor: ScoreEditor = match name.map(|name| (name, library.piece(name))) {
+            Some((name, Ok(piece))) => ScoreEditor::open(name, piece.clone()),
+            Some((_, Err(e))) => {
+                log::error!("{}", e);
                 return;
             }
+            // Un nombre libre para la pieza nueva: nueva-pieza, nueva-pieza-2...
+            None => ScoreEditor::new_piece(&library.free_name(NEW_PIECE)),
         };
+        self.editor = Some(editor);
+        self.state = AppState::Editor;
+    }
 
-        // Cargar notas de la partitura seleccionada
-        let notes_l: Vec<Note> = match load_notes_from_file(&Hand::Left, &metadata, &sections) {
-            Ok(notes) => notes,
-            Err(e) => {
-                log::error!("Error cargando notas mano derecha -> {}", e);
-                return;
+    // Editar la pieza abierta en el editor
+    fn edit_score(&mut self, msg: EditorMessage) {
+        let Some(editor) = self.editor.as_mut() else {
+            return;
+        };
+        match msg {
+            EditorMessage::Note(pitch) => {
+                let pitch: u8 = editor.keyboard_pitch(pitch);
+                editor.insert(pitch, false)
+            }
+            EditorMessage::Chord(pitch) => {
+                let pitch: u8 = editor.keyboard_pitch(pitch);
+                editor.insert(pitch, true)
             }
+            EditorMessage::Rest => editor.insert(0, false),
+            EditorMessage::Click(hand, time, pitch) => editor.toggle(hand, time, pitch),
+            EditorMessage::Value(value) => editor.set_value(value),
+            EditorMessage::Dot => editor.toggle_dot(),
+            EditorMessage::Move(steps) => editor.move_cursor(steps),
+            EditorMessage::Transpose(semitones) => editor.transpose(semitones),
+            EditorMessage::Octave(octaves) => editor.shift_octave(octaves),
+            EditorMessage::Delete => editor.delete(),
+            EditorMessage::DeletePrevious => editor.delete_previous(),
+            EditorMessage::ToggleHand => editor.toggle_hand(),
+            EditorMessage::Text(field, text) => editor.set_text(field, text),
+            EditorMessage::Key(key) => editor.set_key(key),
+            EditorMessage::Meter(meter) => editor.set_meter(meter),
+            EditorMessage::Section(label) => editor.select_section(label),
+            EditorMessage::AddSection => editor.add_section(),
+            EditorMessage::RemoveSection => editor.remove_section(),
+            EditorMessage::PushStructure => editor.push_structure(),
+            EditorMessage::PopStructure => editor.pop_structure(),
+            EditorMessage::Undo => editor.undo(),
+            EditorMessage::Redo => editor.redo(),
+            EditorMessage::Save | EditorMessage::Back => {}
+        }
+    }
+
+    /// Validar la pieza del editor y guardarla en `partitures.json`. Si tiene errores no se
+    /// guarda y el primero se muestra en el editor.
+    fn save_editor(&mut self) {
+        let Some(editor) = self.editor.as_mut() else {
+            return;
         };
-        let notes_r: Vec<Note> = match load_notes_from_file(&Hand::Right, &metadata, &sections) {
-            Ok(notes) => notes,
+        let name: String = editor.name.trim().to_string();
+        match store_piece(&name, &editor.piece, editor.saved_name.as_deref()) {
+            Ok(()) => {
+                editor.name = name;
+                editor.mark_saved();
+                editor.status = Some("Guardada en partitures.json".to_string());
+                self.songs = MyApp::load_songs();
+            }
+            Err(message) => editor.status = Some(message),
+        }
+    }
+
+    // Piezas de la biblioteca con su dificultad, calculada una vez al cargarla
+    fn load_songs() -> Vec<(String, Option<DifficultyRating>)> {
+        match load_library() {
+            Ok(library) => rate_library(&library),
             Err(e) => {
-                log::error!("Error cargando notas mano derecha -> {}", e);
-                return;
+                log::error!("{}", e);
+                Vec::new()
             }
+        }
+    }
+
+    // Grabar tras la cuenta de los ajustes, con el metrónomo marcando el pulso aunque esté desactivado
+    fn start_capture(&mut self) {
+        let metadata: PieceMetadata = self.capture.metadata();
+        let count_in: f32 = self.settings.count_in_seconds(Some(&metadata));
+        let (pulses, pulse_seconds) = metadata.pulses();
+        let count_in_pulses: u32 = (count_in / pulse_seconds.max(f32::EPSILON)).round() as u32;
+        // El metrónomo sigue hasta que se para: lo que queda de cuenta es toda la grabación
+        self.metronome.cue(count_in_pulses, pulses, pulse_seconds);
+
+        let name: String = load_library()
+            .map(|library| library.free_name(CAPTURE_PIECE))
+            .unwrap_or_else(|e| {
+                log::error!("{}", e);
+                CAPTURE_PIECE.to_string()
+            });
+        self.capture_session = Some(CaptureSession::new(
+            self.capture.clone(),
+            &name,
+            Instant::now() + Duration::from_secs_f32(count_in),
+        ));
+    }
+
+    // Guardar la grabación ajustada a la rejilla como una pieza nueva
+    fn save_capture(&mut self) {
+        let Some(session) = self.capture_session.as_mut() else {
+            return;
         };
+        let name: String = session.name.trim().to_string();
+        session.status = Some(match store_piece(&name, &session.to_piece(), None) {
+            Ok(()) => {
+                self.songs = MyApp::load_songs();
+                format!("Guardada en partitures.json como '{}'", name)
+            }
+            Err(message) => message,
+        });
+    }
 
-        //  Calcular duración total antes de crear Partiture (usar máximo, no el último elemento)
-        let duration_left: f32 = notes_l
-            .iter()
-            .map(|n| n.start + n.duration)
-            .fold(0.0, f32::max);
-        let duration_right: f32 = notes_r
-            .iter()
-            .map(|n| n.start + n.duration)
-            .fold(0.0, f32::max);
-        let total_duration: f32 = duration_left.max(duration_right);
-
-        // Crear las partituras con las notas cargadas
-        let mut partiture_l: Partiture = Partiture {
-            notes: notes_l,
-            time: total_duration,
-            elapsed: 0.0,
-            settings: self.settings.clone(),
-            hand: Hand::Left,
-            metadata: None,
-            img_width: 200.0,
+    /// Empezar el entrenamiento auditivo en una categoría y tocar la primera pregunta.
+    /// Las estadísticas siguen de una categoría a otra.
+    fn open_ear_training(&mut self, category: EarCategory) {
+        let stats: EarTrainingStats = match self.ear_training.take() {
+            Some(session) => session.stats,
+            None => EarTrainingStats::load(),
+        };
+        self.ear_training = Some(EarTrainingSession::new(category, stats, unix_now()));
+        if self.tone_player.is_none() {
+            self.tone_player = Some(TonePlayer::new());
+        }
+        self.state = AppState::EarTraining;
+        self.play_ear_question();
+    }
+
+    // Tocar la pregunta actual desde el principio
+    fn play_ear_question(&self) {
+        if let (Some(session), Some(player)) = (&self.ear_training, &self.tone_player) {
+            player.play(&session.question.tones());
+        }
+    }
+
+    // Contestar la pregunta, guardar las estadísticas y tocar la siguiente
+    fn answer_ear_question(&mut self, answer: EarAnswer) {
+        let Some(session) = self.ear_training.as_mut() else {
+            return;
+        };
+        session.answer(&answer);
+        self.save_ear_training();
+    }
+
+    fn save_ear_training(&self) {
+        if let Some(session) = &self.ear_training {
+            session.stats.save().unwrap_or_else(|e| {
+                log::error!("{}", e);
+            });
+        }
+        self.play_ear_question();
+    }
+
+    /// Abrir el mapa de lecciones, leyendo otra vez los cursos por si se han editado
+    fn open_lesson_map(&mut self) {
+        self.curriculum = Curriculum::load();
+        match load_library() {
+            Ok(library) => {
+                for problem in self.curriculum.problems(&library) {
+                    log::error!("Curso inválido: {}", problem);
+                }
+            }
+            Err(e) => log::error!("{}", e),
+        }
+        self.state = AppState::LessonMap;
+    }
+
+    // Valorar la partida terminada contra los requisitos de la lección en curso
+    fn grade_lesson(&mut self, take: &Take) {
+        let (Some(accuracy), Some(lesson)) = (self.run_accuracy(take), &self.lesson) else {
+            return;
         };
-        let mut partiture_r: Partiture = Partiture {
-            notes: notes_r,
-            time: total_duration,
-            elapsed: 0.0,
-            settings: self.settings.clone(),
-            hand: Hand::Right,
-            metadata: None,
-            img_width: 200.0,
+
+        let passed: bool =
+            self.lesson_progress
+                .register(lesson, accuracy, self.clock.rate(), unix_now());
+        self.lesson_progress.save().unwrap_or_else(|e| {
+            log::error!("{}", e);
+        });
+
+        self.lesson_result = Some(format!(
+            "{}: {:.0} % de {:.0} %",
+            if passed {
+                "Lección superada"
+            } else {
+                "Lección no superada"
+            },
+            accuracy * 100.0,
+            lesson.required_accuracy * 100.0
+        ));
+    }
+
+    // Precisión y regularidad de un ejercicio de escalas terminado
+    fn report_exercise(&mut self, take: &Take) {
+        let (Some(SCALE_EXERCISE), Some((left, right))) =
+            (self.partiture_name.as_deref(), &self.partiture_selected)
+        else {
+            return;
         };
+        let judged: Vec<JudgedNote> = judge_take(take, &right.notes, &left.notes);
+        let accuracy: f32 = summarize(&judged, expected_notes(&right.notes, &left.notes)).accuracy;
+
+        let mut report: String = format!("Precisión: {:.0} %", accuracy * 100.0);
+        match evenness(&judged, &right.notes, &left.notes) {
+            Some(deviation) => report.push_str(&format!(
+                "  ·  Regularidad: ±{:.0} ms entre notas",
+                deviation * 1000.0
+            )),
+            None => report.push_str("  ·  Sin notas suficientes para medir la regularidad"),
+        }
+        self.exercise_report = Some(report);
+    }
+
+    // Texto de la lección en el menú de pausa: el resultado al terminar o el objetivo mientras se toca
+    fn lesson_status(&self) -> Option<String> {
+        let lesson: &Lesson = self.lesson.as_ref()?;
+        Some(self.lesson_result.clone().unwrap_or_else(|| {
+            format!(
+                "{}: {:.0} % al {:.0} % del tempo",
+                lesson.title,
+                lesson.required_accuracy * 100.0,
+                lesson.playback_rate() * 100.0
+            )
+        }))
+    }
 
-        let fur_elise_meta: PieceMetadata = match get_price_metdata_compas(metadata) {
-            Ok(data) => data,
+    /// Abrir la repetición de la última grabación, o de la última guardada en el historial
+    fn open_replay(&mut self) {
+        let Some(name) = self.partiture_name.clone() else {
+            return;
+        };
+        let take: Option<Take> = self
+            .last_take
+            .clone()
+            .filter(|take| take.piece == name)
+            .or_else(|| {
+                let history: ProgressHistory = ProgressHistory::load();
+                let file: &str = history.last_take_of(&name)?.take.as_deref()?;
+                load_take(file)
+                    .map_err(|e| log::error!("No se pudo cargar la grabación {}: {}", file, e))
+                    .ok()
+            });
+
+        let (Some(take), Some((left, right))) = (take, self.partiture_selected.as_mut()) else {
+            return;
+        };
+
+        // Repartir las notas valoradas entre las dos manos
+        let judged: Vec<JudgedNote> = judge_take(&take, &right.notes, &left.notes);
+        let (judged_r, judged_l): (Vec<JudgedNote>, Vec<JudgedNote>) =
+            judged.into_iter().partition(|note| match note.expected {
+                Some((hand, _)) => hand == Hand::Right,
+                None => note.played.pitch >= 60,
+            });
+        right.replay = judged_r;
+        left.replay = judged_l;
+
+        self.replay_playing = false;
+        self.clock.pause();
+        self.state = AppState::Replay;
+        self.seek_replay(0.0);
+    }
+
+    // Posición de la repetición en segundos de partitura
+    fn replay_position(&self) -> f32 {
+        self.partiture_selected
+            .as_ref()
+            .map(|(_, right)| right.elapsed() - self.timer())
+            .unwrap_or(0.0)
+    }
+
+    fn replay_at_end(&self) -> bool {
+        self.partiture_selected
+            .as_ref()
+            .is_some_and(|(_, right)| self.replay_position() >= right.time)
+    }
+
+    // Mover las dos manos al segundo indicado, parando al llegar al final
+    fn seek_replay(&mut self, position: f32) {
+        let Some((_, right)) = &self.partiture_selected else {
+            return;
+        };
+        let position: f32 = position.clamp(0.0, right.time);
+        if position >= right.time {
+            self.replay_playing = false;
+            self.clock.pause();
+        }
+        self.clock.seek(position + self.timer());
+    }
+
+    /// Exportar la partitura actual como archivo MIDI en `assets/exports`
+    fn export_midi(&self) {
+        if let (Some(name), Some((left, right))) =
+            (self.partiture_name.as_deref(), &self.partiture_selected)
+        {
+            let path: String = asset_path!(format!("exports/{}.mid", name));
+            match save_smf(Path::new(&path), &partitures_to_smf((left, right))) {
+                Ok(()) => log::info!("MIDI exportado en {}", path),
+                Err(e) => log::error!("No se pudo exportar el MIDI a {}: {}", path, e),
+            }
+        }
+    }
+
+    /// Exportar la última grabación como archivo MIDI en `assets/exports`, con el compás y el
+    /// tempo de la partitura con la que se grabó
+    fn export_take(&self) {
+        if let (Some(take), Some((left, right))) = (&self.last_take, &self.partiture_selected) {
+            let path: String =
+                asset_path!(format!("exports/{}-{}.mid", take.piece, take.recorded_at));
+            let bytes: Vec<u8> = take_to_smf(take, &loaded_metadata((left, right)));
+            match save_smf(Path::new(&path), &bytes) {
+                Ok(()) => log::info!("Grabación exportada en {}", path),
+                Err(e) => log::error!("No se pudo exportar la grabación a {}: {}", path, e),
+            }
+        }
+    }
+
+    /// Empezar juego con partitura
+    fn start_game_with_partiture(&mut self, name: &str) {
+        // Reloj nuevo y parado hasta que la partitura esté lista
+        self.clock = PlaybackClock::new();
+        self.partiture_name = Some(name.to_string());
+        self.count_in_target = None;
+        self.scrubbing = None;
+
+        // Empezar una nueva grabación
+        self.recorder = Some(TakeRecorder::new(name, unix_now()));
+
+        // Escuchar el piano acústico si se ha activado en los ajustes
+        self.listener = None;
+        if self.settings.microphone.use_in_game {
+            match MicrophoneListener::open(&self.settings.microphone) {
+                Ok(listener) => self.listener = Some(listener),
+                Err(e) => log::error!("No se pudo abrir el micrófono: {}", e),
+            }
+        }
+        self.last_take = None;
+        self.lesson_result = None;
+        self.exercise_report = None;
+        self.rewards.clear();
+        self.practice_mark = None;
+        self.results = None;
+        self.run_started = Some(Instant::now());
+
+        // Le decimos que no esta pausado
+        self.is_paused.store(false, Ordering::SeqCst);
+
+        // Archivo con todas las partituras, o solo el ejercicio generado
+        let library: Result<ScoreFile, ScoreError> = match name {
+            GENERATED_PIECE => Ok(ScoreFile(vec![BTreeMap::from([(
+                name.to_string(),
+                generate_piece(&self.sight_reading),
+            )])])),
+            SCALE_EXERCISE => Ok(ScoreFile(vec![BTreeMap::from([(
+                name.to_string(),
+                scales::generate_piece(&self.scales),
+            )])])),
+            _ => load_library(),
+        };
+        let score: ScoreFile = match library {
+            Ok(score) => score,
             Err(e) => {
-                log::error!("Error obteniendo metadata del compás: {}", e);
+                log::error!("{}", e);
                 return;
             }
         };
 
-        // Damos los valores de metadata a las partituras para que lo tengan en cuenta a ala hora de dibujar compases velocidad etc
-        (partiture_r.metadata, partiture_l.metadata) =
-            (Some(fur_elise_meta.clone()), Some(fur_elise_meta));
+        self.piece_info = score.piece(name).ok().map(|piece| piece.metadata.clone());
 
-        // Sanitizar notas con los datos necesarios y asignar los datos a las partituras
-        sanitize_data(&mut partiture_r);
-        sanitize_data(&mut partiture_l);
+        // Cargar y validar las notas de la partitura seleccionada
+        let (notes_l, notes_r, piece_metadata): (Vec<Note>, Vec<Note>, PieceMetadata) =
+            match load_piece_hands(&score, name) {
+                Ok(hands) => hands,
+                Err(e) => {
+                    log::error!("{}", e);
+                    return;
+                }
+            };
+
+        // Dinámicas, ligaduras y pedal, ya en el orden de `structure`
+        let markings: Markings = score
+            .piece(name)
+            .and_then(Piece::expand_markings)
+            .unwrap_or_default();
+
+        // Crear las partituras de las dos manos con la cuenta previa de la pieza
+        let (partiture_l, partiture_r): (Partiture, Partiture) = build_partitures(
+            notes_l,
+            notes_r,
+            markings,
+            piece_metadata.clone(),
+            &self.settings,
+            &self.clock,
+        );
+        let partiture_settings: CustomSettings = partiture_r.settings.clone();
+
+        // Las lecciones se tocan a su velocidad; el metrónomo la sigue con pulsos más largos
+        let rate: f32 = self.lesson.as_ref().map_or(1.0, Lesson::playback_rate);
+        self.clock.set_rate(rate);
+        self.metronome.configure(&PieceMetadata {
+            base_note_value: piece_metadata.base_note_value / rate,
+            ..piece_metadata.clone()
+        });
+
+        // El metrónomo empieza con la cuenta previa, con el reloj reiniciado a la vez
+        self.clock.play();
+        self.start_metronome(-partiture_settings.timer);
+
+        // El piano digital toca y enciende las luces al ritmo del mismo reloj
+        if let Some(output) = &self.midi_output {
+            output.play(
+                output_events((&partiture_l, &partiture_r), &self.settings.midi_output),
+                &self.clock,
+            );
+        }
 
         // Actualizamos con la nueva partitura
         self.partiture_selected = Some((partiture_l, partiture_r));
 
         // Cambiamos el estado a el juego
         self.state = AppState::Game;
+        self.reward(PracticeEvent::Started {
+            piece: name.to_string(),
+        });
     }
 }
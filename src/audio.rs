@@ -0,0 +1,9 @@
+pub mod analyser;
+pub mod metronome;
+pub mod onset;
+pub mod output;
+pub mod pitch;
+pub mod spectrum;
+pub mod tone;
+pub mod tuning;
+pub mod wav;
@@ -0,0 +1,333 @@
+use {
+    crate::audio::{
+        onset::{Onset, OnsetConfig, OnsetDetector},
+        pitch::{PitchConfig, PitchEstimator},
+        spectrum::SpectrumAnalyzer,
+    },
+    std::collections::VecDeque,
+};
+
+/// Parámetros del analizador completo
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalyserConfig {
+    pub onset: OnsetConfig,
+    pub pitch: PitchConfig,
+    pub pitch_delay: f32, // Segundos tras el ataque que se saltan antes de medir el tono
+    pub pitch_window: f32, // Segundos de audio usados para medir el tono
+    pub change_window: f32, // Segundos antes y después del ataque para ver qué notas son nuevas
+    pub new_note_ratio: f32, // Cuánto debe crecer un tono en el ataque para contar como nota nueva
+}
+
+impl Default for AnalyserConfig {
+    fn default() -> Self {
+        AnalyserConfig {
+            onset: OnsetConfig::default(),
+            pitch: PitchConfig::default(),
+            pitch_delay: 0.02,
+            pitch_window: 0.12,
+            change_window: 0.05,
+            new_note_ratio: 1.4,
+        }
+    }
+}
+
+/// Perfil del ruido de fondo medido durante un silencio
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoiseProfile {
+    pub rms: f32,           // Nivel medio del ruido
+    pub flux_mean: f32,     // Flujo espectral medio del ruido
+    pub flux_std: f32,      // Desviación típica de ese flujo
+    pub spectrum: Vec<f32>, // Espectro medio con el tamaño de la ventana de tono
+}
+
+impl NoiseProfile {
+    /// Mide el ruido de una grabación sin notas
+    pub fn measure(silence: &[f32], sample_rate: u32, config: &AnalyserConfig) -> Self {
+        let rms: f32 = if silence.is_empty() {
+            0.0
+        } else {
+            (silence.iter().map(|s| s * s).sum::<f32>() / silence.len() as f32).sqrt()
+        };
+
+        // Flujo de los bloques del silencio
+        let mut detector: OnsetDetector = OnsetDetector::new(config.onset.clone(), sample_rate);
+        let fluxes: Vec<f32> = silence
+            .windows(config.onset.frame_len)
+            .step_by(config.onset.hop)
+            .map(|frame| detector.flux(frame))
+            .skip(1)
+            .collect();
+        let flux_mean: f32 = mean(&fluxes);
+        let flux_std: f32 = mean(
+            &fluxes
+                .iter()
+                .map(|f| (f - flux_mean).powi(2))
+                .collect::<Vec<f32>>(),
+        )
+        .sqrt();
+
+        // Espectro medio con la misma ventana que se usa para el tono
+        let (frame_len, fft_len) = pitch_sizes(config, sample_rate);
+        let mut analyzer: SpectrumAnalyzer = SpectrumAnalyzer::new(frame_len, fft_len);
+        let mut spectrum: Vec<f32> = vec![0.0; analyzer.bins()];
+        let mut count: usize = 0;
+        for frame in silence.chunks_exact(frame_len) {
+            for (total, value) in spectrum.iter_mut().zip(analyzer.magnitudes(frame)) {
+                *total += value;
+            }
+            count += 1;
+        }
+        if count > 0 {
+            spectrum.iter_mut().for_each(|v| *v /= count as f32);
+        }
+
+        NoiseProfile {
+            rms,
+            flux_mean,
+            flux_std,
+            spectrum,
+        }
+    }
+
+    // Umbral de flujo por encima del ruido
+    pub fn onset_threshold(&self) -> f32 {
+        self.flux_mean + 6.0 * self.flux_std
+    }
+}
+
+/// Ataque detectado con los tonos que suenan en él
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetectedOnset {
+    pub time: f32,         // Segundos desde el inicio del audio
+    pub pitches: Vec<u8>,  // Tonos tocados en este ataque, de grave a agudo
+    pub sounding: Vec<u8>, // Todos los tonos que suenan, incluidos los que siguen pulsados
+    pub strength: f32,     // Flujo espectral del ataque
+}
+
+/// Analizador en directo: recibe bloques de audio de cualquier tamaño y devuelve
+/// los ataques con sus tonos en cuanto hay audio suficiente para medirlos.
+/// Nunca bloquea esperando muestras.
+pub struct StreamingAnalyser {
+    sample_rate: u32,
+    config: AnalyserConfig,
+    onsets: OnsetDetector,
+    spectrum: SpectrumAnalyzer,
+    change: SpectrumAnalyzer, // Ventanas cortas justo antes y después del ataque
+    pitch: PitchEstimator,
+    noise: Option<NoiseProfile>,
+    buffer: VecDeque<f32>,         // Audio pendiente de analizar
+    buffer_start: u64,             // Muestra absoluta de `buffer[0]`
+    next_frame: u64,               // Muestra absoluta del siguiente bloque de ataques
+    pending: VecDeque<(u64, f32)>, // Ataques esperando audio para el tono: (muestra, fuerza)
+}
+
+impl StreamingAnalyser {
+    pub fn new(sample_rate: u32, config: AnalyserConfig) -> Self {
+        let (frame_len, fft_len) = pitch_sizes(&config, sample_rate);
+        let spectrum: SpectrumAnalyzer = SpectrumAnalyzer::new(frame_len, fft_len);
+        let change_len: usize = ((config.change_window * sample_rate as f32) as usize)
+            .clamp(64, frame_len)
+            .next_power_of_two();
+        // Mismo tamaño de FFT para poder comparar las saliencias de los dos espectros
+        let change: SpectrumAnalyzer = SpectrumAnalyzer::new(change_len, fft_len);
+        let pitch: PitchEstimator =
+            PitchEstimator::new(config.pitch.clone(), sample_rate, &spectrum);
+
+        StreamingAnalyser {
+            sample_rate,
+            onsets: OnsetDetector::new(config.onset.clone(), sample_rate),
+            config,
+            spectrum,
+            change,
+            pitch,
+            noise: None,
+            buffer: VecDeque::new(),
+            buffer_start: 0,
+            next_frame: 0,
+            pending: VecDeque::new(),
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Usar un perfil de ruido: sube el umbral de ataques y se resta del espectro
+    pub fn set_noise_profile(&mut self, noise: NoiseProfile) {
+        self.onsets
+            .set_threshold(self.config.onset.threshold.max(noise.onset_threshold()));
+        self.noise = Some(noise);
+    }
+
+    /// Medir el ruido de fondo a partir de un silencio y usarlo desde ahora
+    pub fn calibrate(&mut self, silence: &[f32]) -> &NoiseProfile {
+        let profile: NoiseProfile = NoiseProfile::measure(silence, self.sample_rate, &self.config);
+        self.set_noise_profile(profile);
+        self.noise.as_ref().expect("perfil recién asignado")
+    }
+
+    /// Usar un umbral de ataques guardado de una calibración anterior
+    pub fn set_onset_threshold(&mut self, threshold: f32) {
+        self.onsets
+            .set_threshold(self.config.onset.threshold.max(threshold));
+    }
+
+    pub fn set_a4(&mut self, a4: f32) {
+        self.pitch.set_a4(a4);
+    }
+
+    /// Añade audio y devuelve los ataques que ya se han podido medir
+    pub fn push(&mut self, samples: &[f32]) -> Vec<DetectedOnset> {
+        self.buffer.extend(samples);
+        let end: u64 = self.buffer_start + self.buffer.len() as u64;
+
+        // Bloques de ataque completos
+        let frame_len: u64 = self.config.onset.frame_len as u64;
+        let hop: u64 = self.config.onset.hop as u64;
+        while self.next_frame + frame_len <= end {
+            let frame: Vec<f32> = self.slice(self.next_frame, frame_len as usize);
+            if let Some(onset) = self.onsets.process(&frame, self.sample_rate) {
+                self.pending
+                    .push_back((self.onset_sample(onset), onset.strength));
+            }
+            self.next_frame += hop;
+        }
+
+        // Ataques con audio suficiente para medir el tono
+        let mut detected: Vec<DetectedOnset> = Vec::new();
+        while let Some(&(sample, strength)) = self.pending.front() {
+            let start: u64 = sample + self.pitch_delay_samples();
+            let len: usize = self.spectrum.frame_len();
+            if start + len as u64 > end {
+                break;
+            }
+            self.pending.pop_front();
+            detected.push(self.measure(sample, start, len, strength));
+        }
+
+        self.trim();
+        detected
+    }
+
+    /// Termina el análisis completando con silencio los ataques que esperan audio
+    pub fn flush(&mut self) -> Vec<DetectedOnset> {
+        let padding: usize = self.spectrum.frame_len()
+            + self.pitch_delay_samples() as usize
+            + 2 * self.config.onset.frame_len;
+        let mut detected: Vec<DetectedOnset> = self.push(&vec![0.0; padding]);
+
+        while let Some((sample, strength)) = self.pending.pop_front() {
+            let start: u64 = sample + self.pitch_delay_samples();
+            let len: usize = self.spectrum.frame_len();
+            detected.push(self.measure(sample, start, len, strength));
+        }
+        detected
+    }
+
+    fn measure(&mut self, sample: u64, start: u64, len: usize, strength: f32) -> DetectedOnset {
+        let frame: Vec<f32> = self.slice(start, len);
+        let magnitudes: Vec<f32> = self.spectrum.magnitudes(&frame);
+        let noise: Option<&[f32]> = self.noise.as_ref().map(|n| n.spectrum.as_slice());
+        let sounding: Vec<u8> = self.pitch.estimate(&magnitudes, noise);
+
+        // Una nota es nueva si su tono crece al pasar el ataque; las que siguen
+        // pulsadas desde antes solo decaen
+        let change_len: usize = self.change.frame_len();
+        let before: Vec<f32> = self.slice(sample.saturating_sub(change_len as u64), change_len);
+        let after: Vec<f32> = self.slice(start, change_len);
+        let before: Vec<f32> = self.change.magnitudes(&before);
+        let after: Vec<f32> = self.change.magnitudes(&after);
+
+        let growth: Vec<(u8, f32)> = sounding
+            .iter()
+            .map(|&pitch| {
+                let now: f32 = self.pitch.salience_of(&after, pitch);
+                let then: f32 = self.pitch.salience_of(&before, pitch);
+                (pitch, now / (then + self.pitch.config().min_salience))
+            })
+            .collect();
+        let mut pitches: Vec<u8> = growth
+            .iter()
+            .filter(|(_, ratio)| *ratio >= self.config.new_note_ratio)
+            .map(|(pitch, _)| *pitch)
+            .collect();
+        // Si ninguna crece lo suficiente (un ataque muy suave), la que más crece
+        if pitches.is_empty()
+            && let Some((pitch, _)) = growth.iter().max_by(|a, b| a.1.total_cmp(&b.1))
+        {
+            pitches.push(*pitch);
+        }
+
+        DetectedOnset {
+            time: sample as f32 / self.sample_rate as f32,
+            pitches,
+            sounding,
+            strength,
+        }
+    }
+
+    // El ataque se sitúa en el centro del bloque que lo detecta
+    fn onset_sample(&self, onset: Onset) -> u64 {
+        onset.frame * self.config.onset.hop as u64 + self.config.onset.frame_len as u64 / 2
+    }
+
+    fn pitch_delay_samples(&self) -> u64 {
+        (self.config.pitch_delay * self.sample_rate as f32) as u64
+    }
+
+    // Copia de `len` muestras desde la muestra absoluta `start` (con ceros si faltan)
+    fn slice(&self, start: u64, len: usize) -> Vec<f32> {
+        let offset: usize = start.saturating_sub(self.buffer_start) as usize;
+        let mut frame: Vec<f32> = self.buffer.iter().skip(offset).take(len).copied().collect();
+        frame.resize(len, 0.0);
+        frame
+    }
+
+    // Descartar el audio que ya no necesita ningún bloque ni ningún ataque pendiente
+    // Se guarda también la ventana corta anterior a cada ataque
+    fn trim(&mut self) {
+        let keep_from: u64 = self
+            .pending
+            .front()
+            .map(|(sample, _)| *sample)
+            .unwrap_or(u64::MAX)
+            .min(self.next_frame)
+            .saturating_sub(self.change.frame_len() as u64);
+        let drop: usize =
+            (keep_from.saturating_sub(self.buffer_start) as usize).min(self.buffer.len());
+        self.buffer.drain(..drop);
+        self.buffer_start += drop as u64;
+    }
+}
+
+/// Analiza una grabación completa
+pub fn analyse(
+    samples: &[f32],
+    sample_rate: u32,
+    config: AnalyserConfig,
+    noise: Option<NoiseProfile>,
+) -> Vec<DetectedOnset> {
+    let mut analyser: StreamingAnalyser = StreamingAnalyser::new(sample_rate, config);
+    if let Some(noise) = noise {
+        analyser.set_noise_profile(noise);
+    }
+    let mut detected: Vec<DetectedOnset> = analyser.push(samples);
+    detected.extend(analyser.flush());
+    detected
+}
+
+// Tamaño de la ventana de tono y de su FFT (con ceros al doble para interpolar)
+fn pitch_sizes(config: &AnalyserConfig, sample_rate: u32) -> (usize, usize) {
+    let frame_len: usize = ((config.pitch_window * sample_rate as f32) as usize)
+        .max(256)
+        .next_power_of_two();
+    (frame_len, frame_len * 2)
+}
+
+fn mean(values: &[f32]) -> f32 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<f32>() / values.len() as f32
+    }
+}
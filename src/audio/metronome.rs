@@ -0,0 +1,256 @@
+use {
+    crate::{
+        audio::output::{AudioOutput, AudioSource},
+        models::{partiture::PieceMetadata, settings::MetronomeSettings},
+    },
+    std::{
+        f32::consts::TAU,
+        sync::{Arc, Mutex},
+    },
+};
+
+// Duración de cada click en segundos
+pub const CLICK_SECONDS: f32 = 0.03;
+
+// Frecuencias del click normal y del acentuado (primer pulso del compás)
+const CLICK_FREQUENCY: f32 = 1000.0;
+const ACCENT_FREQUENCY: f32 = 1600.0;
+
+// Frecuencia de muestreo usada cuando no hay salida de audio
+const FALLBACK_SAMPLE_RATE: u32 = 44_100;
+
+/// Click programado, con su muestra exacta contada desde `ClickScheduler::start`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Click {
+    pub sample: u64,
+    pub beat: i64, // Índice del pulso en la partitura (negativo durante la cuenta previa)
+    pub accent: bool,
+}
+
+/// Programa los clicks del metrónomo con precisión de muestra.
+/// Los pulsos caen en múltiplos exactos de la unidad del compás desde el segundo 0 de la partitura,
+/// así que no acumulan deriva aunque se rendericen en bloques de cualquier tamaño.
+pub struct ClickScheduler {
+    sample_rate: u32,
+    beat_seconds: f64,  // Duración de un pulso (unidad del compás)
+    beats_per_bar: u32, // Pulsos por compás, el primero va acentuado
+    volume: f32,
+    running: bool,
+    origin: f64,   // Segundo de la partitura que corresponde a la muestra 0
+    position: u64, // Muestras renderizadas desde `start`
+    click: Vec<f32>,
+    accent: Vec<f32>,
+}
+
+impl ClickScheduler {
+    pub fn new(sample_rate: u32) -> Self {
+        let sample_rate: u32 = sample_rate.max(1);
+        ClickScheduler {
+            sample_rate,
+            beat_seconds: 0.5,
+            beats_per_bar: 4,
+            volume: 1.0,
+            running: false,
+            origin: 0.0,
+            position: 0,
+            click: click_wave(sample_rate, CLICK_FREQUENCY, 0.6),
+            accent: click_wave(sample_rate, ACCENT_FREQUENCY, 1.0),
+        }
+    }
+
+    // Compás y tempo a partir de los metadatos de la pieza, con un click por pulso
+    pub fn configure(&mut self, metadata: &PieceMetadata) {
+        let (pulses, pulse_seconds) = metadata.pulses();
+        self.set_meter(pulses, pulse_seconds);
+    }
+
+    pub fn set_meter(&mut self, beats_per_bar: u32, beat_seconds: f32) {
+        self.beats_per_bar = beats_per_bar.max(1);
+        self.beat_seconds = beat_seconds.max(0.0) as f64;
+    }
+
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume.clamp(0.0, 1.0);
+    }
+
+    // Empezar a sonar desde un segundo de la partitura (negativo durante la cuenta previa)
+    pub fn start(&mut self, score_time: f32) {
+        self.origin = score_time as f64;
+        self.position = 0;
+        self.running = true;
+    }
+
+    pub fn stop(&mut self) {
+        self.running = false;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    // Muestras renderizadas desde el último `start`
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    pub fn is_accent(&self, beat: i64) -> bool {
+        beat.rem_euclid(self.beats_per_bar as i64) == 0
+    }
+
+    // Muestra en la que suena el pulso, o `None` si es anterior al inicio
+    pub fn beat_sample(&self, beat: i64) -> Option<u64> {
+        let seconds: f64 = beat as f64 * self.beat_seconds - self.origin;
+        let sample: f64 = (seconds * self.sample_rate as f64).round();
+        (sample >= 0.0).then_some(sample as u64)
+    }
+
+    /// Clicks que empiezan en el intervalo de muestras `[from, to)`
+    pub fn clicks_between(&self, from: u64, to: u64) -> Vec<Click> {
+        if self.beat_seconds <= 0.0 || from >= to {
+            return Vec::new();
+        }
+
+        let sample_rate: f64 = self.sample_rate as f64;
+        let from_time: f64 = self.origin + from as f64 / sample_rate;
+        // Un pulso de margen por el redondeo de `beat_sample`
+        let mut beat: i64 = (from_time / self.beat_seconds).floor() as i64 - 1;
+
+        let mut clicks: Vec<Click> = Vec::new();
+        loop {
+            if let Some(sample) = self.beat_sample(beat) {
+                if sample >= to {
+                    break;
+                }
+                if sample >= from {
+                    clicks.push(Click {
+                        sample,
+                        beat,
+                        accent: self.is_accent(beat),
+                    });
+                }
+            }
+            beat += 1;
+        }
+        clicks
+    }
+}
+
+impl AudioSource for ClickScheduler {
+    fn render(&mut self, out: &mut [f32]) {
+        out.fill(0.0);
+        if !self.running {
+            return;
+        }
+
+        let start: u64 = self.position;
+        let end: u64 = start + out.len() as u64;
+        self.position = end;
+
+        if self.volume <= 0.0 {
+            return;
+        }
+
+        // Incluir los clicks que empezaron en el bloque anterior y aún suenan
+        let tail: u64 = self.click.len().max(self.accent.len()) as u64;
+        for click in self.clicks_between(start.saturating_sub(tail), end) {
+            let wave: &[f32] = if click.accent {
+                &self.accent
+            } else {
+                &self.click
+            };
+            for (i, sample) in wave.iter().enumerate() {
+                let absolute: u64 = click.sample + i as u64;
+                if absolute >= start && absolute < end {
+                    out[(absolute - start) as usize] += sample * self.volume;
+                }
+            }
+        }
+    }
+}
+
+/// Click corto: un seno con caída exponencial
+pub fn click_wave(sample_rate: u32, frequency: f32, gain: f32) -> Vec<f32> {
+    let length: usize = (CLICK_SECONDS * sample_rate as f32).round() as usize;
+    let decay: f32 = CLICK_SECONDS / 5.0;
+
+    (0..length)
+        .map(|i| {
+            let t: f32 = i as f32 / sample_rate as f32;
+            gain * (TAU * frequency * t).sin() * (-t / decay).exp()
+        })
+        .collect()
+}
+
+/// Metrónomo de la aplicación: el programador de clicks conectado a la salida de audio.
+/// Si no hay dispositivo de salida sigue funcionando en silencio.
+pub struct Metronome {
+    scheduler: Arc<Mutex<ClickScheduler>>,
+    _output: Option<AudioOutput>,
+    enabled: bool,
+}
+
+impl Metronome {
+    pub fn new(settings: &MetronomeSettings) -> Self {
+        let (output, scheduler) = match AudioOutput::open(ClickScheduler::new) {
+            Ok((output, scheduler)) => (Some(output), scheduler),
+            Err(e) => {
+                log::error!("Metrónomo sin sonido: {}", e);
+                let scheduler: ClickScheduler = ClickScheduler::new(FALLBACK_SAMPLE_RATE);
+                (None, Arc::new(Mutex::new(scheduler)))
+            }
+        };
+
+        let mut metronome: Metronome = Metronome {
+            scheduler,
+            _output: output,
+            enabled: false,
+        };
+        metronome.apply_settings(settings);
+        metronome
+    }
+
+    pub fn apply_settings(&mut self, settings: &MetronomeSettings) {
+        self.enabled = settings.enabled;
+        self.with_scheduler(|scheduler| {
+            scheduler.set_volume(settings.volume);
+            if !settings.enabled {
+                scheduler.stop();
+            }
+        });
+    }
+
+    pub fn configure(&self, metadata: &PieceMetadata) {
+        self.with_scheduler(|scheduler| scheduler.configure(metadata));
+    }
+
+    // Empezar desde un segundo de la partitura, si el metrónomo está activado
+    pub fn start(&self, score_time: f32) {
+        if self.enabled {
+            self.with_scheduler(|scheduler| scheduler.start(score_time));
+        }
+    }
+
+    pub fn stop(&self) {
+        self.with_scheduler(ClickScheduler::stop);
+    }
+
+    /// Cuenta de `count_in` pulsos que sigue sonando desde el momento de tocar, aunque el
+    /// metrónomo esté desactivado. Cambia el compás, así que hay que volver a `configure`.
+    pub fn cue(&self, count_in: u32, beats_per_bar: u32, beat_seconds: f32) {
+        self.with_scheduler(|scheduler| {
+            scheduler.set_meter(beats_per_bar, beat_seconds);
+            scheduler.start(-(count_in as f32) * beat_seconds);
+        });
+    }
+
+    fn with_scheduler(&self, f: impl FnOnce(&mut ClickScheduler)) {
+        match self.scheduler.lock() {
+            Ok(mut scheduler) => f(&mut scheduler),
+            Err(e) => log::error!("{}", e),
+        }
+    }
+}
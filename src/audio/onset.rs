@@ -0,0 +1,159 @@
+use {crate::audio::spectrum::SpectrumAnalyzer, std::collections::VecDeque};
+
+/// Parámetros del detector de ataques
+#[derive(Debug, Clone, PartialEq)]
+pub struct OnsetConfig {
+    pub frame_len: usize,   // Muestras por bloque
+    pub hop: usize,         // Muestras entre bloques consecutivos
+    pub compression: f32,   // Compresión logarítmica de las magnitudes: log(1 + c·|X|)
+    pub history: usize,     // Bloques anteriores usados para el umbral adaptativo
+    pub multiplier: f32,    // El flujo debe superar la media reciente multiplicada por esto
+    pub threshold: f32,     // Flujo mínimo absoluto (se sube al calibrar el ruido)
+    pub min_interval: f32,  // Segundos mínimos entre dos ataques
+    pub max_frequency: f32, // Solo se tienen en cuenta los bins por debajo de esta frecuencia
+    pub masking: f32,       // Fracción de la fuerza del último ataque que debe superar el siguiente
+    pub masking_time: f32,  // Segundos tras un ataque durante los que se aplica `masking`
+}
+
+impl Default for OnsetConfig {
+    fn default() -> Self {
+        OnsetConfig {
+            frame_len: 1024,
+            hop: 256,
+            compression: 100.0,
+            history: 8,
+            multiplier: 1.5,
+            threshold: 4.0,
+            min_interval: 0.06,
+            max_frequency: 6000.0,
+            masking: 0.2,
+            masking_time: 0.5,
+        }
+    }
+}
+
+/// Ataque detectado en el bloque `frame`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Onset {
+    pub frame: u64,
+    pub strength: f32, // Flujo espectral del bloque
+}
+
+/// Detector de ataques por flujo espectral: suma de los aumentos de magnitud (comprimida)
+/// entre bloques consecutivos, con umbral adaptativo y búsqueda de máximos locales.
+/// Se alimenta bloque a bloque, así que funciona sobre audio en directo.
+pub struct OnsetDetector {
+    config: OnsetConfig,
+    spectrum: SpectrumAnalyzer,
+    max_bin: usize,
+    previous: Option<Vec<f32>>, // Magnitudes comprimidas del bloque anterior
+    flux: VecDeque<f32>,        // Flujo de los últimos bloques
+    frame: u64,                 // Índice del siguiente bloque
+    last_onset: Option<Onset>,
+}
+
+impl OnsetDetector {
+    pub fn new(config: OnsetConfig, sample_rate: u32) -> Self {
+        let spectrum: SpectrumAnalyzer = SpectrumAnalyzer::new(config.frame_len, config.frame_len);
+        let max_bin: usize = (spectrum.frequency_bin(config.max_frequency, sample_rate) as usize)
+            .clamp(1, spectrum.bins());
+
+        OnsetDetector {
+            config,
+            spectrum,
+            max_bin,
+            previous: None,
+            flux: VecDeque::new(),
+            frame: 0,
+            last_onset: None,
+        }
+    }
+
+    pub fn config(&self) -> &OnsetConfig {
+        &self.config
+    }
+
+    pub fn set_threshold(&mut self, threshold: f32) {
+        self.config.threshold = threshold;
+    }
+
+    /// Flujo espectral de un bloque respecto al anterior
+    pub fn flux(&mut self, frame: &[f32]) -> f32 {
+        let compression: f32 = self.config.compression;
+        let current: Vec<f32> = self.spectrum.magnitudes(frame)[..self.max_bin]
+            .iter()
+            .map(|m| (1.0 + compression * m).ln())
+            .collect();
+
+        let flux: f32 = match &self.previous {
+            Some(previous) => current
+                .iter()
+                .zip(previous)
+                .map(|(now, before)| (now - before).max(0.0))
+                .sum(),
+            None => 0.0,
+        };
+        self.previous = Some(current);
+        flux
+    }
+
+    /// Procesa el siguiente bloque. Como hay que ver el bloque siguiente para saber
+    /// si hay un máximo, el ataque que se devuelve es el del bloque anterior.
+    pub fn process(&mut self, frame: &[f32], sample_rate: u32) -> Option<Onset> {
+        let flux: f32 = self.flux(frame);
+        self.flux.push_back(flux);
+        self.frame += 1;
+
+        // Se necesitan el bloque candidato, el anterior y el siguiente, más el historial
+        let len: usize = self.flux.len();
+        let onset: Option<Onset> = if len >= 3 {
+            let before: f32 = self.flux[len - 3];
+            let candidate: f32 = self.flux[len - 2];
+            let after: f32 = self.flux[len - 1];
+
+            let history: usize = (len - 2).min(self.config.history);
+            let mean: f32 = if history > 0 {
+                self.flux.range(len - 2 - history..len - 2).sum::<f32>() / history as f32
+            } else {
+                0.0
+            };
+
+            let frame: u64 = self.frame - 2;
+            let min_frames: u64 =
+                (self.config.min_interval * sample_rate as f32 / self.config.hop as f32) as u64;
+            let far_enough: bool = self
+                .last_onset
+                .is_none_or(|last| frame >= last.frame + min_frames.max(1));
+
+            // Tras un ataque fuerte, los batidos entre parciales dan picos pequeños de flujo
+            let masking_frames: u64 =
+                (self.config.masking_time * sample_rate as f32 / self.config.hop as f32) as u64;
+            let masked: f32 = self
+                .last_onset
+                .filter(|last| frame < last.frame + masking_frames)
+                .map_or(0.0, |last| last.strength * self.config.masking);
+
+            let is_peak: bool = candidate > before && candidate >= after;
+            let above: bool = candidate > self.config.threshold
+                && candidate > mean * self.config.multiplier
+                && candidate > masked;
+
+            (is_peak && above && far_enough).then_some(Onset {
+                frame,
+                strength: candidate,
+            })
+        } else {
+            None
+        };
+
+        if onset.is_some() {
+            self.last_onset = onset;
+        }
+
+        // Solo se guarda el historial necesario
+        while self.flux.len() > self.config.history + 3 {
+            self.flux.pop_front();
+        }
+        onset
+    }
+}
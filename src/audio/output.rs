@@ -0,0 +1,123 @@
+use {
+    cpal::{
+        Device, FromSample, Host, SampleFormat, SizedSample, Stream, StreamConfig,
+        SupportedStreamConfig,
+        traits::{DeviceTrait, HostTrait, StreamTrait},
+    },
+    std::{
+        error, fmt,
+        sync::{Arc, Mutex},
+    },
+};
+
+/// Fuente de audio mono que se renderiza bloque a bloque desde el hilo de audio
+pub trait AudioSource: Send + 'static {
+    fn render(&mut self, out: &mut [f32]);
+}
+
+/// Errores al abrir la salida de audio
+#[derive(Debug)]
+pub enum AudioError {
+    NoDevice,       // No hay dispositivo de salida
+    NoInputDevice,  // No hay micrófono
+    Config(String), // El dispositivo no da una configuración válida
+    Stream(String), // No se pudo crear o arrancar el stream
+}
+
+impl fmt::Display for AudioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AudioError::NoDevice => write!(f, "no hay ningún dispositivo de salida de audio"),
+            AudioError::NoInputDevice => write!(f, "no hay ningún micrófono"),
+            AudioError::Config(e) => write!(f, "configuración de audio no válida: {}", e),
+            AudioError::Stream(e) => write!(f, "no se pudo abrir el dispositivo de audio: {}", e),
+        }
+    }
+}
+
+impl error::Error for AudioError {}
+
+/// Salida de audio por el dispositivo por defecto. El sonido se detiene al soltarla.
+pub struct AudioOutput {
+    _stream: Stream,
+    sample_rate: u32,
+}
+
+impl AudioOutput {
+    /// Abre el dispositivo por defecto y crea la fuente con su frecuencia de muestreo.
+    /// Devuelve la salida junto a la fuente compartida con el hilo de audio.
+    pub fn open<S, F>(make_source: F) -> Result<(Self, Arc<Mutex<S>>), AudioError>
+    where
+        S: AudioSource,
+        F: FnOnce(u32) -> S,
+    {
+        let host: Host = cpal::default_host();
+        let device: Device = host.default_output_device().ok_or(AudioError::NoDevice)?;
+        let supported: SupportedStreamConfig = device
+            .default_output_config()
+            .map_err(|e| AudioError::Config(e.to_string()))?;
+
+        let sample_format: SampleFormat = supported.sample_format();
+        let config: StreamConfig = supported.into();
+        let sample_rate: u32 = config.sample_rate.0;
+        let source: Arc<Mutex<S>> = Arc::new(Mutex::new(make_source(sample_rate)));
+
+        let stream: Stream = match sample_format {
+            SampleFormat::F32 => build_stream::<f32, S>(&device, &config, source.clone()),
+            SampleFormat::I16 => build_stream::<i16, S>(&device, &config, source.clone()),
+            SampleFormat::U16 => build_stream::<u16, S>(&device, &config, source.clone()),
+            other => Err(AudioError::Config(format!(
+                "formato {} no soportado",
+                other
+            ))),
+        }?;
+        stream
+            .play()
+            .map_err(|e| AudioError::Stream(e.to_string()))?;
+
+        Ok((
+            AudioOutput {
+                _stream: stream,
+                sample_rate,
+            },
+            source,
+        ))
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+}
+
+// Crea el stream copiando la señal mono de la fuente a todos los canales
+fn build_stream<T, S>(
+    device: &Device,
+    config: &StreamConfig,
+    source: Arc<Mutex<S>>,
+) -> Result<Stream, AudioError>
+where
+    T: SizedSample + FromSample<f32>,
+    S: AudioSource,
+{
+    let channels: usize = config.channels.max(1) as usize;
+    let mut mono: Vec<f32> = Vec::new();
+
+    device
+        .build_output_stream(
+            config,
+            move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+                let frames: usize = data.len() / channels;
+                mono.resize(frames, 0.0);
+                match source.lock() {
+                    Ok(mut source) => source.render(&mut mono),
+                    Err(_) => mono.fill(0.0),
+                }
+                for (frame, sample) in data.chunks_mut(channels).zip(mono.iter()) {
+                    frame.fill(T::from_sample(*sample));
+                }
+            },
+            |e| log::error!("Error en la salida de audio: {}", e),
+            None,
+        )
+        .map_err(|e| AudioError::Stream(e.to_string()))
+}
@@ -0,0 +1,178 @@
+use crate::audio::spectrum::{SpectrumAnalyzer, midi_to_frequency};
+
+/// Parámetros del estimador de tonos
+#[derive(Debug, Clone, PartialEq)]
+pub struct PitchConfig {
+    pub a4: f32,                 // Frecuencia de referencia del La4
+    pub lowest: u8,              // Tono MIDI más grave que se busca
+    pub highest: u8,             // Tono MIDI más agudo que se busca
+    pub harmonics: usize,        // Armónicos sumados por candidato
+    pub tolerance: f32,          // Desviación relativa admitida para cada armónico
+    pub inharmonicity: f32,      // Coeficiente B de las cuerdas: f_h = h·f0·sqrt(1 + B·h²)
+    pub max_polyphony: usize,    // Notas simultáneas como máximo
+    pub relative_threshold: f32, // Saliencia mínima respecto a la de la primera nota
+    pub min_salience: f32,       // Saliencia mínima absoluta
+}
+
+impl Default for PitchConfig {
+    fn default() -> Self {
+        PitchConfig {
+            a4: 440.0,
+            lowest: 21,
+            highest: 108,
+            harmonics: 10,
+            tolerance: 0.025,
+            inharmonicity: 0.0002,
+            max_polyphony: 6,
+            relative_threshold: 0.3,
+            min_salience: 0.01,
+        }
+    }
+}
+
+/// Estimador de varios tonos simultáneos (acordes de piano) a partir de un espectro.
+/// Sigue la idea de la estimación iterativa con cancelación: se elige el tono con mayor
+/// suma ponderada de armónicos, se restan sus armónicos del espectro y se repite.
+/// Al restar se usa una envolvente suavizada, así un armónico compartido con otra
+/// nota (por ejemplo el tercero de Sol2 y la fundamental de Re4) no desaparece del todo.
+pub struct PitchEstimator {
+    config: PitchConfig,
+    sample_rate: u32,
+    bin_hz: f32,
+}
+
+impl PitchEstimator {
+    pub fn new(config: PitchConfig, sample_rate: u32, spectrum: &SpectrumAnalyzer) -> Self {
+        PitchEstimator {
+            config,
+            sample_rate,
+            bin_hz: spectrum.bin_frequency(1, sample_rate),
+        }
+    }
+
+    pub fn config(&self) -> &PitchConfig {
+        &self.config
+    }
+
+    pub fn set_a4(&mut self, a4: f32) {
+        self.config.a4 = a4;
+    }
+
+    /// Tonos MIDI presentes en el espectro, ordenados de grave a agudo.
+    /// `noise` es el espectro medio del ruido de fondo y se resta antes de buscar.
+    pub fn estimate(&self, magnitudes: &[f32], noise: Option<&[f32]>) -> Vec<u8> {
+        let mut residual: Vec<f32> = match noise {
+            Some(noise) => magnitudes
+                .iter()
+                .zip(noise.iter().chain(std::iter::repeat(&0.0)))
+                .map(|(m, n)| (m - 2.0 * n).max(0.0))
+                .collect(),
+            None => magnitudes.to_vec(),
+        };
+
+        let mut pitches: Vec<u8> = Vec::new();
+        let mut first_salience: Option<f32> = None;
+
+        while pitches.len() < self.config.max_polyphony {
+            let best: Option<(u8, f32)> = (self.config.lowest..=self.config.highest)
+                .filter(|pitch| !pitches.contains(pitch))
+                .map(|pitch| (pitch, self.salience(&residual, pitch)))
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+
+            let Some((pitch, salience)) = best else {
+                break;
+            };
+            let reference: f32 = *first_salience.get_or_insert(salience);
+            if salience < self.config.min_salience
+                || salience < reference * self.config.relative_threshold
+            {
+                break;
+            }
+
+            pitches.push(pitch);
+            self.cancel(&mut residual, pitch);
+        }
+
+        pitches.sort_unstable();
+        pitches
+    }
+
+    /// Saliencia de un tono concreto: suma ponderada de los picos en sus armónicos
+    pub fn salience_of(&self, spectrum: &[f32], pitch: u8) -> f32 {
+        self.salience(spectrum, pitch)
+    }
+
+    fn salience(&self, spectrum: &[f32], pitch: u8) -> f32 {
+        let f0: f32 = midi_to_frequency(pitch as f32, self.config.a4);
+        self.harmonic_peaks(spectrum, f0)
+            .iter()
+            .enumerate()
+            .map(|(i, (_, amplitude))| harmonic_weight(f0, i + 1) * amplitude)
+            .sum()
+    }
+
+    // Resta los armónicos del tono usando una envolvente suavizada de sus amplitudes
+    fn cancel(&self, spectrum: &mut [f32], pitch: u8) {
+        let f0: f32 = midi_to_frequency(pitch as f32, self.config.a4);
+        let peaks: Vec<(usize, f32)> = self.harmonic_peaks(spectrum, f0);
+        let amplitudes: Vec<f32> = peaks.iter().map(|(_, a)| *a).collect();
+
+        for (i, (bin, amplitude)) in peaks.iter().enumerate() {
+            if *amplitude <= 0.0 {
+                continue;
+            }
+            // El menor de los armónicos vecinos, sin superar la amplitud medida: si este
+            // armónico es además la fundamental de otra nota, sus vecinos no lo son
+            let smooth: f32 = [i.checked_sub(1), Some(i + 1)]
+                .iter()
+                .filter_map(|j| j.and_then(|j| amplitudes.get(j)))
+                .fold(*amplitude, |m, a| m.min(*a));
+            // El primer armónico se quita entero: es el que identifica la nota
+            let remove: f32 = if i == 0 { 1.0 } else { smooth / amplitude };
+
+            // El pico ocupa unos pocos bins por la ventana
+            let width: usize = 3;
+            let end: usize = (bin + width + 1).min(spectrum.len());
+            for value in spectrum
+                .iter_mut()
+                .take(end)
+                .skip(bin.saturating_sub(width))
+            {
+                *value *= 1.0 - remove;
+            }
+        }
+    }
+
+    // Bin y amplitud del pico más alto cerca de cada armónico
+    fn harmonic_peaks(&self, spectrum: &[f32], f0: f32) -> Vec<(usize, f32)> {
+        let nyquist: f32 = self.sample_rate as f32 / 2.0;
+        let mut peaks: Vec<(usize, f32)> = Vec::new();
+
+        for h in 1..=self.config.harmonics {
+            let h_f: f32 = h as f32;
+            let frequency: f32 = h_f * f0 * (1.0 + self.config.inharmonicity * h_f * h_f).sqrt();
+            if frequency >= nyquist {
+                break;
+            }
+
+            // Al menos un bin a cada lado para las notas graves
+            let spread: f32 = (frequency * self.config.tolerance).max(self.bin_hz);
+            let low: usize = ((frequency - spread) / self.bin_hz).floor().max(0.0) as usize;
+            let high: usize = (((frequency + spread) / self.bin_hz).ceil() as usize)
+                .min(spectrum.len().saturating_sub(1));
+
+            let peak: (usize, f32) = (low..=high)
+                .map(|bin| (bin, spectrum[bin]))
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap_or((low, 0.0));
+            peaks.push(peak);
+        }
+
+        peaks
+    }
+}
+
+// Peso de cada armónico: los graves pesan más, como en el estimador de Klapuri
+fn harmonic_weight(f0: f32, harmonic: usize) -> f32 {
+    (f0 + 27.0) / (harmonic as f32 * f0 + 320.0)
+}
@@ -0,0 +1,88 @@
+use {
+    rustfft::{Fft, FftPlanner, num_complex::Complex},
+    std::{f32::consts::TAU, sync::Arc},
+};
+
+/// Ventana de Hann
+pub fn hann(len: usize) -> Vec<f32> {
+    (0..len)
+        .map(|i| 0.5 - 0.5 * (TAU * i as f32 / len as f32).cos())
+        .collect()
+}
+
+/// Frecuencia en Hz de un tono MIDI (puede ser fraccionario) con la referencia del La4
+pub fn midi_to_frequency(pitch: f32, a4: f32) -> f32 {
+    a4 * 2f32.powf((pitch - 69.0) / 12.0)
+}
+
+/// Tono MIDI fraccionario de una frecuencia con la referencia del La4
+pub fn frequency_to_midi(frequency: f32, a4: f32) -> f32 {
+    69.0 + 12.0 * (frequency / a4).log2()
+}
+
+/// Calcula el espectro de magnitudes de bloques de audio con ventana de Hann.
+/// El bloque se completa con ceros hasta `fft_len` para interpolar el espectro.
+pub struct SpectrumAnalyzer {
+    fft: Arc<dyn Fft<f32>>,
+    window: Vec<f32>,
+    fft_len: usize,
+    scale: f32, // Normaliza para que un seno de amplitud A dé un pico de A
+    buffer: Vec<Complex<f32>>,
+}
+
+impl SpectrumAnalyzer {
+    pub fn new(frame_len: usize, fft_len: usize) -> Self {
+        let fft_len: usize = fft_len.max(frame_len);
+        let window: Vec<f32> = hann(frame_len);
+        let scale: f32 = 2.0 / window.iter().sum::<f32>().max(f32::EPSILON);
+
+        SpectrumAnalyzer {
+            fft: FftPlanner::new().plan_fft_forward(fft_len),
+            window,
+            fft_len,
+            scale,
+            buffer: vec![Complex::default(); fft_len],
+        }
+    }
+
+    pub fn frame_len(&self) -> usize {
+        self.window.len()
+    }
+
+    pub fn fft_len(&self) -> usize {
+        self.fft_len
+    }
+
+    // Número de bins del espectro (hasta la frecuencia de Nyquist incluida)
+    pub fn bins(&self) -> usize {
+        self.fft_len / 2 + 1
+    }
+
+    /// Magnitudes del bloque. Si `frame` es más corto que la ventana se completa con ceros.
+    pub fn magnitudes(&mut self, frame: &[f32]) -> Vec<f32> {
+        for (i, value) in self.buffer.iter_mut().enumerate() {
+            let sample: f32 = match (frame.get(i), self.window.get(i)) {
+                (Some(sample), Some(weight)) => sample * weight,
+                _ => 0.0,
+            };
+            *value = Complex::new(sample, 0.0);
+        }
+
+        self.fft.process(&mut self.buffer);
+
+        self.buffer[..self.bins()]
+            .iter()
+            .map(|c| c.norm() * self.scale)
+            .collect()
+    }
+
+    // Frecuencia central de un bin
+    pub fn bin_frequency(&self, bin: usize, sample_rate: u32) -> f32 {
+        bin as f32 * sample_rate as f32 / self.fft_len as f32
+    }
+
+    // Bin (fraccionario) de una frecuencia
+    pub fn frequency_bin(&self, frequency: f32, sample_rate: u32) -> f32 {
+        frequency * self.fft_len as f32 / sample_rate as f32
+    }
+}
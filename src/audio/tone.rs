@@ -0,0 +1,191 @@
+use {
+    crate::audio::output::{AudioOutput, AudioSource},
+    std::{
+        f32::consts::TAU,
+        sync::{Arc, Mutex},
+    },
+};
+
+// Ataque y caída al soltar, en segundos, para que las notas no chasqueen
+const ATTACK_SECONDS: f32 = 0.01;
+const RELEASE_SECONDS: f32 = 0.08;
+
+// Caída del sonido mientras la nota se mantiene, como una cuerda de piano
+const DECAY_SECONDS: f32 = 1.2;
+
+// Intensidad de los armónicos sobre la fundamental: un timbre suave, menos puro que un seno
+const HARMONICS: [f32; 4] = [1.0, 0.5, 0.25, 0.12];
+
+// Volumen de cada nota. Se deja margen para acordes de cuatro notas sin saturar.
+const NOTE_GAIN: f32 = 0.15;
+
+// Frecuencia de muestreo usada cuando no hay salida de audio
+const FALLBACK_SAMPLE_RATE: u32 = 44_100;
+
+/// Nota que suena desde `start` segundos durante `duration` segundos
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToneNote {
+    pub pitch: u8,
+    pub start: f32,
+    pub duration: f32,
+}
+
+impl ToneNote {
+    pub fn new(pitch: u8, start: f32, duration: f32) -> Self {
+        ToneNote {
+            pitch,
+            start,
+            duration,
+        }
+    }
+}
+
+// Nota programada en muestras contadas desde `ToneGenerator::play`
+#[derive(Debug, Clone, Copy)]
+struct Voice {
+    frequency: f32,
+    start: u64,
+    length: u64,
+}
+
+/// Sintetizador sencillo: toca una lista de notas programadas con precisión de muestra
+pub struct ToneGenerator {
+    sample_rate: u32,
+    voices: Vec<Voice>,
+    position: u64, // Muestras renderizadas desde `play`
+    end: u64,      // Muestra en la que se apaga la última nota
+}
+
+impl ToneGenerator {
+    pub fn new(sample_rate: u32) -> Self {
+        ToneGenerator {
+            sample_rate: sample_rate.max(1),
+            voices: Vec::new(),
+            position: 0,
+            end: 0,
+        }
+    }
+
+    /// Empezar a tocar las notas, cortando lo que estuviera sonando
+    pub fn play(&mut self, notes: &[ToneNote]) {
+        let rate: f32 = self.sample_rate as f32;
+        self.voices = notes
+            .iter()
+            .map(|note| Voice {
+                frequency: pitch_frequency(note.pitch),
+                start: (note.start.max(0.0) * rate).round() as u64,
+                length: (note.duration.max(0.0) * rate).round() as u64,
+            })
+            .collect();
+        let release: u64 = (RELEASE_SECONDS * rate).round() as u64;
+        self.end = self
+            .voices
+            .iter()
+            .map(|voice| voice.start + voice.length + release)
+            .max()
+            .unwrap_or(0);
+        self.position = 0;
+    }
+
+    pub fn stop(&mut self) {
+        self.voices.clear();
+        self.end = 0;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.position < self.end
+    }
+
+    // Envolvente de una nota `elapsed` segundos después de empezar
+    fn envelope(&self, elapsed: f32, length: f32) -> f32 {
+        let attack: f32 = (elapsed / ATTACK_SECONDS).min(1.0);
+        let sustain: f32 = (-elapsed / DECAY_SECONDS).exp();
+        let release: f32 = if elapsed > length {
+            (1.0 - (elapsed - length) / RELEASE_SECONDS).max(0.0)
+        } else {
+            1.0
+        };
+        attack * sustain * release
+    }
+}
+
+impl AudioSource for ToneGenerator {
+    fn render(&mut self, out: &mut [f32]) {
+        out.fill(0.0);
+        if !self.is_playing() {
+            return;
+        }
+
+        let start: u64 = self.position;
+        let end: u64 = start + out.len() as u64;
+        self.position = end;
+
+        let rate: f32 = self.sample_rate as f32;
+        let release: u64 = (RELEASE_SECONDS * rate).round() as u64;
+        for voice in &self.voices {
+            let voice_end: u64 = voice.start + voice.length + release;
+            if voice_end <= start || voice.start >= end {
+                continue;
+            }
+            let length: f32 = voice.length as f32 / rate;
+            for absolute in voice.start.max(start)..voice_end.min(end) {
+                let elapsed: f32 = (absolute - voice.start) as f32 / rate;
+                let wave: f32 = HARMONICS
+                    .iter()
+                    .enumerate()
+                    .map(|(i, gain)| {
+                        gain * (TAU * voice.frequency * (i + 1) as f32 * elapsed).sin()
+                    })
+                    .sum();
+                out[(absolute - start) as usize] +=
+                    NOTE_GAIN * wave * self.envelope(elapsed, length);
+            }
+        }
+    }
+}
+
+/// Frecuencia temperada de un tono MIDI con el La4 a 440 Hz
+pub fn pitch_frequency(pitch: u8) -> f32 {
+    440.0 * 2f32.powf((pitch as f32 - 69.0) / 12.0)
+}
+
+/// Generador de notas conectado a la salida de audio.
+/// Si no hay dispositivo de salida sigue funcionando en silencio.
+pub struct TonePlayer {
+    generator: Arc<Mutex<ToneGenerator>>,
+    _output: Option<AudioOutput>,
+}
+
+impl TonePlayer {
+    pub fn new() -> Self {
+        let (output, generator) = match AudioOutput::open(ToneGenerator::new) {
+            Ok((output, generator)) => (Some(output), generator),
+            Err(e) => {
+                log::error!("Generador de notas sin sonido: {}", e);
+                let generator: ToneGenerator = ToneGenerator::new(FALLBACK_SAMPLE_RATE);
+                (None, Arc::new(Mutex::new(generator)))
+            }
+        };
+        TonePlayer {
+            generator,
+            _output: output,
+        }
+    }
+
+    pub fn play(&self, notes: &[ToneNote]) {
+        self.with_generator(|generator| generator.play(notes));
+    }
+
+    fn with_generator(&self, f: impl FnOnce(&mut ToneGenerator)) {
+        match self.generator.lock() {
+            Ok(mut generator) => f(&mut generator),
+            Err(e) => log::error!("{}", e),
+        }
+    }
+}
+
+impl Default for TonePlayer {
+    fn default() -> Self {
+        TonePlayer::new()
+    }
+}
@@ -0,0 +1,83 @@
+use crate::audio::spectrum::{SpectrumAnalyzer, frequency_to_midi, midi_to_frequency};
+
+// Relleno con ceros de la FFT para medir la frecuencia con precisión de céntimos
+const ZERO_PADDING: usize = 8;
+
+// Armónicos usados para medir la frecuencia de una nota
+const HARMONICS: usize = 3;
+
+// Coeficiente de inharmonicidad supuesto para corregir los armónicos
+const INHARMONICITY: f32 = 0.0002;
+
+/// Frecuencia fundamental de una nota conocida en un fragmento de audio. Busca cada
+/// armónico a menos de un semitono de donde debería estar con `a4`, afina su pico con
+/// una interpolación parabólica y promedia las fundamentales que indican, pesadas por amplitud.
+pub fn measure_frequency(samples: &[f32], sample_rate: u32, pitch: u8, a4: f32) -> Option<f32> {
+    if samples.is_empty() {
+        return None;
+    }
+    let mut analyzer: SpectrumAnalyzer =
+        SpectrumAnalyzer::new(samples.len(), samples.len() * ZERO_PADDING);
+    let magnitudes: Vec<f32> = analyzer.magnitudes(samples);
+    let expected: f32 = midi_to_frequency(pitch as f32, a4);
+
+    let mut total: f32 = 0.0;
+    let mut weight: f32 = 0.0;
+    for h in 1..=HARMONICS {
+        let stretch: f32 = h as f32 * (1.0 + INHARMONICITY * (h * h) as f32).sqrt();
+        let low: f32 = expected * stretch * 2f32.powf(-1.0 / 12.0);
+        let high: f32 = expected * stretch * 2f32.powf(1.0 / 12.0);
+
+        let low_bin: usize = analyzer.frequency_bin(low, sample_rate).floor().max(1.0) as usize;
+        let high_bin: usize = (analyzer.frequency_bin(high, sample_rate).ceil() as usize)
+            .min(magnitudes.len().saturating_sub(2));
+        if low_bin >= high_bin {
+            break;
+        }
+
+        let Some((bin, amplitude)) = (low_bin..=high_bin)
+            .map(|bin| (bin, magnitudes[bin]))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+        else {
+            continue;
+        };
+        let peak: f32 = bin as f32 + parabolic_offset(&magnitudes, bin);
+        total += amplitude * analyzer.bin_frequency(1, sample_rate) * peak / stretch;
+        weight += amplitude;
+    }
+
+    (weight > 0.0).then(|| total / weight)
+}
+
+/// La4 que da una nota medida: la frecuencia que tendría el La si la nota estuviera afinada
+pub fn a4_from(pitch: u8, frequency: f32) -> f32 {
+    frequency * 2f32.powf((69.0 - pitch as f32) / 12.0)
+}
+
+/// Desviación en céntimos de una frecuencia respecto a la nota temperada con `a4`
+pub fn cents_off(frequency: f32, pitch: u8, a4: f32) -> f32 {
+    (frequency_to_midi(frequency, a4) - pitch as f32) * 100.0
+}
+
+/// Mediana, más robusta que la media si una de las lecturas sale mal
+pub fn median(values: &[f32]) -> Option<f32> {
+    let mut sorted: Vec<f32> = values.to_vec();
+    sorted.sort_by(f32::total_cmp);
+    let len: usize = sorted.len();
+    match len {
+        0 => None,
+        _ if len % 2 == 1 => Some(sorted[len / 2]),
+        _ => Some((sorted[len / 2 - 1] + sorted[len / 2]) / 2.0),
+    }
+}
+
+// Desplazamiento del máximo real respecto al bin, entre -0.5 y 0.5
+fn parabolic_offset(magnitudes: &[f32], bin: usize) -> f32 {
+    let (a, b, c) = (magnitudes[bin - 1], magnitudes[bin], magnitudes[bin + 1]);
+    let denominator: f32 = a - 2.0 * b + c;
+    if denominator.abs() < f32::EPSILON {
+        0.0
+    } else {
+        (0.5 * (a - c) / denominator).clamp(-0.5, 0.5)
+    }
+}
@@ -0,0 +1,151 @@
+use std::{error, fmt, fs, io, path::Path};
+
+/// Errores al leer un archivo WAV
+#[derive(Debug)]
+pub enum WavError {
+    Io(io::Error),
+    Format(String), // Cabecera no válida o formato no soportado
+}
+
+impl fmt::Display for WavError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WavError::Io(e) => write!(f, "{}", e),
+            WavError::Format(e) => write!(f, "WAV no válido: {}", e),
+        }
+    }
+}
+
+impl error::Error for WavError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            WavError::Io(e) => Some(e),
+            WavError::Format(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for WavError {
+    fn from(e: io::Error) -> Self {
+        WavError::Io(e)
+    }
+}
+
+/// Audio mono con su frecuencia de muestreo
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wav {
+    pub sample_rate: u32,
+    pub samples: Vec<f32>, // Entre -1.0 y 1.0
+}
+
+/// Lee un WAV PCM (8, 16, 24 o 32 bits) o de coma flotante (32 bits), mezclando los canales a mono
+pub fn read_wav(path: &Path) -> Result<Wav, WavError> {
+    parse_wav(&fs::read(path)?)
+}
+
+pub fn parse_wav(bytes: &[u8]) -> Result<Wav, WavError> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err(WavError::Format("falta la cabecera RIFF/WAVE".to_string()));
+    }
+
+    let mut format: Option<(u16, u16, u32, u16)> = None; // Formato, canales, frecuencia, bits
+    let mut data: Option<&[u8]> = None;
+
+    // Recorrer los bloques del archivo
+    let mut pos: usize = 12;
+    while pos + 8 <= bytes.len() {
+        let id: &[u8] = &bytes[pos..pos + 4];
+        let size: usize = u32::from_le_bytes([
+            bytes[pos + 4],
+            bytes[pos + 5],
+            bytes[pos + 6],
+            bytes[pos + 7],
+        ]) as usize;
+        let body: &[u8] = &bytes[pos + 8..(pos + 8 + size).min(bytes.len())];
+
+        match id {
+            b"fmt " if body.len() >= 16 => {
+                let mut tag: u16 = u16::from_le_bytes([body[0], body[1]]);
+                // WAVE_FORMAT_EXTENSIBLE: el formato real está en el subformato
+                if tag == 0xFFFE && body.len() >= 26 {
+                    tag = u16::from_le_bytes([body[24], body[25]]);
+                }
+                format = Some((
+                    tag,
+                    u16::from_le_bytes([body[2], body[3]]),
+                    u32::from_le_bytes([body[4], body[5], body[6], body[7]]),
+                    u16::from_le_bytes([body[14], body[15]]),
+                ));
+            }
+            b"data" => data = Some(body),
+            _ => {}
+        }
+
+        // Los bloques ocupan un número par de bytes
+        pos += 8 + size + size % 2;
+    }
+
+    let (tag, channels, sample_rate, bits) =
+        format.ok_or_else(|| WavError::Format("falta el bloque fmt".to_string()))?;
+    let data: &[u8] = data.ok_or_else(|| WavError::Format("falta el bloque data".to_string()))?;
+    if channels == 0 {
+        return Err(WavError::Format("el archivo no tiene canales".to_string()));
+    }
+
+    let decode: fn(&[u8]) -> f32 = match (tag, bits) {
+        (1, 8) => |b| (b[0] as f32 - 128.0) / 128.0,
+        (1, 16) => |b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32_768.0,
+        (1, 24) => |b| (i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8) as f32 / 8_388_608.0,
+        (1, 32) => |b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2_147_483_648.0,
+        (3, 32) => |b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+        _ => {
+            return Err(WavError::Format(format!(
+                "formato {} de {} bits no soportado",
+                tag, bits
+            )));
+        }
+    };
+
+    let sample_bytes: usize = bits as usize / 8;
+    let frame_bytes: usize = sample_bytes * channels as usize;
+    let samples: Vec<f32> = data
+        .chunks_exact(frame_bytes)
+        .map(|frame| frame.chunks_exact(sample_bytes).map(decode).sum::<f32>() / channels as f32)
+        .collect();
+
+    Ok(Wav {
+        sample_rate,
+        samples,
+    })
+}
+
+/// Escribe audio mono como WAV PCM de 16 bits
+pub fn write_wav(path: &Path, wav: &Wav) -> io::Result<()> {
+    let data_len: u32 = wav.samples.len() as u32 * 2;
+
+    let mut bytes: Vec<u8> = Vec::with_capacity(44 + data_len as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVE");
+
+    bytes.extend_from_slice(b"fmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // Mono
+    bytes.extend_from_slice(&wav.sample_rate.to_le_bytes());
+    bytes.extend_from_slice(&(wav.sample_rate * 2).to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for sample in &wav.samples {
+        let value: i16 = (sample.clamp(-1.0, 1.0) * 32_767.0).round() as i16;
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, bytes)
+}
@@ -0,0 +1,546 @@
+use {
+    octarust::{
+        midi::smf::{piece_to_smf, save_smf},
+        models::{
+            note::Note,
+            partiture::{Hand, PieceMetadata},
+            score::{Piece, ScoreFile},
+            score_error::{Diagnostic, ScoreError, Severity},
+        },
+        utils::{
+            fingering::suggest_fingering,
+            helper_json::{LoadedScore, ScoreFormat, load_any_score_file},
+            import::import_score_file,
+            note_names::note_name,
+            validation::{has_errors, validate_piece, validate_score},
+        },
+    },
+    std::{
+        collections::{BTreeMap, HashMap},
+        env, fmt, fs,
+        io::{self, Write},
+        ops::Range,
+        path::Path,
+        process::ExitCode,
+    },
+};
+
+// Códigos de salida, pensados para usarse desde scripts
+const EXIT_OK: u8 = 0; // Todo correcto
+const EXIT_INVALID: u8 = 1; // La validación encontró errores
+const EXIT_USAGE: u8 = 2; // Argumentos incorrectos
+const EXIT_IO: u8 = 3; // No se pudo leer, interpretar o escribir un archivo
+
+const USAGE: &str = "\
+octarust-cli: valida, convierte e inspecciona archivos de partituras
+
+USO:
+    octarust-cli validate [--strict] [--quiet] <archivo>...
+    octarust-cli convert <archivo> [-o <salida>] [--minify] [--flatten]
+    octarust-cli info <archivo> [<pieza>]
+    octarust-cli expand <archivo> <pieza> [--hand right|left] [--json]
+    octarust-cli midi <archivo> <pieza> -o <salida.mid> [--sections <desde>-<hasta>]
+    octarust-cli fingering <archivo> [<pieza>] [-o <salida>] [--minify]
+    octarust-cli import <archivo.mid|.musicxml> [--name <pieza>] [-o <salida>] [--minify]
+
+COMANDOS:
+    validate   Ejecuta todas las comprobaciones de las partituras
+    convert    Reescribe el archivo en el formato actual (acepta el antiguo notes.json)
+    info       Muestra metadatos, duración, compases y número de notas
+    expand     Muestra la lista de notas tras expandir 'structure'
+    midi       Exporta la pieza como archivo MIDI de dos pistas
+    fingering  Sugiere la digitación de las manos que no tienen ninguna
+    import     Convierte un MIDI o un MusicXML en un archivo de partituras, con matices,
+               articulaciones, ligaduras y pedal
+
+OPCIONES:
+    --strict      Los avisos también hacen fallar la validación
+    --quiet       Solo muestra los problemas encontrados
+    -o, --output  Archivo de salida (por defecto la salida estándar)
+    --minify      JSON compacto en lugar de indentado
+    --flatten     Sustituye las secciones por una única sección en orden de 'structure'
+    --hand        Solo las notas de esa mano
+    --json        Salida en JSON
+    --sections    Entradas de 'structure' a exportar, contando desde 1 (ej: 2-4)
+    --name        Nombre de la pieza importada (por defecto el del archivo)
+
+CÓDIGOS DE SALIDA:
+    0  correcto
+    1  la validación encontró errores
+    2  uso incorrecto
+    3  error al leer, interpretar o escribir un archivo";
+
+// Errores de la herramienta, cada uno con su código de salida
+enum CliError {
+    Usage(String),
+    Score(ScoreError),
+    Write(String, io::Error),
+    Json(serde_json::Error),
+}
+
+impl CliError {
+    fn exit_code(&self) -> u8 {
+        match self {
+            CliError::Usage(_) => EXIT_USAGE,
+            CliError::Score(ScoreError::Invalid { .. }) => EXIT_INVALID,
+            CliError::Score(ScoreError::PieceNotFound { .. }) => EXIT_USAGE,
+            CliError::Score(_) | CliError::Write(..) | CliError::Json(_) => EXIT_IO,
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Usage(msg) => {
+                write!(f, "{}\n\nUsa 'octarust-cli --help' para ver la ayuda", msg)
+            }
+            CliError::Score(e) => write!(f, "{}", e),
+            CliError::Write(path, e) => write!(f, "{}: no se pudo escribir: {}", path, e),
+            CliError::Json(e) => write!(f, "no se pudo generar el JSON: {}", e),
+        }
+    }
+}
+
+impl From<ScoreError> for CliError {
+    fn from(e: ScoreError) -> Self {
+        CliError::Score(e)
+    }
+}
+
+// Argumentos de un subcomando ya separados en posicionales, banderas y opciones con valor
+struct ParsedArgs {
+    positional: Vec<String>,
+    flags: Vec<String>,
+    options: HashMap<String, String>,
+}
+
+impl ParsedArgs {
+    fn parse(args: &[String], flags: &[&str], options: &[&str]) -> Result<Self, CliError> {
+        let mut parsed: ParsedArgs = ParsedArgs {
+            positional: Vec::new(),
+            flags: Vec::new(),
+            options: HashMap::new(),
+        };
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if flags.contains(&arg.as_str()) {
+                parsed.flags.push(arg.clone());
+            } else if options.contains(&arg.as_str()) {
+                let value: &String = iter
+                    .next()
+                    .ok_or_else(|| CliError::Usage(format!("falta el valor de '{}'", arg)))?;
+                parsed.options.insert(arg.clone(), value.clone());
+            } else if arg.starts_with('-') {
+                return Err(CliError::Usage(format!("opción desconocida '{}'", arg)));
+            } else {
+                parsed.positional.push(arg.clone());
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    fn has(&self, flag: &str) -> bool {
+        self.flags.iter().any(|f| f == flag)
+    }
+
+    fn option(&self, names: &[&str]) -> Option<&String> {
+        names.iter().find_map(|name| self.options.get(*name))
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let result: Result<u8, CliError> = match args.first().map(String::as_str) {
+        Some("validate") => validate(&args[1..]),
+        Some("convert") => convert(&args[1..]),
+        Some("info") => info(&args[1..]),
+        Some("expand") => expand(&args[1..]),
+        Some("midi") => midi(&args[1..]),
+        Some("fingering") => fingering(&args[1..]),
+        Some("import") => import(&args[1..]),
+        Some("-h" | "--help" | "help") => {
+            println!("{}", USAGE);
+            Ok(EXIT_OK)
+        }
+        Some(other) => Err(CliError::Usage(format!("comando desconocido '{}'", other))),
+        None => Err(CliError::Usage(USAGE.to_string())),
+    };
+
+    match result {
+        Ok(code) => ExitCode::from(code),
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
+
+// validate: muestra todos los diagnósticos y falla si hay errores
+fn validate(args: &[String]) -> Result<u8, CliError> {
+    let parsed: ParsedArgs = ParsedArgs::parse(args, &["--strict", "--quiet"], &[])?;
+    if parsed.positional.is_empty() {
+        return Err(CliError::Usage(
+            "validate necesita al menos un archivo".into(),
+        ));
+    }
+
+    let strict: bool = parsed.has("--strict");
+    let mut code: u8 = EXIT_OK;
+
+    for path in &parsed.positional {
+        let LoadedScore {
+            score,
+            format,
+            warnings,
+        } = match load_any_score_file(path) {
+            Ok(loaded) => loaded,
+            // Un archivo antiguo con nombres de nota incoherentes no llega a convertirse
+            Err(ScoreError::Invalid { diagnostics, .. }) => {
+                for diagnostic in &diagnostics {
+                    println!("{}", diagnostic);
+                }
+                code = code.max(EXIT_INVALID);
+                continue;
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                code = code.max(EXIT_IO);
+                continue;
+            }
+        };
+
+        // Los avisos de la conversión van delante de los de la partitura ya convertida
+        let diagnostics: Vec<Diagnostic> = warnings
+            .into_iter()
+            .chain(validate_score(&score).into_iter().map(|d| Diagnostic {
+                location: d.location.clone().with_file(path),
+                ..d
+            }))
+            .collect();
+        for diagnostic in &diagnostics {
+            println!("{}", diagnostic);
+        }
+
+        let failed: bool = diagnostics
+            .iter()
+            .any(|d| strict || d.severity() == Severity::Error);
+        if failed {
+            code = code.max(EXIT_INVALID);
+        } else if !parsed.has("--quiet") {
+            let legacy: &str = match format {
+                ScoreFormat::Current => "",
+                ScoreFormat::Legacy => " (formato antiguo)",
+            };
+            println!(
+                "ok: {}{}: {} pieza(s), {} aviso(s)",
+                path,
+                legacy,
+                score.pieces().count(),
+                diagnostics.len()
+            );
+        }
+    }
+
+    Ok(code)
+}
+
+// convert: reescribe el archivo en el formato actual
+fn convert(args: &[String]) -> Result<u8, CliError> {
+    let parsed: ParsedArgs =
+        ParsedArgs::parse(args, &["--minify", "--flatten"], &["-o", "--output"])?;
+    let [input] = parsed.positional.as_slice() else {
+        return Err(CliError::Usage(
+            "convert necesita exactamente un archivo".into(),
+        ));
+    };
+
+    let LoadedScore { mut score, .. } = load_any_score_file(input)?;
+
+    if parsed.has("--flatten") {
+        for entry in score.0.iter_mut() {
+            for (name, piece) in entry.iter_mut() {
+                *piece = piece
+                    .flatten()
+                    .map_err(|e| e.in_piece(name).in_file(input))?;
+            }
+        }
+    }
+
+    write_score(&score, &parsed)?;
+    Ok(EXIT_OK)
+}
+
+// fingering: rellena la digitación de cada mano que no tiene ninguna en una sección
+fn fingering(args: &[String]) -> Result<u8, CliError> {
+    let parsed: ParsedArgs = ParsedArgs::parse(args, &["--minify"], &["-o", "--output"])?;
+    let (input, filter) = match parsed.positional.as_slice() {
+        [input] => (input, None),
+        [input, piece] => (input, Some(piece)),
+        _ => {
+            return Err(CliError::Usage(
+                "fingering necesita un archivo y opcionalmente una pieza".into(),
+            ));
+        }
+    };
+
+    let LoadedScore { mut score, .. } = load_any_score_file(input)?;
+    if let Some(name) = filter {
+        score.piece(name).map_err(|e| e.in_file(input))?;
+    }
+
+    for entry in score.0.iter_mut() {
+        for (name, piece) in entry.iter_mut() {
+            if filter.is_some_and(|f| f != name) {
+                continue;
+            }
+            for section in piece.sections.values_mut() {
+                for (notes, hand) in [
+                    (&mut section.right, Hand::Right),
+                    (&mut section.left, Hand::Left),
+                ] {
+                    if notes.iter().all(|note| note.finger.is_none()) {
+                        suggest_fingering(notes, hand);
+                    }
+                }
+            }
+        }
+    }
+
+    write_score(&score, &parsed)?;
+    Ok(EXIT_OK)
+}
+
+// import: pieza nueva a partir de un MIDI o un MusicXML
+fn import(args: &[String]) -> Result<u8, CliError> {
+    let parsed: ParsedArgs = ParsedArgs::parse(args, &["--minify"], &["-o", "--output", "--name"])?;
+    let [input] = parsed.positional.as_slice() else {
+        return Err(CliError::Usage(
+            "import necesita exactamente un archivo".into(),
+        ));
+    };
+
+    let (name, piece) = import_score_file(input)?;
+    let name: String = parsed.option(&["--name"]).cloned().unwrap_or(name);
+
+    // La pieza importada tiene que pasar la misma validación que las de la biblioteca
+    let diagnostics: Vec<Diagnostic> = validate_piece(&name, &piece);
+    for diagnostic in &diagnostics {
+        eprintln!("{}", diagnostic);
+    }
+
+    write_score(&ScoreFile(vec![BTreeMap::from([(name, piece)])]), &parsed)?;
+    Ok(if has_errors(&diagnostics) {
+        EXIT_INVALID
+    } else {
+        EXIT_OK
+    })
+}
+
+// Escribe el archivo de partituras en `-o` o en la salida estándar
+fn write_score(score: &ScoreFile, parsed: &ParsedArgs) -> Result<(), CliError> {
+    let json: String = if parsed.has("--minify") {
+        serde_json::to_string(score)
+    } else {
+        serde_json::to_string_pretty(score)
+    }
+    .map_err(CliError::Json)?;
+
+    match parsed.option(&["-o", "--output"]) {
+        Some(output) => {
+            fs::write(output, json + "\n").map_err(|e| CliError::Write(output.clone(), e))?
+        }
+        None => writeln!(io::stdout().lock(), "{}", json)
+            .map_err(|e| CliError::Write("<stdout>".into(), e))?,
+    }
+    Ok(())
+}
+
+// info: resumen de cada pieza
+fn info(args: &[String]) -> Result<u8, CliError> {
+    let parsed: ParsedArgs = ParsedArgs::parse(args, &[], &[])?;
+    let (input, filter) = match parsed.positional.as_slice() {
+        [input] => (input, None),
+        [input, piece] => (input, Some(piece)),
+        _ => {
+            return Err(CliError::Usage(
+                "info necesita un archivo y opcionalmente una pieza".into(),
+            ));
+        }
+    };
+
+    let LoadedScore { score, .. } = load_any_score_file(input)?;
+    if let Some(name) = filter {
+        score.piece(name).map_err(|e| e.in_file(input))?;
+    }
+
+    for (name, piece) in score.pieces() {
+        if filter.is_some_and(|f| f != name) {
+            continue;
+        }
+        print_info(name, piece).map_err(|e| e.in_piece(name).in_file(input))?;
+    }
+
+    Ok(EXIT_OK)
+}
+
+fn print_info(name: &str, piece: &Piece) -> Result<(), ScoreError> {
+    let metadata: PieceMetadata = PieceMetadata::from(&piece.metadata);
+    let duration: f32 = piece.duration()?;
+    let notes_r: Vec<Note> = piece.expand(&Hand::Right)?;
+    let notes_l: Vec<Note> = piece.expand(&Hand::Left)?;
+    let bars: f32 = if metadata.bar_duration() > 0.0 {
+        duration / metadata.bar_duration()
+    } else {
+        0.0
+    };
+
+    println!("{}", name);
+    println!("  Título:      {}", piece.metadata.title);
+    if let Some(composer) = &piece.metadata.composer {
+        println!("  Compositor:  {}", composer);
+    }
+    if let Some(key) = &piece.metadata.key {
+        println!("  Tonalidad:   {}", key);
+    }
+    if let Some(tempo) = &piece.metadata.tempo {
+        println!("  Tempo:       {}", tempo);
+    }
+    println!("  Compás:      {}", piece.metadata.meter);
+    println!(
+        "  Unidad:      {} s",
+        piece.metadata.note_duration_unit_seconds
+    );
+    println!("  Estructura:  {}", piece.metadata.structure.join(" "));
+    println!("  Duración:    {:.2} s", duration);
+    println!("  Compases:    {:.1}", bars);
+    println!(
+        "  Notas:       {} derecha, {} izquierda",
+        notes_r.len(),
+        notes_l.len()
+    );
+    for (label, section) in &piece.sections {
+        println!(
+            "  Sección {}:   {} derecha, {} izquierda, {:.2} s",
+            label,
+            section.right.len(),
+            section.left.len(),
+            section.length()
+        );
+    }
+
+    Ok(())
+}
+
+// expand: lista plana de notas tras aplicar 'structure'
+fn expand(args: &[String]) -> Result<u8, CliError> {
+    let parsed: ParsedArgs = ParsedArgs::parse(args, &["--json"], &["--hand"])?;
+    let [input, name] = parsed.positional.as_slice() else {
+        return Err(CliError::Usage(
+            "expand necesita un archivo y una pieza".into(),
+        ));
+    };
+
+    let hands: Vec<Hand> = match parsed.option(&["--hand"]).map(String::as_str) {
+        None => vec![Hand::Right, Hand::Left],
+        Some("right") => vec![Hand::Right],
+        Some("left") => vec![Hand::Left],
+        Some(other) => return Err(CliError::Usage(format!("mano desconocida '{}'", other))),
+    };
+
+    let LoadedScore { score, .. } = load_any_score_file(input)?;
+    let piece: &Piece = score.piece(name).map_err(|e| e.in_file(input))?;
+
+    let mut rows: Vec<(Hand, Note)> = Vec::new();
+    for hand in hands {
+        let notes: Vec<Note> = piece
+            .expand(&hand)
+            .map_err(|e| e.in_piece(name).in_file(input))?;
+        rows.extend(notes.into_iter().map(|note| (hand, note)));
+    }
+    rows.sort_by(|a, b| a.1.start.total_cmp(&b.1.start));
+
+    if parsed.has("--json") {
+        let json: Vec<serde_json::Value> = rows
+            .iter()
+            .map(|(hand, note)| {
+                serde_json::json!({
+                    "hand": hand.to_string(),
+                    "name": note_name(note.pitch),
+                    "start": note.start,
+                    "pitch": note.pitch,
+                    "duration": note.duration,
+                })
+            })
+            .collect();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&json).map_err(CliError::Json)?
+        );
+    } else {
+        println!(
+            "{:<6} {:>9} {:>5} {:>6} {:>9}",
+            "mano", "inicio", "tono", "nota", "duración"
+        );
+        for (hand, note) in &rows {
+            // El tono 0 es un silencio
+            let name: String = if note.pitch == 0 {
+                "-".to_string()
+            } else {
+                note_name(note.pitch)
+            };
+            println!(
+                "{:<6} {:>9.3} {:>5} {:>6} {:>9.3}",
+                hand.to_string(),
+                note.start,
+                note.pitch,
+                name,
+                note.duration
+            );
+        }
+    }
+
+    Ok(EXIT_OK)
+}
+
+// midi: exporta la pieza expandida (o un rango de 'structure') a un archivo MIDI
+fn midi(args: &[String]) -> Result<u8, CliError> {
+    let parsed: ParsedArgs = ParsedArgs::parse(args, &[], &["-o", "--output", "--sections"])?;
+    let [input, name] = parsed.positional.as_slice() else {
+        return Err(CliError::Usage(
+            "midi necesita un archivo y una pieza".into(),
+        ));
+    };
+    let output: &String = parsed
+        .option(&["-o", "--output"])
+        .ok_or_else(|| CliError::Usage("midi necesita el archivo de salida (-o)".into()))?;
+
+    let LoadedScore { score, .. } = load_any_score_file(input)?;
+    let piece: &Piece = score.piece(name).map_err(|e| e.in_file(input))?;
+
+    let range: Option<Range<usize>> = match parsed.option(&["--sections"]) {
+        None => None,
+        Some(sections) => Some(parse_sections(sections, piece.metadata.structure.len())?),
+    };
+
+    let bytes: Vec<u8> = piece_to_smf(piece, range).map_err(|e| e.in_piece(name).in_file(input))?;
+    save_smf(Path::new(output), &bytes).map_err(|e| CliError::Write(output.clone(), e))?;
+
+    Ok(EXIT_OK)
+}
+
+// "2-4" -> 1..4 (índices de 'structure'), "3" -> 2..3
+fn parse_sections(value: &str, len: usize) -> Result<Range<usize>, CliError> {
+    let invalid = || CliError::Usage(format!("rango de secciones inválido '{}'", value));
+    let (from, to) = value.split_once('-').unwrap_or((value, value));
+    let from: usize = from.trim().parse().map_err(|_| invalid())?;
+    let to: usize = to.trim().parse().map_err(|_| invalid())?;
+
+    if from == 0 || from > to || to > len {
+        return Err(invalid());
+    }
+    Ok(from - 1..to)
+}
@@ -1,9 +1,14 @@
 pub mod app;
+pub mod audio;
 pub mod message;
+pub mod midi;
 pub mod models;
 pub mod styles;
 pub mod utils;
 pub mod views;
 pub mod widgets;
 
-pub use app::MyApp;
+pub use {
+    app::MyApp,
+    utils::difficulty::{DifficultyRating, rate_difficulty},
+};
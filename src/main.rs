@@ -20,7 +20,7 @@ fn main() -> Result {
 
     // Configuración de la ventana de la aplicación
     let window_settings: window::Settings = window::Settings {
-        icon: icon,                              // Icono de la ventana
+        icon,                                    // Icono de la ventana
         position: Position::Centered,            // Centrar la ventana
         min_size: Some(Size::new(600.0, 600.0)), // Tamaño mínimo de la ventana
         resizable: true,                         // Permitir redimensionar la ventana
@@ -36,7 +36,7 @@ fn main() -> Result {
         id: Some("OctaRust".to_string()),
         fonts: vec![Cow::Owned(rustica_font_bytes)],
         default_font: Font {
-            family: Family::Name("PlayfairDisplay-Medium".into()),
+            family: Family::Name("PlayfairDisplay-Medium"),
             weight: Weight::Normal,
             stretch: Stretch::Normal,
             style: Style::Normal,
@@ -1,13 +1,48 @@
-use {iced::Event, std::time::Instant};
+use {
+    crate::{
+        models::{
+            capture::HandSplit,
+            ear_training::EarCategory,
+            editor::TextField,
+            flashcards::Clef,
+            input::TimedInput,
+            partiture::{Hand, Hands},
+            rhythm::RhythmSource,
+            score::Meter,
+            take::InputSource,
+        },
+        utils::{
+            quantize::QuantizeGrid,
+            scales::{MinorForm, ScalePattern},
+            sight_reading::{Key, NoteValue},
+        },
+    },
+    iced::Event,
+    std::time::Instant,
+};
 
 /// Mensajes principales de la App
 #[derive(Debug, Clone)]
 pub enum AppMessage {
-    MainMenu(MainMenuMessage),   // Menú
-    Game(GameMessage),           // Juego
-    Settings(SettingsMessage),   // Ajustes
-    Selection(SelectionMessage), // Selecion de mensajes
-    Event(Event),                // Eventos
+    MainMenu(MainMenuMessage),         // Menú
+    Game(GameMessage),                 // Juego
+    Results(ResultsMessage),           // Resultados al terminar la pieza
+    Settings(SettingsMessage),         // Ajustes
+    Selection(SelectionMessage),       // Selecion de mensajes
+    Replay(ReplayMessage),             // Repetición de la última grabación
+    Calibration(CalibrationMessage),   // Asistente de calibración del micrófono
+    Latency(LatencyMessage),           // Prueba de latencia de las entradas
+    Lesson(LessonMessage),             // Mapa de lecciones y página de cada lección
+    SightReading(SightReadingMessage), // Generador de ejercicios de lectura
+    Scales(ScaleMessage),              // Ejercicios de escalas y arpegios
+    Flashcards(FlashcardMessage),      // Tarjetas de lectura de notas
+    EarTraining(EarTrainingMessage),   // Entrenamiento auditivo
+    Rhythm(RhythmMessage),             // Ejercicios de ritmo
+    Trophies(TrophyMessage),           // Nivel, racha y logros del jugador
+    Editor(EditorMessage),             // Editor de partituras
+    Capture(CaptureMessage),           // Grabar lo que se toca y convertirlo en partitura
+    Input(TimedInput),                 // Nota o pulsación sellada al capturarla
+    Event(Event),                      // Eventos
 }
 
 /// Estados principales de la App
@@ -18,12 +53,32 @@ pub enum AppState {
     Settings,
     SelectionPartiture,
     Paused,
+    Results,
+    Replay,
+    Calibration,
+    Latency,
+    LessonMap,
+    Lesson,
+    SightReading,
+    Scales,
+    Flashcards,
+    EarTraining,
+    Rhythm,
+    Trophies,
+    Editor,
+    Capture,
 }
 
 // Mensajes específicos para la selección de partituras
 #[derive(Debug, Clone)]
 pub enum SelectionMessage {
-    StartGame(&'static str),
+    StartGame(String),
+    OpenSightReading,  // Abrir el generador de ejercicios de lectura
+    OpenScales,        // Abrir los ejercicios de escalas y arpegios
+    OpenRhythm,        // Abrir los ejercicios de ritmo
+    NewPiece,          // Abrir el editor con una pieza vacía
+    EditPiece(String), // Abrir una pieza de la biblioteca en el editor
+    OpenCapture,       // Grabar una pieza tocándola
     BackToMenu,
 }
 
@@ -31,6 +86,10 @@ pub enum SelectionMessage {
 #[derive(Debug, Clone, Copy)]
 pub enum MainMenuMessage {
     SelectPartiture,
+    OpenLessons,
+    OpenFlashcards,
+    OpenEarTraining,
+    OpenTrophies,
     OpenSettings,
     Exit,
 }
@@ -40,10 +99,204 @@ pub enum GameMessage {
     Tick(Instant),
     ResumeGame,
     RestartGame,
+    ExportMidi,
+    Seek(f32),        // Saltar a un segundo de la partitura, con cuenta previa
+    JumpBar(i32),     // Saltar compases hacia atrás (-1) o hacia delante (1)
+    JumpSection(i32), // Saltar secciones hacia atrás (-1) o hacia delante (1)
+    Scrub(f32),       // Arrastrar la barra de progreso
+    ScrubRelease,     // Soltar la barra de progreso
+}
+
+// Mensajes de la pantalla de resultados
+#[derive(Debug, Clone)]
+pub enum ResultsMessage {
+    Retry,            // Volver a tocar la pieza desde el principio
+    ChoosePiece,      // Volver a la selección de partituras
+    FromSection(f32), // Volver a tocar la pieza desde el segundo donde empieza una sección
+}
+
+// Mensajes del modo repetición
+#[derive(Debug, Clone)]
+pub enum ReplayMessage {
+    Open,          // Abrir la última grabación de la partitura
+    Tick(Instant), // Avanzar la reproducción
+    TogglePlay,    // Reproducir o pausar
+    Seek(f32),     // Saltar a un segundo de la partitura
+    Export,        // Exportar la grabación como archivo MIDI
+    Back,          // Volver a los resultados
 }
 
 #[derive(Debug, Clone)]
 pub enum SettingsMessage {
     ChangeTheme(iced::Theme),
+    ToggleMetronome(bool),
+    MetronomeVolume(f32),
+    CountInBars(u8),
+    ToggleVisualBeat(bool),
+    ToggleMicrophone(bool), // Escuchar el piano por el micrófono durante la partida
+    MidiInputDevice(String), // Puerto del teclado MIDI ("Ninguno" para no usar ninguno)
+    MidiDevice(String),     // Puerto de salida MIDI ("Ninguno" para no usar ninguno)
+    RefreshMidiPorts,       // Volver a buscar los puertos MIDI
+    MidiSound(bool),        // Mandar las notas para que suenen en el dispositivo
+    MidiHands(Hands),       // Manos que suenan por la salida MIDI
+    MidiChannel(u8),        // Canal de las notas que suenan
+    MidiGuide(bool),        // Encender las luces de guía del teclado
+    GuideChannel(Hand, u8), // Canal de las luces de cada mano
     BackToMenu,
 }
+
+// Mensajes del asistente de calibración del micrófono
+#[derive(Debug, Clone)]
+pub enum CalibrationMessage {
+    Open,          // Abrir el asistente desde los ajustes
+    Next,          // Empezar el paso actual (silencio o nota de referencia)
+    Tick(Instant), // Recoger el audio del micrófono
+    Save,          // Guardar el resultado en los ajustes
+    Cancel,        // Volver a los ajustes sin guardar
+}
+
+// Mensajes de la prueba de latencia
+#[derive(Debug, Clone)]
+pub enum LatencyMessage {
+    Open,                     // Abrir la prueba desde los ajustes
+    Start(InputSource),       // Empezar la prueba con una entrada
+    Tick(Instant),            // Avanzar el pulso y escuchar el micrófono
+    Save,                     // Guardar el retraso medido
+    Adjust(InputSource, f32), // Ajustar a mano el retraso de una entrada
+    Back,                     // Volver a los ajustes
+}
+
+// Mensajes del mapa de lecciones
+#[derive(Debug, Clone)]
+pub enum LessonMessage {
+    Select(String), // Abrir la página de la lección con este id
+    Start,          // Tocar la lección abierta
+    BackToMap,      // Volver al mapa de lecciones
+    BackToMenu,     // Volver al menú principal
+}
+
+// Mensajes del generador de ejercicios de lectura a primera vista
+#[derive(Debug, Clone)]
+pub enum SightReadingMessage {
+    Level(u8),               // Nivel de 1 a 3: figuras, saltos y registros
+    Key(Key),                // Tonalidad
+    Meter(Meter),            // Compás
+    Bars(u32),               // Número de compases
+    ToggleChords(bool),      // Acordes e intervalos armónicos
+    ToggleAccidentals(bool), // Notas cromáticas
+    Seed(String),            // Semilla escrita a mano
+    NewSeed,                 // Semilla nueva al azar
+    Start,                   // Generar el ejercicio y tocarlo
+    Back,                    // Volver a la selección de partituras
+}
+
+// Mensajes de los ejercicios de escalas y arpegios
+#[derive(Debug, Clone)]
+pub enum ScaleMessage {
+    Key(Key),              // Tonalidad
+    MinorForm(MinorForm),  // Forma de la escala menor
+    Pattern(ScalePattern), // Escala, arpegio o movimiento contrario
+    Octaves(u8),           // Número de octavas
+    Hands(Hands),          // Mano derecha, izquierda o las dos
+    NoteValue(NoteValue),  // Figura de las notas
+    Start,                 // Generar el ejercicio y tocarlo
+    Back,                  // Volver a la selección de partituras
+}
+
+// Mensajes de las tarjetas de lectura de notas
+#[derive(Debug, Clone)]
+pub enum FlashcardMessage {
+    Clef(Clef),     // Clave de las tarjetas
+    Cluster(usize), // Notas por tarjeta
+    Answer(u8),     // Respuesta con un botón: clase de nota (0 = Do)
+    Typed(String),  // Respuesta que se está escribiendo
+    Submit,         // Enviar la respuesta escrita
+    Tick(Instant),  // Escuchar el micrófono
+    Back,           // Volver al menú principal
+}
+
+// Mensajes del entrenamiento auditivo
+#[derive(Debug, Clone)]
+pub enum EarTrainingMessage {
+    Category(EarCategory), // Intervalos, acordes o dictado melódico
+    Level(u8),             // Nivel de dificultad de la categoría
+    Play,                  // Volver a oír la pregunta
+    Choice(usize),         // Respuesta con el botón de un intervalo o un acorde
+    Note(u8),              // Nota del dictado con un botón: clase de nota (0 = Do)
+    Undo,                  // Borrar la última nota del dictado
+    Typed(String),         // Respuesta que se está escribiendo
+    Submit,                // Enviar la respuesta escrita
+    Back,                  // Volver al menú principal
+}
+
+// Mensajes de los ejercicios de ritmo
+#[derive(Debug, Clone)]
+pub enum RhythmMessage {
+    Source(RhythmSource), // Ejercicio generado o pieza de la biblioteca
+    Meter(Meter),         // Compás del ejercicio generado
+    Tempo(u32),           // Pulsos por minuto del ejercicio generado
+    Bars(u32),            // Número de compases del ejercicio generado
+    Level(u8),            // Nivel de 1 a 3: figuras, silencios y síncopas
+    Start,                // Preparar la línea y empezar tras la cuenta
+    Retry,                // Repetir la misma línea
+    Tick(Instant),        // Avanzar la línea
+    Stop,                 // Volver a los ajustes del ejercicio
+    Back,                 // Volver a la selección de partituras
+}
+
+// Mensajes de la pantalla de trofeos
+#[derive(Debug, Clone)]
+pub enum TrophyMessage {
+    Back, // Volver al menú principal
+}
+
+// Mensajes del editor de partituras
+#[derive(Debug, Clone)]
+pub enum EditorMessage {
+    Note(u8),                // Escribir una nota en el cursor y avanzar
+    Chord(u8),               // Añadir una nota al acorde del cursor sin avanzar
+    Rest,                    // Escribir un silencio en el cursor y avanzar
+    Click(Hand, f32, u8),    // Click en el pentagrama: mano, segundo y tono
+    Value(NoteValue),        // Figura de las notas que se escriben
+    Dot,                     // Poner o quitar el puntillo a la figura
+    Move(i32),               // Mover el cursor figuras hacia atrás (-1) o hacia delante (1)
+    Transpose(i8),           // Subir o bajar un semitono las notas del cursor
+    Octave(i8),              // Subir o bajar una octava las teclas del ordenador
+    Delete,                  // Borrar las notas del cursor
+    DeletePrevious,          // Borrar las notas anteriores al cursor
+    ToggleHand,              // Escribir en la otra mano
+    Text(TextField, String), // Nombre, título, compositor o tempo
+    Key(Key),                // Tonalidad
+    Meter(Meter),            // Compás
+    Section(String),         // Editar otra sección
+    AddSection,              // Nueva sección al final de la pieza
+    RemoveSection,           // Quitar la sección actual
+    PushStructure,           // Repetir la sección actual al final de `structure`
+    PopStructure,            // Quitar la última entrada de `structure`
+    Undo,                    // Deshacer la última edición
+    Redo,                    // Rehacer lo deshecho
+    Save,                    // Guardar la pieza en `partitures.json`
+    Back,                    // Volver a la selección de partituras
+}
+
+// Mensajes de la grabación de partituras
+#[derive(Debug, Clone)]
+pub enum CaptureMessage {
+    Meter(Meter),       // Compás de la grabación
+    Tempo(u32),         // Pulsos por minuto del metrónomo
+    Grid(QuantizeGrid), // Figura más corta de la rejilla
+    Triplets(bool),     // Rejilla de tresillos
+    Swing(u8),          // Swing en porcentaje, 50 sin swing
+    Split(HandSplit),   // Separar las manos por altura o por canal
+    SplitPitch(u8),     // Primer tono de la mano derecha
+    Start,              // Empezar a grabar tras la cuenta
+    Tick(Instant),      // Avanzar el pulso visual
+    Channel,            // Tocar por el otro canal
+    Stop,               // Parar la grabación
+    Name(String),       // Nombre en la biblioteca
+    Title(String),      // Título de la pieza
+    Save,               // Guardar la pieza en `partitures.json`
+    Edit,               // Abrir la pieza en el editor
+    Retry,              // Volver a los ajustes para grabar otra vez
+    Back,               // Volver a la selección de partituras
+}
@@ -0,0 +1,4 @@
+pub mod import;
+pub mod input;
+pub mod output;
+pub mod smf;
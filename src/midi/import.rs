@@ -0,0 +1,541 @@
+use {
+    crate::{
+        midi::smf::SUSTAIN_CONTROLLER,
+        models::{
+            markings::{
+                Articulation, Dynamic, DynamicMark, Hairpin, HairpinKind, Markings, Pedal, Slur,
+            },
+            note::Note,
+            partiture::Hand,
+            score::{Meter, Section},
+        },
+        utils::import::ImportedScore,
+    },
+    std::collections::{BTreeMap, HashMap},
+};
+
+// Tempo de un archivo sin tempo: 120 negras por minuto
+const DEFAULT_MICROS_PER_QUARTER: u32 = 500_000;
+
+// Inicios y duraciones se cuantizan a semicorcheas
+const GRID_PER_QUARTER: u32 = 4;
+
+// Tono que separa las manos cuando no vienen en pistas ni canales distintos
+const SPLIT_PITCH: u8 = 60;
+
+// Lo que una nota tiene que superar la velocidad de las notas vecinas para llevar acento
+const ACCENT_MARGIN: f32 = 12.0;
+
+// Parte del tiempo hasta la siguiente nota por debajo de la cual la nota es staccato
+const STACCATO_RATIO: f32 = 0.6;
+
+// Cambio de la velocidad media entre compases seguidos que se lee como regulador
+const HAIRPIN_CHANGE: f32 = 6.0;
+
+// Nota leída del archivo, en ticks
+struct RawNote {
+    on: u32,
+    off: u32,
+    pitch: u8,
+    velocity: u8,
+    channel: u8,
+    track: usize,
+}
+
+// Lo que interesa de las pistas del archivo
+#[derive(Default)]
+struct SmfContents {
+    division: u16,                    // Ticks por negra
+    tempos: Vec<u32>,                 // Microsegundos por negra de cada cambio de tempo
+    time_signature: Option<(u8, u8)>, // Primer compás del archivo
+    format: u16,
+    title: Option<String>, // Nombre de la primera pista
+    notes: Vec<RawNote>,
+    pedal: Vec<(u32, u32)>, // Ticks en los que se pisa y se levanta el pedal
+}
+
+// Lector de bytes con la posición actual
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end: usize = self.pos + len;
+        let slice: &[u8] = self
+            .bytes
+            .get(self.pos..end)
+            .ok_or_else(|| "el archivo MIDI está cortado".to_string())?;
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let bytes: &[u8] = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let bytes: &[u8] = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    // Cantidad de longitud variable, como la escribe `write_vlq`
+    fn vlq(&mut self) -> Result<u32, String> {
+        let mut value: u32 = 0;
+        for _ in 0..4 {
+            let byte: u8 = self.u8()?;
+            value = (value << 7) | (byte & 0x7F) as u32;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("cantidad de longitud variable demasiado larga".to_string())
+    }
+
+    fn done(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+}
+
+/// Lee un archivo MIDI de tipo 0 o 1 y lo convierte en una sección.
+/// Las manos salen de las dos primeras pistas con notas, de los dos primeros canales si todo va
+/// en una pista, o del Do central. Los matices y los reguladores salen de la velocidad media de
+/// cada compás, los acentos de las notas más fuertes que sus vecinas, el staccato de las notas que
+/// se sueltan pronto, las ligaduras de las notas que se solapan y el pedal del controlador 64.
+pub fn smf_to_section(bytes: &[u8]) -> Result<ImportedScore, String> {
+    let contents: SmfContents = read_smf(bytes)?;
+    if contents.notes.is_empty() {
+        return Err("el archivo MIDI no tiene notas".to_string());
+    }
+    if contents.tempos.len() > 1 {
+        log::warn!("el archivo MIDI cambia de tempo; se usa el primero");
+    }
+
+    let division: u32 = contents.division.max(1) as u32;
+    let micros_per_quarter: u32 = contents
+        .tempos
+        .first()
+        .copied()
+        .unwrap_or(DEFAULT_MICROS_PER_QUARTER);
+    let seconds_per_tick: f64 = micros_per_quarter as f64 / 1_000_000.0 / division as f64;
+    let grid: u32 = (division / GRID_PER_QUARTER).max(1);
+    let quantize = |tick: u32| -> u32 { (tick + grid / 2) / grid * grid };
+    let seconds = |tick: u32| -> f32 { (tick as f64 * seconds_per_tick) as f32 };
+
+    let meter: Meter = match contents.time_signature {
+        Some((beats, beat_unit)) if beats > 0 && beat_unit.is_power_of_two() => {
+            Meter { beats, beat_unit }
+        }
+        _ => Meter {
+            beats: 4,
+            beat_unit: 4,
+        },
+    };
+    let bar_ticks: u32 = (division * 4 * meter.beats as u32 / meter.beat_unit as u32).max(1);
+
+    // Velocidad media de cada compás con notas
+    let mut bars: BTreeMap<u32, (f32, u32)> = BTreeMap::new();
+    for note in &contents.notes {
+        let bar: &mut (f32, u32) = bars.entry(quantize(note.on) / bar_ticks).or_default();
+        bar.0 += note.velocity as f32;
+        bar.1 += 1;
+    }
+    let averages: BTreeMap<u32, f32> = bars
+        .into_iter()
+        .map(|(bar, (sum, count))| (bar, sum / count as f32))
+        .collect();
+
+    let mut section: Section = Section::default();
+    let mut markings: Markings = Markings {
+        dynamics: bar_dynamics(&averages, |bar| seconds(bar * bar_ticks)),
+        hairpins: bar_hairpins(&averages, |bar| seconds(bar * bar_ticks)),
+        ..Markings::default()
+    };
+
+    let hands: Vec<Hand> = assign_hands(&contents.notes);
+    for hand in [Hand::Right, Hand::Left] {
+        let mut raw: Vec<&RawNote> = contents
+            .notes
+            .iter()
+            .zip(&hands)
+            .filter(|(_, note_hand)| **note_hand == hand)
+            .map(|(note, _)| note)
+            .collect();
+        raw.sort_by_key(|note| (note.on, note.pitch));
+
+        let mut notes: Vec<Note> = Vec::with_capacity(raw.len());
+        for (i, note) in raw.iter().enumerate() {
+            let on: u32 = quantize(note.on);
+            let length: u32 = note.off.saturating_sub(note.on);
+            let next_on: Option<u32> = raw[i + 1..]
+                .iter()
+                .map(|next| quantize(next.on))
+                .find(|&next| next > on);
+
+            let mut articulations: Vec<Articulation> = Vec::new();
+            let mut written: u32 = length;
+            if let Some(next) = next_on
+                && (length as f32) < (next - on) as f32 * STACCATO_RATIO
+            {
+                // Se escribe con la duración que ocupa, no con la que suena
+                articulations.push(Articulation::Staccato);
+                written = (next - on).min(length * 2);
+            }
+            if note.velocity as f32 >= neighbour_velocity(&raw, i) + ACCENT_MARGIN {
+                articulations.push(Articulation::Accent);
+            }
+
+            let duration: f32 = seconds(quantize(written).max(grid));
+            notes.push(Note {
+                start: seconds(on),
+                articulations,
+                ..Note::new(note.pitch, duration, false, Default::default())
+            });
+        }
+
+        markings.slurs.extend(
+            legato_runs(&raw, grid / 4)
+                .into_iter()
+                .map(|(start, end)| Slur {
+                    hand,
+                    start: seconds(quantize(start)),
+                    end: seconds(quantize(end)),
+                }),
+        );
+
+        match hand {
+            Hand::Right => section.right = notes,
+            Hand::Left => section.left = notes,
+        }
+    }
+
+    markings.pedal = merge_pedal(&contents.pedal)
+        .into_iter()
+        .map(|(down, up)| Pedal {
+            start: seconds(quantize(down)),
+            end: seconds(quantize(up).max(quantize(down) + grid)),
+        })
+        .collect();
+    section.markings = markings;
+
+    Ok(ImportedScore {
+        title: contents.title,
+        composer: None,
+        key: None,
+        meter,
+        quarter_seconds: micros_per_quarter as f32 / 1_000_000.0,
+        section,
+    })
+}
+
+// Cabecera y pistas del archivo
+fn read_smf(bytes: &[u8]) -> Result<SmfContents, String> {
+    let mut reader: Reader = Reader { bytes, pos: 0 };
+    if reader.take(4).ok() != Some(b"MThd".as_slice()) {
+        return Err("no es un archivo MIDI (falta la cabecera MThd)".to_string());
+    }
+    let header_len: u32 = reader.u32()?;
+    let format: u16 = reader.u16()?;
+    let tracks: u16 = reader.u16()?;
+    let division: u16 = reader.u16()?;
+    if division & 0x8000 != 0 {
+        return Err("la resolución en tiempo SMPTE no está soportada".to_string());
+    }
+    reader.take((header_len as usize).saturating_sub(6))?;
+
+    let mut contents: SmfContents = SmfContents {
+        division,
+        format,
+        ..SmfContents::default()
+    };
+    let mut track: usize = 0;
+    while track < tracks as usize && !reader.done() {
+        // Los bloques que no son pistas se saltan
+        let id: &[u8] = reader.take(4)?;
+        let len: u32 = reader.u32()?;
+        let body: &[u8] = reader.take(len as usize)?;
+        if id == b"MTrk" {
+            read_track(body, track, &mut contents)?;
+            track += 1;
+        }
+    }
+
+    // En un archivo de tipo 1 el nombre de la primera pista solo es el título si no tiene notas
+    if contents.format != 0 && contents.notes.iter().any(|note| note.track == 0) {
+        contents.title = None;
+    }
+    Ok(contents)
+}
+
+// Eventos de una pista, con el estado implícito (running status) de los mensajes de canal
+fn read_track(body: &[u8], track: usize, contents: &mut SmfContents) -> Result<(), String> {
+    let mut reader: Reader = Reader {
+        bytes: body,
+        pos: 0,
+    };
+    let mut tick: u32 = 0;
+    let mut status: Option<u8> = None;
+    let mut sounding: HashMap<(u8, u8), Vec<(u32, u8)>> = HashMap::new(); // Inicio y velocidad por canal y tono
+    let mut pedal: [Option<u32>; 16] = [None; 16];
+
+    while !reader.done() {
+        tick = tick.saturating_add(reader.vlq()?);
+        let byte: u8 = reader.u8()?;
+
+        match byte {
+            0xFF => {
+                let kind: u8 = reader.u8()?;
+                let len: u32 = reader.vlq()?;
+                let data: &[u8] = reader.take(len as usize)?;
+                match (kind, data) {
+                    (0x03, name) if track == 0 && contents.title.is_none() && !name.is_empty() => {
+                        contents.title = Some(String::from_utf8_lossy(name).trim().to_string());
+                    }
+                    (0x51, [a, b, c]) => {
+                        contents.tempos.push(u32::from_be_bytes([0, *a, *b, *c]));
+                    }
+                    (0x58, [beats, power, ..]) if contents.time_signature.is_none() => {
+                        contents.time_signature =
+                            Some((*beats, 1u8.checked_shl(*power as u32).unwrap_or(0)));
+                    }
+                    (0x2F, _) => break,
+                    _ => {}
+                }
+            }
+            0xF0 | 0xF7 => {
+                let len: u32 = reader.vlq()?;
+                reader.take(len as usize)?;
+            }
+            _ => {
+                // Sin byte de estado se repite el del mensaje anterior
+                let (command, first): (u8, u8) = if byte & 0x80 != 0 {
+                    (byte, reader.u8()?)
+                } else {
+                    (
+                        status.ok_or_else(|| "mensaje MIDI sin byte de estado".to_string())?,
+                        byte,
+                    )
+                };
+                status = Some(command);
+                let second: u8 = match command & 0xF0 {
+                    0xC0 | 0xD0 => 0,
+                    _ => reader.u8()?,
+                };
+                let channel: u8 = command & 0x0F;
+
+                match (command & 0xF0, first, second) {
+                    (0x90, pitch, velocity) if velocity > 0 => {
+                        sounding
+                            .entry((channel, pitch))
+                            .or_default()
+                            .push((tick, velocity));
+                    }
+                    (0x80 | 0x90, pitch, _) => {
+                        if let Some(started) = sounding.get_mut(&(channel, pitch))
+                            && !started.is_empty()
+                        {
+                            let (on, velocity): (u32, u8) = started.remove(0);
+                            contents.notes.push(RawNote {
+                                on,
+                                off: tick,
+                                pitch,
+                                velocity,
+                                channel,
+                                track,
+                            });
+                        }
+                    }
+                    (0xB0, SUSTAIN_CONTROLLER, value) => {
+                        let down: &mut Option<u32> = &mut pedal[channel as usize];
+                        if value >= 64 {
+                            down.get_or_insert(tick);
+                        } else if let Some(start) = down.take() {
+                            contents.pedal.push((start, tick));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    // Lo que sigue sonando o pisado al acabar la pista termina con ella
+    for ((channel, pitch), started) in sounding {
+        for (on, velocity) in started {
+            contents.notes.push(RawNote {
+                on,
+                off: tick,
+                pitch,
+                velocity,
+                channel,
+                track,
+            });
+        }
+    }
+    contents
+        .pedal
+        .extend(pedal.into_iter().flatten().map(|start| (start, tick)));
+    Ok(())
+}
+
+// Mano de cada nota: por pista, por canal o por el Do central, en ese orden
+fn assign_hands(notes: &[RawNote]) -> Vec<Hand> {
+    let mut tracks: Vec<usize> = notes.iter().map(|note| note.track).collect();
+    tracks.sort_unstable();
+    tracks.dedup();
+    let mut channels: Vec<u8> = notes.iter().map(|note| note.channel).collect();
+    channels.sort_unstable();
+    channels.dedup();
+
+    let by_pitch = |note: &RawNote| -> Hand {
+        if note.pitch >= SPLIT_PITCH {
+            Hand::Right
+        } else {
+            Hand::Left
+        }
+    };
+
+    notes
+        .iter()
+        .map(|note| {
+            if tracks.len() >= 2 {
+                match tracks.iter().position(|&track| track == note.track) {
+                    Some(0) => Hand::Right,
+                    Some(1) => Hand::Left,
+                    _ => by_pitch(note),
+                }
+            } else if channels.len() >= 2 {
+                match channels.iter().position(|&channel| channel == note.channel) {
+                    Some(0) => Hand::Right,
+                    Some(1) => Hand::Left,
+                    _ => by_pitch(note),
+                }
+            } else {
+                by_pitch(note)
+            }
+        })
+        .collect()
+}
+
+// Velocidad media de las notas que empiezan justo antes y justo después de la indicada
+fn neighbour_velocity(notes: &[&RawNote], index: usize) -> f32 {
+    let on: u32 = notes[index].on;
+    let previous: Option<u8> = notes[..index]
+        .iter()
+        .rev()
+        .find(|note| note.on < on)
+        .map(|note| note.velocity);
+    let next: Option<u8> = notes[index + 1..]
+        .iter()
+        .find(|note| note.on > on)
+        .map(|note| note.velocity);
+    let neighbours: Vec<f32> = previous.into_iter().chain(next).map(f32::from).collect();
+    if neighbours.is_empty() {
+        notes[index].velocity as f32
+    } else {
+        neighbours.iter().sum::<f32>() / neighbours.len() as f32
+    }
+}
+
+// Un matiz al principio y otro en cada compás cuya velocidad media cambia de matiz
+fn bar_dynamics(averages: &BTreeMap<u32, f32>, start: impl Fn(u32) -> f32) -> Vec<DynamicMark> {
+    let mut marks: Vec<DynamicMark> = Vec::new();
+    for (&bar, &average) in averages {
+        let level: Dynamic = Dynamic::from_velocity(average.round() as u8);
+        if marks.last().is_none_or(|last| last.level != level) {
+            marks.push(DynamicMark {
+                start: start(bar),
+                level,
+            });
+        }
+    }
+    marks
+}
+
+// Un regulador por cada tramo de al menos tres compases seguidos que suben o bajan
+fn bar_hairpins(averages: &BTreeMap<u32, f32>, start: impl Fn(u32) -> f32) -> Vec<Hairpin> {
+    let bars: Vec<(u32, f32)> = averages.iter().map(|(&bar, &v)| (bar, v)).collect();
+    let mut hairpins: Vec<Hairpin> = Vec::new();
+    let mut run: Option<(u32, u32, HairpinKind)> = None; // Primer compás, último y sentido
+
+    for pair in bars.windows(2) {
+        let ((bar, from), (next, to)) = (pair[0], pair[1]);
+        let kind: Option<HairpinKind> = if next != bar + 1 {
+            None
+        } else if to - from >= HAIRPIN_CHANGE {
+            Some(HairpinKind::Crescendo)
+        } else if from - to >= HAIRPIN_CHANGE {
+            Some(HairpinKind::Diminuendo)
+        } else {
+            None
+        };
+
+        run = match (run, kind) {
+            (Some((first, _, current)), Some(kind)) if current == kind => Some((first, next, kind)),
+            (previous, kind) => {
+                if let Some((first, last, kind)) = previous
+                    && last >= first + 2
+                {
+                    hairpins.push(Hairpin {
+                        start: start(first),
+                        end: start(last),
+                        kind,
+                    });
+                }
+                kind.map(|kind| (bar, next, kind))
+            }
+        };
+    }
+    if let Some((first, last, kind)) = run
+        && last >= first + 2
+    {
+        hairpins.push(Hairpin {
+            start: start(first),
+            end: start(last),
+            kind,
+        });
+    }
+    hairpins
+}
+
+// Tramos de notas que se solapan con la siguiente: inicio de la primera y de la última
+fn legato_runs(notes: &[&RawNote], margin: u32) -> Vec<(u32, u32)> {
+    let mut runs: Vec<(u32, u32)> = Vec::new();
+    let mut current: Option<(u32, u32)> = None;
+
+    for (i, note) in notes.iter().enumerate() {
+        let next: Option<&&RawNote> = notes[i + 1..].iter().find(|next| next.on > note.on);
+        match next {
+            Some(next) if note.off > next.on + margin => {
+                current = Some((current.map_or(note.on, |(start, _)| start), next.on));
+            }
+            // Las notas del mismo acorde no cortan la ligadura
+            _ if notes
+                .get(i + 1)
+                .is_some_and(|following| following.on == note.on) => {}
+            _ => runs.extend(current.take()),
+        }
+    }
+    runs.extend(current);
+    runs
+}
+
+// Une los tramos de pedal que se solapan (el pedal de cada canal se lee por separado)
+fn merge_pedal(pedal: &[(u32, u32)]) -> Vec<(u32, u32)> {
+    let mut sorted: Vec<(u32, u32)> = pedal.to_vec();
+    sorted.sort_unstable();
+    let mut merged: Vec<(u32, u32)> = Vec::new();
+    for (down, up) in sorted {
+        match merged.last_mut() {
+            Some(last) if down < last.1 => last.1 = last.1.max(up),
+            _ => merged.push((down, up)),
+        }
+    }
+    merged
+}
@@ -0,0 +1,93 @@
+use {
+    crate::{
+        midi::output::CLIENT_NAME,
+        models::{
+            input::{InputAction, TimedInput},
+            take::InputSource,
+        },
+    },
+    midir::MidiInputConnection,
+    std::{
+        sync::mpsc::{self, Receiver, Sender},
+        time::Instant,
+    },
+};
+
+/// Convierte un mensaje del teclado en una entrada con el momento en que llegó.
+/// Un note-on con velocidad 0 es un note-off; lo que no sea una nota se ignora.
+pub fn parse_message(bytes: &[u8], at: Instant) -> Option<TimedInput> {
+    let action: InputAction = match *bytes {
+        [status, pitch, velocity, ..] if status & 0xF0 == 0x90 && velocity > 0 => {
+            InputAction::NoteOn {
+                pitch: pitch & 0x7F,
+                velocity: velocity & 0x7F,
+            }
+        }
+        [status, pitch, _, ..] if status & 0xF0 == 0x90 || status & 0xF0 == 0x80 => {
+            InputAction::NoteOff {
+                pitch: pitch & 0x7F,
+            }
+        }
+        _ => return None,
+    };
+    Some(TimedInput::new(at, InputSource::Midi, action))
+}
+
+/// Nombres de los puertos de entrada MIDI del sistema
+pub fn input_ports() -> Vec<String> {
+    match midir::MidiInput::new(CLIENT_NAME) {
+        Ok(input) => input
+            .ports()
+            .iter()
+            .filter_map(|port| input.port_name(port).ok())
+            .collect(),
+        Err(e) => {
+            log::error!("No se pudo abrir el sistema MIDI: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Teclado MIDI conectado. Cada nota se sella al llegar, en el hilo de midir, y espera en
+/// una cola hasta que el tick de la interfaz la recoge con `poll`.
+pub struct MidiInput {
+    _connection: MidiInputConnection<()>, // Al soltarla se cierra el puerto
+    received: Receiver<TimedInput>,
+}
+
+impl MidiInput {
+    /// Conectar con el puerto de entrada que tiene ese nombre
+    pub fn open(device: &str) -> Result<Self, String> {
+        let input: midir::MidiInput =
+            midir::MidiInput::new(CLIENT_NAME).map_err(|e| e.to_string())?;
+        let port = input
+            .ports()
+            .into_iter()
+            .find(|port| input.port_name(port).is_ok_and(|name| name == device))
+            .ok_or_else(|| format!("no hay ningún puerto MIDI llamado '{}'", device))?;
+
+        let (sender, received): (Sender<TimedInput>, Receiver<TimedInput>) = mpsc::channel();
+        let connection: MidiInputConnection<()> = input
+            .connect(
+                &port,
+                CLIENT_NAME,
+                move |_, bytes, _| {
+                    if let Some(input) = parse_message(bytes, Instant::now()) {
+                        let _ = sender.send(input);
+                    }
+                },
+                (),
+            )
+            .map_err(|e| e.to_string())?;
+
+        Ok(MidiInput {
+            _connection: connection,
+            received,
+        })
+    }
+
+    /// Notas tocadas desde la última llamada, en orden de llegada
+    pub fn poll(&mut self) -> Vec<TimedInput> {
+        self.received.try_iter().collect()
+    }
+}
@@ -0,0 +1,423 @@
+use {
+    crate::{
+        midi::smf::{MidiTrack, SUSTAIN_CONTROLLER},
+        models::{
+            note::Note,
+            partiture::{Hand, Partiture},
+            playback::PlaybackClock,
+            settings::MidiOutputSettings,
+        },
+    },
+    midir::MidiOutputConnection,
+    std::{
+        mem,
+        sync::{
+            Arc, Mutex,
+            atomic::{AtomicBool, Ordering},
+        },
+        thread::{self, JoinHandle},
+        time::Duration,
+    },
+};
+
+/// Nombre del cliente en el sistema MIDI
+pub const CLIENT_NAME: &str = "octarust";
+
+// Velocidad de las luces de guía: basta con que no sea 0, que sería un note-off
+pub const GUIDE_VELOCITY: u8 = 1;
+
+// Un avance mayor que esto entre dos lecturas del reloj es un salto, no el paso del tiempo
+pub const MAX_STEP: f32 = 0.25;
+
+// Cada cuánto mira la salida el reloj de reproducción
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Mensaje de canal que se manda al dispositivo, con el canal de 0 a 15
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MidiMessage {
+    NoteOn {
+        channel: u8,
+        pitch: u8,
+        velocity: u8,
+    },
+    NoteOff {
+        channel: u8,
+        pitch: u8,
+    },
+    Sustain {
+        channel: u8,
+        down: bool,
+    },
+}
+
+impl MidiMessage {
+    /// Los tres bytes del mensaje, como en un archivo MIDI
+    pub fn bytes(self) -> [u8; 3] {
+        match self {
+            MidiMessage::NoteOn {
+                channel,
+                pitch,
+                velocity,
+            } => [
+                0x90 | (channel & 0x0F),
+                pitch & 0x7F,
+                velocity.clamp(1, 127),
+            ],
+            MidiMessage::NoteOff { channel, pitch } => [0x80 | (channel & 0x0F), pitch & 0x7F, 0],
+            MidiMessage::Sustain { channel, down } => [
+                0xB0 | (channel & 0x0F),
+                SUSTAIN_CONTROLLER,
+                if down { 127 } else { 0 },
+            ],
+        }
+    }
+
+    // Los note-off y el pedal levantado van antes que lo que empieza en el mismo segundo
+    fn order(self) -> u8 {
+        match self {
+            MidiMessage::NoteOff { .. } | MidiMessage::Sustain { down: false, .. } => 0,
+            _ => 1,
+        }
+    }
+}
+
+/// Mensaje con el segundo de `elapsed` (la cuenta previa incluida) en que se manda
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MidiEvent {
+    pub time: f32,
+    pub message: MidiMessage,
+}
+
+/// Destino de los mensajes: el puerto de un dispositivo o, en las pruebas, una lista
+pub trait MidiSink: Send {
+    fn send(&mut self, message: MidiMessage) -> Result<(), String>;
+}
+
+impl MidiSink for MidiOutputConnection {
+    fn send(&mut self, message: MidiMessage) -> Result<(), String> {
+        MidiOutputConnection::send(self, &message.bytes()).map_err(|e| e.to_string())
+    }
+}
+
+// Canal de los ajustes (1 a 16) en el mensaje (0 a 15)
+fn wire_channel(channel: u8) -> u8 {
+    channel.clamp(1, 16) - 1
+}
+
+/// Mensajes de la pieza cargada según los ajustes, ordenados por tiempo.
+/// Las manos elegidas suenan por un canal con la velocidad de las dinámicas y el pedal; las
+/// luces de guía se encienden con cada nota de cada mano por su canal y se apagan al acabar.
+pub fn output_events(
+    partiture: (&Partiture, &Partiture),
+    settings: &MidiOutputSettings,
+) -> Vec<MidiEvent> {
+    let (left, right) = partiture;
+    let offset: f32 = right.settings.timer;
+    let mut events: Vec<MidiEvent> = Vec::new();
+    let mut push = |time: f32, message: MidiMessage| {
+        events.push(MidiEvent {
+            time: time + offset,
+            message,
+        })
+    };
+
+    if settings.sound {
+        let notes: Vec<Note> = [left, right]
+            .into_iter()
+            .filter(|partiture| settings.hands.plays(partiture.hand))
+            .flat_map(|partiture| partiture.notes.iter().cloned())
+            .collect();
+        if !notes.is_empty() {
+            let channel: u8 = wire_channel(settings.channel);
+            let track: MidiTrack = MidiTrack::from_notes("", channel, &notes, &right.markings);
+            for note in &track.notes {
+                push(
+                    note.start,
+                    MidiMessage::NoteOn {
+                        channel,
+                        pitch: note.pitch,
+                        velocity: note.velocity,
+                    },
+                );
+                push(
+                    note.start + note.duration,
+                    MidiMessage::NoteOff {
+                        channel,
+                        pitch: note.pitch,
+                    },
+                );
+            }
+            for pedal in &track.pedal {
+                push(
+                    pedal.start,
+                    MidiMessage::Sustain {
+                        channel,
+                        down: true,
+                    },
+                );
+                push(
+                    pedal.end,
+                    MidiMessage::Sustain {
+                        channel,
+                        down: false,
+                    },
+                );
+            }
+        }
+    }
+
+    if settings.guide {
+        for partiture in [left, right] {
+            let channel: u8 = wire_channel(match partiture.hand {
+                Hand::Right => settings.guide_right,
+                Hand::Left => settings.guide_left,
+            });
+            for note in partiture
+                .notes
+                .iter()
+                .filter(|n| n.pitch != 0 && !n.is_rest)
+            {
+                push(
+                    note.start,
+                    MidiMessage::NoteOn {
+                        channel,
+                        pitch: note.pitch,
+                        velocity: GUIDE_VELOCITY,
+                    },
+                );
+                push(
+                    note.start + note.duration,
+                    MidiMessage::NoteOff {
+                        channel,
+                        pitch: note.pitch,
+                    },
+                );
+            }
+        }
+    }
+
+    events.sort_by(|a, b| {
+        a.time
+            .total_cmp(&b.time)
+            .then(a.message.order().cmp(&b.message.order()))
+    });
+    events
+}
+
+/// Manda los mensajes al llegar su segundo de `elapsed`. Un salto del reloj (hacia atrás o de
+/// más de `MAX_STEP`) apaga lo que suena y sigue desde la nueva posición sin tocar lo saltado.
+#[derive(Debug, Clone, Default)]
+pub struct MidiScheduler {
+    events: Vec<MidiEvent>,
+    next: usize,             // Primer mensaje sin mandar
+    position: Option<f32>,   // Última posición leída del reloj
+    sounding: Vec<(u8, u8)>, // Canal y tono de las notas encendidas
+    sustain: Vec<u8>,        // Canales con el pedal pisado
+}
+
+impl MidiScheduler {
+    pub fn new(events: Vec<MidiEvent>) -> Self {
+        MidiScheduler {
+            events,
+            ..MidiScheduler::default()
+        }
+    }
+
+    /// Mandar lo que toca hasta `elapsed`
+    pub fn update(&mut self, elapsed: f32, sink: &mut dyn MidiSink) {
+        // Al empezar se cuenta desde el 0, para no perder lo que empieza justo ahí
+        let last: f32 = self.position.unwrap_or(0.0);
+        if elapsed < last || elapsed - last > MAX_STEP {
+            self.seek(elapsed, sink);
+        }
+
+        while let Some(event) = self.events.get(self.next).filter(|e| e.time <= elapsed) {
+            let message: MidiMessage = event.message;
+            self.next += 1;
+            self.send(message, sink);
+        }
+        self.position = Some(elapsed);
+    }
+
+    /// Apagar lo que suena y colocarse en `elapsed`: lo que empieza justo ahí aún se manda
+    pub fn seek(&mut self, elapsed: f32, sink: &mut dyn MidiSink) {
+        self.silence(sink);
+        self.next = self.events.partition_point(|e| e.time < elapsed);
+        self.position = Some(elapsed);
+    }
+
+    /// Apagar las notas encendidas y levantar el pedal, como al pausar
+    pub fn silence(&mut self, sink: &mut dyn MidiSink) {
+        for (channel, pitch) in mem::take(&mut self.sounding) {
+            deliver(sink, MidiMessage::NoteOff { channel, pitch });
+        }
+        for channel in mem::take(&mut self.sustain) {
+            deliver(
+                sink,
+                MidiMessage::Sustain {
+                    channel,
+                    down: false,
+                },
+            );
+        }
+    }
+
+    // Mandar un mensaje llevando la cuenta de lo que queda encendido. Un note-off de una
+    // nota ya apagada (por una pausa o un salto) no se manda.
+    fn send(&mut self, message: MidiMessage, sink: &mut dyn MidiSink) {
+        match message {
+            MidiMessage::NoteOn { channel, pitch, .. } => self.sounding.push((channel, pitch)),
+            MidiMessage::NoteOff { channel, pitch } => {
+                match self.sounding.iter().position(|&on| on == (channel, pitch)) {
+                    Some(i) => {
+                        self.sounding.remove(i);
+                    }
+                    None => return,
+                }
+            }
+            MidiMessage::Sustain {
+                channel,
+                down: true,
+            } => self.sustain.push(channel),
+            MidiMessage::Sustain {
+                channel,
+                down: false,
+            } => {
+                if !self.sustain.contains(&channel) {
+                    return;
+                }
+                self.sustain.retain(|&down| down != channel);
+            }
+        }
+        deliver(sink, message);
+    }
+}
+
+fn deliver(sink: &mut dyn MidiSink, message: MidiMessage) {
+    if let Err(e) = sink.send(message) {
+        log::error!("No se pudo mandar el mensaje MIDI: {}", e);
+    }
+}
+
+/// Nombres de los puertos de salida MIDI del sistema
+pub fn output_ports() -> Vec<String> {
+    match midir::MidiOutput::new(CLIENT_NAME) {
+        Ok(output) => output
+            .ports()
+            .iter()
+            .filter_map(|port| output.port_name(port).ok())
+            .collect(),
+        Err(e) => {
+            log::error!("No se pudo abrir el sistema MIDI: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+// Lo que comparten la aplicación y el hilo de la salida
+struct OutputState {
+    scheduler: MidiScheduler,
+    clock: Option<PlaybackClock>, // Reloj al que sigue, `None` sin pieza
+    sink: Box<dyn MidiSink>,
+}
+
+/// Salida MIDI de la aplicación: un hilo que lee el reloj de reproducción y manda los mensajes
+/// de la pieza en cuanto llega su segundo, sin esperar al tick de la interfaz
+pub struct MidiOutput {
+    state: Arc<Mutex<OutputState>>,
+    running: Arc<AtomicBool>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl MidiOutput {
+    /// Conectar con el puerto de salida que tiene ese nombre
+    pub fn open(device: &str) -> Result<Self, String> {
+        let output: midir::MidiOutput =
+            midir::MidiOutput::new(CLIENT_NAME).map_err(|e| e.to_string())?;
+        let port = output
+            .ports()
+            .into_iter()
+            .find(|port| output.port_name(port).is_ok_and(|name| name == device))
+            .ok_or_else(|| format!("no hay ningún puerto MIDI llamado '{}'", device))?;
+        let connection: MidiOutputConnection = output
+            .connect(&port, CLIENT_NAME)
+            .map_err(|e| e.to_string())?;
+        Ok(MidiOutput::with_sink(Box::new(connection)))
+    }
+
+    /// Salida a cualquier destino, por ejemplo uno que guarde los mensajes en las pruebas
+    pub fn with_sink(sink: Box<dyn MidiSink>) -> Self {
+        let state: Arc<Mutex<OutputState>> = Arc::new(Mutex::new(OutputState {
+            scheduler: MidiScheduler::default(),
+            clock: None,
+            sink,
+        }));
+        let running: Arc<AtomicBool> = Arc::new(AtomicBool::new(true));
+
+        let worker: JoinHandle<()> = {
+            let state: Arc<Mutex<OutputState>> = Arc::clone(&state);
+            let running: Arc<AtomicBool> = Arc::clone(&running);
+            thread::spawn(move || {
+                while running.load(Ordering::SeqCst) {
+                    if let Ok(mut state) = state.lock() {
+                        let OutputState {
+                            scheduler,
+                            clock,
+                            sink,
+                        } = &mut *state;
+                        if let Some(clock) = clock {
+                            scheduler.update(clock.position(), sink.as_mut());
+                        }
+                    }
+                    thread::sleep(POLL_INTERVAL);
+                }
+            })
+        };
+
+        MidiOutput {
+            state,
+            running,
+            worker: Some(worker),
+        }
+    }
+
+    /// Seguir al reloj con los mensajes de una pieza, apagando los de la anterior
+    pub fn play(&self, events: Vec<MidiEvent>, clock: &PlaybackClock) {
+        self.with_state(|state| {
+            state.scheduler.silence(state.sink.as_mut());
+            state.scheduler = MidiScheduler::new(events);
+            state.clock = Some(clock.clone());
+        });
+    }
+
+    /// Apagar lo que suena, como al pausar; al volver a moverse el reloj sigue sonando
+    pub fn silence(&self) {
+        self.with_state(|state| state.scheduler.silence(state.sink.as_mut()));
+    }
+
+    /// Apagar lo que suena y dejar de seguir al reloj
+    pub fn stop(&self) {
+        self.with_state(|state| {
+            state.scheduler.silence(state.sink.as_mut());
+            state.scheduler = MidiScheduler::default();
+            state.clock = None;
+        });
+    }
+
+    fn with_state(&self, f: impl FnOnce(&mut OutputState)) {
+        match self.state.lock() {
+            Ok(mut state) => f(&mut state),
+            Err(e) => log::error!("{}", e),
+        }
+    }
+}
+
+impl Drop for MidiOutput {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+        self.stop();
+    }
+}
@@ -0,0 +1,278 @@
+use {
+    crate::models::{
+        markings::{Articulation, Markings, Pedal},
+        note::Note,
+        partiture::{Hand, Partiture, PieceMetadata},
+        score::Piece,
+        score_error::ScoreError,
+        take::Take,
+    },
+    std::{fs, io, ops::Range, path::Path},
+};
+
+// Resolución del archivo: ticks por negra
+pub const TICKS_PER_QUARTER: u16 = 480;
+
+// Parte de su duración que suena una nota en staccato
+const STACCATO_LENGTH: f32 = 0.5;
+
+// Controlador MIDI del pedal de resonancia
+pub const SUSTAIN_CONTROLLER: u8 = 64;
+
+/// Nota lista para escribirse en un archivo MIDI, con tiempos en segundos
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MidiNote {
+    pub start: f32,
+    pub duration: f32,
+    pub pitch: u8,
+    pub velocity: u8,
+}
+
+impl MidiNote {
+    // Nota tal y como suena: con la velocidad de las dinámicas y acortada si es staccato
+    pub fn from_note(note: &Note, markings: &Markings) -> Self {
+        let length: f32 = if note.articulations.contains(&Articulation::Staccato) {
+            STACCATO_LENGTH
+        } else {
+            1.0
+        };
+        MidiNote {
+            start: note.start,
+            duration: note.duration * length,
+            pitch: note.pitch,
+            velocity: markings.note_velocity(note),
+        }
+    }
+}
+
+/// Pista del archivo MIDI (una por mano)
+#[derive(Debug, Clone, PartialEq)]
+pub struct MidiTrack {
+    pub name: String,
+    pub channel: u8,
+    pub notes: Vec<MidiNote>,
+    pub pedal: Vec<Pedal>, // El pedal va por canal, así que cada pista lleva el suyo
+}
+
+impl MidiTrack {
+    // Pista a partir de las notas de una partitura y sus marcas, ignorando silencios
+    pub fn from_notes(name: &str, channel: u8, notes: &[Note], markings: &Markings) -> Self {
+        MidiTrack {
+            name: name.to_string(),
+            channel,
+            notes: notes
+                .iter()
+                .filter(|n| n.pitch != 0 && !n.is_rest)
+                .map(|n| MidiNote::from_note(n, markings))
+                .collect(),
+            pedal: markings.pedal.clone(),
+        }
+    }
+}
+
+// Evento MIDI con su tiempo absoluto en ticks
+struct TimedEvent {
+    tick: u32,
+    order: u8, // Los note-off y el pedal levantado van antes que los note-on en el mismo tick
+    bytes: Vec<u8>,
+}
+
+/// Microsegundos por negra a partir de la duración de la unidad del compás
+pub fn tempo_micros_per_quarter(metadata: &PieceMetadata) -> u32 {
+    let (_, beat_unit) = metadata.time_signature;
+    let quarter_seconds: f32 = metadata.base_note_value * beat_unit.max(1) as f32 / 4.0;
+    (quarter_seconds * 1_000_000.0)
+        .round()
+        .clamp(1.0, 0xFF_FFFF as f32) as u32
+}
+
+/// Genera un archivo MIDI de tipo 1 con una pista por cada `MidiTrack`.
+/// El tempo y el compás se escriben en la primera pista.
+pub fn write_smf(tracks: &[MidiTrack], metadata: &PieceMetadata) -> Vec<u8> {
+    let micros_per_quarter: u32 = tempo_micros_per_quarter(metadata);
+    let seconds_per_tick: f32 = micros_per_quarter as f32 / 1_000_000.0 / TICKS_PER_QUARTER as f32;
+
+    let mut bytes: Vec<u8> = Vec::new();
+
+    // Cabecera: formato 1, número de pistas y resolución
+    bytes.extend_from_slice(b"MThd");
+    bytes.extend_from_slice(&6u32.to_be_bytes());
+    bytes.extend_from_slice(&1u16.to_be_bytes());
+    bytes.extend_from_slice(&(tracks.len() as u16).to_be_bytes());
+    bytes.extend_from_slice(&TICKS_PER_QUARTER.to_be_bytes());
+
+    for (i, track) in tracks.iter().enumerate() {
+        let mut events: Vec<TimedEvent> = Vec::new();
+
+        // Nombre de la pista
+        events.push(meta_event(0, 0x03, track.name.as_bytes()));
+
+        if i == 0 {
+            let (beats, beat_unit) = metadata.time_signature;
+            events.push(meta_event(0, 0x51, &micros_per_quarter.to_be_bytes()[1..]));
+            events.push(meta_event(
+                0,
+                0x58,
+                &[beats, beat_unit.max(1).trailing_zeros() as u8, 24, 8],
+            ));
+        }
+
+        let channel: u8 = track.channel & 0x0F;
+        for note in &track.notes {
+            let on: u32 = seconds_to_ticks(note.start, seconds_per_tick);
+            let off: u32 =
+                seconds_to_ticks(note.start + note.duration, seconds_per_tick).max(on + 1);
+            events.push(TimedEvent {
+                tick: on,
+                order: 2,
+                bytes: vec![
+                    0x90 | channel,
+                    note.pitch & 0x7F,
+                    note.velocity.clamp(1, 127),
+                ],
+            });
+            events.push(TimedEvent {
+                tick: off,
+                order: 1,
+                bytes: vec![0x80 | channel, note.pitch & 0x7F, 0],
+            });
+        }
+
+        for pedal in &track.pedal {
+            let down: u32 = seconds_to_ticks(pedal.start, seconds_per_tick);
+            let up: u32 = seconds_to_ticks(pedal.end, seconds_per_tick).max(down + 1);
+            events.push(TimedEvent {
+                tick: down,
+                order: 2,
+                bytes: vec![0xB0 | channel, SUSTAIN_CONTROLLER, 127],
+            });
+            events.push(TimedEvent {
+                tick: up,
+                order: 1,
+                bytes: vec![0xB0 | channel, SUSTAIN_CONTROLLER, 0],
+            });
+        }
+
+        events.sort_by_key(|e| (e.tick, e.order));
+        let end_tick: u32 = events.last().map(|e| e.tick).unwrap_or(0);
+        events.push(meta_event(end_tick, 0x2F, &[]));
+
+        // Cuerpo de la pista con tiempos delta
+        let mut body: Vec<u8> = Vec::new();
+        let mut last_tick: u32 = 0;
+        for event in events {
+            write_vlq(&mut body, event.tick - last_tick);
+            body.extend_from_slice(&event.bytes);
+            last_tick = event.tick;
+        }
+
+        bytes.extend_from_slice(b"MTrk");
+        bytes.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&body);
+    }
+
+    bytes
+}
+
+/// Compás y tempo de una partitura cargada; sin metadatos, 4/4 con la negra de medio segundo
+pub fn loaded_metadata(partiture: (&Partiture, &Partiture)) -> PieceMetadata {
+    let (left, right) = partiture;
+    right
+        .metadata
+        .clone()
+        .or_else(|| left.metadata.clone())
+        .unwrap_or(PieceMetadata {
+            time_signature: (4, 4),
+            base_note_value: 0.5,
+            sections: Vec::new(),
+        })
+}
+
+/// Exporta las dos manos de una partitura cargada como archivo MIDI de dos pistas
+pub fn partitures_to_smf(partiture: (&Partiture, &Partiture)) -> Vec<u8> {
+    let (left, right) = partiture;
+    let metadata: PieceMetadata = loaded_metadata(partiture);
+
+    let tracks: [MidiTrack; 2] = [
+        MidiTrack::from_notes("Right hand", 0, &right.notes, &right.markings),
+        MidiTrack::from_notes("Left hand", 1, &left.notes, &left.markings),
+    ];
+    write_smf(&tracks, &metadata)
+}
+
+/// Exporta una pieza de la biblioteca expandiendo `structure`.
+/// Con `range` solo se incluyen esas entradas de `structure` (por índice).
+pub fn piece_to_smf(piece: &Piece, range: Option<Range<usize>>) -> Result<Vec<u8>, ScoreError> {
+    let range: Range<usize> = range.unwrap_or(0..piece.metadata.structure.len());
+    let right: Vec<Note> = piece.expand_range(&Hand::Right, range.clone())?;
+    let left: Vec<Note> = piece.expand_range(&Hand::Left, range.clone())?;
+    let markings: Markings = piece.expand_markings_range(range)?;
+
+    let tracks: [MidiTrack; 2] = [
+        MidiTrack::from_notes("Right hand", 0, &right, &markings),
+        MidiTrack::from_notes("Left hand", 1, &left, &markings),
+    ];
+    Ok(write_smf(&tracks, &PieceMetadata::from(&piece.metadata)))
+}
+
+/// Exporta una grabación como archivo MIDI de una pista, con las notas tal y como se tocaron
+/// y la velocidad de cada pulsación, en el compás y el tempo de la pieza
+pub fn take_to_smf(take: &Take, metadata: &PieceMetadata) -> Vec<u8> {
+    let track: MidiTrack = MidiTrack {
+        name: take.piece.clone(),
+        channel: 0,
+        notes: take
+            .notes
+            .iter()
+            .map(|played| MidiNote {
+                start: played.on,
+                duration: (played.off - played.on).max(0.0),
+                pitch: played.pitch,
+                velocity: played.velocity,
+            })
+            .collect(),
+        pedal: Vec::new(),
+    };
+    write_smf(&[track], metadata)
+}
+
+/// Escribe el archivo MIDI en disco, creando la carpeta si no existe
+pub fn save_smf(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, bytes)
+}
+
+fn seconds_to_ticks(seconds: f32, seconds_per_tick: f32) -> u32 {
+    (seconds.max(0.0) / seconds_per_tick).round() as u32
+}
+
+fn meta_event(tick: u32, kind: u8, data: &[u8]) -> TimedEvent {
+    let mut bytes: Vec<u8> = vec![0xFF, kind];
+    write_vlq(&mut bytes, data.len() as u32);
+    bytes.extend_from_slice(data);
+    TimedEvent {
+        tick,
+        order: 0,
+        bytes,
+    }
+}
+
+// Cantidad de longitud variable: 7 bits por byte, con el bit alto indicando continuación
+fn write_vlq(out: &mut Vec<u8>, mut value: u32) {
+    let mut buffer: [u8; 5] = [0; 5];
+    let mut len: usize = 0;
+    loop {
+        buffer[len] = (value & 0x7F) as u8;
+        len += 1;
+        value >>= 7;
+        if value == 0 {
+            break;
+        }
+    }
+    for i in (0..len).rev() {
+        let continuation: u8 = if i > 0 { 0x80 } else { 0 };
+        out.push(buffer[i] | continuation);
+    }
+}
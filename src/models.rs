@@ -1,3 +1,20 @@
+pub mod calibration;
+pub mod capture;
+pub mod curriculum;
+pub mod ear_training;
+pub mod editor;
+pub mod flashcards;
+pub mod input;
+pub mod latency;
+pub mod markings;
 pub mod note;
 pub mod partiture;
+pub mod playback;
+pub mod progress;
+pub mod results;
+pub mod rewards;
+pub mod rhythm;
+pub mod score;
+pub mod score_error;
 pub mod settings;
+pub mod take;
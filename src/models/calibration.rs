@@ -0,0 +1,271 @@
+use {
+    crate::{
+        audio::{
+            analyser::{AnalyserConfig, DetectedOnset, NoiseProfile, analyse},
+            tuning::{a4_from, measure_frequency, median},
+        },
+        models::settings::MicrophoneSettings,
+        utils::{
+            frecuency::{InputBlock, SampleClock},
+            note_names::note_name,
+        },
+    },
+    std::time::{Duration, Instant},
+};
+
+/// Notas de referencia que se piden al calibrar: La4, La3 y Mi4
+pub const REFERENCE_NOTES: [u8; 3] = [69, 57, 64];
+
+/// Segundos de silencio grabados para medir el ruido
+pub const SILENCE_SECONDS: f32 = 2.0;
+
+/// Pulsos de cuenta antes de tocar la nota de referencia
+pub const CUE_BEATS: u32 = 3;
+
+/// Segundos entre pulsos de la cuenta
+pub const BEAT_SECONDS: f32 = 0.75;
+
+// Ventana en la que se acepta la nota alrededor del pulso
+const EARLIEST: f32 = -0.3;
+const LATEST: f32 = 1.0;
+
+// Audio de la nota usado para medir su frecuencia
+const TONE_DELAY: f32 = 0.08;
+const TONE_SECONDS: f32 = 0.3;
+
+// Valores razonables: un piano muy desafinado sigue cerca de 440 Hz
+const A4_RANGE: (f32, f32) = (415.0, 466.0);
+const MAX_LATENCY: f32 = 0.5;
+
+/// Paso del asistente de calibración
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CalibrationStep {
+    Intro,            // Explicación, esperando a empezar
+    Silence,          // Grabando el ruido de fondo
+    Reference(usize), // Esperando la nota de referencia con este índice
+    Done,             // Terminado, falta guardar
+}
+
+/// Lectura de una nota de referencia
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReferenceReading {
+    pub pitch: u8,
+    pub frequency: f32, // Frecuencia medida de la fundamental
+    pub offset: f32,    // Segundos entre el pulso y la nota detectada
+}
+
+/// Asistente de calibración del micrófono: mide el ruido con un silencio y después
+/// pide unas notas de referencia al ritmo de una cuenta para medir la afinación y la
+/// latencia. Recibe el audio en bloques, así que no depende del dispositivo.
+pub struct CalibrationWizard {
+    sample_rate: u32,
+    step: CalibrationStep,
+    samples: Vec<f32>,    // Audio del paso actual
+    clock: SampleClock,   // Relación entre las muestras del paso y el reloj
+    cue: Option<Instant>, // Momento en que hay que tocar la nota
+    now: Option<Instant>, // Último tick, para las etiquetas de la vista
+    noise: Option<NoiseProfile>,
+    readings: Vec<ReferenceReading>,
+    message: Option<String>, // Aviso del último intento fallido
+}
+
+impl CalibrationWizard {
+    pub fn new(sample_rate: u32) -> Self {
+        CalibrationWizard {
+            sample_rate,
+            step: CalibrationStep::Intro,
+            samples: Vec::new(),
+            clock: SampleClock::default(),
+            cue: None,
+            now: None,
+            noise: None,
+            readings: Vec::new(),
+            message: None,
+        }
+    }
+
+    pub fn step(&self) -> CalibrationStep {
+        self.step
+    }
+
+    pub fn readings(&self) -> &[ReferenceReading] {
+        &self.readings
+    }
+
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    /// Si hay que pulsar el botón para seguir (no se está grabando nada)
+    pub fn waiting(&self) -> bool {
+        match self.step {
+            CalibrationStep::Intro => true,
+            CalibrationStep::Reference(_) => self.cue.is_none(),
+            _ => false,
+        }
+    }
+
+    /// Empezar el paso actual: el silencio o la cuenta de la nota de referencia.
+    /// Devuelve el momento en que hay que tocar la nota, para programar los clicks.
+    pub fn start(&mut self, now: Instant) -> Option<Instant> {
+        self.samples.clear();
+        self.clock = SampleClock::default();
+        self.message = None;
+
+        match self.step {
+            CalibrationStep::Intro => {
+                self.step = CalibrationStep::Silence;
+                None
+            }
+            CalibrationStep::Reference(_) => {
+                let cue: Instant = now + Duration::from_secs_f32(CUE_BEATS as f32 * BEAT_SECONDS);
+                self.cue = Some(cue);
+                Some(cue)
+            }
+            _ => None,
+        }
+    }
+
+    /// Añadir audio del micrófono. Solo se guarda mientras se está grabando.
+    pub fn push(&mut self, block: &InputBlock) {
+        let recording: bool = match self.step {
+            CalibrationStep::Silence => true,
+            CalibrationStep::Reference(_) => self.cue.is_some(),
+            _ => false,
+        };
+        if recording {
+            self.clock.push(block);
+            self.samples.extend_from_slice(&block.samples);
+        }
+    }
+
+    /// Avanzar cuando el paso actual tiene audio suficiente
+    pub fn tick(&mut self, now: Instant) {
+        self.now = Some(now);
+        match self.step {
+            CalibrationStep::Silence
+                if self.samples.len() as f32 >= SILENCE_SECONDS * self.sample_rate as f32 =>
+            {
+                self.noise = Some(NoiseProfile::measure(
+                    &self.samples,
+                    self.sample_rate,
+                    &AnalyserConfig::default(),
+                ));
+                self.step = CalibrationStep::Reference(0);
+            }
+            CalibrationStep::Reference(index) => {
+                let Some(cue) = self.cue else {
+                    return;
+                };
+                if now < cue + Duration::from_secs_f32(LATEST + TONE_DELAY + TONE_SECONDS) {
+                    return;
+                }
+                self.cue = None;
+
+                match self.read_reference(REFERENCE_NOTES[index], cue) {
+                    Ok(reading) => {
+                        self.readings.push(reading);
+                        self.step = if index + 1 < REFERENCE_NOTES.len() {
+                            CalibrationStep::Reference(index + 1)
+                        } else {
+                            CalibrationStep::Done
+                        };
+                    }
+                    Err(message) => self.message = Some(message),
+                }
+                self.samples.clear();
+            }
+            _ => {}
+        }
+    }
+
+    /// Texto de la cuenta: "3", "2", "1" y "¡Ahora!"
+    pub fn countdown(&self) -> Option<String> {
+        let (cue, now) = (self.cue?, self.now?);
+        if now >= cue {
+            return Some("¡Ahora!".to_string());
+        }
+        let remaining: f32 = cue.duration_since(now).as_secs_f32();
+        Some(format!("{}", (remaining / BEAT_SECONDS).ceil() as u32))
+    }
+
+    /// Nota que se pide en el paso actual, por ejemplo "A4"
+    pub fn reference_name(&self) -> Option<String> {
+        match self.step {
+            CalibrationStep::Reference(index) => Some(note_name(REFERENCE_NOTES[index])),
+            _ => None,
+        }
+    }
+
+    /// Ajustes calculados con el ruido y las lecturas. `None` hasta terminar.
+    pub fn result(&self) -> Option<MicrophoneSettings> {
+        if self.step != CalibrationStep::Done {
+            return None;
+        }
+        let noise: &NoiseProfile = self.noise.as_ref()?;
+        let a4s: Vec<f32> = self
+            .readings
+            .iter()
+            .map(|reading| a4_from(reading.pitch, reading.frequency))
+            .collect();
+        let offsets: Vec<f32> = self.readings.iter().map(|r| r.offset).collect();
+
+        Some(MicrophoneSettings {
+            noise_threshold: Some(noise.onset_threshold()),
+            noise_rms: noise.rms,
+            a4: median(&a4s)?.clamp(A4_RANGE.0, A4_RANGE.1),
+            latency: median(&offsets)?.clamp(0.0, MAX_LATENCY),
+            use_in_game: true,
+        })
+    }
+
+    // Buscar el ataque de la nota pedida cerca del pulso y medirlo
+    fn read_reference(&self, pitch: u8, cue: Instant) -> Result<ReferenceReading, String> {
+        let onsets: Vec<DetectedOnset> = analyse(
+            &self.samples,
+            self.sample_rate,
+            AnalyserConfig::default(),
+            self.noise.clone(),
+        );
+
+        // Segundos de cada ataque respecto al pulso
+        let offset_of = |onset: &DetectedOnset| -> Option<f32> {
+            let sample: u64 = (onset.time * self.sample_rate as f32) as u64;
+            let at: Instant = self.clock.instant_of(sample, self.sample_rate)?;
+            Some(if at >= cue {
+                at.duration_since(cue).as_secs_f32()
+            } else {
+                -cue.duration_since(at).as_secs_f32()
+            })
+        };
+
+        // La nota puede sonar un semitono desviada si el piano está muy desafinado
+        let (onset, offset) = onsets
+            .iter()
+            .filter_map(|onset| offset_of(onset).map(|offset| (onset, offset)))
+            .filter(|(_, offset)| (EARLIEST..=LATEST).contains(offset))
+            .filter(|(onset, _)| onset.pitches.iter().any(|p| p.abs_diff(pitch) <= 1))
+            .max_by(|a, b| a.0.strength.total_cmp(&b.0.strength))
+            .ok_or_else(|| {
+                format!(
+                    "No se ha oído el {} con la cuenta. Inténtalo otra vez.",
+                    note_name(pitch)
+                )
+            })?;
+
+        let start: usize = ((onset.time + TONE_DELAY) * self.sample_rate as f32) as usize;
+        let end: usize =
+            (start + (TONE_SECONDS * self.sample_rate as f32) as usize).min(self.samples.len());
+        let frequency: f32 = self
+            .samples
+            .get(start..end)
+            .and_then(|tone| measure_frequency(tone, self.sample_rate, pitch, 440.0))
+            .ok_or_else(|| "La nota ha sonado demasiado poco. Inténtalo otra vez.".to_string())?;
+
+        Ok(ReferenceReading {
+            pitch,
+            frequency,
+            offset: offset.max(0.0),
+        })
+    }
+}
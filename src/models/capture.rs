@@ -0,0 +1,257 @@
+use {
+    crate::{
+        models::{
+            note::Note,
+            partiture::PieceMetadata,
+            rhythm::RHYTHM_METERS,
+            score::{FLAT_SECTION, Meter, Piece, ScoreMetadata, Section},
+        },
+        utils::{
+            quantize::{QuantizeGrid, Quantizer},
+            time::seconds_between,
+        },
+    },
+    core::fmt,
+    std::{collections::BTreeMap, time::Instant},
+};
+
+/// Nombre con el que se propone guardar una grabación
+pub const CAPTURE_PIECE: &str = "grabacion";
+
+/// Canal de la mano derecha al separar las manos por canal; el resto es la izquierda
+pub const RIGHT_CHANNEL: u8 = 1;
+
+/// Cómo se reparten las notas grabadas entre las manos
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandSplit {
+    Pitch,   // Desde el tono de corte hacia arriba, mano derecha
+    Channel, // Canal 1 mano derecha, canal 2 mano izquierda
+}
+
+impl HandSplit {
+    pub const ALL: [HandSplit; 2] = [HandSplit::Pitch, HandSplit::Channel];
+}
+
+impl fmt::Display for HandSplit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            HandSplit::Pitch => "Por altura",
+            HandSplit::Channel => "Por canal",
+        })
+    }
+}
+
+/// Parámetros de la grabación
+#[derive(Debug, Clone)]
+pub struct CaptureConfig {
+    pub meter: Meter,
+    pub tempo: u32, // Pulsos por minuto (negras con puntillo en los compuestos)
+    pub grid: QuantizeGrid,
+    pub triplets: bool,
+    pub swing: u8, // Porcentaje del par donde cae la segunda figura, 50 sin swing
+    pub split: HandSplit,
+    pub split_pitch: u8, // Primer tono de la mano derecha al separar por altura
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        CaptureConfig {
+            meter: RHYTHM_METERS[2],
+            tempo: 80,
+            grid: QuantizeGrid::Sixteenth,
+            triplets: false,
+            swing: 50,
+            split: HandSplit::Pitch,
+            split_pitch: 60,
+        }
+    }
+}
+
+impl CaptureConfig {
+    /// Metadatos de la grabación: la unidad del compás sale del tempo del pulso
+    pub fn metadata(&self) -> PieceMetadata {
+        let mut metadata: PieceMetadata = PieceMetadata {
+            time_signature: (self.meter.beats, self.meter.beat_unit),
+            base_note_value: 60.0 / self.tempo.max(1) as f32,
+            sections: Vec::new(),
+        };
+        if metadata.is_compound() {
+            metadata.base_note_value /= 3.0;
+        }
+        metadata
+    }
+
+    /// Segundos de una negra al tempo de la grabación
+    pub fn quarter_seconds(&self) -> f32 {
+        self.metadata().base_note_value * self.meter.beat_unit as f32 / 4.0
+    }
+
+    pub fn quantizer(&self) -> Quantizer {
+        Quantizer::new(self.quarter_seconds(), self.grid, self.triplets, self.swing)
+    }
+}
+
+/// Nota tal y como se tocó, en segundos desde el final de la cuenta
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CapturedNote {
+    pub pitch: u8,
+    pub channel: u8,
+    pub on: f32,
+    pub off: Option<f32>, // Sin soltar todavía
+}
+
+/// Grabación en curso o terminada
+#[derive(Debug, Clone)]
+pub struct CaptureSession {
+    pub config: CaptureConfig,
+    pub notes: Vec<CapturedNote>,
+    pub channel: u8,   // Canal de lo que se toca con el teclado del ordenador
+    pub name: String,  // Nombre con el que se guarda en la biblioteca
+    pub title: String, // Título de la pieza
+    pub status: Option<String>, // Resultado de guardar
+    zero: Instant,     // Momento del segundo 0, tras la cuenta
+    position: f32,
+    end: Option<f32>, // Segundo en que se paró
+}
+
+impl CaptureSession {
+    /// Empezar con `zero` como el momento del primer pulso, cuando acaba la cuenta
+    pub fn new(config: CaptureConfig, name: &str, zero: Instant) -> Self {
+        CaptureSession {
+            config,
+            notes: Vec::new(),
+            channel: RIGHT_CHANNEL,
+            name: name.to_string(),
+            title: "Grabación".to_string(),
+            status: None,
+            zero,
+            position: f32::NEG_INFINITY,
+            end: None,
+        }
+    }
+
+    pub fn tick(&mut self, now: Instant) {
+        if self.end.is_none() {
+            self.position = seconds_between(self.zero, now);
+        }
+    }
+
+    /// Segundo de la grabación, negativo durante la cuenta
+    pub fn position(&self) -> f32 {
+        self.position
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.end.is_none()
+    }
+
+    /// Lo que se toca a partir de ahora va por el otro canal
+    pub fn toggle_channel(&mut self) {
+        self.channel = if self.channel == RIGHT_CHANNEL {
+            RIGHT_CHANNEL + 1
+        } else {
+            RIGHT_CHANNEL
+        };
+    }
+
+    /// Registrar una nota con el momento en que se tocó, ya corregido el retraso de la entrada
+    pub fn note_on(&mut self, pitch: u8, at: Instant) {
+        if !self.is_recording() {
+            return;
+        }
+        let time: f32 = seconds_between(self.zero, at);
+        // La tecla mantenida se repite: solo cuenta la primera pulsación
+        if self
+            .notes
+            .iter()
+            .any(|note| note.pitch == pitch && note.off.is_none())
+        {
+            return;
+        }
+        self.notes.push(CapturedNote {
+            pitch,
+            channel: self.channel,
+            on: time,
+            off: None,
+        });
+    }
+
+    pub fn note_off(&mut self, pitch: u8, at: Instant) {
+        let time: f32 = seconds_between(self.zero, at);
+        if let Some(note) = self
+            .notes
+            .iter_mut()
+            .rev()
+            .find(|note| note.pitch == pitch && note.off.is_none())
+        {
+            note.off = Some(time.max(note.on));
+        }
+    }
+
+    /// Parar la grabación: las notas que siguen pulsadas acaban aquí
+    pub fn stop(&mut self, at: Instant) {
+        if !self.is_recording() {
+            return;
+        }
+        let time: f32 = seconds_between(self.zero, at);
+        for note in self.notes.iter_mut().filter(|note| note.off.is_none()) {
+            note.off = Some(time.max(note.on));
+        }
+        self.position = time;
+        self.end = Some(time);
+    }
+
+    /// Pieza de una sola sección con lo grabado ajustado a la rejilla y repartido entre las manos
+    pub fn to_piece(&self) -> Piece {
+        let quantizer: Quantizer = self.config.quantizer();
+        let mut section: Section = Section::default();
+
+        for captured in &self.notes {
+            let start: f32 = quantizer.onset(captured.on);
+            let duration: f32 =
+                quantizer.duration(captured.on, captured.off.unwrap_or(captured.on));
+            let right: bool = match self.config.split {
+                HandSplit::Pitch => captured.pitch >= self.config.split_pitch,
+                HandSplit::Channel => captured.channel == RIGHT_CHANNEL,
+            };
+            let notes: &mut Vec<Note> = if right {
+                &mut section.right
+            } else {
+                &mut section.left
+            };
+            // La misma tecla dos veces en la misma posición de la rejilla es una sola nota
+            if let Some(note) = notes
+                .iter_mut()
+                .find(|note| note.pitch == captured.pitch && note.start == start)
+            {
+                note.duration = note.duration.max(duration);
+                continue;
+            }
+            notes.push(Note {
+                start,
+                ..Note::new(captured.pitch, duration, false, Default::default())
+            });
+        }
+        for notes in [&mut section.right, &mut section.left] {
+            notes.sort_by(|a, b| a.start.total_cmp(&b.start).then(a.pitch.cmp(&b.pitch)));
+        }
+
+        let metadata: PieceMetadata = self.config.metadata();
+        let bpm: f32 = 60.0 / self.config.quarter_seconds().max(f32::EPSILON);
+        Piece {
+            metadata: ScoreMetadata {
+                title: self.title.clone(),
+                composer: None,
+                source: None,
+                meter: self.config.meter,
+                tempo: Some(format!("{} bpm", bpm.round())),
+                key: None,
+                note_duration_unit_seconds: metadata.base_note_value,
+                structure: vec![FLAT_SECTION.to_string()],
+                playback_instructions: None,
+                exercise: false,
+            },
+            sections: BTreeMap::from([(FLAT_SECTION.to_string(), section)]),
+        }
+    }
+}
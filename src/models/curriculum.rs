@@ -0,0 +1,190 @@
+use {
+    crate::{asset_path, models::score::ScoreFile},
+    serde::{Deserialize, Serialize},
+    std::{
+        collections::{BTreeMap, HashSet},
+        error, fs,
+        path::PathBuf,
+    },
+};
+
+// Límites de la velocidad de una lección respecto al tempo escrito
+const MIN_TEMPO: f32 = 0.25;
+const MAX_TEMPO: f32 = 2.0;
+
+/// Lección: página de explicación, pieza que hay que tocar y lo necesario para aprobarla
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lesson {
+    pub id: String, // Único entre todos los cursos
+    pub title: String,
+    pub explanation: Vec<String>, // Párrafos de la página de explicación
+    pub piece: String,            // Pieza de la biblioteca que hay que tocar
+    pub required_accuracy: f32,   // Precisión mínima para aprobar, de 0.0 a 1.0
+    #[serde(default = "default_tempo")]
+    pub tempo: f32, // Velocidad a la que se toca respecto al tempo escrito, 1.0 es a tempo
+    #[serde(default)]
+    pub unlocks: Vec<String>, // Lecciones que se desbloquean al aprobarla
+}
+
+impl Lesson {
+    // Velocidad del reloj de reproducción al tocar la lección
+    pub fn playback_rate(&self) -> f32 {
+        self.tempo.clamp(MIN_TEMPO, MAX_TEMPO)
+    }
+
+    // Si una interpretación a `rate` con precisión `accuracy` aprueba la lección
+    pub fn passes(&self, accuracy: f32, rate: f32) -> bool {
+        accuracy >= self.required_accuracy && rate >= self.playback_rate()
+    }
+}
+
+fn default_tempo() -> f32 {
+    1.0
+}
+
+/// Curso: lecciones en orden, escrito como un archivo JSON en `assets/courses`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Course {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    pub lessons: Vec<Lesson>,
+}
+
+/// Todos los cursos, en el orden de los nombres de archivo
+#[derive(Debug, Clone, Default)]
+pub struct Curriculum {
+    pub courses: Vec<Course>,
+}
+
+impl Curriculum {
+    /// Cargar los cursos de `assets/courses`. Un archivo mal escrito se avisa y se salta.
+    pub fn load() -> Self {
+        let mut paths: Vec<PathBuf> = fs::read_dir(asset_path!("courses"))
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+                    .collect()
+            })
+            .unwrap_or_else(|e| {
+                log::error!("No se pudieron leer los cursos: {}", e);
+                Vec::new()
+            });
+        paths.sort();
+
+        let courses: Vec<Course> = paths
+            .iter()
+            .filter_map(|path| {
+                load_course(path)
+                    .map_err(|e| log::error!("Curso inválido {}: {}", path.display(), e))
+                    .ok()
+            })
+            .collect();
+        Curriculum { courses }
+    }
+
+    pub fn lessons(&self) -> impl Iterator<Item = &Lesson> {
+        self.courses.iter().flat_map(|course| course.lessons.iter())
+    }
+
+    pub fn lesson(&self, id: &str) -> Option<&Lesson> {
+        self.lessons().find(|lesson| lesson.id == id)
+    }
+
+    /// Una lección está abierta si ninguna otra la desbloquea o si ya se aprobó alguna que lo hace
+    pub fn is_unlocked(&self, id: &str, progress: &LessonProgress) -> bool {
+        let mut unlocked_by = self
+            .lessons()
+            .filter(|lesson| lesson.unlocks.iter().any(|unlock| unlock == id))
+            .peekable();
+        unlocked_by.peek().is_none() || unlocked_by.any(|lesson| progress.is_passed(&lesson.id))
+    }
+
+    /// Errores de los cursos: ids repetidos, piezas que no están en la biblioteca y
+    /// desbloqueos de lecciones que no existen
+    pub fn problems(&self, library: &ScoreFile) -> Vec<String> {
+        let mut problems: Vec<String> = Vec::new();
+        let mut ids: HashSet<&str> = HashSet::new();
+
+        for lesson in self.lessons() {
+            if !ids.insert(&lesson.id) {
+                problems.push(format!("lección repetida '{}'", lesson.id));
+            }
+            if library.piece(&lesson.piece).is_err() {
+                problems.push(format!(
+                    "la lección '{}' usa la pieza desconocida '{}'",
+                    lesson.id, lesson.piece
+                ));
+            }
+        }
+        for lesson in self.lessons() {
+            for unlock in &lesson.unlocks {
+                if !ids.contains(unlock.as_str()) {
+                    problems.push(format!(
+                        "la lección '{}' desbloquea la lección desconocida '{}'",
+                        lesson.id, unlock
+                    ));
+                }
+            }
+        }
+        problems
+    }
+}
+
+fn load_course(path: &PathBuf) -> Result<Course, Box<dyn error::Error>> {
+    let json: String = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// Mejor resultado de una lección
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct LessonRecord {
+    pub best_accuracy: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passed_at: Option<u64>, // Segundos desde UNIX_EPOCH del primer aprobado
+}
+
+/// Lecciones hechas, guardadas junto a `settings.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LessonProgress {
+    pub lessons: BTreeMap<String, LessonRecord>,
+}
+
+impl LessonProgress {
+    // Cargar el progreso, o uno vacío si aún no existe
+    pub fn load() -> Self {
+        fs::read_to_string(asset_path!("lessons.json"))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    // Guardar el progreso
+    pub fn save(&self) -> Result<(), Box<dyn error::Error>> {
+        let json: String = serde_json::to_string_pretty(self)?;
+        fs::write(asset_path!("lessons.json"), json)?;
+        Ok(())
+    }
+
+    pub fn record(&self, id: &str) -> Option<&LessonRecord> {
+        self.lessons.get(id)
+    }
+
+    pub fn is_passed(&self, id: &str) -> bool {
+        self.record(id)
+            .is_some_and(|record| record.passed_at.is_some())
+    }
+
+    /// Apuntar un intento terminado. Devuelve si la lección quedó aprobada con él.
+    pub fn register(&mut self, lesson: &Lesson, accuracy: f32, rate: f32, now: u64) -> bool {
+        let passed: bool = lesson.passes(accuracy, rate);
+        let record: &mut LessonRecord = self.lessons.entry(lesson.id.clone()).or_default();
+        record.best_accuracy = record.best_accuracy.max(accuracy);
+        if passed && record.passed_at.is_none() {
+            record.passed_at = Some(now);
+        }
+        passed
+    }
+}
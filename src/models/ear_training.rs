@@ -0,0 +1,576 @@
+use {
+    crate::{
+        asset_path,
+        audio::tone::ToneNote,
+        models::flashcards::Guess,
+        utils::{
+            note_names::note_name,
+            sight_reading::{Key, SeededRng},
+        },
+    },
+    core::fmt,
+    serde::{Deserialize, Serialize},
+    std::{collections::BTreeMap, error, fs},
+};
+
+/// Niveles de dificultad de cada categoría, de 1 a `MAX_LEVEL`
+pub const MAX_LEVEL: u8 = 3;
+
+/// Intervalos que se preguntan: semitonos y nombre
+pub const INTERVALS: [(u8, &str); 12] = [
+    (1, "2ª menor"),
+    (2, "2ª mayor"),
+    (3, "3ª menor"),
+    (4, "3ª mayor"),
+    (5, "4ª justa"),
+    (6, "Tritono"),
+    (7, "5ª justa"),
+    (8, "6ª menor"),
+    (9, "6ª mayor"),
+    (10, "7ª menor"),
+    (11, "7ª mayor"),
+    (12, "Octava"),
+];
+
+// Intervalos de cada nivel: los más fáciles de distinguir primero
+const INTERVAL_LEVELS: [&[u8]; MAX_LEVEL as usize] = [
+    &[2, 4, 5, 7, 12],
+    &[1, 2, 3, 4, 5, 7, 8, 9, 12],
+    &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12],
+];
+
+/// Acordes que se preguntan: nombre y semitonos desde la fundamental
+pub const CHORDS: [(&str, &[u8]); 7] = [
+    ("Mayor", &[0, 4, 7]),
+    ("Menor", &[0, 3, 7]),
+    ("Disminuido", &[0, 3, 6]),
+    ("Aumentado", &[0, 4, 8]),
+    ("7ª de dominante", &[0, 4, 7, 10]),
+    ("7ª mayor", &[0, 4, 7, 11]),
+    ("7ª menor", &[0, 3, 7, 10]),
+];
+
+// Acordes de cada nivel, contando desde el principio de `CHORDS`
+const CHORD_LEVELS: [usize; MAX_LEVEL as usize] = [2, 4, 7];
+
+// Notas del dictado melódico y salto máximo en grados de la escala en cada nivel
+const MELODY_LEVELS: [(usize, usize); MAX_LEVEL as usize] = [(3, 1), (4, 4), (5, 7)];
+
+// Duración de las notas en segundos: sucesivas, a la vez y en el dictado
+const MELODIC_SECONDS: f32 = 0.8;
+const HARMONIC_SECONDS: f32 = 1.6;
+const MELODY_SECONDS: f32 = 0.6;
+
+// Registro de la nota de partida de intervalos y acordes
+const LOWEST_ROOT: u8 = 52; // Mi3
+const HIGHEST_ROOT: u8 = 67; // Sol4
+
+/// Qué se entrena
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum EarCategory {
+    Interval,
+    Chord,
+    Melody,
+}
+
+impl EarCategory {
+    pub const ALL: [EarCategory; 3] = [
+        EarCategory::Interval,
+        EarCategory::Chord,
+        EarCategory::Melody,
+    ];
+
+    /// Respuestas posibles con botón en un nivel, como índices de `INTERVALS` o de `CHORDS`.
+    /// El dictado melódico se contesta nota a nota.
+    pub fn choices(&self, level: u8) -> Vec<usize> {
+        let level: usize = level.clamp(1, MAX_LEVEL) as usize - 1;
+        match self {
+            EarCategory::Interval => INTERVALS
+                .iter()
+                .enumerate()
+                .filter(|(_, (semitones, _))| INTERVAL_LEVELS[level].contains(semitones))
+                .map(|(i, _)| i)
+                .collect(),
+            EarCategory::Chord => (0..CHORD_LEVELS[level]).collect(),
+            EarCategory::Melody => Vec::new(),
+        }
+    }
+
+    /// Nombre de una respuesta con botón
+    pub fn choice_name(&self, choice: usize) -> &'static str {
+        match self {
+            EarCategory::Interval => INTERVALS.get(choice).map_or("", |(_, name)| name),
+            EarCategory::Chord => CHORDS.get(choice).map_or("", |(name, _)| name),
+            EarCategory::Melody => "",
+        }
+    }
+}
+
+impl fmt::Display for EarCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            EarCategory::Interval => "Intervalos",
+            EarCategory::Chord => "Acordes",
+            EarCategory::Melody => "Dictado melódico",
+        })
+    }
+}
+
+/// Respuesta del jugador
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EarAnswer {
+    Choice(usize),  // Botón del intervalo o del acorde
+    Notes(Vec<u8>), // Clases de nota (0 = Do) de las notas que siguen a la dada
+}
+
+impl EarAnswer {
+    /// Leer notas escritas separadas por espacios o comas: "E G", "mi sol", "C4 E4 G4".
+    /// La octava no cuenta.
+    pub fn parse(text: &str) -> Option<EarAnswer> {
+        let notes: Option<Vec<u8>> = text
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|word| !word.is_empty())
+            .map(|word| match Guess::parse(word)? {
+                Guess::Pitch(pitch) => Some(pitch % 12),
+                Guess::PitchClass(class) => Some(class),
+            })
+            .collect();
+        notes
+            .filter(|notes| !notes.is_empty())
+            .map(EarAnswer::Notes)
+    }
+}
+
+/// Pregunta: las notas que suenan y la respuesta con botón si la tiene
+#[derive(Debug, Clone, PartialEq)]
+pub struct EarQuestion {
+    pub category: EarCategory,
+    pub pitches: Vec<u8>,      // De grave a agudo, o en orden en el dictado
+    pub harmonic: bool,        // Las notas suenan a la vez
+    pub choice: Option<usize>, // Índice en `INTERVALS` o en `CHORDS`
+}
+
+impl EarQuestion {
+    /// Pregunta al azar de una categoría y un nivel
+    pub fn generate(category: EarCategory, level: u8, rng: &mut SeededRng) -> Self {
+        let level: u8 = level.clamp(1, MAX_LEVEL);
+        let root: u8 = LOWEST_ROOT + rng.below((HIGHEST_ROOT - LOWEST_ROOT + 1) as usize) as u8;
+        let choices: Vec<usize> = category.choices(level);
+        let choice: Option<usize> = rng.pick(&choices).copied();
+
+        match category {
+            EarCategory::Interval => {
+                let semitones: u8 = choice.map_or(12, |i| INTERVALS[i].0);
+                EarQuestion {
+                    category,
+                    pitches: vec![root, root + semitones],
+                    // En el último nivel también a la vez
+                    harmonic: level == MAX_LEVEL && rng.chance(0.5),
+                    choice,
+                }
+            }
+            EarCategory::Chord => {
+                let mut pitches: Vec<u8> = CHORDS[choice.unwrap_or(0)]
+                    .1
+                    .iter()
+                    .map(|semitones| root + semitones)
+                    .collect();
+                // En el último nivel también invertidos: la fundamental sube una octava
+                if level == MAX_LEVEL {
+                    for _ in 0..rng.below(pitches.len()) {
+                        let lowest: u8 = pitches.remove(0);
+                        pitches.push(lowest + 12);
+                    }
+                }
+                EarQuestion {
+                    category,
+                    pitches,
+                    harmonic: true,
+                    choice,
+                }
+            }
+            EarCategory::Melody => EarQuestion {
+                category,
+                pitches: generate_melody(level, rng),
+                harmonic: false,
+                choice: None,
+            },
+        }
+    }
+
+    /// Nota que se da como referencia: la primera o la más grave
+    pub fn given(&self) -> u8 {
+        self.pitches.first().copied().unwrap_or(60)
+    }
+
+    /// Notas que hay que nombrar después de la dada
+    pub fn answer_len(&self) -> usize {
+        self.pitches.len().saturating_sub(1)
+    }
+
+    /// Notas para el generador de sonido
+    pub fn tones(&self) -> Vec<ToneNote> {
+        if self.harmonic {
+            return self
+                .pitches
+                .iter()
+                .map(|&pitch| ToneNote::new(pitch, 0.0, HARMONIC_SECONDS))
+                .collect();
+        }
+        let seconds: f32 = match self.category {
+            EarCategory::Melody => MELODY_SECONDS,
+            _ => MELODIC_SECONDS,
+        };
+        self.pitches
+            .iter()
+            .enumerate()
+            .map(|(i, &pitch)| ToneNote::new(pitch, i as f32 * seconds, seconds))
+            .collect()
+    }
+
+    /// Si la respuesta es correcta. Las notas escritas pueden empezar por la dada o no;
+    /// en los acordes da igual el orden.
+    pub fn check(&self, answer: &EarAnswer) -> bool {
+        match answer {
+            EarAnswer::Choice(choice) => self.choice == Some(*choice),
+            EarAnswer::Notes(notes) => {
+                let classes: Vec<u8> = self.pitches.iter().map(|pitch| pitch % 12).collect();
+                let notes: &[u8] = match notes.split_first() {
+                    Some((first, rest)) if notes.len() == classes.len() && *first == classes[0] => {
+                        rest
+                    }
+                    _ => notes,
+                };
+                let mut expected: Vec<u8> = classes[1.min(classes.len())..].to_vec();
+                let mut notes: Vec<u8> = notes.to_vec();
+                if self.category == EarCategory::Chord {
+                    expected.sort_unstable();
+                    notes.sort_unstable();
+                }
+                notes == expected
+            }
+        }
+    }
+
+    /// La solución para enseñarla después de contestar: "3ª mayor: C4 E4"
+    pub fn solution(&self) -> String {
+        let names: String = self
+            .pitches
+            .iter()
+            .map(|&pitch| note_name(pitch))
+            .collect::<Vec<String>>()
+            .join(" ");
+        match self.choice {
+            Some(choice) => format!("{}: {}", self.category.choice_name(choice), names),
+            None => names,
+        }
+    }
+}
+
+// Melodía corta en una tonalidad mayor que empieza en la tónica y se mueve por grados
+// de la escala, con saltos de como mucho los grados del nivel
+fn generate_melody(level: u8, rng: &mut SeededRng) -> Vec<u8> {
+    let (length, max_leap): (usize, usize) = MELODY_LEVELS[level as usize - 1];
+    // Los dos primeros niveles en Do mayor, el último en cualquier tonalidad
+    let tonic: u8 = if level == MAX_LEVEL {
+        rng.below(12) as u8
+    } else {
+        0
+    };
+    let key: Key = Key::new(tonic, false);
+    let scale: Vec<u8> = key.pitches_in(55 + tonic, 79 + tonic);
+    let start: usize = scale
+        .iter()
+        .position(|&pitch| pitch == 60 + tonic)
+        .unwrap_or(0);
+
+    let mut index: usize = start;
+    let mut melody: Vec<u8> = vec![scale[index]];
+    while melody.len() < length {
+        let leap: usize = 1 + rng.below(max_leap);
+        index = if rng.chance(0.5) && index >= leap {
+            index - leap
+        } else if index + leap < scale.len() {
+            index + leap
+        } else {
+            index.saturating_sub(leap)
+        };
+        melody.push(scale[index]);
+    }
+    melody
+}
+
+/// Aciertos en un nivel
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct LevelScore {
+    pub asked: u32,
+    pub correct: u32,
+}
+
+impl LevelScore {
+    pub fn accuracy(&self) -> Option<f32> {
+        (self.asked > 0).then(|| self.correct as f32 / self.asked as f32)
+    }
+}
+
+/// Nivel elegido y aciertos de cada nivel de una categoría
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CategoryStats {
+    #[serde(default)]
+    level: u8,
+    pub levels: BTreeMap<u8, LevelScore>,
+    #[serde(default)]
+    pub best_streak: u32,
+}
+
+impl CategoryStats {
+    pub fn level(&self) -> u8 {
+        self.level.clamp(1, MAX_LEVEL)
+    }
+
+    pub fn score(&self, level: u8) -> LevelScore {
+        self.levels.get(&level).copied().unwrap_or_default()
+    }
+
+    /// Aciertos sumando todos los niveles
+    pub fn total(&self) -> LevelScore {
+        self.levels
+            .values()
+            .fold(LevelScore::default(), |total, score| LevelScore {
+                asked: total.asked + score.asked,
+                correct: total.correct + score.correct,
+            })
+    }
+}
+
+/// Estadísticas del entrenamiento auditivo, guardadas junto a `settings.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EarTrainingStats {
+    pub categories: BTreeMap<EarCategory, CategoryStats>,
+}
+
+impl EarTrainingStats {
+    // Cargar las estadísticas, o unas vacías si aún no existen
+    pub fn load() -> Self {
+        fs::read_to_string(asset_path!("ear_training.json"))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    // Guardar las estadísticas
+    pub fn save(&self) -> Result<(), Box<dyn error::Error>> {
+        let json: String = serde_json::to_string_pretty(self)?;
+        fs::write(asset_path!("ear_training.json"), json)?;
+        Ok(())
+    }
+
+    pub fn category(&self, category: EarCategory) -> CategoryStats {
+        self.categories.get(&category).cloned().unwrap_or_default()
+    }
+
+    pub fn set_level(&mut self, category: EarCategory, level: u8) {
+        self.categories.entry(category).or_default().level = level.clamp(1, MAX_LEVEL);
+    }
+
+    /// Apuntar una respuesta en el nivel elegido de la categoría
+    pub fn record(&mut self, category: EarCategory, correct: bool, streak: u32) {
+        let stats: &mut CategoryStats = self.categories.entry(category).or_default();
+        let level: u8 = stats.level();
+        let score: &mut LevelScore = stats.levels.entry(level).or_default();
+        score.asked += 1;
+        if correct {
+            score.correct += 1;
+        }
+        stats.best_streak = stats.best_streak.max(streak);
+    }
+}
+
+/// Sesión de entrenamiento auditivo en curso
+#[derive(Debug, Clone)]
+pub struct EarTrainingSession {
+    pub category: EarCategory,
+    pub stats: EarTrainingStats,
+    pub question: EarQuestion,
+    pub notes: Vec<u8>,           // Notas del dictado contestadas con botones
+    pub typed: String,            // Respuesta que se está escribiendo
+    pub feedback: Option<String>, // Resultado de la pregunta anterior
+    pub streak: u32,              // Preguntas acertadas seguidas
+    rng: SeededRng,
+}
+
+impl EarTrainingSession {
+    pub fn new(category: EarCategory, stats: EarTrainingStats, seed: u64) -> Self {
+        let mut rng: SeededRng = SeededRng::new(seed);
+        let question: EarQuestion =
+            EarQuestion::generate(category, stats.category(category).level(), &mut rng);
+        EarTrainingSession {
+            category,
+            stats,
+            question,
+            notes: Vec::new(),
+            typed: String::new(),
+            feedback: None,
+            streak: 0,
+            rng,
+        }
+    }
+
+    pub fn level(&self) -> u8 {
+        self.stats.category(self.category).level()
+    }
+
+    /// Cambiar de nivel y pasar a una pregunta de ese nivel
+    pub fn set_level(&mut self, level: u8) {
+        self.stats.set_level(self.category, level);
+        self.next_question();
+    }
+
+    /// Contestar la pregunta actual, apuntarla y pasar a la siguiente.
+    /// Devuelve si se acertó.
+    pub fn answer(&mut self, answer: &EarAnswer) -> bool {
+        let correct: bool = self.question.check(answer);
+        if correct {
+            self.streak += 1;
+            self.feedback = Some(format!("✓ {}", self.question.solution()));
+        } else {
+            self.streak = 0;
+            self.feedback = Some(format!("✗ Era {}", self.question.solution()));
+        }
+        self.stats.record(self.category, correct, self.streak);
+        self.next_question();
+        correct
+    }
+
+    /// Añadir una nota al dictado con un botón. Cuando están todas se contesta.
+    pub fn push_note(&mut self, pitch_class: u8) -> Option<bool> {
+        self.notes.push(pitch_class % 12);
+        if self.notes.len() < self.question.answer_len() {
+            return None;
+        }
+        let answer: EarAnswer = EarAnswer::Notes(std::mem::take(&mut self.notes));
+        Some(self.answer(&answer))
+    }
+
+    fn next_question(&mut self) {
+        self.question = EarQuestion::generate(self.category, self.level(), &mut self.rng);
+        self.notes.clear();
+        self.typed.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Índice en `INTERVALS` de un intervalo en semitonos
+    fn interval(semitones: u8) -> usize {
+        INTERVALS.iter().position(|(s, _)| *s == semitones).unwrap()
+    }
+
+    fn question(category: EarCategory, pitches: &[u8], choice: Option<usize>) -> EarQuestion {
+        EarQuestion {
+            category,
+            pitches: pitches.to_vec(),
+            harmonic: category == EarCategory::Chord,
+            choice,
+        }
+    }
+
+    fn notes(text: &str) -> EarAnswer {
+        EarAnswer::parse(text).unwrap()
+    }
+
+    #[test]
+    fn intervals_are_checked_by_button_or_by_name() {
+        // Do4 y Mi4: 3ª mayor
+        let third: EarQuestion = question(EarCategory::Interval, &[60, 64], Some(interval(4)));
+        assert!(third.check(&EarAnswer::Choice(interval(4))));
+        assert!(!third.check(&EarAnswer::Choice(interval(3))));
+
+        // Se puede escribir la nota dada o no, y la octava no cuenta
+        assert!(third.check(&notes("mi")));
+        assert!(third.check(&notes("C E5")));
+        assert!(!third.check(&notes("Eb")));
+        assert!(!third.check(&notes("E G")));
+        assert_eq!(third.solution(), "3ª mayor: C4 E4");
+    }
+
+    #[test]
+    fn chords_accept_any_order_and_melodies_do_not() {
+        let chord: EarQuestion = question(EarCategory::Chord, &[60, 64, 67], Some(0));
+        assert!(chord.check(&notes("E G")));
+        assert!(chord.check(&notes("G E")));
+        assert!(chord.check(&notes("do sol mi")));
+        assert!(!chord.check(&notes("E")));
+
+        let melody: EarQuestion = question(EarCategory::Melody, &[60, 62, 64], None);
+        assert!(melody.check(&notes("D E")));
+        assert!(!melody.check(&notes("E D")));
+        assert!(!melody.check(&EarAnswer::Choice(0)));
+    }
+
+    #[test]
+    fn stats_add_up_per_category_and_level() {
+        let mut stats: EarTrainingStats = EarTrainingStats::default();
+        stats.record(EarCategory::Interval, true, 1);
+        stats.record(EarCategory::Interval, true, 2);
+        stats.record(EarCategory::Interval, false, 0);
+        stats.set_level(EarCategory::Interval, 3);
+        stats.record(EarCategory::Interval, true, 1);
+        stats.record(EarCategory::Chord, false, 0);
+
+        let intervals: CategoryStats = stats.category(EarCategory::Interval);
+        assert_eq!(
+            (intervals.score(1).asked, intervals.score(1).correct),
+            (3, 2)
+        );
+        assert_eq!(
+            (intervals.score(3).asked, intervals.score(3).correct),
+            (1, 1)
+        );
+        assert_eq!(intervals.score(2).accuracy(), None);
+        assert_eq!((intervals.total().asked, intervals.total().correct), (4, 3));
+        assert_eq!(intervals.best_streak, 2);
+
+        // Cada categoría lleva sus propias cuentas
+        let chords: CategoryStats = stats.category(EarCategory::Chord);
+        assert_eq!(chords.total().accuracy(), Some(0.0));
+        assert_eq!(stats.category(EarCategory::Melody).total().asked, 0);
+    }
+
+    #[test]
+    fn sessions_record_each_answer_and_keep_the_streak() {
+        let mut session: EarTrainingSession =
+            EarTrainingSession::new(EarCategory::Interval, EarTrainingStats::default(), 5);
+        for _ in 0..3 {
+            let choice: Option<usize> = session.question.choice;
+            assert!(session.answer(&EarAnswer::Choice(choice.unwrap())));
+        }
+        assert_eq!(session.streak, 3);
+
+        // Un intervalo que no está en el nivel 1 siempre es un fallo
+        assert!(!session.answer(&EarAnswer::Choice(interval(6))));
+        assert_eq!(session.streak, 0);
+
+        let stats: CategoryStats = session.stats.category(EarCategory::Interval);
+        assert_eq!((stats.score(1).asked, stats.score(1).correct), (4, 3));
+        assert_eq!(stats.best_streak, 3);
+    }
+
+    #[test]
+    fn dictation_is_answered_when_every_note_is_in() {
+        let mut session: EarTrainingSession =
+            EarTrainingSession::new(EarCategory::Melody, EarTrainingStats::default(), 9);
+        let pitches: Vec<u8> = session.question.pitches.clone();
+        let (last, rest) = pitches[1..].split_last().unwrap();
+        for pitch in rest {
+            assert_eq!(session.push_note(*pitch), None);
+        }
+        assert_eq!(session.push_note(*last), Some(true));
+        assert_eq!(
+            session.stats.category(EarCategory::Melody).total().correct,
+            1
+        );
+    }
+}
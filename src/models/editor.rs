@@ -0,0 +1,489 @@
+use {
+    crate::{
+        models::{
+            note::Note,
+            partiture::{Hand, PieceMetadata},
+            score::{Meter, Piece, ScoreMetadata, Section},
+        },
+        utils::sight_reading::{Key, NoteValue},
+    },
+    std::collections::BTreeMap,
+};
+
+/// Nombre que se propone para una pieza nueva
+pub const NEW_PIECE: &str = "nueva-pieza";
+
+// Ediciones que se pueden deshacer
+const HISTORY_LIMIT: usize = 100;
+
+// Margen para considerar que dos notas empiezan a la vez
+const TIME_EPSILON: f32 = 1e-3;
+
+/// Campo de texto de los metadatos. Escribir en el mismo campo seguido se deshace de una vez,
+/// salvo el nombre, que no se deshace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextField {
+    Name,
+    Title,
+    Composer,
+    Tempo,
+}
+
+/// Pieza abierta en el editor, con el cursor, la figura elegida y el historial para deshacer
+#[derive(Debug, Clone)]
+pub struct ScoreEditor {
+    pub name: String,               // Nombre de la pieza en la biblioteca
+    pub saved_name: Option<String>, // Nombre con el que está guardada, si ya lo está
+    pub piece: Piece,               // Pieza tal y como se guardará
+    pub section: String,            // Sección que se está editando
+    pub hand: Hand,                 // Mano en la que se escriben las notas
+    pub cursor: f32,                // Segundo del cursor desde el inicio de la sección
+    pub last_start: Option<f32>,    // Inicio de la última nota escrita, donde van los acordes
+    pub value: NoteValue,           // Figura de las notas que se escriben
+    pub octave: i8,                 // Octavas que se suben o bajan las teclas del ordenador
+    pub dirty: bool,                // Si hay cambios sin guardar
+    pub status: Option<String>,     // Resultado de la última acción (guardar, deshacer...)
+    undo: Vec<(Piece, String)>,     // Estados anteriores y la sección que se editaba
+    redo: Vec<(Piece, String)>,     // Estados deshechos
+    typing: Option<TextField>,      // Campo en el que se está escribiendo
+}
+
+impl ScoreEditor {
+    /// Pieza vacía en 4/4 con una sección "A"
+    pub fn new_piece(name: &str) -> Self {
+        let piece: Piece = Piece {
+            metadata: ScoreMetadata {
+                title: "Nueva pieza".to_string(),
+                composer: None,
+                source: None,
+                meter: Meter {
+                    beats: 4,
+                    beat_unit: 4,
+                },
+                tempo: Some("Moderato".to_string()),
+                key: Some("C major".to_string()),
+                note_duration_unit_seconds: 1.0,
+                structure: vec!["A".to_string()],
+                playback_instructions: None,
+                exercise: false,
+            },
+            sections: BTreeMap::from([("A".to_string(), Section::default())]),
+        };
+        ScoreEditor::unsaved(name, piece)
+    }
+
+    /// Pieza que aún no está en la biblioteca, como una grabación
+    pub fn unsaved(name: &str, piece: Piece) -> Self {
+        let mut editor: ScoreEditor = ScoreEditor::open(name, piece);
+        editor.saved_name = None;
+        editor
+    }
+
+    /// Pieza de la biblioteca, con el cursor al principio de su primera sección
+    pub fn open(name: &str, piece: Piece) -> Self {
+        let section: String = piece
+            .metadata
+            .structure
+            .first()
+            .filter(|label| piece.sections.contains_key(*label))
+            .or_else(|| piece.sections.keys().next())
+            .cloned()
+            .unwrap_or_default();
+
+        ScoreEditor {
+            name: name.to_string(),
+            saved_name: Some(name.to_string()),
+            piece,
+            section,
+            hand: Hand::Right,
+            cursor: 0.0,
+            last_start: None,
+            value: NoteValue::Quarter,
+            octave: 0,
+            dirty: false,
+            status: None,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            typing: None,
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    // Duración en segundos de una negra: la unidad del compás escalada a cuartos
+    pub fn quarter_seconds(&self) -> f32 {
+        let metadata: &ScoreMetadata = &self.piece.metadata;
+        metadata.note_duration_unit_seconds * metadata.meter.beat_unit as f32 / 4.0
+    }
+
+    /// Duración en segundos de la figura elegida, que es también lo que avanza el cursor
+    pub fn step(&self) -> f32 {
+        // `NoteValue::seconds` cuenta la negra como un segundo
+        self.value.seconds() * self.quarter_seconds()
+    }
+
+    pub fn bar_seconds(&self) -> f32 {
+        PieceMetadata::from(&self.piece.metadata).bar_duration()
+    }
+
+    pub fn current_section(&self) -> Option<&Section> {
+        self.piece.sections.get(&self.section)
+    }
+
+    /// Notas de una mano de la sección actual, medidas desde su inicio
+    pub fn section_notes(&self, hand: Hand) -> Vec<Note> {
+        let Some(section) = self.current_section() else {
+            return Vec::new();
+        };
+        section
+            .notes(&hand)
+            .iter()
+            .map(|note| Note {
+                start: note.start - section.start_time,
+                is_rest: note.pitch == 0,
+                ..note.clone()
+            })
+            .collect()
+    }
+
+    // Tono de una tecla del ordenador en la mano actual: la izquierda suena una octava más grave
+    pub fn keyboard_pitch(&self, pitch: u8) -> u8 {
+        let hand_octave: i32 = match self.hand {
+            Hand::Right => 0,
+            Hand::Left => -1,
+        };
+        (pitch as i32 + (self.octave as i32 + hand_octave) * 12).clamp(21, 108) as u8
+    }
+
+    // Guardar el estado actual antes de una edición y olvidar lo deshecho
+    fn checkpoint(&mut self) {
+        self.undo.push((self.piece.clone(), self.section.clone()));
+        if self.undo.len() > HISTORY_LIMIT {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+        self.dirty = true;
+        self.status = None;
+        self.typing = None;
+    }
+
+    // Aplicar una edición a las notas de la mano actual en la sección actual
+    fn edit_notes(&mut self, edit: impl FnOnce(&mut Vec<Note>, f32)) {
+        if !self.piece.sections.contains_key(&self.section) {
+            return;
+        }
+        self.checkpoint();
+        let hand: Hand = self.hand;
+        if let Some(section) = self.piece.sections.get_mut(&self.section) {
+            let notes: &mut Vec<Note> = match hand {
+                Hand::Left => &mut section.left,
+                Hand::Right => &mut section.right,
+            };
+            edit(notes, section.start_time);
+            notes.sort_by(|a, b| a.start.total_cmp(&b.start).then(a.pitch.cmp(&b.pitch)));
+        }
+    }
+
+    /// Escribir una nota (o un silencio con el tono 0) en el cursor y avanzar lo que dura la figura.
+    /// Las notas de un acorde van con la última nota escrita y no mueven el cursor.
+    pub fn insert(&mut self, pitch: u8, chord: bool) {
+        let position: f32 = match (chord, self.last_start) {
+            (true, Some(last_start)) => last_start,
+            _ => self.cursor,
+        };
+        let step: f32 = self.step();
+        self.edit_notes(|notes, start_time| {
+            let start: f32 = start_time + position;
+            // Un silencio o una nota nueva sin acorde sustituye a lo que había en el cursor
+            notes.retain(|note| {
+                (note.start - start).abs() >= TIME_EPSILON
+                    || (chord && note.pitch != 0 && note.pitch != pitch)
+            });
+            notes.push(Note {
+                start,
+                ..Note::new(pitch, step, false, Default::default())
+            });
+        });
+        self.last_start = Some(position);
+        if !chord {
+            self.cursor = position + step;
+        }
+    }
+
+    /// Click en el pentagrama: quita la nota si ya estaba y si no la añade al acorde de ese momento
+    pub fn toggle(&mut self, hand: Hand, time: f32, pitch: u8) {
+        let step: f32 = self.step();
+        self.hand = hand;
+        self.cursor = ((time / step).round() * step).max(0.0);
+        self.last_start = None;
+
+        let start_time: f32 = self.current_section().map_or(0.0, |s| s.start_time);
+        let exists: bool = self.current_section().is_some_and(|section| {
+            section.notes(&hand).iter().any(|note| {
+                note.pitch == pitch && (note.start - start_time - self.cursor).abs() < TIME_EPSILON
+            })
+        });
+
+        if exists {
+            let cursor: f32 = self.cursor;
+            self.edit_notes(|notes, start_time| {
+                notes.retain(|note| {
+                    note.pitch != pitch || (note.start - start_time - cursor).abs() >= TIME_EPSILON
+                });
+            });
+        } else {
+            self.insert(pitch, true);
+        }
+    }
+
+    /// Borrar las notas que empiezan en el cursor
+    pub fn delete(&mut self) {
+        if self.notes_at_cursor() == 0 {
+            return;
+        }
+        let cursor: f32 = self.cursor;
+        self.edit_notes(|notes, start_time| {
+            notes.retain(|note| (note.start - start_time - cursor).abs() >= TIME_EPSILON);
+        });
+    }
+
+    /// Borrar las notas anteriores al cursor y llevar el cursor hasta ellas
+    pub fn delete_previous(&mut self) {
+        let previous: Option<f32> = self
+            .section_notes(self.hand)
+            .iter()
+            .map(|note| note.start)
+            .filter(|start| *start < self.cursor - TIME_EPSILON)
+            .reduce(f32::max);
+        if let Some(previous) = previous {
+            self.cursor = previous;
+            self.last_start = None;
+            self.delete();
+        }
+    }
+
+    // Notas de la mano actual que empiezan en el cursor
+    fn notes_at_cursor(&self) -> usize {
+        self.section_notes(self.hand)
+            .iter()
+            .filter(|note| (note.start - self.cursor).abs() < TIME_EPSILON)
+            .count()
+    }
+
+    /// Subir o bajar semitonos las notas del cursor (los silencios no cambian)
+    pub fn transpose(&mut self, semitones: i8) {
+        if self.notes_at_cursor() == 0 {
+            return;
+        }
+        let cursor: f32 = self.cursor;
+        self.edit_notes(|notes, start_time| {
+            for note in notes.iter_mut().filter(|note| {
+                note.pitch != 0 && (note.start - start_time - cursor).abs() < TIME_EPSILON
+            }) {
+                note.pitch = (note.pitch as i32 + semitones as i32).clamp(21, 108) as u8;
+            }
+        });
+    }
+
+    /// Mover el cursor figuras hacia atrás o hacia delante, sobre la rejilla de la figura
+    pub fn move_cursor(&mut self, steps: i32) {
+        let step: f32 = self.step();
+        let current: f32 = (self.cursor / step + TIME_EPSILON).floor();
+        let target: f32 = if steps < 0 && (self.cursor - current * step).abs() > TIME_EPSILON {
+            // Fuera de la rejilla, el primer paso atrás lleva al punto anterior de la rejilla
+            current + (steps + 1) as f32
+        } else {
+            current + steps as f32
+        };
+        self.cursor = (target * step).max(0.0);
+        self.last_start = None;
+    }
+
+    pub fn set_value(&mut self, value: NoteValue) {
+        self.value = value;
+    }
+
+    /// Poner o quitar el puntillo a la figura elegida
+    pub fn toggle_dot(&mut self) {
+        self.value = match self.value {
+            NoteValue::Half => NoteValue::DottedHalf,
+            NoteValue::DottedHalf => NoteValue::Half,
+            NoteValue::Quarter => NoteValue::DottedQuarter,
+            NoteValue::DottedQuarter => NoteValue::Quarter,
+            value => value,
+        };
+    }
+
+    pub fn toggle_hand(&mut self) {
+        self.hand = match self.hand {
+            Hand::Right => Hand::Left,
+            Hand::Left => Hand::Right,
+        };
+    }
+
+    pub fn shift_octave(&mut self, octaves: i8) {
+        self.octave = (self.octave + octaves).clamp(-3, 3);
+    }
+
+    /// Escribir en un campo de texto. Solo la primera letra seguida en el mismo campo
+    /// guarda un estado para deshacer. El nombre no es parte de la pieza y no se deshace.
+    pub fn set_text(&mut self, field: TextField, text: String) {
+        if field == TextField::Name {
+            self.name = text;
+            self.dirty = true;
+            self.typing = None;
+            return;
+        }
+        if self.typing != Some(field) {
+            self.checkpoint();
+            self.typing = Some(field);
+        }
+        let optional: Option<String> = (!text.trim().is_empty()).then(|| text.clone());
+        let metadata: &mut ScoreMetadata = &mut self.piece.metadata;
+        match field {
+            TextField::Name => {} // Ya cambiado, fuera del historial
+            TextField::Title => metadata.title = text,
+            TextField::Composer => metadata.composer = optional,
+            TextField::Tempo => metadata.tempo = optional,
+        }
+    }
+
+    pub fn set_key(&mut self, key: Key) {
+        self.checkpoint();
+        self.piece.metadata.key = Some(key.to_string());
+    }
+
+    /// Cambiar el compás sin cambiar la duración de la negra, así que las notas no se mueven
+    pub fn set_meter(&mut self, meter: Meter) {
+        let quarter: f32 = self.quarter_seconds();
+        self.checkpoint();
+        let metadata: &mut ScoreMetadata = &mut self.piece.metadata;
+        metadata.meter = meter;
+        metadata.note_duration_unit_seconds = quarter * 4.0 / meter.beat_unit as f32;
+    }
+
+    /// Tonalidad de los metadatos, si se entiende
+    pub fn key(&self) -> Option<Key> {
+        self.piece.metadata.key.as_deref().and_then(Key::parse)
+    }
+
+    /// Editar otra sección, con el cursor al principio
+    pub fn select_section(&mut self, label: String) {
+        if self.piece.sections.contains_key(&label) {
+            self.section = label;
+            self.cursor = 0.0;
+            self.last_start = None;
+        }
+    }
+
+    /// Nueva sección vacía al final de la pieza, con la siguiente letra libre, y añadida a
+    /// `structure`. Empieza en el compás siguiente a la última nota de las demás secciones.
+    pub fn add_section(&mut self) {
+        let Some(label) = ('A'..='Z')
+            .map(String::from)
+            .find(|label| !self.piece.sections.contains_key(label))
+        else {
+            self.status = Some("No quedan letras para más secciones".to_string());
+            return;
+        };
+
+        let end: f32 = self
+            .piece
+            .sections
+            .values()
+            .map(|section| section.start_time + section.length())
+            .fold(0.0, f32::max);
+        let bar: f32 = self.bar_seconds().max(TIME_EPSILON);
+
+        self.checkpoint();
+        self.piece.sections.insert(
+            label.clone(),
+            Section {
+                start_time: (end / bar - TIME_EPSILON).ceil().max(0.0) * bar,
+                ..Section::default()
+            },
+        );
+        self.piece.metadata.structure.push(label.clone());
+        self.section = label;
+        self.cursor = 0.0;
+        self.last_start = None;
+    }
+
+    /// Quitar la sección actual y sus apariciones en `structure`. Siempre queda una sección.
+    pub fn remove_section(&mut self) {
+        if self.piece.sections.len() <= 1 {
+            return;
+        }
+        self.checkpoint();
+        self.piece.sections.remove(&self.section);
+        self.piece
+            .metadata
+            .structure
+            .retain(|label| *label != self.section);
+        if let Some(label) = self.piece.sections.keys().next() {
+            self.section = label.clone();
+        }
+        self.cursor = 0.0;
+        self.last_start = None;
+    }
+
+    /// Añadir la sección actual al final de `structure` (para repetirla)
+    pub fn push_structure(&mut self) {
+        self.checkpoint();
+        self.piece.metadata.structure.push(self.section.clone());
+    }
+
+    /// Quitar la última entrada de `structure`
+    pub fn pop_structure(&mut self) {
+        if self.piece.metadata.structure.is_empty() {
+            return;
+        }
+        self.checkpoint();
+        self.piece.metadata.structure.pop();
+    }
+
+    pub fn undo(&mut self) {
+        if let Some((piece, section)) = self.undo.pop() {
+            let current: Piece = std::mem::replace(&mut self.piece, piece);
+            self.redo
+                .push((current, std::mem::replace(&mut self.section, section)));
+            self.after_history("Deshecho");
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if let Some((piece, section)) = self.redo.pop() {
+            let current: Piece = std::mem::replace(&mut self.piece, piece);
+            self.undo
+                .push((current, std::mem::replace(&mut self.section, section)));
+            self.after_history("Rehecho");
+        }
+    }
+
+    // Tras deshacer o rehacer, la sección puede haber desaparecido
+    fn after_history(&mut self, status: &str) {
+        if !self.piece.sections.contains_key(&self.section)
+            && let Some(label) = self.piece.sections.keys().next()
+        {
+            self.section = label.clone();
+            self.cursor = 0.0;
+            self.last_start = None;
+        }
+        self.dirty = true;
+        self.typing = None;
+        self.status = Some(status.to_string());
+    }
+
+    /// Apuntar que la pieza se ha guardado con su nombre actual
+    pub fn mark_saved(&mut self) {
+        self.saved_name = Some(self.name.clone());
+        self.dirty = false;
+        self.typing = None;
+    }
+}
@@ -0,0 +1,452 @@
+use {
+    crate::{
+        asset_path,
+        utils::{
+            note_names::{note_name, parse_note_name},
+            sight_reading::SeededRng,
+        },
+    },
+    core::fmt,
+    serde::{Deserialize, Serialize},
+    std::{collections::BTreeMap, error, fs, time::Instant},
+};
+
+// Tarjetas que se esperan antes de repetir una nota según su nivel: una nota fallada
+// vuelve a salir enseguida y una que se sabe bien tarda mucho en volver
+const REVIEW_INTERVALS: [u64; 5] = [2, 4, 8, 16, 32];
+
+// Una respuesta acertada pero más lenta que esto no sube el nivel de la nota
+pub const SLOW_ANSWER_SECONDS: f32 = 5.0;
+
+// Notas como mucho en un grupo y distancia máxima en grados entre la más grave y la más aguda
+pub const MAX_CLUSTER: usize = 3;
+const CLUSTER_SPAN: usize = 5;
+
+// Teclas blancas desde el Do
+const NATURALS: [u8; 7] = [0, 2, 4, 5, 7, 9, 11];
+
+// Nombres de las notas en solfeo, con su letra
+const SOLFEGE: [(&str, &str); 7] = [
+    ("sol", "g"),
+    ("do", "c"),
+    ("re", "d"),
+    ("mi", "e"),
+    ("fa", "f"),
+    ("la", "a"),
+    ("si", "b"),
+];
+
+/// Clave del pentagrama
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Clef {
+    Treble,
+    Bass,
+}
+
+impl Clef {
+    pub const ALL: [Clef; 2] = [Clef::Treble, Clef::Bass];
+
+    /// Teclas blancas que se preguntan: el pentagrama y una línea adicional por arriba y por abajo
+    pub fn pitches(&self) -> Vec<u8> {
+        let (low, high): (u8, u8) = match self {
+            Clef::Treble => (60, 81), // Do4 a La5
+            Clef::Bass => (40, 60),   // Mi2 a Do4
+        };
+        (low..=high)
+            .filter(|pitch| NATURALS.contains(&(pitch % 12)))
+            .collect()
+    }
+
+    /// Posición diatónica (Do0 = 0, Re0 = 1...) de la línea inferior del pentagrama
+    pub fn bottom_line(&self) -> i32 {
+        match self {
+            Clef::Treble => diatonic_position(64), // Mi4
+            Clef::Bass => diatonic_position(43),   // Sol2
+        }
+    }
+}
+
+impl fmt::Display for Clef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Clef::Treble => "Clave de sol",
+            Clef::Bass => "Clave de fa",
+        })
+    }
+}
+
+/// Posición diatónica de una tecla blanca: siete por octava, empezando en el Do0
+pub fn diatonic_position(pitch: u8) -> i32 {
+    let octave: i32 = pitch as i32 / 12;
+    let step: i32 = NATURALS
+        .iter()
+        .rposition(|&natural| natural <= pitch % 12)
+        .unwrap_or(0) as i32;
+    octave * 7 + step
+}
+
+/// Respuesta del jugador
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Guess {
+    Pitch(u8),      // Tono exacto: "C4" escrito o una nota tocada en un teclado MIDI
+    PitchClass(u8), // Solo el nombre (0 = Do): un botón, "do" escrito o el micrófono
+}
+
+impl Guess {
+    /// Leer una respuesta escrita: "C4", "c", "Bb", "do", "sol3"...
+    pub fn parse(text: &str) -> Option<Guess> {
+        let mut name: String = text.trim().to_lowercase();
+        if let Some((solfege, letter)) = SOLFEGE.iter().find(|(s, _)| name.starts_with(s)) {
+            name = format!("{}{}", letter, &name[solfege.len()..]);
+        }
+        if let Some(pitch) = parse_note_name(&name) {
+            return Some(Guess::Pitch(pitch));
+        }
+        parse_note_name(&format!("{}4", name)).map(|pitch| Guess::PitchClass(pitch % 12))
+    }
+
+    fn matches(&self, pitch: u8) -> bool {
+        match self {
+            Guess::Pitch(guess) => *guess == pitch,
+            Guess::PitchClass(class) => *class == pitch % 12,
+        }
+    }
+}
+
+/// Aciertos, tiempos y nivel de repaso de una nota
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct PitchStats {
+    pub seen: u32,
+    pub correct: u32,
+    pub answer_seconds: f32, // Suma de los tiempos de las respuestas acertadas
+    pub level: u8,           // Nivel de repaso, de 0 (fallada) a 4 (bien sabida)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_card: Option<u64>, // Tarjeta en la que salió por última vez
+}
+
+impl PitchStats {
+    pub fn accuracy(&self) -> Option<f32> {
+        (self.seen > 0).then(|| self.correct as f32 / self.seen as f32)
+    }
+
+    /// Tiempo medio de las respuestas acertadas
+    pub fn mean_seconds(&self) -> Option<f32> {
+        (self.correct > 0).then(|| self.answer_seconds / self.correct as f32)
+    }
+
+    // Tarjetas que faltan para que toque repasarla (0 o menos: ya toca). Las notas nuevas
+    // salen cuando no hay ninguna que repasar.
+    fn due_in(&self, card: u64) -> i64 {
+        match self.last_card {
+            None => 1,
+            Some(last) => {
+                let interval: u64 = REVIEW_INTERVALS[(self.level as usize).min(4)];
+                (last + interval) as i64 - card as i64
+            }
+        }
+    }
+}
+
+/// Estadísticas de todas las notas, guardadas junto a `settings.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FlashcardStats {
+    pub cards: u64, // Tarjetas mostradas en total
+    pub pitches: BTreeMap<u8, PitchStats>,
+}
+
+impl FlashcardStats {
+    // Cargar las estadísticas, o unas vacías si aún no existen
+    pub fn load() -> Self {
+        fs::read_to_string(asset_path!("flashcards.json"))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    // Guardar las estadísticas
+    pub fn save(&self) -> Result<(), Box<dyn error::Error>> {
+        let json: String = serde_json::to_string_pretty(self)?;
+        fs::write(asset_path!("flashcards.json"), json)?;
+        Ok(())
+    }
+
+    pub fn pitch(&self, pitch: u8) -> PitchStats {
+        self.pitches.get(&pitch).copied().unwrap_or_default()
+    }
+
+    /// Apuntar una respuesta. Fallar devuelve la nota al nivel 0; acertar rápido la sube uno.
+    pub fn record(&mut self, pitch: u8, correct: bool, seconds: f32) {
+        let card: u64 = self.cards;
+        let stats: &mut PitchStats = self.pitches.entry(pitch).or_default();
+        stats.seen += 1;
+        stats.last_card = Some(card);
+        if correct {
+            stats.correct += 1;
+            stats.answer_seconds += seconds;
+            if seconds <= SLOW_ANSWER_SECONDS {
+                stats.level = (stats.level + 1).min(4);
+            }
+        } else {
+            stats.level = 0;
+        }
+    }
+
+    /// Siguiente nota que toca repasar entre `candidates`: la que más se ha pasado de su
+    /// turno, y entre las empatadas una al azar. Nunca repite `previous` si hay otra.
+    pub fn next_pitch(&self, candidates: &[u8], previous: &[u8], rng: &mut SeededRng) -> u8 {
+        let choices: Vec<u8> = candidates
+            .iter()
+            .copied()
+            .filter(|pitch| !previous.contains(pitch))
+            .collect();
+        let choices: &[u8] = if choices.is_empty() {
+            candidates
+        } else {
+            &choices
+        };
+
+        let most_due: i64 = choices
+            .iter()
+            .map(|&pitch| self.pitch(pitch).due_in(self.cards))
+            .min()
+            .unwrap_or(0);
+        let due: Vec<u8> = choices
+            .iter()
+            .copied()
+            .filter(|&pitch| self.pitch(pitch).due_in(self.cards) == most_due)
+            .collect();
+        rng.pick(&due).copied().unwrap_or(0)
+    }
+}
+
+/// Tarjeta: una nota o un grupo pequeño de notas en una clave
+#[derive(Debug, Clone, PartialEq)]
+pub struct Flashcard {
+    pub clef: Clef,
+    pub pitches: Vec<u8>, // De grave a agudo
+}
+
+/// Partida de tarjetas en curso
+#[derive(Debug, Clone)]
+pub struct FlashcardSession {
+    pub clef: Clef,
+    pub cluster: usize, // Notas por tarjeta, de 1 a `MAX_CLUSTER`
+    pub stats: FlashcardStats,
+    pub card: Flashcard,
+    pub remaining: Vec<u8>,       // Notas de la tarjeta que faltan por nombrar
+    pub typed: String,            // Respuesta que se está escribiendo
+    pub feedback: Option<String>, // Resultado de la tarjeta anterior
+    pub streak: u32,              // Tarjetas acertadas seguidas
+    shown_at: Instant,
+    rng: SeededRng,
+}
+
+impl FlashcardSession {
+    pub fn new(clef: Clef, cluster: usize, stats: FlashcardStats, seed: u64, now: Instant) -> Self {
+        let mut session: FlashcardSession = FlashcardSession {
+            clef,
+            cluster: cluster.clamp(1, MAX_CLUSTER),
+            stats,
+            card: Flashcard {
+                clef,
+                pitches: Vec::new(),
+            },
+            remaining: Vec::new(),
+            typed: String::new(),
+            feedback: None,
+            streak: 0,
+            shown_at: now,
+            rng: SeededRng::new(seed),
+        };
+        session.deal(now);
+        session
+    }
+
+    /// Responder con una nota. Cada acierto quita una nota de la tarjeta; al nombrarlas
+    /// todas, o al fallar una, se apunta la tarjeta y sale la siguiente.
+    /// Devuelve si se acertó la tarjeta cuando se termina.
+    pub fn answer(&mut self, guess: Guess, now: Instant) -> Option<bool> {
+        let seconds: f32 = now.saturating_duration_since(self.shown_at).as_secs_f32();
+        self.typed.clear();
+
+        match self
+            .remaining
+            .iter()
+            .position(|&pitch| guess.matches(pitch))
+        {
+            Some(index) => {
+                self.remaining.remove(index);
+                if !self.remaining.is_empty() {
+                    return None;
+                }
+                self.streak += 1;
+                self.feedback = Some(format!("✓ {} en {:.1} s", self.card_names(), seconds));
+                self.finish_card(true, seconds, now);
+                Some(true)
+            }
+            None => {
+                self.streak = 0;
+                self.feedback = Some(format!("✗ Era {}", self.card_names()));
+                self.finish_card(false, seconds, now);
+                Some(false)
+            }
+        }
+    }
+
+    // Apuntar todas las notas de la tarjeta y sacar otra
+    fn finish_card(&mut self, correct: bool, seconds: f32, now: Instant) {
+        for &pitch in &self.card.pitches {
+            self.stats.record(pitch, correct, seconds);
+        }
+        self.stats.cards += 1;
+        self.deal(now);
+    }
+
+    // Sacar la tarjeta siguiente: la nota que toca repasar y, en grupos, otras cercanas
+    fn deal(&mut self, now: Instant) {
+        let candidates: Vec<u8> = self.clef.pitches();
+        let lead: u8 = self
+            .stats
+            .next_pitch(&candidates, &self.card.pitches, &mut self.rng);
+
+        let mut pitches: Vec<u8> = vec![lead];
+        let lead_index: usize = candidates.iter().position(|&p| p == lead).unwrap_or(0);
+        let mut nearby: Vec<u8> = candidates
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(i, p)| *p != lead && i.abs_diff(lead_index) < CLUSTER_SPAN)
+            .map(|(_, p)| p)
+            .collect();
+        while pitches.len() < self.cluster && !nearby.is_empty() {
+            let index: usize = self.rng.below(nearby.len());
+            pitches.push(nearby.remove(index));
+            // Todas dentro de `CLUSTER_SPAN` grados
+            let (low, high) = (pitches.iter().min(), pitches.iter().max());
+            if let (Some(&low), Some(&high)) = (low, high) {
+                nearby.retain(|&p| {
+                    diatonic_position(p.max(high)) - diatonic_position(p.min(low))
+                        < CLUSTER_SPAN as i32
+                });
+            }
+        }
+        pitches.sort_unstable();
+
+        self.card = Flashcard {
+            clef: self.clef,
+            pitches: pitches.clone(),
+        };
+        self.remaining = pitches;
+        self.shown_at = now;
+    }
+
+    // Nombres de las notas de la tarjeta: "C4 E4"
+    fn card_names(&self) -> String {
+        self.card
+            .pitches
+            .iter()
+            .map(|&pitch| note_name(pitch))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Respuesta rápida, que sube el nivel si se acierta
+    const FAST: f32 = 1.0;
+
+    // Apuntar una respuesta como lo hace la partida: cada una es una tarjeta nueva
+    fn answer(stats: &mut FlashcardStats, pitch: u8, correct: bool, seconds: f32) {
+        stats.record(pitch, correct, seconds);
+        stats.cards += 1;
+    }
+
+    #[test]
+    fn correct_answers_double_the_interval() {
+        let mut stats: FlashcardStats = FlashcardStats::default();
+        let mut intervals: Vec<i64> = Vec::new();
+        for _ in 0..6 {
+            answer(&mut stats, 60, true, FAST);
+            // Tarjetas hasta que vuelva a tocar, contando desde la que se acaba de responder
+            intervals.push(stats.pitch(60).due_in(stats.cards - 1));
+        }
+
+        // El nivel se queda en 4 y el intervalo en el último
+        assert_eq!(intervals, vec![4, 8, 16, 32, 32, 32]);
+        assert_eq!(stats.pitch(60).level, 4);
+        assert_eq!(stats.pitch(60).correct, 6);
+    }
+
+    #[test]
+    fn slow_answers_count_but_do_not_level_up() {
+        let mut stats: FlashcardStats = FlashcardStats::default();
+        answer(&mut stats, 60, true, SLOW_ANSWER_SECONDS + 1.0);
+
+        let pitch: PitchStats = stats.pitch(60);
+        assert_eq!((pitch.seen, pitch.correct, pitch.level), (1, 1, 0));
+        assert_eq!(pitch.mean_seconds(), Some(SLOW_ANSWER_SECONDS + 1.0));
+    }
+
+    #[test]
+    fn a_miss_resets_the_level() {
+        let mut stats: FlashcardStats = FlashcardStats::default();
+        for _ in 0..3 {
+            answer(&mut stats, 60, true, FAST);
+        }
+        assert_eq!(stats.pitch(60).level, 3);
+
+        answer(&mut stats, 60, false, FAST);
+        let pitch: PitchStats = stats.pitch(60);
+        assert_eq!(pitch.level, 0);
+        assert_eq!(pitch.accuracy(), Some(0.75));
+        // Vuelve a salir a las dos tarjetas, como una nota recién fallada
+        assert_eq!(pitch.due_in(stats.cards - 1), REVIEW_INTERVALS[0] as i64);
+    }
+
+    #[test]
+    fn the_most_overdue_pitch_comes_first() {
+        let mut stats: FlashcardStats = FlashcardStats::default();
+        let mut rng: SeededRng = SeededRng::new(7);
+        answer(&mut stats, 60, true, FAST); // Nivel 1: vuelve en la tarjeta 4
+        answer(&mut stats, 62, false, FAST); // Nivel 0: vuelve en la tarjeta 3
+        for _ in 0..4 {
+            answer(&mut stats, 67, true, FAST);
+        }
+
+        // En la tarjeta 6 la 62 lleva tres de retraso y la 60 dos; la 64 es nueva
+        assert_eq!(stats.cards, 6);
+        let candidates: [u8; 3] = [60, 62, 64];
+        assert_eq!(stats.next_pitch(&candidates, &[], &mut rng), 62);
+        assert_eq!(stats.next_pitch(&candidates, &[62], &mut rng), 60);
+        assert_eq!(stats.next_pitch(&candidates, &[60, 62], &mut rng), 64);
+    }
+
+    #[test]
+    fn new_pitches_wait_for_due_reviews() {
+        let mut stats: FlashcardStats = FlashcardStats::default();
+        let mut rng: SeededRng = SeededRng::new(3);
+        answer(&mut stats, 60, false, FAST);
+        answer(&mut stats, 67, true, FAST);
+
+        // La 60 toca ya y va antes que cualquier nota nueva
+        assert_eq!(stats.next_pitch(&[60, 62, 64], &[], &mut rng), 60);
+
+        // Sin nada que repasar sale una nueva antes que una que aún no toca
+        let mut stats: FlashcardStats = FlashcardStats::default();
+        answer(&mut stats, 60, true, FAST);
+        assert_eq!(stats.next_pitch(&[60, 62], &[], &mut rng), 62);
+    }
+
+    #[test]
+    fn the_previous_card_repeats_only_without_other_choice() {
+        let stats: FlashcardStats = FlashcardStats::default();
+        let mut rng: SeededRng = SeededRng::new(1);
+        for _ in 0..20 {
+            assert_eq!(stats.next_pitch(&[60, 62], &[60], &mut rng), 62);
+        }
+        assert_eq!(stats.next_pitch(&[60], &[60], &mut rng), 60);
+    }
+}
@@ -0,0 +1,37 @@
+use {
+    crate::models::take::InputSource,
+    std::time::{Duration, Instant},
+};
+
+/// Lo que ha hecho el usuario en una entrada
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputAction {
+    NoteOn { pitch: u8, velocity: u8 },
+    NoteOff { pitch: u8 },
+    Tap, // Pulsación de la prueba de latencia
+}
+
+/// Evento de entrada con el momento en que se capturó, no el del tick que lo procesa
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimedInput {
+    pub at: Instant,
+    pub source: InputSource,
+    pub action: InputAction,
+}
+
+impl TimedInput {
+    pub fn new(at: Instant, source: InputSource, action: InputAction) -> Self {
+        TimedInput { at, source, action }
+    }
+
+    /// Momento en que se tocó de verdad, restando el retraso de su entrada.
+    /// Un retraso negativo (el usuario se adelanta siempre) lo retrasa.
+    pub fn corrected(&self, latency: f32) -> Instant {
+        let shift: Duration = Duration::from_secs_f32(latency.abs());
+        if latency >= 0.0 {
+            self.at.checked_sub(shift).unwrap_or(self.at)
+        } else {
+            self.at + shift
+        }
+    }
+}
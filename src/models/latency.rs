@@ -0,0 +1,123 @@
+use {
+    crate::{audio::tuning::median, models::take::InputSource, utils::time::seconds_between},
+    std::time::{Duration, Instant},
+};
+
+/// Segundos entre pulsos de la prueba
+pub const TAP_BEAT_SECONDS: f32 = 0.6;
+
+/// Pulsos de cuenta antes de empezar a contar las pulsaciones
+pub const TAP_COUNT_IN: u32 = 4;
+
+/// Pulsaciones necesarias para dar un resultado
+pub const TAPS_NEEDED: usize = 12;
+
+// Dos pulsaciones más juntas que esto son la repetición de una tecla mantenida
+const MIN_TAP_GAP: f32 = 0.15;
+
+// Fracción del pulso alrededor de cada pulso en la que una pulsación cuenta
+const TAP_WINDOW: f32 = 0.45;
+
+/// Resultado de la prueba: retraso medio de la entrada y cuánto varía
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TapResult {
+    pub source: InputSource,
+    pub offset: f32, // Segundos de retraso (mediana de las pulsaciones)
+    pub spread: f32, // Desviación mediana respecto al retraso, en segundos
+}
+
+/// Prueba de latencia: el usuario sigue un pulso visual y sonoro y se mide cuánto
+/// llegan tarde sus pulsaciones por una entrada concreta
+pub struct TapTest {
+    source: InputSource,
+    beat_zero: Option<Instant>, // Primer pulso que cuenta, tras la cuenta
+    offsets: Vec<f32>,          // Retraso de cada pulsación respecto a su pulso
+    last_tap: Option<Instant>,
+    now: Option<Instant>,
+}
+
+impl TapTest {
+    pub fn new(source: InputSource) -> Self {
+        TapTest {
+            source,
+            beat_zero: None,
+            offsets: Vec::new(),
+            last_tap: None,
+            now: None,
+        }
+    }
+
+    pub fn source(&self) -> InputSource {
+        self.source
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.beat_zero.is_some() && !self.is_finished()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.offsets.len() >= TAPS_NEEDED
+    }
+
+    pub fn taps(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Empezar la cuenta. Devuelve el momento del primer pulso que cuenta.
+    pub fn start(&mut self, now: Instant) -> Instant {
+        let zero: Instant = now + Duration::from_secs_f32(TAP_COUNT_IN as f32 * TAP_BEAT_SECONDS);
+        self.beat_zero = Some(zero);
+        self.offsets.clear();
+        self.last_tap = None;
+        self.now = Some(now);
+        zero
+    }
+
+    pub fn tick(&mut self, now: Instant) {
+        self.now = Some(now);
+    }
+
+    /// Registrar una pulsación con el momento en que se capturó
+    pub fn tap(&mut self, at: Instant) {
+        let Some(zero) = self.beat_zero else {
+            return;
+        };
+        if self.is_finished()
+            || self
+                .last_tap
+                .is_some_and(|last| seconds_between(last, at) < MIN_TAP_GAP)
+        {
+            return;
+        }
+        self.last_tap = Some(at);
+
+        // Emparejar con el pulso más cercano; durante la cuenta no se cuenta nada
+        let position: f32 = seconds_between(zero, at) / TAP_BEAT_SECONDS;
+        let beat: f32 = position.round();
+        let offset: f32 = (position - beat) * TAP_BEAT_SECONDS;
+        if beat >= 0.0 && offset.abs() <= TAP_WINDOW * TAP_BEAT_SECONDS {
+            self.offsets.push(offset);
+        }
+    }
+
+    /// Pulso actual (negativo durante la cuenta) y segundos desde que sonó
+    pub fn beat(&self) -> Option<(i64, f32)> {
+        let (zero, now) = (self.beat_zero?, self.now?);
+        let position: f32 = seconds_between(zero, now) / TAP_BEAT_SECONDS;
+        let beat: f32 = position.floor();
+        Some((beat as i64, (position - beat) * TAP_BEAT_SECONDS))
+    }
+
+    pub fn result(&self) -> Option<TapResult> {
+        if !self.is_finished() {
+            return None;
+        }
+        let offset: f32 = median(&self.offsets)?;
+        let deviations: Vec<f32> = self.offsets.iter().map(|o| (o - offset).abs()).collect();
+        Some(TapResult {
+            source: self.source,
+            offset,
+            spread: median(&deviations)?,
+        })
+    }
+}
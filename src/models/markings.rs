@@ -0,0 +1,272 @@
+use {
+    crate::models::{note::Note, partiture::Hand},
+    serde::{Deserialize, Serialize},
+    std::fmt,
+};
+
+// Margen para considerar que una marca empieza en el mismo momento que una nota
+const TIME_EPSILON: f32 = 1e-3;
+
+// Lo que sube la velocidad una nota con acento
+const ACCENT_BOOST: u8 = 16;
+
+// Lo que cambia un regulador si no hay un matiz escrito después
+const HAIRPIN_STEP: i32 = 16;
+
+/// Matiz de dinámica, de pianissimo a fortissimo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Dynamic {
+    Pp,
+    P,
+    Mp,
+    Mf,
+    F,
+    Ff,
+}
+
+impl Dynamic {
+    pub const ALL: [Dynamic; 6] = [
+        Dynamic::Pp,
+        Dynamic::P,
+        Dynamic::Mp,
+        Dynamic::Mf,
+        Dynamic::F,
+        Dynamic::Ff,
+    ];
+
+    // Velocidad MIDI con la que suena
+    pub fn velocity(self) -> u8 {
+        match self {
+            Dynamic::Pp => 33,
+            Dynamic::P => 49,
+            Dynamic::Mp => 64,
+            Dynamic::Mf => 80,
+            Dynamic::F => 96,
+            Dynamic::Ff => 112,
+        }
+    }
+
+    // Matiz más cercano a una velocidad MIDI
+    pub fn from_velocity(velocity: u8) -> Self {
+        Dynamic::ALL
+            .into_iter()
+            .min_by_key(|dynamic| (dynamic.velocity() as i32 - velocity as i32).abs())
+            .unwrap_or(Dynamic::Mf)
+    }
+
+    // Matiz a partir de su nombre ("mf"). Los extremos (ppp, fff...) se quedan en pp y ff.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "mp" => Some(Dynamic::Mp),
+            "mf" => Some(Dynamic::Mf),
+            "p" => Some(Dynamic::P),
+            "f" => Some(Dynamic::F),
+            _ if name.len() > 1 && name.chars().all(|c| c == 'p') => Some(Dynamic::Pp),
+            _ if name.len() > 1 && name.chars().all(|c| c == 'f') => Some(Dynamic::Ff),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Dynamic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name: &str = match self {
+            Dynamic::Pp => "pp",
+            Dynamic::P => "p",
+            Dynamic::Mp => "mp",
+            Dynamic::Mf => "mf",
+            Dynamic::F => "f",
+            Dynamic::Ff => "ff",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Articulación de una nota
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Articulation {
+    Staccato, // Más corta, separada de la siguiente
+    Accent,   // Más fuerte que el matiz
+}
+
+/// Matiz escrito a partir de un segundo
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DynamicMark {
+    pub start: f32,
+    pub level: Dynamic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HairpinKind {
+    Crescendo,
+    Diminuendo,
+}
+
+/// Regulador de crescendo o diminuendo
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Hairpin {
+    pub start: f32,
+    pub end: f32,
+    pub kind: HairpinKind,
+}
+
+/// Ligadura de expresión (legato) de una mano
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Slur {
+    pub hand: Hand,
+    pub start: f32, // Inicio de la primera nota
+    pub end: f32,   // Inicio de la última nota
+}
+
+/// Pedal de resonancia pisado entre dos segundos
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Pedal {
+    pub start: f32,
+    pub end: f32,
+}
+
+/// Dinámicas, ligaduras y pedal de una sección, en el mismo tiempo absoluto que sus notas
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Markings {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dynamics: Vec<DynamicMark>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hairpins: Vec<Hairpin>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub slurs: Vec<Slur>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pedal: Vec<Pedal>,
+}
+
+impl Markings {
+    pub fn is_empty(&self) -> bool {
+        self.dynamics.is_empty()
+            && self.hairpins.is_empty()
+            && self.slurs.is_empty()
+            && self.pedal.is_empty()
+    }
+
+    // Las mismas marcas `offset` segundos más tarde
+    pub fn shifted(&self, offset: f32) -> Markings {
+        Markings {
+            dynamics: self
+                .dynamics
+                .iter()
+                .map(|mark| DynamicMark {
+                    start: mark.start + offset,
+                    ..*mark
+                })
+                .collect(),
+            hairpins: self
+                .hairpins
+                .iter()
+                .map(|hairpin| Hairpin {
+                    start: hairpin.start + offset,
+                    end: hairpin.end + offset,
+                    ..*hairpin
+                })
+                .collect(),
+            slurs: self
+                .slurs
+                .iter()
+                .map(|slur| Slur {
+                    start: slur.start + offset,
+                    end: slur.end + offset,
+                    ..*slur
+                })
+                .collect(),
+            pedal: self
+                .pedal
+                .iter()
+                .map(|pedal| Pedal {
+                    start: pedal.start + offset,
+                    end: pedal.end + offset,
+                })
+                .collect(),
+        }
+    }
+
+    // Las mismas marcas con todos los tiempos multiplicados por `factor`
+    pub fn scaled(&self, factor: f32) -> Markings {
+        let mut scaled: Markings = self.clone();
+        for mark in scaled.dynamics.iter_mut() {
+            mark.start *= factor;
+        }
+        for hairpin in scaled.hairpins.iter_mut() {
+            (hairpin.start, hairpin.end) = (hairpin.start * factor, hairpin.end * factor);
+        }
+        for slur in scaled.slurs.iter_mut() {
+            (slur.start, slur.end) = (slur.start * factor, slur.end * factor);
+        }
+        for pedal in scaled.pedal.iter_mut() {
+            (pedal.start, pedal.end) = (pedal.start * factor, pedal.end * factor);
+        }
+        scaled
+    }
+
+    // Añade las marcas de otra sección
+    pub fn append(&mut self, other: Markings) {
+        self.dynamics.extend(other.dynamics);
+        self.hairpins.extend(other.hairpins);
+        self.slurs.extend(other.slurs);
+        self.pedal.extend(other.pedal);
+    }
+
+    // Último matiz escrito hasta ese segundo
+    pub fn dynamic_at(&self, time: f32) -> Option<Dynamic> {
+        self.dynamics
+            .iter()
+            .filter(|mark| mark.start <= time + TIME_EPSILON)
+            .max_by(|a, b| a.start.total_cmp(&b.start))
+            .map(|mark| mark.level)
+    }
+
+    /// Velocidad MIDI en un segundo: la del matiz vigente (mf si no hay ninguno) o, dentro de
+    /// un regulador, la que va del matiz de su inicio al siguiente matiz escrito
+    pub fn velocity_at(&self, time: f32) -> u8 {
+        let base = |time: f32| -> u8 { self.dynamic_at(time).unwrap_or(Dynamic::Mf).velocity() };
+
+        let Some(hairpin) = self
+            .hairpins
+            .iter()
+            .find(|hairpin| hairpin.start <= time + TIME_EPSILON && time < hairpin.end)
+        else {
+            return base(time);
+        };
+
+        let from: u8 = base(hairpin.start);
+        let step: i32 = match hairpin.kind {
+            HairpinKind::Crescendo => HAIRPIN_STEP,
+            HairpinKind::Diminuendo => -HAIRPIN_STEP,
+        };
+        let to: u8 = self
+            .dynamics
+            .iter()
+            .filter(|mark| mark.start >= hairpin.end - TIME_EPSILON)
+            .min_by(|a, b| a.start.total_cmp(&b.start))
+            .map_or((from as i32 + step).clamp(1, 127) as u8, |mark| {
+                mark.level.velocity()
+            });
+
+        let progress: f32 = ((time - hairpin.start)
+            / (hairpin.end - hairpin.start).max(TIME_EPSILON))
+        .clamp(0.0, 1.0);
+        (from as f32 + (to as f32 - from as f32) * progress).round() as u8
+    }
+
+    /// Velocidad con la que suena una nota: la suya si la tiene o la de las dinámicas,
+    /// más fuerte si lleva acento
+    pub fn note_velocity(&self, note: &Note) -> u8 {
+        let velocity: u8 = note
+            .velocity
+            .unwrap_or_else(|| self.velocity_at(note.start));
+        if note.articulations.contains(&Articulation::Accent) {
+            velocity.saturating_add(ACCENT_BOOST).min(127)
+        } else {
+            velocity
+        }
+    }
+}
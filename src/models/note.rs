@@ -1,4 +1,5 @@
 use {
+    crate::models::markings::Articulation,
     iced::{Color, Point},
     serde::{Deserialize, Serialize},
 };
@@ -17,6 +18,12 @@ pub struct Note {
     pub last_position: Point, // Nota anterior
     #[serde(skip)]
     pub is_rest: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finger: Option<u8>, // Dedo con el que se toca, del 1 (pulgar) al 5 (meñique)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub velocity: Option<u8>, // Velocidad MIDI (1-127); sin ella se usa la de las dinámicas
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub articulations: Vec<Articulation>,
 }
 
 pub struct PaletteColors {
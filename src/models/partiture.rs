@@ -1,9 +1,14 @@
 use {
-    crate::models::{note::Note, settings::CustomSettings},
+    crate::models::{
+        markings::Markings, note::Note, playback::PlaybackClock, settings::CustomSettings,
+        take::JudgedNote,
+    },
     core::fmt,
+    serde::{Deserialize, Serialize},
 };
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Hand {
     Left,
     Right,
@@ -18,20 +23,140 @@ impl fmt::Display for Hand {
     }
 }
 
+/// Manos con las que se toca, o que suenan por la salida MIDI.
+/// En las escalas el movimiento contrario es siempre con las dos.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Hands {
+    Right,
+    Left,
+    Together,
+}
+
+impl Hands {
+    pub const ALL: [Hands; 3] = [Hands::Right, Hands::Left, Hands::Together];
+
+    pub fn plays(&self, hand: Hand) -> bool {
+        match self {
+            Hands::Together => true,
+            Hands::Right => hand == Hand::Right,
+            Hands::Left => hand == Hand::Left,
+        }
+    }
+}
+
+impl fmt::Display for Hands {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Hands::Right => "Mano derecha",
+            Hands::Left => "Mano izquierda",
+            Hands::Together => "Manos juntas",
+        })
+    }
+}
+
 // Estructura de la partitura
 pub struct Partiture {
     pub notes: Vec<Note>,                // Notas de la partitura
+    pub markings: Markings,              // Dinámicas, ligaduras y pedal de toda la pieza
     pub time: f32,                       // Tiempo total de la partitura
-    pub elapsed: f32,                    // Tiempo de actual de la partitura
+    pub clock: PlaybackClock,            // Reloj compartido por las dos manos
     pub settings: CustomSettings,        // Configuración de la partitura
     pub hand: Hand,                      // Mano utilizada (izquierda o derecha)
     pub metadata: Option<PieceMetadata>, // Metadata de la partitura selecionada
     pub img_width: f32,
+    pub replay: Vec<JudgedNote>, // Notas grabadas que se muestran en el modo repetición
+}
+
+impl Partiture {
+    // Partitura de una mano, aún sin metadatos ni repetición
+    pub fn new(
+        hand: Hand,
+        notes: Vec<Note>,
+        markings: Markings,
+        time: f32,
+        clock: PlaybackClock,
+        settings: CustomSettings,
+    ) -> Self {
+        Partiture {
+            notes,
+            markings,
+            time,
+            clock,
+            settings,
+            hand,
+            metadata: None,
+            img_width: 200.0,
+            replay: Vec::new(),
+        }
+    }
+
+    // Tiempo actual de la partitura, con la cuenta previa incluida
+    pub fn elapsed(&self) -> f32 {
+        self.clock.position()
+    }
+
+    // La partida termina al pasar la duración más la cuenta del inicio y otra al final
+    pub fn has_ended(&self) -> bool {
+        self.elapsed() > self.time + self.settings.timer * 2.0
+    }
 }
 
 // 1. METADATOS NECESARIOS DE LA OBRA (necesario para el cálculo)
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct PieceMetadata {
-    pub time_signature: (u8, u8), // Ej: (3, 8) para compás 3/8
+    pub time_signature: (u8, u8),     // Ej: (3, 8) para compás 3/8
     pub base_note_value: f32, // Duración en segundos de la unidad del compás (ej: 0.5s para corchea en 3/8)
+    pub sections: Vec<(String, f32)>, // Etiqueta y segundo de inicio de cada sección tras expandir `structure`
+}
+
+impl PieceMetadata {
+    // Duración en segundos de un compás completo
+    pub fn bar_duration(&self) -> f32 {
+        let (beats, _) = self.time_signature;
+        beats as f32 * self.base_note_value
+    }
+
+    // Compás compuesto (6/8, 9/8, 12/8): cada pulso es una negra con puntillo de tres unidades
+    pub fn is_compound(&self) -> bool {
+        let (beats, beat_unit) = self.time_signature;
+        beat_unit >= 8 && beats > 3 && beats % 3 == 0
+    }
+
+    // Pulsos que se cuentan en cada compás y su duración en segundos.
+    // En los compuestos se cuenta la negra con puntillo; el 3/8 se sigue contando en corcheas.
+    pub fn pulses(&self) -> (u32, f32) {
+        let (beats, _) = self.time_signature;
+        if self.is_compound() {
+            (beats as u32 / 3, self.base_note_value * 3.0)
+        } else {
+            (beats as u32, self.base_note_value)
+        }
+    }
+
+    // Inicio del compás anterior o siguiente (`step` = -1 o 1) a partir de un segundo de la partitura
+    pub fn bar_jump(&self, position: f32, step: i32) -> f32 {
+        let bar: f32 = self.bar_duration();
+        if bar <= 0.0 {
+            return position;
+        }
+        // Pequeño margen para no quedarse en el mismo compás por redondeos
+        let current: i32 = ((position + 1e-3) / bar).floor() as i32;
+        ((current + step).max(0) as f32) * bar
+    }
+
+    // Inicio de la sección anterior o siguiente a partir de un segundo de la partitura
+    pub fn section_jump(&self, position: f32, step: i32) -> Option<f32> {
+        let starts = self.sections.iter().map(|(_, start)| *start);
+        if step < 0 {
+            Some(
+                starts
+                    .filter(|start| *start < position - 1e-3)
+                    .fold(0.0, f32::max),
+            )
+        } else {
+            starts
+                .filter(|start| *start > position + 1e-3)
+                .reduce(f32::min)
+        }
+    }
 }
@@ -0,0 +1,172 @@
+use {
+    crate::utils::time::seconds_between,
+    std::{
+        sync::{
+            Arc, Mutex, PoisonError,
+            atomic::{AtomicU64, Ordering},
+        },
+        time::{Duration, Instant},
+    },
+};
+
+/// Fuente de tiempo monótono del reloj de reproducción
+pub trait TimeSource: Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+/// Reloj monótono del sistema
+pub struct MonotonicTime;
+
+impl TimeSource for MonotonicTime {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Tiempo que solo avanza a mano, para pruebas deterministas. Las copias comparten el
+/// tiempo, así que se puede dar una al reloj y avanzar con otra.
+#[derive(Clone)]
+pub struct FakeTime {
+    base: Instant,
+    offset: Arc<AtomicU64>, // Nanosegundos avanzados desde `base`
+}
+
+impl FakeTime {
+    pub fn new() -> Self {
+        FakeTime {
+            base: Instant::now(),
+            offset: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.offset
+            .fetch_add(duration.as_nanos() as u64, Ordering::SeqCst);
+    }
+}
+
+impl Default for FakeTime {
+    fn default() -> Self {
+        FakeTime::new()
+    }
+}
+
+impl TimeSource for FakeTime {
+    fn now(&self) -> Instant {
+        self.base + Duration::from_nanos(self.offset.load(Ordering::SeqCst))
+    }
+}
+
+// Estado compartido del reloj
+struct ClockState {
+    anchor: Option<Instant>, // Momento desde el que avanza, `None` si está parado
+    base: f32,               // Posición en el momento `anchor` (o la fija si está parado)
+    rate: f32,               // Velocidad de reproducción, 1.0 es tiempo real
+}
+
+/// Reloj de reproducción: posición en segundos que avanza con el tiempo monótono mientras
+/// suena, independiente de cada cuánto llegue el tick de la interfaz. Las copias comparten
+/// el estado, así que las dos manos y la aplicación leen siempre la misma posición.
+#[derive(Clone)]
+pub struct PlaybackClock {
+    time: Arc<dyn TimeSource>,
+    state: Arc<Mutex<ClockState>>,
+}
+
+impl PlaybackClock {
+    /// Reloj parado en 0 con el reloj del sistema
+    pub fn new() -> Self {
+        PlaybackClock::with_source(Arc::new(MonotonicTime))
+    }
+
+    /// Reloj parado en 0 con otra fuente de tiempo
+    pub fn with_source(time: Arc<dyn TimeSource>) -> Self {
+        PlaybackClock {
+            time,
+            state: Arc::new(Mutex::new(ClockState {
+                anchor: None,
+                base: 0.0,
+                rate: 1.0,
+            })),
+        }
+    }
+
+    pub fn now(&self) -> Instant {
+        self.time.now()
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.with_state(|state| state.anchor.is_some())
+    }
+
+    pub fn rate(&self) -> f32 {
+        self.with_state(|state| state.rate)
+    }
+
+    /// Posición actual en segundos
+    pub fn position(&self) -> f32 {
+        self.position_at(self.now())
+    }
+
+    /// Posición que tenía (o tendrá) el reloj en un momento concreto, por ejemplo el
+    /// de una nota capturada antes del último tick. Parado siempre da la misma.
+    pub fn position_at(&self, at: Instant) -> f32 {
+        self.with_state(|state| match state.anchor {
+            Some(anchor) => state.base + seconds_between(anchor, at) * state.rate,
+            None => state.base,
+        })
+    }
+
+    /// Empezar o seguir avanzando desde la posición actual
+    pub fn play(&self) {
+        let now: Instant = self.now();
+        self.with_state(|state| {
+            if state.anchor.is_none() {
+                state.anchor = Some(now);
+            }
+        });
+    }
+
+    /// Parar en la posición actual
+    pub fn pause(&self) {
+        let now: Instant = self.now();
+        self.with_state(|state| {
+            if let Some(anchor) = state.anchor.take() {
+                state.base += seconds_between(anchor, now) * state.rate;
+            }
+        });
+    }
+
+    /// Colocar el reloj en una posición, sin cambiar si está sonando o parado
+    pub fn seek(&self, position: f32) {
+        let now: Instant = self.now();
+        self.with_state(|state| {
+            state.base = position;
+            if state.anchor.is_some() {
+                state.anchor = Some(now);
+            }
+        });
+    }
+
+    /// Cambiar la velocidad manteniendo la posición actual
+    pub fn set_rate(&self, rate: f32) {
+        let now: Instant = self.now();
+        self.with_state(|state| {
+            if let Some(anchor) = state.anchor {
+                state.base += seconds_between(anchor, now) * state.rate;
+                state.anchor = Some(now);
+            }
+            state.rate = rate.max(0.0);
+        });
+    }
+
+    fn with_state<T>(&self, f: impl FnOnce(&mut ClockState) -> T) -> T {
+        f(&mut self.state.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+impl Default for PlaybackClock {
+    fn default() -> Self {
+        PlaybackClock::new()
+    }
+}
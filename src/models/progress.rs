@@ -0,0 +1,75 @@
+use {
+    crate::{asset_path, models::take::Take},
+    serde::{Deserialize, Serialize},
+    std::{
+        error, fs,
+        time::{SystemTime, UNIX_EPOCH},
+    },
+};
+
+/// Registro de una partida jugada
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub piece: String,
+    pub started_at: u64,     // Segundos desde UNIX_EPOCH
+    pub played_seconds: f32, // Tiempo en juego, sin contar las pausas
+    pub finished: bool,      // Si se llegó al final de la partitura
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub take: Option<String>, // Archivo de la grabación dentro de `assets/takes`
+}
+
+/// Historial de progreso del usuario, guardado junto a `settings.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProgressHistory {
+    pub runs: Vec<RunRecord>,
+}
+
+impl ProgressHistory {
+    // Cargar el historial, o uno vacío si aún no existe
+    pub fn load() -> Self {
+        fs::read_to_string(asset_path!("progress.json"))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    // Guardar el historial
+    pub fn save(&self) -> Result<(), Box<dyn error::Error>> {
+        let json: String = serde_json::to_string_pretty(self)?;
+        fs::write(asset_path!("progress.json"), json)?;
+        Ok(())
+    }
+
+    // Última partida con grabación de la pieza indicada
+    pub fn last_take_of(&self, piece: &str) -> Option<&RunRecord> {
+        self.runs
+            .iter()
+            .rev()
+            .find(|run| run.piece == piece && run.take.is_some())
+    }
+}
+
+/// Guarda una grabación en `assets/takes` y devuelve el nombre del archivo
+pub fn save_take(take: &Take) -> Result<String, Box<dyn error::Error>> {
+    let file_name: String = format!("{}-{}.json", take.piece, take.recorded_at);
+    fs::create_dir_all(asset_path!("takes"))?;
+    fs::write(
+        asset_path!(format!("takes/{}", file_name)),
+        serde_json::to_string(take)?,
+    )?;
+    Ok(file_name)
+}
+
+/// Carga una grabación de `assets/takes`
+pub fn load_take(file_name: &str) -> Result<Take, Box<dyn error::Error>> {
+    let json: String = fs::read_to_string(asset_path!(format!("takes/{}", file_name)))?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// Segundos actuales desde UNIX_EPOCH
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,118 @@
+use crate::models::{
+    note::Note,
+    partiture::{Hand, PieceMetadata},
+    score::ScoreMetadata,
+    take::{JudgedNote, Judgement},
+};
+
+/// Medida de cada compás para colorear el resumen de la partitura.
+/// Devuelve de 0.0 (mal) a 1.0 (bien), o `None` si en el compás no hay nada que medir.
+pub trait BarMetric {
+    fn label(&self) -> &str;
+    fn bar(&self, start: f32, end: f32) -> Option<f32>;
+}
+
+/// Precisión de cada compás, contada como la de toda la pieza: las notas a tiempo valen uno,
+/// las adelantadas o retrasadas medio, y las que faltan o sobran cero
+pub struct BarAccuracy {
+    attempts: Vec<(f32, f32)>, // Segundo de la partitura y lo que vale cada intento
+}
+
+impl BarAccuracy {
+    pub fn new(judged: &[JudgedNote], right: &[Note], left: &[Note]) -> Self {
+        let mut credit: Vec<Vec<f32>> = vec![vec![0.0; right.len()], vec![0.0; left.len()]];
+        let mut attempts: Vec<(f32, f32)> = Vec::new();
+
+        for note in judged {
+            let value: f32 = match note.judgement {
+                Judgement::OnTime => 1.0,
+                Judgement::Early | Judgement::Late => 0.5,
+                Judgement::Wrong => 0.0,
+            };
+            match note.expected {
+                Some((Hand::Right, index)) => credit[0][index] = value,
+                Some((Hand::Left, index)) => credit[1][index] = value,
+                None => attempts.push((note.played.on, 0.0)),
+            }
+        }
+
+        for (notes, credit) in [right, left].into_iter().zip(&credit) {
+            attempts.extend(
+                notes
+                    .iter()
+                    .zip(credit)
+                    .filter(|(note, _)| !note.is_rest)
+                    .map(|(note, value)| (note.start, *value)),
+            );
+        }
+        BarAccuracy { attempts }
+    }
+}
+
+impl BarMetric for BarAccuracy {
+    fn label(&self) -> &str {
+        "Precisión por compás"
+    }
+
+    fn bar(&self, start: f32, end: f32) -> Option<f32> {
+        let values: Vec<f32> = self
+            .attempts
+            .iter()
+            .filter(|(time, _)| *time >= start && *time < end)
+            .map(|(_, value)| *value)
+            .collect();
+        (!values.is_empty()).then(|| values.iter().sum::<f32>() / values.len() as f32)
+    }
+}
+
+/// Resultado de una pieza tocada hasta el final
+#[derive(Debug, Clone)]
+pub struct SongResults {
+    pub info: ScoreMetadata,          // Título, compositor, compás...
+    pub wall_seconds: f32,            // Desde que empezó la partida, con las pausas
+    pub score_seconds: f32,           // Duración de la partitura
+    pub sections: Vec<(String, f32)>, // Etiqueta y segundo de inicio de cada entrada de `structure`
+    pub section_bars: Vec<usize>,     // Compás donde empieza cada sección, desde 0
+    pub metric: String,               // Qué se mide en cada compás
+    pub bars: Vec<Option<f32>>,       // Medida de cada compás
+}
+
+impl SongResults {
+    pub fn new(
+        info: ScoreMetadata,
+        metadata: &PieceMetadata,
+        score_seconds: f32,
+        wall_seconds: f32,
+        metric: &dyn BarMetric,
+    ) -> Self {
+        // Sin compás, toda la pieza es un único compás
+        let bar_duration: f32 = if metadata.bar_duration() > 0.0 {
+            metadata.bar_duration()
+        } else {
+            score_seconds.max(f32::EPSILON)
+        };
+        let count: usize = ((score_seconds / bar_duration - 1e-3).ceil() as usize).max(1);
+
+        // Pequeño margen para que una sección no empiece en el compás anterior por redondeos
+        let section_bars: Vec<usize> = metadata
+            .sections
+            .iter()
+            .map(|(_, start)| (((start + 1e-3) / bar_duration).floor() as usize).min(count - 1))
+            .collect();
+
+        SongResults {
+            info,
+            wall_seconds,
+            score_seconds,
+            sections: metadata.sections.clone(),
+            section_bars,
+            metric: metric.label().to_string(),
+            bars: (0..count)
+                .map(|bar| {
+                    let start: f32 = bar as f32 * bar_duration;
+                    metric.bar(start, start + bar_duration)
+                })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,531 @@
+use {
+    crate::asset_path,
+    chrono::{Local, TimeZone},
+    serde::{Deserialize, Serialize},
+    std::{
+        collections::{BTreeMap, BTreeSet, HashSet},
+        error, fmt, fs,
+    },
+};
+
+// Segundos de un día
+const DAY: i64 = 86_400;
+
+// Día local de un instante, en días desde UNIX_EPOCH: las rachas cambian de día a medianoche
+// en la zona horaria del sistema, no en la de UTC
+fn local_day(now: u64) -> u64 {
+    let now: i64 = now as i64;
+    (now + utc_offset(now)).div_euclid(DAY).max(0) as u64
+}
+
+// Segundos que la hora local va por delante de UTC en ese instante, con el horario de verano
+fn utc_offset(now: i64) -> i64 {
+    Local
+        .timestamp_opt(now, 0)
+        .single()
+        .map_or(0, |time| time.offset().local_minus_utc() as i64)
+}
+
+/// XP que da cada cosa que se hace en la app
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct XpRules {
+    pub finished_piece: u32,  // Por cada pieza tocada hasta el final
+    pub new_piece: u32,       // La primera vez que se termina una pieza
+    pub accuracy: u32,        // Multiplicado por la precisión de la pieza terminada
+    pub practice_minute: u32, // Por cada minuto de partida, sin contar las pausas
+}
+
+/// Condición para desbloquear un logro
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Rule {
+    /// Terminar una pieza (o una en concreto) con una precisión y un tempo mínimos
+    Run {
+        #[serde(default)]
+        min_accuracy: f32, // De 0.0 a 1.0
+        #[serde(default)]
+        min_tempo: f32, // Velocidad respecto al tempo escrito, 1.0 es a tempo
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        piece: Option<String>,
+    },
+    Streak {
+        days: u32,
+    }, // Días seguidos practicando
+    PiecesStarted {
+        count: u32,
+    }, // Partidas empezadas
+    PiecesFinished {
+        count: u32,
+    }, // Partidas terminadas
+    DistinctPieces {
+        count: u32,
+    }, // Piezas distintas terminadas
+    PracticeMinutes {
+        minutes: u32,
+    }, // Minutos de partida en total
+    Level {
+        level: u32,
+    }, // Nivel alcanzado
+}
+
+/// Logro: se desbloquea una vez al cumplir su regla y da su XP
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Achievement {
+    pub id: String, // Único entre todos los logros
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub xp: u32,
+    pub rule: Rule,
+}
+
+/// Reglas de las recompensas, escritas en `assets/rewards.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RewardRules {
+    #[serde(default)]
+    pub xp: XpRules,
+    #[serde(default)]
+    pub levels: Vec<u32>, // XP para llegar a cada nivel, empezando por el nivel 1
+    #[serde(default)]
+    pub achievements: Vec<Achievement>,
+}
+
+impl RewardRules {
+    /// Cargar las reglas. Si el archivo falta o está mal escrito se avisa y no hay recompensas.
+    pub fn load() -> Self {
+        let rules: RewardRules = load_rules().unwrap_or_else(|e| {
+            log::error!("No se pudieron cargar las recompensas: {}", e);
+            RewardRules::default()
+        });
+        for problem in rules.problems() {
+            log::error!("Recompensas: {}", problem);
+        }
+        rules
+    }
+
+    /// Nivel con esa XP: cuántos umbrales se han pasado, al menos el 1
+    pub fn level(&self, xp: u32) -> u32 {
+        (self.levels.iter().filter(|&&needed| needed <= xp).count() as u32).max(1)
+    }
+
+    /// XP del nivel actual y del siguiente, o `None` en el último nivel
+    pub fn level_range(&self, xp: u32) -> Option<(u32, u32)> {
+        let level: usize = self.level(xp) as usize;
+        let next: u32 = *self.levels.get(level)?;
+        let current: u32 = self.levels.get(level - 1).copied().unwrap_or(0);
+        Some((current, next))
+    }
+
+    /// Errores de las reglas: ids repetidos, niveles que no suben y precisiones fuera de rango
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems: Vec<String> = Vec::new();
+        let mut ids: HashSet<&str> = HashSet::new();
+
+        for achievement in &self.achievements {
+            if !ids.insert(&achievement.id) {
+                problems.push(format!("logro repetido '{}'", achievement.id));
+            }
+            if let Rule::Run { min_accuracy, .. } = achievement.rule
+                && !(0.0..=1.0).contains(&min_accuracy)
+            {
+                problems.push(format!(
+                    "el logro '{}' pide una precisión de {} (de 0 a 1)",
+                    achievement.id, min_accuracy
+                ));
+            }
+        }
+        if self.levels.windows(2).any(|pair| pair[0] >= pair[1]) {
+            problems.push("la XP de los niveles tiene que ir subiendo".to_string());
+        }
+        problems
+    }
+}
+
+fn load_rules() -> Result<RewardRules, Box<dyn error::Error>> {
+    let json: String = fs::read_to_string(asset_path!("rewards.json"))?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// Partida terminada, tal y como la valora la app
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunResult {
+    pub piece: String,
+    pub accuracy: f32,  // De 0.0 a 1.0
+    pub tempo: f32,     // Velocidad del reloj, 1.0 es a tempo
+    pub exercise: bool, // Ejercicio y no pieza: no cuenta como pieza nueva ni distinta
+}
+
+/// Lo que pasa en la app y puede dar recompensas
+#[derive(Debug, Clone, PartialEq)]
+pub enum PracticeEvent {
+    Started { piece: String },  // Empieza una partida
+    Practiced { seconds: f32 }, // Tiempo en partida desde el último aviso
+    Finished(RunResult),        // Se llegó al final de la partitura
+}
+
+/// Recompensa que se acaba de ganar, para avisar al jugador
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reward {
+    Xp { amount: u32, reason: String },
+    LevelUp(u32),
+    Streak(u32),
+    Achievement { title: String, description: String },
+}
+
+impl fmt::Display for Reward {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reward::Xp { amount, reason } => write!(f, "+{} XP · {}", amount, reason),
+            Reward::LevelUp(level) => write!(f, "¡Nivel {}!", level),
+            Reward::Streak(days) => write!(f, "Racha de {} días seguidos", days),
+            Reward::Achievement { title, description } => {
+                write!(f, "Logro: {} · {}", title, description)
+            }
+        }
+    }
+}
+
+/// XP, rachas y logros del jugador, guardados junto a `settings.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerProgress {
+    pub xp: u32,
+    pub pieces_started: u32,
+    pub pieces_finished: u32,
+    pub finished_pieces: BTreeSet<String>, // Piezas terminadas alguna vez
+    pub practice_seconds: f32,             // Tiempo en partida, sin contar las pausas
+    pub streak: u32,                       // Días seguidos hasta `last_day`
+    pub best_streak: u32,
+    pub last_day: Option<u64>, // Último día local con práctica, en días desde UNIX_EPOCH
+    pub achievements: BTreeMap<String, u64>, // Id -> segundos desde UNIX_EPOCH al desbloquearlo
+}
+
+impl PlayerProgress {
+    // Cargar el progreso, o uno vacío si aún no existe
+    pub fn load() -> Self {
+        fs::read_to_string(asset_path!("player.json"))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    // Guardar el progreso
+    pub fn save(&self) -> Result<(), Box<dyn error::Error>> {
+        let json: String = serde_json::to_string_pretty(self)?;
+        fs::write(asset_path!("player.json"), json)?;
+        Ok(())
+    }
+
+    /// Racha que sigue viva hoy: se pierde si ayer no se practicó
+    pub fn current_streak(&self, now: u64) -> u32 {
+        match self.last_day {
+            Some(last) if last + 1 >= local_day(now) => self.streak,
+            _ => 0,
+        }
+    }
+
+    pub fn is_unlocked(&self, id: &str) -> bool {
+        self.achievements.contains_key(id)
+    }
+
+    /// Apuntar lo que ha pasado y devolver las recompensas ganadas con ello
+    pub fn record(&mut self, rules: &RewardRules, event: &PracticeEvent, now: u64) -> Vec<Reward> {
+        let level_before: u32 = rules.level(self.xp);
+        let mut rewards: Vec<Reward> = Vec::new();
+
+        match event {
+            PracticeEvent::Started { .. } => self.pieces_started += 1,
+            PracticeEvent::Practiced { seconds } if *seconds > 0.0 => {
+                if let Some(days) = self.practice_day(local_day(now)) {
+                    rewards.push(Reward::Streak(days));
+                }
+                let minutes_before: u32 = (self.practice_seconds / 60.0) as u32;
+                self.practice_seconds += seconds;
+                let minutes: u32 = (self.practice_seconds / 60.0) as u32 - minutes_before;
+                self.gain(
+                    minutes * rules.xp.practice_minute,
+                    "minutos de práctica".to_string(),
+                    &mut rewards,
+                );
+            }
+            PracticeEvent::Practiced { .. } => {}
+            PracticeEvent::Finished(run) => {
+                self.pieces_finished += 1;
+                self.gain(
+                    rules.xp.finished_piece,
+                    "pieza terminada".to_string(),
+                    &mut rewards,
+                );
+                if !run.exercise && self.finished_pieces.insert(run.piece.clone()) {
+                    self.gain(rules.xp.new_piece, "pieza nueva".to_string(), &mut rewards);
+                }
+                self.gain(
+                    (rules.xp.accuracy as f32 * run.accuracy.clamp(0.0, 1.0)).round() as u32,
+                    format!("precisión del {:.0} %", run.accuracy * 100.0),
+                    &mut rewards,
+                );
+            }
+        }
+
+        // La XP de un logro puede subir de nivel y desbloquear otro
+        loop {
+            let unlocked: Vec<&Achievement> = rules
+                .achievements
+                .iter()
+                .filter(|achievement| {
+                    !self.is_unlocked(&achievement.id)
+                        && self.meets(rules, &achievement.rule, event)
+                })
+                .collect();
+            if unlocked.is_empty() {
+                break;
+            }
+            for achievement in unlocked {
+                self.achievements.insert(achievement.id.clone(), now);
+                rewards.push(Reward::Achievement {
+                    title: achievement.title.clone(),
+                    description: achievement.description.clone(),
+                });
+                self.gain(achievement.xp, achievement.title.clone(), &mut rewards);
+            }
+        }
+
+        let level_after: u32 = rules.level(self.xp);
+        if level_after > level_before {
+            rewards.push(Reward::LevelUp(level_after));
+        }
+        rewards
+    }
+
+    // Sumar XP con su motivo, si hay algo que sumar
+    fn gain(&mut self, amount: u32, reason: String, rewards: &mut Vec<Reward>) {
+        if amount > 0 {
+            self.xp += amount;
+            rewards.push(Reward::Xp { amount, reason });
+        }
+    }
+
+    // Apuntar que hoy se ha practicado. Devuelve la racha si ha crecido con ello.
+    fn practice_day(&mut self, today: u64) -> Option<u32> {
+        let grown: bool = match self.last_day {
+            Some(last) if last >= today => return None,
+            Some(last) if last + 1 == today => {
+                self.streak += 1;
+                true
+            }
+            _ => {
+                self.streak = 1;
+                false
+            }
+        };
+        self.last_day = Some(today);
+        self.best_streak = self.best_streak.max(self.streak);
+        grown.then_some(self.streak)
+    }
+
+    // Si el progreso, con lo que acaba de pasar, cumple una regla
+    fn meets(&self, rules: &RewardRules, rule: &Rule, event: &PracticeEvent) -> bool {
+        match rule {
+            Rule::Run {
+                min_accuracy,
+                min_tempo,
+                piece,
+            } => match event {
+                PracticeEvent::Finished(run) => {
+                    run.accuracy >= *min_accuracy
+                        && run.tempo >= *min_tempo
+                        && piece.as_ref().is_none_or(|piece| *piece == run.piece)
+                }
+                _ => false,
+            },
+            Rule::Streak { days } => self.streak >= *days,
+            Rule::PiecesStarted { count } => self.pieces_started >= *count,
+            Rule::PiecesFinished { count } => self.pieces_finished >= *count,
+            Rule::DistinctPieces { count } => self.finished_pieces.len() as u32 >= *count,
+            Rule::PracticeMinutes { minutes } => self.practice_seconds >= *minutes as f32 * 60.0,
+            Rule::Level { level } => rules.level(self.xp) >= *level,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 12 de enero de 2026 a mediodía en UTC: lejos de la medianoche en cualquier zona horaria
+    const NOON: u64 = 1_768_219_200;
+
+    fn day(n: u64) -> u64 {
+        NOON + n * DAY as u64
+    }
+
+    fn achievement(id: &str, xp: u32, rule: Rule) -> Achievement {
+        Achievement {
+            id: id.to_string(),
+            title: id.to_string(),
+            description: String::new(),
+            xp,
+            rule,
+        }
+    }
+
+    fn rules() -> RewardRules {
+        RewardRules {
+            xp: XpRules {
+                finished_piece: 50,
+                new_piece: 100,
+                accuracy: 50,
+                practice_minute: 5,
+            },
+            levels: vec![0, 100, 250, 500],
+            achievements: vec![
+                achievement("primera", 25, Rule::PiecesFinished { count: 1 }),
+                achievement("racha-3", 50, Rule::Streak { days: 3 }),
+            ],
+        }
+    }
+
+    fn finished(piece: &str, accuracy: f32, exercise: bool) -> PracticeEvent {
+        PracticeEvent::Finished(RunResult {
+            piece: piece.to_string(),
+            accuracy,
+            tempo: 1.0,
+            exercise,
+        })
+    }
+
+    fn practiced(seconds: f32) -> PracticeEvent {
+        PracticeEvent::Practiced { seconds }
+    }
+
+    fn xp(rewards: &[Reward]) -> u32 {
+        rewards
+            .iter()
+            .map(|reward| match reward {
+                Reward::Xp { amount, .. } => *amount,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    fn unlocked(rewards: &[Reward]) -> Vec<&str> {
+        rewards
+            .iter()
+            .filter_map(|reward| match reward {
+                Reward::Achievement { title, .. } => Some(title.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn finished_pieces_give_xp_once_as_new() {
+        let rules: RewardRules = rules();
+        let mut player: PlayerProgress = PlayerProgress::default();
+
+        // 50 por terminar, 100 por nueva, 40 por precisión y 25 del primer logro
+        let rewards: Vec<Reward> = player.record(&rules, &finished("a", 0.8, false), day(0));
+        assert_eq!(xp(&rewards), 215);
+        assert_eq!(player.xp, 215);
+        assert_eq!(rewards.last(), Some(&Reward::LevelUp(2)));
+
+        // La misma pieza ya no es nueva
+        let rewards: Vec<Reward> = player.record(&rules, &finished("a", 1.0, false), day(0));
+        assert_eq!(xp(&rewards), 100);
+        assert_eq!(rewards.last(), Some(&Reward::LevelUp(3)));
+
+        // Los ejercicios tampoco, y no cuentan como pieza distinta
+        let rewards: Vec<Reward> = player.record(&rules, &finished("escala", 0.0, true), day(0));
+        assert_eq!(xp(&rewards), 50);
+        assert_eq!(player.pieces_finished, 3);
+        assert_eq!(player.finished_pieces.len(), 1);
+    }
+
+    #[test]
+    fn practice_time_gives_xp_per_whole_minute() {
+        let rules: RewardRules = rules();
+        let mut player: PlayerProgress = PlayerProgress::default();
+
+        assert_eq!(xp(&player.record(&rules, &practiced(40.0), day(0))), 0);
+        assert_eq!(xp(&player.record(&rules, &practiced(40.0), day(0))), 5);
+        assert_eq!(xp(&player.record(&rules, &practiced(30.0), day(0))), 0);
+        assert_eq!(xp(&player.record(&rules, &practiced(15.0), day(0))), 5);
+        assert_eq!(player.xp, 10);
+    }
+
+    #[test]
+    fn streaks_grow_day_by_day_and_reset_after_a_gap() {
+        let rules: RewardRules = rules();
+        let mut player: PlayerProgress = PlayerProgress::default();
+
+        // El primer día empieza la racha sin avisar; practicar otra vez el mismo día no suma
+        assert!(player.record(&rules, &practiced(5.0), day(0)).is_empty());
+        assert!(
+            player
+                .record(&rules, &practiced(5.0), day(0) + 3600)
+                .is_empty()
+        );
+        assert_eq!(player.streak, 1);
+
+        let rewards: Vec<Reward> = player.record(&rules, &practiced(5.0), day(1));
+        assert_eq!(rewards, vec![Reward::Streak(2)]);
+        let rewards: Vec<Reward> = player.record(&rules, &practiced(5.0), day(2));
+        assert_eq!(rewards[0], Reward::Streak(3));
+        assert_eq!(unlocked(&rewards), vec!["racha-3"]);
+
+        // Sigue viva el día siguiente y se pierde si ese día no se practica
+        assert_eq!(player.current_streak(day(3)), 3);
+        assert_eq!(player.current_streak(day(4)), 0);
+
+        let rewards: Vec<Reward> = player.record(&rules, &practiced(5.0), day(4));
+        assert!(rewards.is_empty());
+        assert_eq!((player.streak, player.best_streak), (1, 3));
+
+        // Sin tiempo de práctica no cuenta el día
+        player.record(&rules, &practiced(0.0), day(5));
+        assert_eq!(player.current_streak(day(6)), 0);
+    }
+
+    #[test]
+    fn achievements_unlock_only_once() {
+        let rules: RewardRules = rules();
+        let mut player: PlayerProgress = PlayerProgress::default();
+
+        let rewards: Vec<Reward> = player.record(&rules, &finished("a", 0.5, false), day(0));
+        assert_eq!(unlocked(&rewards), vec!["primera"]);
+        assert_eq!(player.achievements.get("primera"), Some(&day(0)));
+
+        let rewards: Vec<Reward> = player.record(&rules, &finished("b", 0.5, false), day(1));
+        assert!(unlocked(&rewards).is_empty());
+        assert_eq!(player.achievements.get("primera"), Some(&day(0)));
+
+        // La racha de tres días se desbloquea la primera vez y no al volver a llegar a ella
+        let mut titles: Vec<String> = Vec::new();
+        for n in [3, 4, 5, 8, 9, 10] {
+            let rewards: Vec<Reward> = player.record(&rules, &practiced(5.0), day(n));
+            titles.extend(unlocked(&rewards).into_iter().map(str::to_string));
+        }
+        assert_eq!(player.streak, 3);
+        assert_eq!(titles, vec!["racha-3"]);
+        assert_eq!(player.achievements.get("racha-3"), Some(&day(5)));
+        assert_eq!(player.achievements.len(), 2);
+    }
+
+    #[test]
+    fn an_achievement_can_unlock_the_next_one() {
+        // El XP del logro sube de nivel, y llegar al nivel 2 es otro logro
+        let mut rules: RewardRules = rules();
+        rules
+            .achievements
+            .push(achievement("nivel-2", 0, Rule::Level { level: 2 }));
+        rules.xp = XpRules::default();
+        rules.achievements[0].xp = 100;
+
+        let mut player: PlayerProgress = PlayerProgress::default();
+        let rewards: Vec<Reward> = player.record(&rules, &finished("a", 1.0, false), day(0));
+        assert_eq!(unlocked(&rewards), vec!["primera", "nivel-2"]);
+        assert_eq!(rewards.last(), Some(&Reward::LevelUp(2)));
+    }
+}
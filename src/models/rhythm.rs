@@ -0,0 +1,655 @@
+use {
+    crate::{
+        models::{note::Note, partiture::PieceMetadata, score::Meter},
+        utils::{sight_reading::SeededRng, time::seconds_between},
+    },
+    core::fmt,
+    std::time::Instant,
+};
+
+/// Compases que ofrece el generador, los compuestos al final
+pub const RHYTHM_METERS: [Meter; 7] = [
+    Meter {
+        beats: 2,
+        beat_unit: 4,
+    },
+    Meter {
+        beats: 3,
+        beat_unit: 4,
+    },
+    Meter {
+        beats: 4,
+        beat_unit: 4,
+    },
+    Meter {
+        beats: 3,
+        beat_unit: 8,
+    },
+    Meter {
+        beats: 6,
+        beat_unit: 8,
+    },
+    Meter {
+        beats: 9,
+        beat_unit: 8,
+    },
+    Meter {
+        beats: 12,
+        beat_unit: 8,
+    },
+];
+
+/// Niveles del generador, de 1 a `MAX_RHYTHM_LEVEL`
+pub const MAX_RHYTHM_LEVEL: u8 = 3;
+
+/// Compases como mucho en un ejercicio, también al sacarlo de una pieza
+pub const MAX_RHYTHM_BARS: u32 = 16;
+
+// Dos pulsaciones más juntas que esto son la repetición de una tecla mantenida
+const MIN_TAP_GAP: f32 = 0.06;
+
+/// Distancia máxima en segundos entre una pulsación y su nota. Entre notas muy juntas
+/// la ventana se reduce a la mitad de la distancia a la vecina.
+pub const TAP_WINDOW: f32 = 0.25;
+
+// Patrones de un pulso de los compases simples, en pulsos, con el nivel desde el que salen.
+// Los negativos son silencios.
+const SIMPLE_PATTERNS: [(u8, &[f32]); 11] = [
+    (1, &[1.0]),
+    (1, &[0.5, 0.5]),
+    (1, &[-1.0]),
+    (2, &[0.25, 0.25, 0.25, 0.25]),
+    (2, &[0.5, 0.25, 0.25]),
+    (2, &[0.25, 0.25, 0.5]),
+    (2, &[0.75, 0.25]),
+    (3, &[-0.5, 0.5]),
+    (3, &[0.25, 0.5, 0.25]),
+    (3, &[-0.25, 0.25, 0.25, 0.25]),
+    (3, &[0.25, 0.75]),
+];
+
+// Patrones de un pulso de tres corcheas (6/8, 9/8, 12/8 y el compás entero en 3/8), en corcheas
+const COMPOUND_PATTERNS: [(u8, &[f32]); 10] = [
+    (1, &[3.0]),
+    (1, &[1.0, 1.0, 1.0]),
+    (1, &[2.0, 1.0]),
+    (2, &[1.0, 2.0]),
+    (2, &[1.5, 0.5, 1.0]),
+    (2, &[0.5, 0.5, 1.0, 1.0]),
+    (2, &[-1.0, 1.0, 1.0]),
+    (3, &[1.0, 0.5, 0.5, 0.5, 0.5]),
+    (3, &[0.5, 0.5, 0.5, 0.5, 0.5, 0.5]),
+    (3, &[-0.5, 0.5, 1.0, 1.0]),
+];
+
+/// De dónde sale el ritmo
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RhythmSource {
+    Generated,     // Generador de patrones por pulsos
+    Piece(String), // Mano derecha de una pieza de la biblioteca
+}
+
+impl fmt::Display for RhythmSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RhythmSource::Generated => f.write_str("Ejercicio generado"),
+            RhythmSource::Piece(name) => f.write_str(name),
+        }
+    }
+}
+
+/// Parámetros del ejercicio de ritmo
+#[derive(Debug, Clone)]
+pub struct RhythmConfig {
+    pub source: RhythmSource,
+    pub sources: Vec<RhythmSource>, // Generador y piezas de la biblioteca
+    pub meter: Meter,
+    pub tempo: u32, // Pulsos por minuto (negras con puntillo en los compuestos)
+    pub bars: u32,
+    pub level: u8,
+}
+
+impl Default for RhythmConfig {
+    fn default() -> Self {
+        RhythmConfig {
+            source: RhythmSource::Generated,
+            sources: vec![RhythmSource::Generated],
+            meter: RHYTHM_METERS[2],
+            tempo: 80,
+            bars: 4,
+            level: 1,
+        }
+    }
+}
+
+impl RhythmConfig {
+    /// Metadatos del ejercicio generado: la unidad del compás sale del tempo del pulso
+    pub fn metadata(&self) -> PieceMetadata {
+        let mut metadata: PieceMetadata = PieceMetadata {
+            time_signature: (self.meter.beats, self.meter.beat_unit),
+            base_note_value: 60.0 / self.tempo.max(1) as f32,
+            sections: Vec::new(),
+        };
+        if metadata.is_compound() {
+            metadata.base_note_value /= 3.0;
+        }
+        metadata
+    }
+}
+
+/// Nota o silencio de la línea de ritmo
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RhythmNote {
+    pub start: f32,
+    pub duration: f32,
+    pub rest: bool,
+}
+
+/// Línea de ritmo: solo los momentos y las duraciones, sin alturas
+#[derive(Debug, Clone)]
+pub struct RhythmLine {
+    pub title: String,
+    pub metadata: PieceMetadata,
+    pub notes: Vec<RhythmNote>, // En orden, con los silencios entre notas
+    pub length: f32,            // Segundos, en compases completos
+}
+
+impl RhythmLine {
+    /// Ritmo de unas notas: los acordes cuentan como un solo ataque y cada nota dura hasta
+    /// la siguiente, salvo que termine antes y deje un silencio
+    pub fn from_notes(title: &str, notes: &[Note], metadata: PieceMetadata) -> Self {
+        let bar: f32 = metadata.bar_duration().max(f32::EPSILON);
+        let limit: f32 = bar * MAX_RHYTHM_BARS as f32;
+
+        let mut sounding: Vec<&Note> = notes
+            .iter()
+            .filter(|note| !note.is_rest && note.pitch > 0 && note.start < limit - 1e-3)
+            .collect();
+        sounding.sort_by(|a, b| a.start.total_cmp(&b.start));
+
+        // Un ataque por momento, con la nota más larga del acorde
+        let mut onsets: Vec<(f32, f32)> = Vec::new();
+        for note in sounding {
+            match onsets.last_mut() {
+                Some((start, end)) if (note.start - *start).abs() < 1e-3 => {
+                    *end = end.max(note.start + note.duration);
+                }
+                _ => onsets.push((note.start, note.start + note.duration)),
+            }
+        }
+
+        let length: f32 = onsets
+            .last()
+            .map_or(bar, |(_, end)| (end.min(limit) / bar - 1e-3).ceil() * bar);
+        let mut line: Vec<RhythmNote> = Vec::new();
+        let mut cursor: f32 = 0.0;
+        for (i, &(start, end)) in onsets.iter().enumerate() {
+            if start - cursor > 1e-3 {
+                line.push(rest(cursor, start - cursor));
+            }
+            let next: f32 = onsets.get(i + 1).map_or(length, |(next, _)| *next);
+            let end: f32 = end.min(next);
+            line.push(RhythmNote {
+                start,
+                duration: end - start,
+                rest: false,
+            });
+            cursor = end;
+        }
+        if length - cursor > 1e-3 {
+            line.push(rest(cursor, length - cursor));
+        }
+
+        RhythmLine {
+            title: title.to_string(),
+            metadata,
+            notes: line,
+            length,
+        }
+    }
+
+    /// Ejercicio generado pulso a pulso con los patrones del nivel. Termina con un pulso
+    /// entero para que se oiga el final.
+    pub fn generate(config: &RhythmConfig, seed: u64) -> Self {
+        let metadata: PieceMetadata = config.metadata();
+        let mut rng: SeededRng = SeededRng::new(seed);
+        let (pulses, pulse_seconds) = metadata.pulses();
+        // En 3/8 el patrón de tres corcheas ocupa el compás entero
+        let (patterns, unit, groups): (&[(u8, &[f32])], f32, u32) = match metadata.time_signature {
+            (_, 8) if metadata.is_compound() => {
+                (&COMPOUND_PATTERNS, metadata.base_note_value, pulses)
+            }
+            (3, 8) => (&COMPOUND_PATTERNS, metadata.base_note_value, 1),
+            _ => (&SIMPLE_PATTERNS, pulse_seconds, pulses),
+        };
+        let available: Vec<&[f32]> = patterns
+            .iter()
+            .filter(|(level, _)| *level <= config.level)
+            .map(|(_, pattern)| *pattern)
+            .collect();
+
+        let bars: u32 = config.bars.clamp(1, MAX_RHYTHM_BARS);
+        let group_seconds: f32 = metadata.bar_duration() / groups as f32;
+        let mut notes: Vec<RhythmNote> = Vec::new();
+        for group in 0..bars * groups {
+            let last: bool = group + 1 == bars * groups;
+            let pattern: &[f32] = match rng.pick(&available) {
+                Some(pattern) if !last => pattern,
+                _ => patterns[0].1,
+            };
+            let mut start: f32 = group as f32 * group_seconds;
+            for &value in pattern {
+                let duration: f32 = value.abs() * unit;
+                notes.push(RhythmNote {
+                    start,
+                    duration,
+                    rest: value < 0.0,
+                });
+                start += duration;
+            }
+        }
+
+        RhythmLine {
+            title: format!("Ritmo en {} (nivel {})", config.meter, config.level),
+            length: bars as f32 * metadata.bar_duration(),
+            metadata,
+            notes,
+        }
+    }
+
+    /// Momentos en que hay que pulsar
+    pub fn onsets(&self) -> Vec<f32> {
+        self.notes
+            .iter()
+            .filter(|note| !note.rest)
+            .map(|note| note.start)
+            .collect()
+    }
+
+    pub fn bars(&self) -> usize {
+        (self.length / self.metadata.bar_duration().max(f32::EPSILON)).round() as usize
+    }
+
+    /// Compás (desde 0) al que pertenece un segundo
+    pub fn bar_of(&self, time: f32) -> usize {
+        let bar: f32 = self.metadata.bar_duration().max(f32::EPSILON);
+        ((time / bar + 1e-3).floor().max(0.0) as usize).min(self.bars().saturating_sub(1))
+    }
+}
+
+fn rest(start: f32, duration: f32) -> RhythmNote {
+    RhythmNote {
+        start,
+        duration,
+        rest: true,
+    }
+}
+
+/// Pulsación con su segundo en la línea y la nota con la que se emparejó
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RhythmTap {
+    pub time: f32,
+    pub onset: Option<usize>, // Índice en `RhythmLine::onsets`, `None` si sobra
+}
+
+/// Desviaciones de las pulsaciones de un compás
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BarTiming {
+    pub deviations: Vec<f32>, // Segundos, negativos si se pulsó antes
+    pub missed: usize,        // Notas sin pulsar
+    pub extra: usize,         // Pulsaciones que no eran de ninguna nota
+}
+
+impl BarTiming {
+    /// Desviación media con signo: si el compás fue adelantado o atrasado
+    pub fn mean(&self) -> Option<f32> {
+        (!self.deviations.is_empty())
+            .then(|| self.deviations.iter().sum::<f32>() / self.deviations.len() as f32)
+    }
+
+    /// Desviación media sin signo: lo lejos que se quedó cada pulsación
+    pub fn mean_abs(&self) -> Option<f32> {
+        (!self.deviations.is_empty()).then(|| {
+            self.deviations.iter().map(|d| d.abs()).sum::<f32>() / self.deviations.len() as f32
+        })
+    }
+}
+
+/// Ejercicio de ritmo en curso: solo cuenta cuándo empieza cada pulsación
+#[derive(Debug, Clone)]
+pub struct RhythmRun {
+    pub line: RhythmLine,
+    pub taps: Vec<RhythmTap>,
+    onsets: Vec<f32>,
+    matched: Vec<Option<f32>>, // Desviación de la pulsación emparejada con cada nota
+    zero: Instant,             // Momento del segundo 0 de la línea, tras la cuenta
+    position: f32,
+    last_tap: Option<Instant>,
+}
+
+impl RhythmRun {
+    /// Empezar con `zero` como el momento de la primera nota, cuando acaba la cuenta
+    pub fn new(line: RhythmLine, zero: Instant) -> Self {
+        let onsets: Vec<f32> = line.onsets();
+        RhythmRun {
+            matched: vec![None; onsets.len()],
+            onsets,
+            line,
+            taps: Vec::new(),
+            zero,
+            position: f32::NEG_INFINITY,
+            last_tap: None,
+        }
+    }
+
+    pub fn tick(&mut self, now: Instant) {
+        self.position = seconds_between(self.zero, now);
+    }
+
+    /// Segundo de la línea, negativo durante la cuenta
+    pub fn position(&self) -> f32 {
+        self.position
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position > self.line.length + TAP_WINDOW
+    }
+
+    /// Registrar una pulsación con el momento en que se tocó, ya corregido el retraso de la entrada
+    pub fn tap(&mut self, at: Instant) {
+        if self.is_finished()
+            || self
+                .last_tap
+                .is_some_and(|last| seconds_between(last, at) < MIN_TAP_GAP)
+        {
+            return;
+        }
+        self.last_tap = Some(at);
+
+        let time: f32 = seconds_between(self.zero, at);
+        if time < -TAP_WINDOW {
+            return; // Durante la cuenta no cuenta nada
+        }
+
+        // La nota más cercana entre las que aún no se han pulsado y tienen la pulsación
+        // dentro de su ventana
+        let onset: Option<usize> = self
+            .onsets
+            .iter()
+            .enumerate()
+            .filter(|(i, start)| {
+                self.matched[*i].is_none() && (time - **start).abs() <= self.window(*i)
+            })
+            .min_by(|(_, a), (_, b)| (time - **a).abs().total_cmp(&(time - **b).abs()))
+            .map(|(i, _)| i);
+        if let Some(i) = onset {
+            self.matched[i] = Some(time - self.onsets[i]);
+        }
+        self.taps.push(RhythmTap { time, onset });
+    }
+
+    // Ventana de una nota: `TAP_WINDOW` o la mitad de la distancia a sus vecinas
+    fn window(&self, i: usize) -> f32 {
+        let before: f32 = i
+            .checked_sub(1)
+            .map_or(f32::INFINITY, |j| self.onsets[i] - self.onsets[j]);
+        let after: f32 = self
+            .onsets
+            .get(i + 1)
+            .map_or(f32::INFINITY, |next| next - self.onsets[i]);
+        TAP_WINDOW.min(before / 2.0).min(after / 2.0)
+    }
+
+    /// Desviación de la pulsación de cada nota, `None` si no se pulsó
+    pub fn deviations(&self) -> &[Option<f32>] {
+        &self.matched
+    }
+
+    /// Desviaciones, notas sin pulsar y pulsaciones de más de cada compás
+    pub fn bar_timings(&self) -> Vec<BarTiming> {
+        let mut bars: Vec<BarTiming> = vec![BarTiming::default(); self.line.bars().max(1)];
+        for (start, deviation) in self.onsets.iter().zip(&self.matched) {
+            let bar: &mut BarTiming = &mut bars[self.line.bar_of(*start)];
+            match deviation {
+                Some(deviation) => bar.deviations.push(*deviation),
+                None => bar.missed += 1,
+            }
+        }
+        for tap in self.taps.iter().filter(|tap| tap.onset.is_none()) {
+            bars[self.line.bar_of(tap.time)].extra += 1;
+        }
+        bars
+    }
+
+    /// Resumen de todo el ejercicio: "12/14 notas, 38 ms de media, 2 de más"
+    pub fn summary(&self) -> String {
+        let hits: Vec<f32> = self.matched.iter().flatten().map(|d| d.abs()).collect();
+        let extra: usize = self.taps.iter().filter(|tap| tap.onset.is_none()).count();
+        let mean: String = if hits.is_empty() {
+            "—".to_string()
+        } else {
+            format!(
+                "{:.0} ms",
+                hits.iter().sum::<f32>() / hits.len() as f32 * 1000.0
+            )
+        };
+        format!(
+            "{}/{} notas a tiempo · desviación media {} · {} pulsaciones de más",
+            hits.len(),
+            self.onsets.len(),
+            mean,
+            extra
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::time::Duration};
+
+    fn config(meter: Meter, level: u8) -> RhythmConfig {
+        RhythmConfig {
+            meter,
+            tempo: 60, // Un pulso por segundo
+            bars: 4,
+            level,
+            ..RhythmConfig::default()
+        }
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    // Las notas van seguidas, sin huecos ni solapes, y llenan los compases
+    fn assert_contiguous(line: &RhythmLine) {
+        let mut cursor: f32 = 0.0;
+        for note in &line.notes {
+            assert!(close(note.start, cursor), "{:?}", line.notes);
+            cursor += note.duration;
+        }
+        assert!(close(cursor, line.length), "{} != {}", cursor, line.length);
+    }
+
+    // Cada pulso empieza con una nota o un silencio: ningún patrón cruza el pulso
+    fn assert_pulses_start_notes(line: &RhythmLine, pulse: f32) {
+        let pulses: usize = (line.length / pulse).round() as usize;
+        for i in 0..pulses {
+            let start: f32 = i as f32 * pulse;
+            assert!(
+                line.notes.iter().any(|note| close(note.start, start)),
+                "nada empieza en {}",
+                start
+            );
+        }
+    }
+
+    // 4/4 con la negra de un segundo: los compases duran cuatro
+    fn line(onsets: &[f32], bars: u32) -> RhythmLine {
+        let metadata: PieceMetadata = PieceMetadata {
+            time_signature: (4, 4),
+            base_note_value: 1.0,
+            sections: Vec::new(),
+        };
+        let length: f32 = bars as f32 * metadata.bar_duration();
+        let notes: Vec<RhythmNote> = onsets
+            .iter()
+            .enumerate()
+            .map(|(i, &start)| RhythmNote {
+                start,
+                duration: onsets.get(i + 1).unwrap_or(&length) - start,
+                rest: false,
+            })
+            .collect();
+        RhythmLine {
+            title: "prueba".to_string(),
+            metadata,
+            notes,
+            length,
+        }
+    }
+
+    // Ejercicio que empieza en `zero`, con una pulsación en cada segundo de `taps`
+    fn tapped(line: RhythmLine, taps: &[f32]) -> RhythmRun {
+        let zero: Instant = Instant::now() + Duration::from_secs(10);
+        let mut run: RhythmRun = RhythmRun::new(line, zero);
+        for &time in taps {
+            let offset: Duration = Duration::from_secs_f32(time.abs());
+            run.tap(if time < 0.0 {
+                zero - offset
+            } else {
+                zero + offset
+            });
+        }
+        run
+    }
+
+    #[test]
+    fn simple_meters_fill_every_beat() {
+        for meter in &RHYTHM_METERS[..3] {
+            for seed in 0..20 {
+                let line: RhythmLine = RhythmLine::generate(&config(*meter, 3), seed);
+                assert!(close(line.length, 4.0 * meter.beats as f32));
+                assert_eq!(line.bars(), 4);
+                assert_contiguous(&line);
+                assert_pulses_start_notes(&line, 1.0);
+
+                // El último pulso es una negra para que se oiga el final
+                let last: &RhythmNote = line.notes.last().unwrap();
+                assert!(!last.rest && close(last.duration, 1.0), "{:?}", last);
+            }
+        }
+    }
+
+    #[test]
+    fn the_first_level_uses_only_quarters_eighths_and_rests() {
+        for seed in 0..20 {
+            let line: RhythmLine = RhythmLine::generate(&config(RHYTHM_METERS[2], 1), seed);
+            for note in &line.notes {
+                assert!(
+                    close(note.duration, 1.0) || (!note.rest && close(note.duration, 0.5)),
+                    "{:?}",
+                    note
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn compound_meters_group_eighths_in_threes() {
+        // 6/8 a 60 la negra con puntillo: la corchea dura un tercio de segundo
+        let six: Meter = RHYTHM_METERS[4];
+        for seed in 0..20 {
+            let line: RhythmLine = RhythmLine::generate(&config(six, 3), seed);
+            assert!(close(line.metadata.base_note_value, 1.0 / 3.0));
+            assert!(close(line.length, 8.0));
+            assert_contiguous(&line);
+            assert_pulses_start_notes(&line, 1.0);
+            assert!(close(line.notes.last().unwrap().duration, 1.0));
+        }
+
+        // En 3/8 el compás entero es un pulso de tres corcheas
+        let three: Meter = RHYTHM_METERS[3];
+        let line: RhythmLine = RhythmLine::generate(&config(three, 2), 4);
+        assert!(close(line.metadata.base_note_value, 1.0));
+        assert!(close(line.length, 12.0));
+        assert_contiguous(&line);
+        assert_pulses_start_notes(&line, 3.0);
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_rhythm() {
+        let a: RhythmLine = RhythmLine::generate(&config(RHYTHM_METERS[5], 3), 11);
+        let b: RhythmLine = RhythmLine::generate(&config(RHYTHM_METERS[5], 3), 11);
+        assert_eq!(a.notes, b.notes);
+    }
+
+    #[test]
+    fn taps_count_up_to_the_edge_of_the_window() {
+        let inside: RhythmRun = tapped(
+            line(&[0.0, 2.0], 1),
+            &[TAP_WINDOW - 0.01, 2.0 - TAP_WINDOW + 0.01],
+        );
+        assert!(inside.deviations().iter().all(Option::is_some));
+
+        let outside: RhythmRun = tapped(
+            line(&[0.0, 2.0], 1),
+            &[TAP_WINDOW + 0.01, 2.0 - TAP_WINDOW - 0.01],
+        );
+        assert_eq!(outside.deviations(), &[None, None]);
+        assert!(outside.taps.iter().all(|tap| tap.onset.is_none()));
+    }
+
+    #[test]
+    fn close_notes_split_the_window_between_them() {
+        // A 0.2 s de distancia cada nota se queda con 0.1 s a cada lado
+        let early: RhythmRun = tapped(line(&[1.0, 1.2], 1), &[1.09]);
+        assert_eq!(early.taps[0].onset, Some(0));
+
+        let late: RhythmRun = tapped(line(&[1.0, 1.2], 1), &[1.11]);
+        assert_eq!(late.taps[0].onset, Some(1));
+        assert!(close(late.deviations()[1].unwrap(), -0.09));
+    }
+
+    #[test]
+    fn a_second_tap_on_the_same_note_is_extra() {
+        let run: RhythmRun = tapped(line(&[0.0, 1.0], 1), &[0.0, 0.1, 1.0]);
+        let onsets: Vec<Option<usize>> = run.taps.iter().map(|tap| tap.onset).collect();
+        assert_eq!(onsets, vec![Some(0), None, Some(1)]);
+        assert!(close(run.deviations()[0].unwrap(), 0.0));
+
+        // A medio camino entre una nota ya pulsada y otra libre, cuenta para la libre
+        let between: RhythmRun = tapped(line(&[0.0, 0.5], 1), &[0.0, 0.25]);
+        assert_eq!(between.taps[1].onset, Some(1));
+        assert!(close(between.deviations()[1].unwrap(), -0.25));
+    }
+
+    #[test]
+    fn key_repeats_and_the_count_in_are_ignored() {
+        // La repetición de una tecla mantenida no es una pulsación, ni lo que cae en la cuenta
+        let run: RhythmRun = tapped(line(&[0.0, 1.0], 1), &[-0.5, -0.1, -0.08, 1.0]);
+        assert_eq!(run.taps.len(), 2);
+        assert!(close(run.deviations()[0].unwrap(), -0.1));
+    }
+
+    #[test]
+    fn bar_timings_split_deviations_misses_and_extra_taps() {
+        // Dos compases de cuatro segundos con dos notas cada uno; la de 6 no se pulsa
+        // y en 7 sobra una pulsación
+        let run: RhythmRun = tapped(line(&[0.0, 2.0, 4.0, 6.0], 2), &[0.05, 1.9, 4.0, 7.0]);
+        let bars: Vec<BarTiming> = run.bar_timings();
+        assert_eq!(bars.len(), 2);
+
+        assert_eq!((bars[0].missed, bars[0].extra), (0, 0));
+        assert!(close(bars[0].mean().unwrap(), -0.025));
+        assert!(close(bars[0].mean_abs().unwrap(), 0.075));
+
+        assert_eq!((bars[1].missed, bars[1].extra), (1, 1));
+        assert!(close(bars[1].mean().unwrap(), 0.0));
+
+        // Un compás sin ninguna pulsación acertada no tiene media
+        let silent: RhythmRun = tapped(line(&[0.0, 4.0], 2), &[]);
+        assert_eq!(silent.bar_timings()[1].mean(), None);
+        assert_eq!(silent.bar_timings()[1].missed, 1);
+    }
+}
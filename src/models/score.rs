@@ -0,0 +1,280 @@
+use {
+    crate::models::{
+        markings::Markings,
+        note::Note,
+        partiture::{Hand, PieceMetadata},
+        score_error::{ScoreError, ScoreLocation},
+    },
+    serde::{Deserialize, Serialize},
+    std::{collections::BTreeMap, fmt, ops::Range},
+};
+
+// Nombre de la sección única de una pieza aplanada
+pub const FLAT_SECTION: &str = "main";
+
+/// Archivo de partituras (`partitures.json`): lista de objetos `{ "nombre": pieza }`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ScoreFile(pub Vec<BTreeMap<String, Piece>>);
+
+/// Pieza completa: metadatos y secciones con las notas de cada mano
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Piece {
+    pub metadata: ScoreMetadata,
+    pub sections: BTreeMap<String, Section>,
+}
+
+/// Metadatos de la pieza tal y como aparecen en el JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreMetadata {
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub composer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    pub meter: Meter,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tempo: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    pub note_duration_unit_seconds: f32, // Duración en segundos de la unidad del compás
+    pub structure: Vec<String>,          // Orden de reproducción de las secciones
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub playback_instructions: Option<String>,
+    // Ejercicio de una lección o generado: no sale en la lista de canciones ni cuenta como pieza nueva
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub exercise: bool,
+}
+
+/// Sección de la pieza, con las notas en tiempo absoluto desde `start_time`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Section {
+    #[serde(default)]
+    pub start_time: f32,
+    #[serde(default)]
+    pub right: Vec<Note>,
+    #[serde(default)]
+    pub left: Vec<Note>,
+    #[serde(default, skip_serializing_if = "Markings::is_empty")]
+    pub markings: Markings,
+}
+
+/// Compás, serializado como "3/8"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Meter {
+    pub beats: u8,     // Numerador
+    pub beat_unit: u8, // Denominador (potencia de dos)
+}
+
+impl TryFrom<String> for Meter {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let (num, den) = value
+            .split_once('/')
+            .ok_or_else(|| format!("compás inválido '{}', se esperaba 'n/d'", value))?;
+        let beats: u8 = num
+            .trim()
+            .parse()
+            .map_err(|e| format!("numerador inválido en '{}': {}", value, e))?;
+        let beat_unit: u8 = den
+            .trim()
+            .parse()
+            .map_err(|e| format!("denominador inválido en '{}': {}", value, e))?;
+
+        if beats == 0 || !beat_unit.is_power_of_two() {
+            return Err(format!("compás inválido '{}'", value));
+        }
+        Ok(Meter { beats, beat_unit })
+    }
+}
+
+impl From<Meter> for String {
+    fn from(meter: Meter) -> Self {
+        meter.to_string()
+    }
+}
+
+impl fmt::Display for Meter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.beats, self.beat_unit)
+    }
+}
+
+impl ScoreFile {
+    // Recorre todas las piezas del archivo en orden
+    pub fn pieces(&self) -> impl Iterator<Item = (&String, &Piece)> {
+        self.0.iter().flat_map(|entry| entry.iter())
+    }
+
+    // Las piezas de la lista de canciones: todas menos los ejercicios de las lecciones
+    pub fn songs(&self) -> impl Iterator<Item = (&String, &Piece)> {
+        self.pieces().filter(|(_, piece)| !piece.metadata.exercise)
+    }
+
+    // Busca una pieza por su nombre
+    pub fn piece(&self, name: &str) -> Result<&Piece, ScoreError> {
+        self.pieces()
+            .find(|(piece_name, _)| piece_name.as_str() == name)
+            .map(|(_, piece)| piece)
+            .ok_or_else(|| ScoreError::PieceNotFound {
+                location: ScoreLocation::piece(name),
+            })
+    }
+
+    // Primer nombre libre a partir de `base`: base, base-2, base-3...
+    pub fn free_name(&self, base: &str) -> String {
+        (1..)
+            .map(|n| match n {
+                1 => base.to_string(),
+                n => format!("{}-{}", base, n),
+            })
+            .find(|name| self.piece(name).is_err())
+            .unwrap_or_else(|| base.to_string())
+    }
+}
+
+impl Section {
+    // Notas de la mano indicada
+    pub fn notes(&self, hand: &Hand) -> &[Note] {
+        match hand {
+            Hand::Left => &self.left,
+            Hand::Right => &self.right,
+        }
+    }
+
+    // Final de la última nota de la sección, medido desde `start_time`
+    pub fn length(&self) -> f32 {
+        self.right
+            .iter()
+            .chain(self.left.iter())
+            .map(|n| n.start + n.duration - self.start_time)
+            .fold(0.0, f32::max)
+    }
+}
+
+impl Piece {
+    // Sección por nombre, con error si `structure` hace referencia a una inexistente
+    pub fn section(&self, label: &str) -> Result<&Section, ScoreError> {
+        self.sections
+            .get(label)
+            .ok_or_else(|| ScoreError::SectionNotFound {
+                location: ScoreLocation {
+                    section: Some(label.to_string()),
+                    ..ScoreLocation::default()
+                },
+            })
+    }
+
+    // Notas de una mano siguiendo el orden de `structure`.
+    // Cada repetición de una sección se desplaza al final de la anterior.
+    pub fn expand(&self, hand: &Hand) -> Result<Vec<Note>, ScoreError> {
+        self.expand_range(hand, 0..self.metadata.structure.len())
+    }
+
+    // Igual que `expand`, pero solo con las entradas de `structure` del rango indicado.
+    // La primera sección del rango empieza en el segundo 0.
+    pub fn expand_range(&self, hand: &Hand, range: Range<usize>) -> Result<Vec<Note>, ScoreError> {
+        let labels: &[String] = self.structure_range(range)?;
+
+        let mut notes: Vec<Note> = Vec::new();
+        let mut cursor: f32 = 0.0;
+
+        for label in labels {
+            let section: &Section = self.section(label)?;
+            let offset: f32 = cursor - section.start_time;
+
+            notes.extend(section.notes(hand).iter().map(|note| Note {
+                start: note.start + offset,
+                ..note.clone()
+            }));
+            cursor += section.length();
+        }
+
+        Ok(notes)
+    }
+
+    // Dinámicas, ligaduras y pedal siguiendo el orden de `structure`, igual que `expand`
+    pub fn expand_markings(&self) -> Result<Markings, ScoreError> {
+        self.expand_markings_range(0..self.metadata.structure.len())
+    }
+
+    // Igual que `expand_markings`, pero solo con las entradas de `structure` del rango indicado
+    pub fn expand_markings_range(&self, range: Range<usize>) -> Result<Markings, ScoreError> {
+        let mut markings: Markings = Markings::default();
+        let mut cursor: f32 = 0.0;
+
+        for label in self.structure_range(range)? {
+            let section: &Section = self.section(label)?;
+            markings.append(section.markings.shifted(cursor - section.start_time));
+            cursor += section.length();
+        }
+
+        Ok(markings)
+    }
+
+    // Etiquetas de `structure` en ese rango, o error si se sale de la estructura
+    fn structure_range(&self, range: Range<usize>) -> Result<&[String], ScoreError> {
+        self.metadata
+            .structure
+            .get(range.clone())
+            .ok_or_else(|| ScoreError::SectionNotFound {
+                location: ScoreLocation {
+                    section: Some(format!("#{}..#{}", range.start, range.end)),
+                    ..ScoreLocation::default()
+                },
+            })
+    }
+
+    // Pieza equivalente con una única sección que ya sigue el orden de `structure`
+    pub fn flatten(&self) -> Result<Piece, ScoreError> {
+        let section: Section = Section {
+            start_time: 0.0,
+            right: self.expand(&Hand::Right)?,
+            left: self.expand(&Hand::Left)?,
+            markings: self.expand_markings()?,
+        };
+
+        Ok(Piece {
+            metadata: ScoreMetadata {
+                structure: vec![FLAT_SECTION.to_string()],
+                playback_instructions: None,
+                ..self.metadata.clone()
+            },
+            sections: BTreeMap::from([(FLAT_SECTION.to_string(), section)]),
+        })
+    }
+
+    // Etiqueta y segundo de inicio de cada entrada de `structure` una vez expandida
+    pub fn section_starts(&self) -> Result<Vec<(String, f32)>, ScoreError> {
+        let mut starts: Vec<(String, f32)> = Vec::new();
+        let mut cursor: f32 = 0.0;
+
+        for label in &self.metadata.structure {
+            starts.push((label.clone(), cursor));
+            cursor += self.section(label)?.length();
+        }
+
+        Ok(starts)
+    }
+
+    // Duración total tras expandir `structure`
+    pub fn duration(&self) -> Result<f32, ScoreError> {
+        self.metadata
+            .structure
+            .iter()
+            .map(|label| self.section(label).map(Section::length))
+            .sum()
+    }
+}
+
+impl From<&ScoreMetadata> for PieceMetadata {
+    fn from(metadata: &ScoreMetadata) -> Self {
+        PieceMetadata {
+            time_signature: (metadata.meter.beats, metadata.meter.beat_unit),
+            base_note_value: metadata.note_duration_unit_seconds,
+            sections: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use {
+    crate::models::partiture::Hand,
+    std::{error, fmt, io},
+};
+
+/// Posición dentro de un archivo de partituras a la que se refiere un error
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScoreLocation {
+    pub file: Option<String>,
+    pub piece: Option<String>,
+    pub section: Option<String>,
+    pub hand: Option<Hand>,
+    pub note: Option<usize>, // Índice de la nota dentro de la sección
+}
+
+impl ScoreLocation {
+    pub fn file(file: &str) -> Self {
+        ScoreLocation {
+            file: Some(file.to_string()),
+            ..ScoreLocation::default()
+        }
+    }
+
+    pub fn piece(piece: &str) -> Self {
+        ScoreLocation {
+            piece: Some(piece.to_string()),
+            ..ScoreLocation::default()
+        }
+    }
+
+    // Misma posición dentro del archivo indicado
+    pub fn with_file(self, file: &str) -> Self {
+        ScoreLocation {
+            file: Some(file.to_string()),
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for ScoreLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts: Vec<String> = Vec::new();
+        if let Some(file) = &self.file {
+            parts.push(file.clone());
+        }
+        if let Some(piece) = &self.piece {
+            parts.push(format!("pieza '{}'", piece));
+        }
+        if let Some(section) = &self.section {
+            parts.push(format!("sección '{}'", section));
+        }
+        if let Some(hand) = &self.hand {
+            parts.push(format!("mano {}", hand));
+        }
+        if let Some(note) = self.note {
+            parts.push(format!("nota #{}", note));
+        }
+
+        if parts.is_empty() {
+            write!(f, "<partitura>")
+        } else {
+            write!(f, "{}", parts.join(" > "))
+        }
+    }
+}
+
+/// Gravedad de un problema encontrado al validar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// Problemas que puede detectar la validación de una pieza
+#[derive(Debug, Clone, PartialEq)]
+pub enum Issue {
+    NegativeStart(f32),
+    NegativeDuration(f32),
+    PitchOutOfRange(u8),
+    InvalidFinger(u8), // Dedo fuera de 1 (pulgar) a 5 (meñique)
+    InvalidVelocity(u8),
+    EmptyMarking {
+        kind: &'static str,
+        start: f32,
+        end: f32,
+    }, // Regulador, ligadura o pedal que no termina después de empezar
+    Overlap {
+        other: usize,
+    }, // Se solapa con otra nota del mismo tono
+    NoteBeforeSection {
+        start: f32,
+        section_start: f32,
+    },
+    UnknownSection(String),
+    EmptyStructure,
+    InvalidNoteUnit(f32),
+    UnusedSection,
+    DuplicatePiece,
+    InvalidNoteName(String),
+    NameMismatch {
+        name: String,
+        pitch: u8,
+        expected: u8,
+    }, // El nombre no coincide con el tono
+    AssumedTiming, // Pieza antigua sin compás ni tempo, convertida con los de por defecto
+}
+
+impl Issue {
+    pub fn severity(&self) -> Severity {
+        match self {
+            Issue::NoteBeforeSection { .. } | Issue::UnusedSection | Issue::AssumedTiming => {
+                Severity::Warning
+            }
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::NegativeStart(start) => write!(f, "inicio negativo ({})", start),
+            Issue::NegativeDuration(duration) => {
+                write!(f, "duración negativa o nula ({})", duration)
+            }
+            Issue::PitchOutOfRange(pitch) => {
+                write!(f, "tono {} fuera del rango del piano (21-108)", pitch)
+            }
+            Issue::InvalidFinger(finger) => {
+                write!(f, "dedo {} fuera de 1 (pulgar) a 5 (meñique)", finger)
+            }
+            Issue::InvalidVelocity(velocity) => {
+                write!(f, "velocidad {} fuera de 1 a 127", velocity)
+            }
+            Issue::EmptyMarking { kind, start, end } => write!(
+                f,
+                "{} que termina en {} sin haber empezado ({})",
+                kind, end, start
+            ),
+            Issue::Overlap { other } => {
+                write!(f, "se solapa con la nota #{} del mismo tono", other)
+            }
+            Issue::NoteBeforeSection {
+                start,
+                section_start,
+            } => write!(
+                f,
+                "empieza en {} antes del inicio de la sección ({})",
+                start, section_start
+            ),
+            Issue::UnknownSection(label) => {
+                write!(f, "'structure' usa la sección '{}' que no existe", label)
+            }
+            Issue::EmptyStructure => write!(f, "'structure' está vacío"),
+            Issue::InvalidNoteUnit(unit) => {
+                write!(
+                    f,
+                    "'note_duration_unit_seconds' debe ser positivo ({})",
+                    unit
+                )
+            }
+            Issue::UnusedSection => write!(f, "la sección no aparece en 'structure'"),
+            Issue::DuplicatePiece => write!(f, "hay otra pieza con el mismo nombre"),
+            Issue::InvalidNoteName(name) => write!(f, "nombre de nota inválido '{}'", name),
+            Issue::NameMismatch {
+                name,
+                pitch,
+                expected,
+            } => write!(
+                f,
+                "el nombre '{}' corresponde al tono {}, pero la nota tiene {}",
+                name, expected, pitch
+            ),
+            Issue::AssumedTiming => write!(
+                f,
+                "el formato antiguo no guarda compás ni tempo: se usan 4/4 y 1 segundo por unidad"
+            ),
+        }
+    }
+}
+
+/// Problema concreto con su posición
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub location: ScoreLocation,
+    pub issue: Issue,
+}
+
+impl Diagnostic {
+    pub fn severity(&self) -> Severity {
+        self.issue.severity()
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level: &str = match self.severity() {
+            Severity::Warning => "aviso",
+            Severity::Error => "error",
+        };
+        write!(f, "{}: {}: {}", level, self.location, self.issue)
+    }
+}
+
+/// Errores al cargar o interpretar partituras
+#[derive(Debug)]
+pub enum ScoreError {
+    Io {
+        location: ScoreLocation,
+        source: io::Error,
+    },
+    Parse {
+        location: ScoreLocation,
+        source: serde_json::Error,
+    },
+    PieceNotFound {
+        location: ScoreLocation,
+    },
+    SectionNotFound {
+        location: ScoreLocation,
+    },
+    Invalid {
+        location: ScoreLocation,
+        diagnostics: Vec<Diagnostic>,
+    },
+}
+
+impl ScoreError {
+    pub fn location(&self) -> &ScoreLocation {
+        match self {
+            ScoreError::Io { location, .. }
+            | ScoreError::Parse { location, .. }
+            | ScoreError::PieceNotFound { location }
+            | ScoreError::SectionNotFound { location }
+            | ScoreError::Invalid { location, .. } => location,
+        }
+    }
+
+    fn location_mut(&mut self) -> &mut ScoreLocation {
+        match self {
+            ScoreError::Io { location, .. }
+            | ScoreError::Parse { location, .. }
+            | ScoreError::PieceNotFound { location }
+            | ScoreError::SectionNotFound { location }
+            | ScoreError::Invalid { location, .. } => location,
+        }
+    }
+
+    // Añade el archivo al contexto del error
+    pub fn in_file(mut self, file: &str) -> Self {
+        self.location_mut().file = Some(file.to_string());
+        self
+    }
+
+    // Añade la pieza al contexto del error
+    pub fn in_piece(mut self, piece: &str) -> Self {
+        self.location_mut().piece = Some(piece.to_string());
+        self
+    }
+}
+
+impl fmt::Display for ScoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScoreError::Io { location, source } => {
+                write!(f, "{}: no se pudo leer el archivo: {}", location, source)
+            }
+            ScoreError::Parse { location, source } => {
+                write!(f, "{}: JSON inválido: {}", location, source)
+            }
+            ScoreError::PieceNotFound { location } => {
+                write!(f, "{}: pieza no encontrada", location)
+            }
+            ScoreError::SectionNotFound { location } => {
+                write!(f, "{}: sección no encontrada", location)
+            }
+            ScoreError::Invalid {
+                location,
+                diagnostics,
+            } => {
+                write!(
+                    f,
+                    "{}: {} problema(s) de validación",
+                    location,
+                    diagnostics.len()
+                )?;
+                for diagnostic in diagnostics {
+                    write!(f, "\n  {}", diagnostic)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl error::Error for ScoreError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ScoreError::Io { source, .. } => Some(source),
+            ScoreError::Parse { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -1,6 +1,11 @@
 use iced::Theme;
 use serde::{Deserialize, Serialize};
 
+use crate::models::{
+    partiture::{Hands, PieceMetadata},
+    take::InputSource,
+};
+
 #[derive(Clone, Serialize, Deserialize)]
 pub enum CustomTheme {
     Light,
@@ -27,13 +32,91 @@ impl CustomTheme {
 #[derive(Clone, Serialize, Deserialize)]
 pub struct CustomSettings {
     pub theme: CustomTheme, // Tema actual (serializable)
-    pub timer: f32,         // Tiempo que dura la partitura
+    pub timer: f32,         // Cuenta atrás inicial en segundos, solo si la pieza no tiene compás
+    #[serde(default)]
+    pub metronome: MetronomeSettings, // Ajustes del metrónomo
+    #[serde(default)]
+    pub microphone: MicrophoneSettings, // Calibración del micrófono
+    #[serde(default)]
+    pub latency: LatencySettings, // Retraso de cada entrada de notas
+    #[serde(default)]
+    pub midi_input: Option<String>, // Puerto del teclado MIDI, `None` sin teclado
+    #[serde(default)]
+    pub midi_output: MidiOutputSettings, // Piano digital o sintetizador externo
+}
+
+// Ajustes del metrónomo y de la cuenta previa
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetronomeSettings {
+    pub enabled: bool,     // Sonar durante la partitura
+    pub volume: f32,       // 0.0 a 1.0
+    pub count_in_bars: u8, // Compases de cuenta previa (1 o 2)
+    pub visual_beat: bool, // Mostrar el indicador visual del pulso
+}
+
+// Resultado del asistente de calibración del micrófono
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MicrophoneSettings {
+    pub noise_threshold: Option<f32>, // Flujo espectral mínimo de un ataque (None sin calibrar)
+    pub noise_rms: f32,               // Nivel del ruido de fondo medido
+    pub a4: f32,                      // Frecuencia del La4 del piano en Hz
+    pub latency: f32,                 // Segundos entre tocar una nota y detectarla
+    pub use_in_game: bool,            // Escuchar el piano durante la partida
+}
+
+// Segundos que tarda en llegar cada entrada desde que se toca la nota.
+// La del micrófono se guarda con su calibración en `MicrophoneSettings`.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LatencySettings {
+    pub keyboard: f32,
+    pub midi: f32,
 }
 
 impl CustomSettings {
     pub fn get_iced_theme(&self) -> Theme {
         self.theme.to_iced_theme()
     }
+
+    // Retraso que hay que restar a las notas de una entrada
+    pub fn input_latency(&self, source: InputSource) -> f32 {
+        match source {
+            InputSource::Keyboard => self.latency.keyboard,
+            InputSource::Midi => self.latency.midi,
+            InputSource::Microphone => self.microphone.latency,
+        }
+    }
+
+    pub fn set_input_latency(&mut self, source: InputSource, latency: f32) {
+        match source {
+            InputSource::Keyboard => self.latency.keyboard = latency,
+            InputSource::Midi => self.latency.midi = latency,
+            InputSource::Microphone => self.microphone.latency = latency,
+        }
+    }
+
+    // Duración de la cuenta previa: los compases elegidos, o `timer` si la pieza no tiene compás
+    pub fn count_in_seconds(&self, metadata: Option<&PieceMetadata>) -> f32 {
+        match metadata.map(PieceMetadata::bar_duration) {
+            Some(bar) if bar > 0.0 => bar * self.metronome.count_in_bars.clamp(1, 2) as f32,
+            _ => self.timer,
+        }
+    }
+}
+
+// Salida MIDI a un dispositivo externo. Los canales van de 1 a 16, como en los teclados.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MidiOutputSettings {
+    pub device: Option<String>, // Nombre del puerto de salida, `None` sin salida
+    pub sound: bool,            // Mandar las notas para que suenen en el dispositivo
+    pub hands: Hands,           // Manos que suenan
+    pub channel: u8,            // Canal de las notas que suenan
+    pub guide: bool,            // Encender las luces de guía de las teclas
+    pub guide_right: u8,        // Canal de las luces de la mano derecha
+    pub guide_left: u8,         // Canal de las luces de la mano izquierda
 }
 
 impl Default for CustomSettings {
@@ -41,6 +124,48 @@ impl Default for CustomSettings {
         Self {
             theme: CustomTheme::Dark,
             timer: 3.0,
+            metronome: MetronomeSettings::default(),
+            microphone: MicrophoneSettings::default(),
+            latency: LatencySettings::default(),
+            midi_input: None,
+            midi_output: MidiOutputSettings::default(),
+        }
+    }
+}
+
+impl Default for MetronomeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            volume: 0.6,
+            count_in_bars: 1,
+            visual_beat: true,
+        }
+    }
+}
+
+impl Default for MicrophoneSettings {
+    fn default() -> Self {
+        Self {
+            noise_threshold: None,
+            noise_rms: 0.0,
+            a4: 440.0,
+            latency: 0.0,
+            use_in_game: false,
+        }
+    }
+}
+
+impl Default for MidiOutputSettings {
+    fn default() -> Self {
+        Self {
+            device: None,
+            sound: true,
+            hands: Hands::Together,
+            channel: 1,
+            guide: false,
+            guide_right: 4,
+            guide_left: 3,
         }
     }
 }
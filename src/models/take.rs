@@ -0,0 +1,107 @@
+use {
+    crate::models::partiture::Hand,
+    serde::{Deserialize, Serialize},
+    std::collections::HashMap,
+};
+
+/// Origen de una nota tocada por el usuario
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InputSource {
+    Keyboard,   // Teclado del ordenador
+    Midi,       // Teclado MIDI
+    Microphone, // Piano acústico por el micrófono
+}
+
+/// Nota tocada, con tiempos en segundos de partitura (sin contar la cuenta atrás)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PlayedNote {
+    pub pitch: u8,
+    pub on: f32,
+    pub off: f32,
+    pub velocity: u8,
+    pub source: InputSource,
+}
+
+/// Grabación de una interpretación completa de una pieza
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Take {
+    pub piece: String,
+    pub recorded_at: u64, // Segundos desde UNIX_EPOCH
+    pub notes: Vec<PlayedNote>,
+}
+
+/// Valoración de una nota tocada frente a la partitura
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Judgement {
+    OnTime,
+    Early,
+    Late,
+    Wrong, // No corresponde a ninguna nota esperada
+}
+
+/// Nota tocada junto con su valoración y la nota esperada con la que se emparejó
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JudgedNote {
+    pub played: PlayedNote,
+    pub judgement: Judgement,
+    pub offset: f32,                     // Segundos de adelanto (<0) o retraso (>0)
+    pub expected: Option<(Hand, usize)>, // Mano e índice de la nota esperada
+}
+
+/// Graba las notas mientras se toca, cerrando cada nota al soltar la tecla
+pub struct TakeRecorder {
+    take: Take,
+    held: HashMap<u8, usize>, // Tono -> índice de la nota que sigue pulsada
+}
+
+impl TakeRecorder {
+    pub fn new(piece: &str, recorded_at: u64) -> Self {
+        TakeRecorder {
+            take: Take {
+                piece: piece.to_string(),
+                recorded_at,
+                notes: Vec::new(),
+            },
+            held: HashMap::new(),
+        }
+    }
+
+    // Empieza una nota. Devuelve `false` si el tono ya estaba pulsado (repetición de tecla)
+    pub fn note_on(&mut self, pitch: u8, time: f32, velocity: u8, source: InputSource) -> bool {
+        if self.held.contains_key(&pitch) {
+            return false;
+        }
+
+        self.held.insert(pitch, self.take.notes.len());
+        self.take.notes.push(PlayedNote {
+            pitch,
+            on: time,
+            off: time,
+            velocity,
+            source,
+        });
+        true
+    }
+
+    // Termina la nota pulsada con ese tono
+    pub fn note_off(&mut self, pitch: u8, time: f32) {
+        if let Some(index) = self.held.remove(&pitch) {
+            let note: &mut PlayedNote = &mut self.take.notes[index];
+            note.off = time.max(note.on);
+        }
+    }
+
+    // Indica si el tono está pulsado ahora mismo
+    pub fn is_held(&self, pitch: u8) -> bool {
+        self.held.contains_key(&pitch)
+    }
+
+    // Cierra las notas que sigan pulsadas y devuelve la grabación
+    pub fn finish(mut self, time: f32) -> Take {
+        let held: Vec<u8> = self.held.keys().copied().collect();
+        for pitch in held {
+            self.note_off(pitch, time);
+        }
+        self.take
+    }
+}
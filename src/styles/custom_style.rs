@@ -85,7 +85,6 @@ pub fn button_settings(_theme: &Theme, _status: button::Status) -> button::Style
 pub fn partiture_title(_theme: &Theme) -> text::Style {
     text::Style {
         color: Some(ColorPalette::ACCENT_RED),
-        ..Default::default()
     }
 }
 
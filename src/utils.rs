@@ -1,4 +1,18 @@
+pub mod difficulty;
+pub mod fingering;
 pub mod frecuency;
 pub mod helper_json;
+pub mod import;
+pub mod keyboard_input;
+pub mod legacy;
+pub mod musicxml;
+pub mod note_names;
+pub mod performance;
+pub mod quantize;
 pub mod reusable;
+pub mod scales;
+pub mod sight_reading;
+pub mod time;
+#[allow(clippy::module_inception)]
 pub mod utils;
+pub mod validation;
@@ -0,0 +1,286 @@
+use crate::{
+    models::{note::Note, partiture::PieceMetadata, score::ScoreFile, score_error::ScoreError},
+    utils::helper_json::load_piece_hands,
+};
+
+// Margen para considerar que dos tiempos de inicio son el mismo
+const TIME_EPSILON: f32 = 1e-3;
+
+// Pesos de cada factor en la puntuación final (suman 1.0)
+const WEIGHT_DENSITY: f32 = 0.25;
+const WEIGHT_RANGE: f32 = 0.10;
+const WEIGHT_JUMPS: f32 = 0.15;
+const WEIGHT_CHORDS: f32 = 0.10;
+const WEIGHT_RHYTHM: f32 = 0.15;
+const WEIGHT_ACCIDENTALS: f32 = 0.10;
+const WEIGHT_INDEPENDENCE: f32 = 0.15;
+
+/// Valoración de dificultad de una pieza.
+/// Cada factor está normalizado entre 0.0 (trivial) y 1.0 (muy difícil),
+/// y `score` los combina en una escala de 1.0 a 10.0.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DifficultyRating {
+    pub density: f32,      // Notas por segundo
+    pub range: f32,        // Registro total entre ambas manos
+    pub jumps: f32,        // Saltos de la mano entre notas consecutivas
+    pub chords: f32,       // Tamaño de los acordes
+    pub rhythm: f32,       // Duraciones cortas, síncopas y variedad rítmica
+    pub accidentals: f32,  // Proporción de teclas negras
+    pub independence: f32, // Independencia rítmica entre las manos
+    pub score: f32,        // Puntuación global (1.0 a 10.0)
+}
+
+impl DifficultyRating {
+    // Nivel redondeado de 1 a 10, útil para ordenar el repertorio
+    pub fn level(&self) -> u8 {
+        self.score.round().clamp(1.0, 10.0) as u8
+    }
+
+    // Etiqueta legible del nivel
+    pub fn label(&self) -> &'static str {
+        match self.level() {
+            1..=3 => "Principiante",
+            4..=6 => "Intermedio",
+            _ => "Avanzado",
+        }
+    }
+}
+
+// Grupo de notas que empiezan a la vez en una mano (nota suelta o acorde)
+struct Onset {
+    start: f32,
+    pitches: Vec<u8>,
+}
+
+impl Onset {
+    // Tono medio del grupo, para medir los desplazamientos de la mano
+    fn center(&self) -> f32 {
+        self.pitches.iter().map(|&p| p as f32).sum::<f32>() / self.pitches.len() as f32
+    }
+}
+
+/// Calcula la dificultad de una pieza a partir de las notas de ambas manos
+pub fn rate_difficulty(
+    right: &[Note],
+    left: &[Note],
+    metadata: &PieceMetadata,
+) -> DifficultyRating {
+    let onsets_r: Vec<Onset> = group_onsets(right);
+    let onsets_l: Vec<Onset> = group_onsets(left);
+
+    let total_time: f32 = right
+        .iter()
+        .chain(left.iter())
+        .map(|n| n.start + n.duration)
+        .fold(0.0, f32::max);
+
+    if total_time <= 0.0 || (onsets_r.is_empty() && onsets_l.is_empty()) {
+        return DifficultyRating {
+            score: 1.0,
+            ..DifficultyRating::default()
+        };
+    }
+
+    let density: f32 = rate_density(&onsets_r, &onsets_l, total_time);
+    let range: f32 = rate_range(right, left);
+    let jumps: f32 = rate_jumps(&onsets_r).max(rate_jumps(&onsets_l));
+    let chords: f32 = rate_chords(&onsets_r).max(rate_chords(&onsets_l));
+    let rhythm: f32 = rate_rhythm(right, left, metadata.base_note_value);
+    let accidentals: f32 = rate_accidentals(right, left);
+    let independence: f32 = rate_independence(&onsets_r, &onsets_l);
+
+    let weighted: f32 = density * WEIGHT_DENSITY
+        + range * WEIGHT_RANGE
+        + jumps * WEIGHT_JUMPS
+        + chords * WEIGHT_CHORDS
+        + rhythm * WEIGHT_RHYTHM
+        + accidentals * WEIGHT_ACCIDENTALS
+        + independence * WEIGHT_INDEPENDENCE;
+
+    DifficultyRating {
+        density,
+        range,
+        jumps,
+        chords,
+        rhythm,
+        accidentals,
+        independence,
+        score: 1.0 + 9.0 * weighted.clamp(0.0, 1.0),
+    }
+}
+
+/// Carga una pieza del archivo de partituras y calcula su dificultad
+pub fn rate_piece(score: &ScoreFile, piece_name: &str) -> Result<DifficultyRating, ScoreError> {
+    let (notes_l, notes_r, piece_metadata) = load_piece_hands(score, piece_name)?;
+
+    Ok(rate_difficulty(&notes_r, &notes_l, &piece_metadata))
+}
+
+/// Dificultad de cada canción de la biblioteca, en su orden; `None` si no se pudo calcular.
+/// Los ejercicios de las lecciones no se valoran.
+pub fn rate_library(score: &ScoreFile) -> Vec<(String, Option<DifficultyRating>)> {
+    score
+        .songs()
+        .map(|(name, _piece)| {
+            let rating: Option<DifficultyRating> = rate_piece(score, name)
+                .map_err(|e| log::error!("No se pudo calcular la dificultad de '{}': {}", name, e))
+                .ok();
+            (name.clone(), rating)
+        })
+        .collect()
+}
+
+// Agrupa las notas que empiezan a la vez, ignorando silencios
+fn group_onsets(notes: &[Note]) -> Vec<Onset> {
+    let mut sorted: Vec<&Note> = notes.iter().filter(|n| !is_rest(n)).collect();
+    sorted.sort_by(|a, b| a.start.total_cmp(&b.start));
+
+    let mut onsets: Vec<Onset> = Vec::new();
+    for note in sorted {
+        match onsets.last_mut() {
+            Some(last) if (note.start - last.start).abs() < TIME_EPSILON => {
+                last.pitches.push(note.pitch)
+            }
+            _ => onsets.push(Onset {
+                start: note.start,
+                pitches: vec![note.pitch],
+            }),
+        }
+    }
+    onsets
+}
+
+fn is_rest(note: &Note) -> bool {
+    note.is_rest || note.pitch == 0
+}
+
+// Ataques por segundo, saturando en 8 ataques/s
+fn rate_density(onsets_r: &[Onset], onsets_l: &[Onset], total_time: f32) -> f32 {
+    let attacks: f32 = (onsets_r.len() + onsets_l.len()) as f32;
+    (attacks / total_time / 8.0).clamp(0.0, 1.0)
+}
+
+// Distancia entre la nota más grave y la más aguda, saturando en 4 octavas
+fn rate_range(right: &[Note], left: &[Note]) -> f32 {
+    let pitches = right.iter().chain(left.iter()).filter(|n| !is_rest(n));
+    let (min, max) = pitches.fold((u8::MAX, u8::MIN), |(min, max), n| {
+        (min.min(n.pitch), max.max(n.pitch))
+    });
+
+    if min > max {
+        return 0.0;
+    }
+    ((max - min) as f32 / 48.0).clamp(0.0, 1.0)
+}
+
+// Saltos medios entre ataques consecutivos más allá de la extensión de la mano (una quinta)
+fn rate_jumps(onsets: &[Onset]) -> f32 {
+    if onsets.len() < 2 {
+        return 0.0;
+    }
+
+    let total: f32 = onsets
+        .windows(2)
+        .map(|pair| {
+            let leap: f32 = (pair[1].center() - pair[0].center()).abs();
+            ((leap - 7.0).max(0.0) / 12.0).min(1.0)
+        })
+        .sum();
+
+    (total / (onsets.len() - 1) as f32 * 2.0).clamp(0.0, 1.0)
+}
+
+// Combina el tamaño medio y máximo de los acordes
+fn rate_chords(onsets: &[Onset]) -> f32 {
+    if onsets.is_empty() {
+        return 0.0;
+    }
+
+    let sizes = onsets.iter().map(|o| o.pitches.len() as f32);
+    let max: f32 = sizes.clone().fold(1.0, f32::max);
+    let avg: f32 = sizes.sum::<f32>() / onsets.len() as f32;
+
+    (0.6 * (avg - 1.0) / 2.0 + 0.4 * (max - 1.0) / 3.0).clamp(0.0, 1.0)
+}
+
+// Duración más corta respecto al pulso, síncopas y variedad de duraciones
+fn rate_rhythm(right: &[Note], left: &[Note], beat: f32) -> f32 {
+    let notes: Vec<&Note> = right
+        .iter()
+        .chain(left.iter())
+        .filter(|n| !is_rest(n) && n.duration > 0.0)
+        .collect();
+
+    if notes.is_empty() || beat <= 0.0 {
+        return 0.0;
+    }
+
+    // Cuantas subdivisiones del pulso hacen falta para la nota más corta (1/16 del pulso satura)
+    let shortest: f32 = notes.iter().map(|n| n.duration).fold(f32::MAX, f32::min);
+    let shortness: f32 = ((beat / shortest).log2().max(0.0) / 4.0).clamp(0.0, 1.0);
+
+    // Una nota sincopada empieza fuera del pulso y se prolonga por encima del siguiente
+    let syncopated: usize = notes
+        .iter()
+        .filter(|n| {
+            let offset: f32 = n.start.rem_euclid(beat);
+            let off_beat: bool = offset > TIME_EPSILON && beat - offset > TIME_EPSILON;
+            off_beat && offset + n.duration > beat + TIME_EPSILON
+        })
+        .count();
+    let syncopation: f32 = (syncopated as f32 / notes.len() as f32 * 4.0).clamp(0.0, 1.0);
+
+    // Número de duraciones distintas, saturando en 6
+    let mut durations: Vec<f32> = notes.iter().map(|n| n.duration).collect();
+    durations.sort_by(f32::total_cmp);
+    durations.dedup_by(|a, b| (*a - *b).abs() < TIME_EPSILON);
+    let variety: f32 = ((durations.len() as f32 - 1.0) / 5.0).clamp(0.0, 1.0);
+
+    0.4 * shortness + 0.4 * syncopation + 0.2 * variety
+}
+
+// Proporción de notas en teclas negras, saturando en la mitad de las notas
+fn rate_accidentals(right: &[Note], left: &[Note]) -> f32 {
+    let notes: Vec<&Note> = right
+        .iter()
+        .chain(left.iter())
+        .filter(|n| !is_rest(n))
+        .collect();
+    if notes.is_empty() {
+        return 0.0;
+    }
+
+    let black_keys: usize = notes
+        .iter()
+        .filter(|n| matches!(n.pitch % 12, 1 | 3 | 6 | 8 | 10))
+        .count();
+
+    (black_keys as f32 / notes.len() as f32 * 2.0).clamp(0.0, 1.0)
+}
+
+// Proporción de ataques que una mano toca sin la otra, ponderada por lo activa que está la menos ocupada
+fn rate_independence(onsets_r: &[Onset], onsets_l: &[Onset]) -> f32 {
+    if onsets_r.is_empty() || onsets_l.is_empty() {
+        return 0.0;
+    }
+
+    let shared: usize = onsets_r
+        .iter()
+        .filter(|r| {
+            onsets_l
+                .iter()
+                .any(|l| (l.start - r.start).abs() < TIME_EPSILON)
+        })
+        .count();
+    let union: usize = onsets_r.len() + onsets_l.len() - shared;
+    let separate: f32 = 1.0 - shared as f32 / union as f32;
+
+    let (busy, idle) = if onsets_r.len() >= onsets_l.len() {
+        (onsets_r.len(), onsets_l.len())
+    } else {
+        (onsets_l.len(), onsets_r.len())
+    };
+    let balance: f32 = idle as f32 / busy as f32;
+
+    (separate * (0.5 + 0.5 * balance)).clamp(0.0, 1.0)
+}
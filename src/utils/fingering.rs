@@ -0,0 +1,279 @@
+use crate::models::{note::Note, partiture::Hand};
+
+// Margen para considerar que dos tiempos de inicio son el mismo
+const TIME_EPSILON: f32 = 1e-3;
+
+// Segundos de silencio tras los que la mano puede cambiar de posición sin coste
+const FREE_SHIFT: f32 = 0.5;
+
+// Coste de un acorde que no cabe en la mano
+const IMPOSSIBLE: f32 = 1000.0;
+
+// Distancias en semitonos entre dos dedos de la mano derecha, del dedo menor al mayor
+// (Parncutt y otros, 1997): práctica mínima, cómoda mínima, relajada mínima,
+// relajada máxima, cómoda máxima y práctica máxima. Negativas si el dedo menor pasa por encima.
+const SPANS: [((u8, u8), [i32; 6]); 10] = [
+    ((1, 2), [-5, -3, 1, 5, 8, 10]),
+    ((1, 3), [-4, -2, 3, 7, 10, 12]),
+    ((1, 4), [-3, -1, 5, 9, 12, 14]),
+    ((1, 5), [-1, 1, 7, 10, 13, 15]),
+    ((2, 3), [1, 1, 1, 2, 3, 5]),
+    ((2, 4), [1, 1, 3, 4, 5, 7]),
+    ((2, 5), [2, 2, 5, 6, 8, 10]),
+    ((3, 4), [1, 1, 1, 2, 2, 4]),
+    ((3, 5), [1, 1, 3, 4, 5, 7]),
+    ((4, 5), [1, 1, 1, 2, 3, 5]),
+];
+
+// Grupo de notas que empiezan a la vez en una mano (nota suelta o acorde)
+struct Onset {
+    start: f32,
+    end: f32,
+    notes: Vec<usize>, // Índices de las notas, de la más cercana al pulgar a la más lejana
+    pitches: Vec<i32>, // Tonos como si fuera la mano derecha, en el mismo orden
+    black: Vec<bool>,  // Si cada nota es una tecla negra, en el mismo orden
+    choices: Vec<Vec<u8>>, // Dedos posibles para las notas, en el mismo orden
+}
+
+/// Sugiere la digitación de las notas de una mano que no la tienen.
+/// Busca la secuencia de dedos más cómoda según la distancia entre notas seguidas y entre
+/// las notas de cada acorde; los dedos ya escritos se respetan. Los acordes de más de cinco
+/// notas se quedan sin digitación.
+pub fn suggest_fingering(notes: &mut [Note], hand: Hand) {
+    let onsets: Vec<Onset> = group_onsets(notes, hand);
+
+    // Cada tramo de acordes que se pueden tocar se resuelve por separado
+    for chain in onsets.split(|onset| onset.choices.is_empty()) {
+        if chain.is_empty() {
+            continue;
+        }
+        for (onset, fingers) in chain.iter().zip(best_fingering(chain)) {
+            for (&index, finger) in onset.notes.iter().zip(fingers) {
+                notes[index].finger.get_or_insert(finger);
+            }
+        }
+    }
+}
+
+// Agrupa las notas que empiezan a la vez, ignorando silencios, con sus dedos posibles
+fn group_onsets(notes: &[Note], hand: Hand) -> Vec<Onset> {
+    let mut sorted: Vec<usize> = (0..notes.len())
+        .filter(|&i| !notes[i].is_rest && notes[i].pitch != 0)
+        .collect();
+    sorted.sort_by(|&a, &b| notes[a].start.total_cmp(&notes[b].start));
+
+    // La mano izquierda es la derecha en espejo: el pulgar queda en la nota más aguda
+    let mirror = |pitch: u8| -> i32 {
+        match hand {
+            Hand::Right => pitch as i32,
+            Hand::Left => -(pitch as i32),
+        }
+    };
+
+    let mut onsets: Vec<Onset> = Vec::new();
+    for index in sorted {
+        let note: &Note = &notes[index];
+        match onsets.last_mut() {
+            Some(last) if (note.start - last.start).abs() < TIME_EPSILON => {
+                last.notes.push(index);
+                last.end = last.end.max(note.start + note.duration);
+            }
+            _ => onsets.push(Onset {
+                start: note.start,
+                end: note.start + note.duration,
+                notes: vec![index],
+                pitches: Vec::new(),
+                black: Vec::new(),
+                choices: Vec::new(),
+            }),
+        }
+    }
+
+    for onset in onsets.iter_mut() {
+        onset.notes.sort_by_key(|&i| mirror(notes[i].pitch));
+        onset.pitches = onset
+            .notes
+            .iter()
+            .map(|&i| mirror(notes[i].pitch))
+            .collect();
+        onset.black = onset
+            .notes
+            .iter()
+            .map(|&i| matches!(notes[i].pitch % 12, 1 | 3 | 6 | 8 | 10))
+            .collect();
+        let written: Vec<Option<u8>> = onset.notes.iter().map(|&i| notes[i].finger).collect();
+        onset.choices = finger_sets(onset.notes.len())
+            .into_iter()
+            .filter(|fingers| {
+                fingers
+                    .iter()
+                    .zip(&written)
+                    .all(|(finger, written)| written.is_none_or(|w| w == *finger))
+            })
+            .collect();
+    }
+    onsets
+}
+
+// Todas las formas de tocar `count` notas con dedos distintos, del pulgar hacia fuera
+fn finger_sets(count: usize) -> Vec<Vec<u8>> {
+    if count == 0 || count > 5 {
+        return Vec::new();
+    }
+    (0u8..32)
+        .filter(|mask| mask.count_ones() as usize == count)
+        .map(|mask| (1..=5).filter(|f| mask & (1 << (f - 1)) != 0).collect())
+        .collect()
+}
+
+// Dedos más cómodos para cada acorde de un tramo, por programación dinámica
+fn best_fingering(chain: &[Onset]) -> Vec<Vec<u8>> {
+    // Coste acumulado y elección anterior de cada forma de tocar cada acorde
+    let mut costs: Vec<Vec<(f32, usize)>> = Vec::with_capacity(chain.len());
+
+    for (i, onset) in chain.iter().enumerate() {
+        let row: Vec<(f32, usize)> = onset
+            .choices
+            .iter()
+            .map(|fingers| {
+                let own: f32 = chord_cost(onset, fingers);
+                if i == 0 {
+                    return (own, 0);
+                }
+                let previous: &Onset = &chain[i - 1];
+                costs[i - 1]
+                    .iter()
+                    .enumerate()
+                    .map(|(j, (cost, _))| {
+                        let step: f32 = step_cost(previous, &previous.choices[j], onset, fingers);
+                        (cost + step + own, j)
+                    })
+                    .min_by(|a, b| a.0.total_cmp(&b.0))
+                    .unwrap_or((own, 0))
+            })
+            .collect();
+        costs.push(row);
+    }
+
+    // Recorrer hacia atrás desde la forma más barata del último acorde
+    let mut choice: usize = costs
+        .last()
+        .and_then(|row| {
+            row.iter()
+                .enumerate()
+                .min_by(|a, b| a.1.0.total_cmp(&b.1.0))
+                .map(|(j, _)| j)
+        })
+        .unwrap_or(0);
+    let mut fingering: Vec<Vec<u8>> = vec![Vec::new(); chain.len()];
+    for i in (0..chain.len()).rev() {
+        fingering[i] = chain[i].choices[choice].clone();
+        choice = costs[i][choice].1;
+    }
+    fingering
+}
+
+// Coste de tocar un acorde con esos dedos: lo que se abre la mano entre notas vecinas
+fn chord_cost(onset: &Onset, fingers: &[u8]) -> f32 {
+    let keys: f32 = onset
+        .black
+        .iter()
+        .zip(fingers)
+        .map(|(&black, &finger)| key_cost(black, finger))
+        .sum();
+    let spans: f32 = onset
+        .pitches
+        .windows(2)
+        .zip(fingers.windows(2))
+        .map(|(p, f)| {
+            let distance: i32 = p[1] - p[0];
+            match span(f[0], f[1]) {
+                Some(limits) if distance <= limits[5] => stretch_cost(distance, limits),
+                _ => IMPOSSIBLE,
+            }
+        })
+        .sum();
+    keys + spans
+}
+
+// Coste de pasar de un acorde al siguiente, medido entre las notas de los extremos
+fn step_cost(from: &Onset, from_fingers: &[u8], to: &Onset, to_fingers: &[u8]) -> f32 {
+    // Tras un silencio largo la mano se recoloca sin coste
+    if to.start - from.end > FREE_SHIFT {
+        return 0.0;
+    }
+    let low: f32 = pair_cost(
+        from.pitches[0],
+        from_fingers[0],
+        to.pitches[0],
+        to_fingers[0],
+    );
+    if from.pitches.len() == 1 && to.pitches.len() == 1 {
+        return low;
+    }
+    let high: f32 = pair_cost(
+        from.pitches[from.pitches.len() - 1],
+        from_fingers[from_fingers.len() - 1],
+        to.pitches[to.pitches.len() - 1],
+        to_fingers[to_fingers.len() - 1],
+    );
+    (low + high) / 2.0
+}
+
+// Coste de tocar una nota con un dedo y la siguiente con otro
+fn pair_cost(from: i32, from_finger: u8, to: i32, to_finger: u8) -> f32 {
+    if from_finger == to_finger {
+        // Repetir dedo solo es cómodo en la misma nota
+        return if from == to {
+            0.0
+        } else {
+            4.0 + (to - from).abs() as f32
+        };
+    }
+
+    // Distancia del dedo menor al mayor
+    let (low, high, distance): (u8, u8, i32) = if from_finger < to_finger {
+        (from_finger, to_finger, to - from)
+    } else {
+        (to_finger, from_finger, from - to)
+    };
+    let Some(limits) = span(low, high) else {
+        return IMPOSSIBLE;
+    };
+
+    let mut cost: f32 = stretch_cost(distance, limits);
+    // Paso del pulgar por debajo o de un dedo por encima del pulgar
+    if low == 1 && distance < 0 {
+        cost += 1.0;
+    }
+    // El tercero y el cuarto seguidos son poco independientes
+    if (low, high) == (3, 4) {
+        cost += 1.0;
+    }
+    cost
+}
+
+// Lo que una distancia se sale de lo relajado, de lo cómodo y de lo practicable
+fn stretch_cost(distance: i32, limits: [i32; 6]) -> f32 {
+    let outside =
+        |min: i32, max: i32| -> f32 { (min - distance).max(distance - max).max(0) as f32 };
+    outside(limits[2], limits[3])
+        + 2.0 * outside(limits[1], limits[4])
+        + 10.0 * outside(limits[0], limits[5])
+}
+
+// El pulgar y el meñique están incómodos en las teclas negras
+fn key_cost(black: bool, finger: u8) -> f32 {
+    match (black, finger) {
+        (true, 1) => 1.0,
+        (true, 5) => 0.5,
+        _ => 0.0,
+    }
+}
+
+fn span(low: u8, high: u8) -> Option<[i32; 6]> {
+    SPANS
+        .iter()
+        .find(|(pair, _)| *pair == (low, high))
+        .map(|(_, limits)| *limits)
+}
@@ -1,77 +1,195 @@
-// use {
-//     crate::widgets::notes::Note,
-//     cpal::{
-//         Device, Host, Stream, StreamError, SupportedStreamConfig,
-//         traits::{DeviceTrait, HostTrait, StreamTrait},
-//     },
-//     rustfft::{Fft, FftPlanner, num_complex::Complex},
-//     std::sync::{Arc, mpsc},
-// };
-
-// pub fn get_frecuency(note: &mut Note) {
-//     // Inicializa el host y el dispositivo de entrada
-//     let host: Host = cpal::default_host();
-//     let device: Device = host
-//         .default_input_device()
-//         .expect("No input device available");
-//     let config: SupportedStreamConfig = device.default_input_config().unwrap();
-//     let sample_rate: f32 = config.sample_rate().0 as f32;
-
-//     // Canal para enviar muestras
-//     let (tx, rx) = mpsc::channel();
-
-//     // Stream de entrada
-//     let stream: Stream = device
-//         .build_input_stream(
-//             &config.into(),
-//             move |data: &[f32], _: &cpal::InputCallbackInfo| {
-//                 for &sample in data {
-//                     tx.send(sample).ok();
-//                 }
-//             },
-//             move |err: StreamError| {
-//                 eprintln!("Error: {:?}", err);
-//             },
-//             None, // Add the missing fourth argument for stream configuration
-//         )
-//         .unwrap();
-
-//     stream.play().unwrap();
-
-//     // Buffer para FFT
-//     let mut samples: Vec<Complex<f32>> = Vec::new();
-//     let fft_size: usize = 1024;
-//     let mut planner: FftPlanner<f32> = FftPlanner::new();
-//     let fft: Arc<dyn Fft<f32>> = planner.plan_fft_forward(fft_size);
-
-//     // Recoge muestras
-//     while samples.len() < fft_size {
-//         if let Ok(sample) = rx.recv() {
-//             samples.push(Complex::new(sample, 0.0));
-//         }
-//     }
-
-//     // Ejecuta FFT
-//     fft.process(&mut samples);
-
-//     // Calcular energía total del buffer
-//     let energy: f32 = samples.iter().map(|c: &Complex<f32>| c.norm()).sum();
-//     let energy_threshold: f32 = 50.0; // Puedes ajustar este valor si lo necesitas
-
-//     if energy > energy_threshold {
-//         // Encuentra la frecuencia dominante
-//         let max_index: usize = samples
-//             .iter()
-//             .enumerate()
-//             .max_by(|a, b| a.1.norm().partial_cmp(&b.1.norm()).unwrap())
-//             .map(|(i, _)| i)
-//             .unwrap();
-//         let freq = max_index as f32 * sample_rate / fft_size as f32;
-
-//         // Filtrar solo frecuencias dentro del rango de un piano real (A0 a C8)
-//         let midi_note: f32 = (69.0 + 12.0 * (freq / 440.0).log2()).round();
-//         if freq >= 27.5 && freq <= 4186.0 && note.pitch == midi_note as u8 {
-//             note.is_active = true;
-//         }
-//     }
-// }
+use {
+    crate::{
+        audio::{
+            analyser::{AnalyserConfig, StreamingAnalyser},
+            output::AudioError,
+        },
+        models::settings::MicrophoneSettings,
+    },
+    cpal::{
+        Device, FromSample, Host, SampleFormat, SizedSample, Stream, StreamConfig,
+        SupportedStreamConfig,
+        traits::{DeviceTrait, HostTrait, StreamTrait},
+    },
+    std::{
+        sync::mpsc::{self, Receiver, Sender},
+        time::{Duration, Instant},
+    },
+};
+
+/// Bloque de audio mono recibido del micrófono
+#[derive(Debug, Clone)]
+pub struct InputBlock {
+    pub captured: Instant, // Momento en que llegó el bloque (su última muestra)
+    pub samples: Vec<f32>,
+}
+
+/// Entrada del micrófono por defecto. El hilo de audio envía bloques por un canal
+/// y la aplicación los recoge sin esperar con `drain`.
+pub struct MicrophoneInput {
+    _stream: Stream,
+    sample_rate: u32,
+    receiver: Receiver<InputBlock>,
+}
+
+impl MicrophoneInput {
+    pub fn open() -> Result<Self, AudioError> {
+        let host: Host = cpal::default_host();
+        let device: Device = host
+            .default_input_device()
+            .ok_or(AudioError::NoInputDevice)?;
+        let supported: SupportedStreamConfig = device
+            .default_input_config()
+            .map_err(|e| AudioError::Config(e.to_string()))?;
+
+        let sample_format: SampleFormat = supported.sample_format();
+        let config: StreamConfig = supported.into();
+        let sample_rate: u32 = config.sample_rate.0;
+        let (sender, receiver) = mpsc::channel();
+
+        let stream: Stream = match sample_format {
+            SampleFormat::F32 => build_input_stream::<f32>(&device, &config, sender),
+            SampleFormat::I16 => build_input_stream::<i16>(&device, &config, sender),
+            SampleFormat::U16 => build_input_stream::<u16>(&device, &config, sender),
+            other => Err(AudioError::Config(format!(
+                "formato {} no soportado",
+                other
+            ))),
+        }?;
+        stream
+            .play()
+            .map_err(|e| AudioError::Stream(e.to_string()))?;
+
+        Ok(MicrophoneInput {
+            _stream: stream,
+            sample_rate,
+            receiver,
+        })
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Bloques recibidos desde la última llamada, sin bloquear
+    pub fn drain(&self) -> Vec<InputBlock> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+// Crea el stream mezclando los canales a mono
+fn build_input_stream<T>(
+    device: &Device,
+    config: &StreamConfig,
+    sender: Sender<InputBlock>,
+) -> Result<Stream, AudioError>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels: usize = config.channels.max(1) as usize;
+
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                let samples: Vec<f32> = data
+                    .chunks(channels)
+                    .map(|frame| {
+                        frame.iter().map(|s| s.to_sample::<f32>()).sum::<f32>() / frame.len() as f32
+                    })
+                    .collect();
+                // Si la aplicación ya no escucha, el bloque se descarta
+                let _ = sender.send(InputBlock {
+                    captured: Instant::now(),
+                    samples,
+                });
+            },
+            |e| log::error!("Error en la entrada de audio: {}", e),
+            None,
+        )
+        .map_err(|e| AudioError::Stream(e.to_string()))
+}
+
+/// Velocidad fija de las notas oídas por el micrófono
+pub const MICROPHONE_VELOCITY: u8 = 80;
+
+/// Notas oídas por el micrófono en un ataque
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeardNotes {
+    pub at: Instant,      // Momento en que se capturó el ataque, sin corregir la latencia
+    pub pitches: Vec<u8>, // Tonos nuevos del ataque
+    pub strength: f32,    // Fuerza del ataque
+}
+
+/// Traduce la posición de una muestra del micrófono a un `Instant`, usando el último
+/// bloque recibido como referencia para que el reloj de la tarjeta no se desvíe
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SampleClock {
+    last: Option<(u64, Instant)>, // Muestra final del último bloque y su llegada
+    samples: u64,                 // Muestras recibidas en total
+}
+
+impl SampleClock {
+    /// Registrar un bloque y devolver la muestra absoluta de su inicio
+    pub fn push(&mut self, block: &InputBlock) -> u64 {
+        let start: u64 = self.samples;
+        self.samples += block.samples.len() as u64;
+        self.last = Some((self.samples, block.captured));
+        start
+    }
+
+    pub fn instant_of(&self, sample: u64, sample_rate: u32) -> Option<Instant> {
+        let (end, captured) = self.last?;
+        let behind: f32 = end.saturating_sub(sample) as f32 / sample_rate as f32;
+        captured.checked_sub(Duration::from_secs_f32(behind))
+    }
+}
+
+/// Camino completo del micrófono al juego: recoge el audio y detecta los ataques
+/// con el ruido y la afinación calibrados. La latencia se resta en `MyApp::update`,
+/// igual que en el resto de entradas.
+pub struct MicrophoneListener {
+    input: MicrophoneInput,
+    analyser: StreamingAnalyser,
+    clock: SampleClock,
+}
+
+impl MicrophoneListener {
+    pub fn open(settings: &MicrophoneSettings) -> Result<Self, AudioError> {
+        let input: MicrophoneInput = MicrophoneInput::open()?;
+        let mut analyser: StreamingAnalyser =
+            StreamingAnalyser::new(input.sample_rate(), AnalyserConfig::default());
+        if let Some(threshold) = settings.noise_threshold {
+            analyser.set_onset_threshold(threshold);
+        }
+        analyser.set_a4(settings.a4);
+
+        Ok(MicrophoneListener {
+            input,
+            analyser,
+            clock: SampleClock::default(),
+        })
+    }
+
+    /// Notas oídas desde la última llamada
+    pub fn poll(&mut self) -> Vec<HeardNotes> {
+        let sample_rate: u32 = self.input.sample_rate();
+        let mut heard: Vec<HeardNotes> = Vec::new();
+
+        for block in self.input.drain() {
+            self.clock.push(&block);
+            for onset in self.analyser.push(&block.samples) {
+                let sample: u64 = (onset.time * sample_rate as f32) as u64;
+                if let Some(at) = self.clock.instant_of(sample, sample_rate) {
+                    heard.push(HeardNotes {
+                        at,
+                        pitches: onset.pitches,
+                        strength: onset.strength,
+                    });
+                }
+            }
+        }
+        heard
+    }
+}
@@ -1,144 +1,318 @@
 use {
-    crate::models::{
-        note::Note,
-        partiture::{Hand, Partiture, PieceMetadata},
+    crate::{
+        asset_path,
+        models::{
+            markings::Markings,
+            note::Note,
+            partiture::{Hand, Partiture, PieceMetadata},
+            playback::PlaybackClock,
+            score::{Piece, ScoreFile},
+            score_error::{Diagnostic, ScoreError, ScoreLocation, Severity},
+            settings::CustomSettings,
+        },
+        utils::{
+            fingering::suggest_fingering,
+            legacy::{LegacyFile, convert_legacy_file, validate_legacy_file},
+            validation::{has_errors, validate_piece},
+        },
+    },
+    serde_json::{Value, from_str},
+    std::{
+        collections::BTreeMap,
+        fs::{read_to_string, write},
+        io,
     },
-    serde_json::{Value, from_str, from_value},
-    std::fs::read_to_string,
 };
 
-pub fn load_partiture(file_path: &str) -> Result<Vec<Value>, String> {
-    if file_path.is_empty() {
-        return Err("Ruta del archivo no puede estar vacía".into());
-    }
+/// Lee y parsea un archivo de partituras
+pub fn load_score_file(file_path: &str) -> Result<ScoreFile, ScoreError> {
+    let json_str: String = read_to_string(file_path).map_err(|source| ScoreError::Io {
+        location: ScoreLocation::file(file_path),
+        source,
+    })?;
+
+    from_str(&json_str).map_err(|source| ScoreError::Parse {
+        location: ScoreLocation::file(file_path),
+        source,
+    })
+}
+
+/// Formato en el que estaba escrito un archivo de partituras
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoreFormat {
+    Current, // `partitures.json`, con metadatos y secciones
+    Legacy,  // `notes.json`, con listas planas por mano
+}
+
+/// Archivo leído en cualquiera de los dos formatos, ya convertido al actual
+pub struct LoadedScore {
+    pub score: ScoreFile,
+    pub format: ScoreFormat,
+    pub warnings: Vec<Diagnostic>, // Avisos de la conversión desde el formato antiguo
+}
 
-    let json_str: String = match read_to_string(file_path) {
-        Ok(content) => content,
-        Err(e) => return Err(format!("Error al leer el archivo: {}", e).into()),
+/// Lee un archivo en cualquiera de los dos formatos y lo devuelve en el formato actual
+pub fn load_any_score_file(file_path: &str) -> Result<LoadedScore, ScoreError> {
+    let json_str: String = read_to_string(file_path).map_err(|source| ScoreError::Io {
+        location: ScoreLocation::file(file_path),
+        source,
+    })?;
+
+    // El formato se decide antes de leer, así el error es siempre el del formato del archivo
+    let parse_error = |source: serde_json::Error| ScoreError::Parse {
+        location: ScoreLocation::file(file_path),
+        source,
     };
+    match detect_format(&json_str) {
+        ScoreFormat::Current => Ok(LoadedScore {
+            score: from_str(&json_str).map_err(parse_error)?,
+            format: ScoreFormat::Current,
+            warnings: Vec::new(),
+        }),
+        ScoreFormat::Legacy => {
+            let legacy: LegacyFile = from_str(&json_str).map_err(parse_error)?;
+
+            // Los nombres de nota deben coincidir con los tonos antes de convertir
+            let diagnostics: Vec<Diagnostic> = validate_legacy_file(&legacy)
+                .into_iter()
+                .map(|d| Diagnostic {
+                    location: d.location.with_file(file_path),
+                    issue: d.issue,
+                })
+                .collect();
+            if has_errors(&diagnostics) {
+                return Err(ScoreError::Invalid {
+                    location: ScoreLocation::file(file_path),
+                    diagnostics,
+                });
+            }
+            Ok(LoadedScore {
+                score: convert_legacy_file(&legacy),
+                format: ScoreFormat::Legacy,
+                warnings: diagnostics,
+            })
+        }
+    }
+}
 
-    let data: Value = match from_str(&json_str) {
-        Ok(value) => value,
-        Err(e) => return Err(format!("Error al parsear JSON: {}", e).into()),
+/// Formato de un archivo de partituras según las claves de sus piezas: `right` o `left` en el
+/// antiguo y `metadata` o `sections` en el actual. Lo que no se reconoce cuenta como actual.
+pub fn detect_format(json_str: &str) -> ScoreFormat {
+    let Ok(Value::Array(entries)) = from_str::<Value>(json_str) else {
+        return ScoreFormat::Current;
     };
+    let keys: Vec<&String> = entries
+        .iter()
+        .filter_map(Value::as_object)
+        .flat_map(|entry| entry.values())
+        .filter_map(Value::as_object)
+        .flat_map(|piece| piece.keys())
+        .collect();
 
-    // El JSON ahora es un array de objetos, cada uno con una pieza
-    match data.as_array() {
-        Some(arr) => Ok(arr.clone()),
-        None => Err("El JSON no es un array de partituras".into()),
+    let current: bool = keys
+        .iter()
+        .any(|key| *key == "metadata" || *key == "sections");
+    let legacy: bool = keys.iter().any(|key| *key == "right" || *key == "left");
+    if legacy && !current {
+        ScoreFormat::Legacy
+    } else {
+        ScoreFormat::Current
     }
 }
 
-// Cargar múltiples notas
-pub fn load_notes_from_file(
-    hand: &Hand, // Mano (izquierda o derecha)
-    metadata: &Value,
-    sections: &Value,
-) -> Result<Vec<Note>, Box<dyn std::error::Error>> {
-    // Obtener la estructura desde metadata
-    let structure = metadata
-        .get("structure")
-        .and_then(Value::as_array)
-        .ok_or("Metadata/structure faltante o con formato inválido")?;
-
-    let mut notes: Vec<Note> = Vec::new();
-    for section in structure {
-        // Pasamos a string la seccion actual
-        let label: &str = section
-            .as_str()
-            .ok_or("Cada entrada de structure debe ser string")?;
-
-        // Obtenemos la sección de notas y luego la sub-sección para la mano
-        let section_selected: &Value = sections.get(label).ok_or("Sección no encontrada")?;
-        let selection_by_hand: &Value = section_selected
-            .get(hand.to_string())
-            .ok_or("Sección para esta mano no encontrada")?;
-
-        let mut section_notes: Vec<Note> = from_value(selection_by_hand.clone())?;
-        notes.append(&mut section_notes);
+/// Biblioteca completa: `partitures.json` más las piezas del antiguo `notes.json`
+/// convertidas automáticamente. Si una pieza existe en ambos, se usa la de `partitures.json`.
+pub fn load_library() -> Result<ScoreFile, ScoreError> {
+    let mut library: ScoreFile = load_score_file(&asset_path!("partitures.json"))?;
+
+    match load_any_score_file(&asset_path!("notes.json")) {
+        Ok(legacy) => {
+            for warning in &legacy.warnings {
+                log::warn!("{}", warning);
+            }
+            merge_legacy(&mut library, &legacy.score);
+        }
+        // Un archivo roto se avisa como error, pero no impide usar la biblioteca
+        Err(e @ (ScoreError::Parse { .. } | ScoreError::Invalid { .. })) => log::error!("{}", e),
+        // El archivo antiguo es opcional
+        Err(e) => log::warn!("{}", e),
     }
 
-    Ok(notes)
+    Ok(library)
 }
 
-// Obtiene la metadata y seccion de los datos de la partitura
-pub fn get_metadata_and_section(
-    file_notes: &[Value],
-    piece_name: &str,
-) -> Result<(Value, Value), Box<dyn std::error::Error>> {
-    for piece_obj in file_notes {
-        // Obtenemos la metadata de la pieza
-        if let Some(piece_data) = piece_obj.get(piece_name) {
-            // Obtener y clonar metadata
-            let metadata: Value = piece_data
-                .get("metadata")
-                .ok_or("No se encontró 'metadata'")?
-                .clone();
-
-            // Obtener y clonar sections
-            let sections: Value = piece_data
-                .get("sections")
-                .ok_or("No se encontraron 'sections'")?
-                .clone();
-
-            return Ok((metadata, sections));
+/// Añade a la biblioteca las piezas antiguas que no tiene: si un nombre está en las dos,
+/// gana la de `partitures.json`
+pub fn merge_legacy(library: &mut ScoreFile, legacy: &ScoreFile) {
+    for (name, piece) in legacy.pieces() {
+        if library.piece(name).is_err() {
+            library
+                .0
+                .push(BTreeMap::from([(name.clone(), piece.clone())]));
         }
     }
-
-    Err(format!("No se pudo obtener metadata/sections de '{}'", piece_name).into())
 }
 
-// Obtiene el PieceMetadata de los metadatos
-pub fn get_price_metdata_compas(
-    metadata: Value,
-) -> Result<PieceMetadata, Box<dyn std::error::Error>> {
-    // Obtener duración base de la unidad de nota (f32)
-    let base_note_value: f32 = metadata
-        .get("note_duration_unit_seconds")
-        .and_then(|v| v.as_f64().map(|f| f as f32))
-        .ok_or_else(|| {
-            std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "Error al obtener la duración de la unidad de nota",
-            )
-        })?;
-
-    // Obtener el compás como string, por ejemplo "3/8"
-    let meter_str: &str = metadata
-        .get("meter")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| {
-            std::io::Error::new(std::io::ErrorKind::Other, "Error al obtener el meter")
-        })?;
-
-    // Pasar el string de meter de "3/8" a (u8, u8)
-    let parts: Vec<&str> = meter_str.split('/').collect();
-    if parts.len() != 2 {
-        return Err(std::io::Error::new(
-            std::io::ErrorKind::InvalidData,
-            format!("Meter inválido: '{}'", meter_str),
-        )
-        .into());
+/// Guarda una pieza en `partitures.json`. Si se indica `replaces`, ocupa el lugar de la pieza
+/// con ese nombre (por si se ha renombrado); si no, el de la pieza con el mismo nombre o el final.
+pub fn save_piece(name: &str, piece: &Piece, replaces: Option<&str>) -> Result<(), ScoreError> {
+    let file_path: String = asset_path!("partitures.json");
+    let mut score: ScoreFile = load_score_file(&file_path)?;
+
+    let old_name: &str = replaces.unwrap_or(name);
+    let entry: Option<&mut BTreeMap<String, Piece>> = score
+        .0
+        .iter_mut()
+        .find(|entry| entry.contains_key(old_name) || entry.contains_key(name));
+    match entry {
+        Some(entry) => {
+            entry.remove(old_name);
+            entry.insert(name.to_string(), piece.clone());
+        }
+        None => score
+            .0
+            .push(BTreeMap::from([(name.to_string(), piece.clone())])),
     }
-    let num: u8 = parts[0].parse::<u8>().map_err(|e| {
-        std::io::Error::new(
-            std::io::ErrorKind::InvalidData,
-            format!("Numerador inválido: {}", e),
-        )
-    })?;
-    let den: u8 = parts[1].parse::<u8>().map_err(|e| {
-        std::io::Error::new(
-            std::io::ErrorKind::InvalidData,
-            format!("Denominador inválido: {}", e),
-        )
+
+    let json: String = serde_json::to_string_pretty(&score).map_err(|e| ScoreError::Io {
+        location: ScoreLocation::file(&file_path),
+        source: io::Error::new(io::ErrorKind::InvalidData, e),
     })?;
+    write(&file_path, json + "\n").map_err(|source| ScoreError::Io {
+        location: ScoreLocation::file(&file_path),
+        source,
+    })
+}
 
-    Ok(PieceMetadata {
-        time_signature: (num, den),
-        base_note_value,
+/// Comprueba el nombre y la pieza antes de `save_piece`, como al guardar desde el editor.
+/// El nombre no puede estar vacío ni ser el de otra pieza de la biblioteca; los avisos de la
+/// validación se registran y el primer error impide guardar. El error es el texto para el usuario.
+pub fn store_piece(name: &str, piece: &Piece, replaces: Option<&str>) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("La pieza necesita un nombre".to_string());
+    }
+    if replaces != Some(name) && load_library().is_ok_and(|library| library.piece(name).is_ok()) {
+        return Err(format!("Ya hay una pieza llamada '{}'", name));
+    }
+
+    let diagnostics: Vec<Diagnostic> = validate_piece(name, piece);
+    for diagnostic in diagnostics
+        .iter()
+        .filter(|d| d.severity() == Severity::Warning)
+    {
+        log::warn!("{}", diagnostic);
+    }
+    if let Some(error) = diagnostics.iter().find(|d| d.severity() == Severity::Error) {
+        return Err(format!("No se ha guardado: {}", error));
+    }
+
+    save_piece(name, piece, replaces).map_err(|e| {
+        log::error!("{}", e);
+        format!("No se ha guardado: {}", e)
     })
 }
 
+/// Valida la pieza indicada y devuelve las notas de cada mano (izquierda, derecha)
+/// ya expandidas según `structure`, junto con sus metadatos
+pub fn load_piece_hands(
+    score: &ScoreFile,
+    piece_name: &str,
+) -> Result<(Vec<Note>, Vec<Note>, PieceMetadata), ScoreError> {
+    let piece: &Piece = score.piece(piece_name)?;
+
+    // Solo los errores impiden cargar la pieza, los avisos se registran
+    let diagnostics: Vec<Diagnostic> = validate_piece(piece_name, piece);
+    for diagnostic in diagnostics
+        .iter()
+        .filter(|d| d.severity() == Severity::Warning)
+    {
+        log::warn!("{}", diagnostic);
+    }
+    if has_errors(&diagnostics) {
+        return Err(ScoreError::Invalid {
+            location: ScoreLocation::piece(piece_name),
+            diagnostics,
+        });
+    }
+
+    let mut notes_l: Vec<Note> = piece
+        .expand(&Hand::Left)
+        .map_err(|e| e.in_piece(piece_name))?;
+    let mut notes_r: Vec<Note> = piece
+        .expand(&Hand::Right)
+        .map_err(|e| e.in_piece(piece_name))?;
+
+    // La mano que no trae ninguna digitación se toca con la sugerida
+    for (notes, hand) in [(&mut notes_l, Hand::Left), (&mut notes_r, Hand::Right)] {
+        if notes.iter().all(|note| note.finger.is_none()) {
+            suggest_fingering(notes, hand);
+        }
+    }
+
+    let metadata: PieceMetadata = PieceMetadata {
+        sections: piece.section_starts().map_err(|e| e.in_piece(piece_name))?,
+        ..PieceMetadata::from(&piece.metadata)
+    };
+
+    Ok((notes_l, notes_r, metadata))
+}
+
+/// Crea las partituras de las dos manos (izquierda, derecha) con las notas y las marcas ya cargadas.
+/// La cuenta atrás de los ajustes pasa a ser la cuenta previa en compases de la pieza.
+pub fn build_partitures(
+    notes_l: Vec<Note>,
+    notes_r: Vec<Note>,
+    markings: Markings,
+    piece_metadata: PieceMetadata,
+    settings: &CustomSettings,
+    clock: &PlaybackClock,
+) -> (Partiture, Partiture) {
+    //  Calcular duración total antes de crear Partiture (usar máximo, no el último elemento)
+    let duration_left: f32 = notes_l
+        .iter()
+        .map(|n| n.start + n.duration)
+        .fold(0.0, f32::max);
+    let duration_right: f32 = notes_r
+        .iter()
+        .map(|n| n.start + n.duration)
+        .fold(0.0, f32::max);
+    let total_duration: f32 = duration_left.max(duration_right);
+
+    // La cuenta atrás inicial pasa a ser la cuenta previa en compases de la pieza
+    let partiture_settings: CustomSettings = CustomSettings {
+        timer: settings.count_in_seconds(Some(&piece_metadata)),
+        ..settings.clone()
+    };
+
+    // Crear las partituras con las notas cargadas
+    let mut partiture_l: Partiture = Partiture::new(
+        Hand::Left,
+        notes_l,
+        markings.clone(),
+        total_duration,
+        clock.clone(),
+        partiture_settings.clone(),
+    );
+    let mut partiture_r: Partiture = Partiture::new(
+        Hand::Right,
+        notes_r,
+        markings,
+        total_duration,
+        clock.clone(),
+        partiture_settings,
+    );
+
+    // Damos los valores de metadata a las partituras para que lo tengan en cuenta a ala hora de dibujar compases velocidad etc
+    (partiture_r.metadata, partiture_l.metadata) =
+        (Some(piece_metadata.clone()), Some(piece_metadata));
+
+    // Sanitizar notas con los datos necesarios y asignar los datos a las partituras
+    sanitize_data(&mut partiture_r);
+    sanitize_data(&mut partiture_l);
+
+    (partiture_l, partiture_r)
+}
+
 // En basae a la informacion añadimos todo lo necesairo que devemos cambiar mediante el código,
 // por ejemplo cuando se juntan las notas
 pub fn sanitize_data(partiture: &mut Partiture) {
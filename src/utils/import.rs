@@ -0,0 +1,100 @@
+use {
+    crate::{
+        midi::import::smf_to_section,
+        models::{
+            score::{FLAT_SECTION, Meter, Piece, ScoreMetadata, Section},
+            score_error::{ScoreError, ScoreLocation},
+        },
+        utils::musicxml::musicxml_to_section,
+    },
+    std::{collections::BTreeMap, fs, io, path::Path},
+};
+
+/// Formatos de los que se pueden importar piezas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    Midi,
+    MusicXml,
+}
+
+impl ImportFormat {
+    // Formato según la extensión del archivo
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension: String = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "mid" | "midi" => Some(ImportFormat::Midi),
+            "musicxml" | "xml" => Some(ImportFormat::MusicXml),
+            _ => None,
+        }
+    }
+}
+
+/// Lo que se lee de un archivo importado antes de convertirlo en pieza
+pub struct ImportedScore {
+    pub title: Option<String>,
+    pub composer: Option<String>,
+    pub key: Option<String>,
+    pub meter: Meter,
+    pub quarter_seconds: f32, // Duración de una negra con el primer tempo del archivo
+    pub section: Section,
+}
+
+/// Lee un archivo MIDI o MusicXML (sin comprimir) y lo convierte en una pieza de una sola sección.
+/// Devuelve también el nombre de la pieza, que es el del archivo sin extensión.
+pub fn import_score_file(file_path: &str) -> Result<(String, Piece), ScoreError> {
+    let path: &Path = Path::new(file_path);
+    // Un archivo que no se puede convertir se trata como uno que no se puede leer
+    let failed = |message: String| ScoreError::Io {
+        location: ScoreLocation::file(file_path),
+        source: io::Error::new(io::ErrorKind::InvalidData, message),
+    };
+
+    let format: ImportFormat = ImportFormat::from_path(path).ok_or_else(|| {
+        failed("se esperaba un archivo .mid, .midi, .musicxml o .xml".to_string())
+    })?;
+    let bytes: Vec<u8> = fs::read(path).map_err(|source| ScoreError::Io {
+        location: ScoreLocation::file(file_path),
+        source,
+    })?;
+
+    let imported: ImportedScore = match format {
+        ImportFormat::Midi => smf_to_section(&bytes),
+        ImportFormat::MusicXml => String::from_utf8(bytes)
+            .map_err(|e| format!("el archivo no es UTF-8: {}", e))
+            .and_then(|text| musicxml_to_section(&text)),
+    }
+    .map_err(failed)?;
+
+    let name: String = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("importada")
+        .to_string();
+    let source: Option<String> = path
+        .file_name()
+        .and_then(|file| file.to_str())
+        .map(str::to_string);
+
+    Ok((name.clone(), imported_piece(name, source, imported)))
+}
+
+// Pieza con una única sección, con el tempo como negras por minuto
+fn imported_piece(name: String, source: Option<String>, imported: ImportedScore) -> Piece {
+    let bpm: f32 = 60.0 / imported.quarter_seconds.max(f32::EPSILON);
+    Piece {
+        metadata: ScoreMetadata {
+            title: imported.title.unwrap_or(name),
+            composer: imported.composer,
+            source,
+            meter: imported.meter,
+            tempo: Some(format!("{} bpm", bpm.round())),
+            key: imported.key,
+            note_duration_unit_seconds: imported.quarter_seconds * 4.0
+                / imported.meter.beat_unit as f32,
+            structure: vec![FLAT_SECTION.to_string()],
+            playback_instructions: None,
+            exercise: false,
+        },
+        sections: BTreeMap::from([(FLAT_SECTION.to_string(), imported.section)]),
+    }
+}
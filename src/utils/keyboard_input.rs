@@ -0,0 +1,174 @@
+use {
+    crate::{
+        message::states::{AppMessage, CaptureMessage, EditorMessage},
+        models::{
+            input::{InputAction, TimedInput},
+            take::InputSource,
+        },
+        utils::sight_reading::NoteValue,
+    },
+    iced::{
+        Event,
+        event::Status,
+        keyboard::{self, Key, Modifiers, key::Named},
+        window,
+    },
+    std::time::Instant,
+};
+
+// Velocidad fija para las notas tocadas con el teclado del ordenador
+pub const KEYBOARD_VELOCITY: u8 = 80;
+
+/// Tono MIDI asociado a una tecla del ordenador, al estilo de un piano:
+/// la fila central (a s d f ...) son las teclas blancas desde el Do4,
+/// la fila superior (w e t y u ...) las negras, y la fila inferior (z x c ...)
+/// las teclas blancas de la octava de abajo.
+pub fn key_to_pitch(key: &Key) -> Option<u8> {
+    let Key::Character(c) = key else {
+        return None;
+    };
+
+    let pitch: u8 = match c.to_lowercase().as_str() {
+        // Octava del Do3 (solo teclas blancas)
+        "z" => 48,
+        "x" => 50,
+        "c" => 52,
+        "v" => 53,
+        "b" => 55,
+        "n" => 57,
+        "m" => 59,
+        // Octava del Do4
+        "a" => 60,
+        "w" => 61,
+        "s" => 62,
+        "e" => 63,
+        "d" => 64,
+        "f" => 65,
+        "t" => 66,
+        "g" => 67,
+        "y" => 68,
+        "h" => 69,
+        "u" => 70,
+        "j" => 71,
+        "k" => 72,
+        "o" => 73,
+        "l" => 74,
+        "p" => 75,
+        ";" | "ñ" => 76,
+        _ => return None,
+    };
+    Some(pitch)
+}
+
+/// Traduce los eventos de la ventana durante la partida. Las teclas de notas se sellan
+/// con el momento en que llegan, antes de esperar al siguiente tick.
+pub fn timed_event(event: Event, _status: Status, _window: window::Id) -> Option<AppMessage> {
+    let action: Option<InputAction> = match &event {
+        Event::Keyboard(keyboard::Event::KeyPressed { key, .. }) => {
+            key_to_pitch(key).map(|pitch| InputAction::NoteOn {
+                pitch,
+                velocity: KEYBOARD_VELOCITY,
+            })
+        }
+        Event::Keyboard(keyboard::Event::KeyReleased { key, .. }) => {
+            key_to_pitch(key).map(|pitch| InputAction::NoteOff { pitch })
+        }
+        _ => None,
+    };
+
+    Some(match action {
+        Some(action) => AppMessage::Input(TimedInput::new(
+            Instant::now(),
+            InputSource::Keyboard,
+            action,
+        )),
+        None => AppMessage::Event(event),
+    })
+}
+
+/// Durante la prueba de latencia cualquier tecla es una pulsación
+pub fn tap_event(event: Event, _status: Status, _window: window::Id) -> Option<AppMessage> {
+    match event {
+        Event::Keyboard(keyboard::Event::KeyPressed { .. }) => Some(AppMessage::Input(
+            TimedInput::new(Instant::now(), InputSource::Keyboard, InputAction::Tap),
+        )),
+        _ => None,
+    }
+}
+
+/// Durante la grabación las teclas de piano son notas, Tab cambia de canal y Escape para
+pub fn capture_event(event: Event, status: Status, window: window::Id) -> Option<AppMessage> {
+    match &event {
+        Event::Keyboard(keyboard::Event::KeyPressed {
+            key: Key::Named(Named::Tab),
+            ..
+        }) => Some(AppMessage::Capture(CaptureMessage::Channel)),
+        Event::Keyboard(keyboard::Event::KeyPressed {
+            key: Key::Named(Named::Escape),
+            ..
+        }) => Some(AppMessage::Capture(CaptureMessage::Stop)),
+        _ => match timed_event(event, status, window) {
+            Some(AppMessage::Input(input)) => Some(AppMessage::Input(input)),
+            _ => None,
+        },
+    }
+}
+
+/// Atajos del editor de partituras. Las teclas que recoge un campo de texto no cuentan.
+pub fn editor_event(event: Event, status: Status, _window: window::Id) -> Option<AppMessage> {
+    let Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) = event else {
+        return None;
+    };
+    if status == Status::Captured {
+        return None;
+    }
+    editor_shortcut(&key, modifiers).map(AppMessage::Editor)
+}
+
+// Acción del editor de una tecla con sus modificadores
+fn editor_shortcut(key: &Key, modifiers: Modifiers) -> Option<EditorMessage> {
+    if modifiers.command() {
+        let Key::Character(c) = key else {
+            return None;
+        };
+        return match c.to_lowercase().as_str() {
+            "z" if modifiers.shift() => Some(EditorMessage::Redo),
+            "z" => Some(EditorMessage::Undo),
+            "y" => Some(EditorMessage::Redo),
+            "s" => Some(EditorMessage::Save),
+            _ => None,
+        };
+    }
+
+    match key {
+        Key::Named(named) => match named {
+            Named::ArrowLeft => Some(EditorMessage::Move(-1)),
+            Named::ArrowRight => Some(EditorMessage::Move(1)),
+            Named::ArrowUp => Some(EditorMessage::Transpose(1)),
+            Named::ArrowDown => Some(EditorMessage::Transpose(-1)),
+            Named::PageUp => Some(EditorMessage::Octave(1)),
+            Named::PageDown => Some(EditorMessage::Octave(-1)),
+            Named::Backspace => Some(EditorMessage::DeletePrevious),
+            Named::Delete => Some(EditorMessage::Delete),
+            Named::Tab => Some(EditorMessage::ToggleHand),
+            _ => None,
+        },
+        Key::Character(c) => match c.to_lowercase().as_str() {
+            "r" => Some(EditorMessage::Rest),
+            "." => Some(EditorMessage::Dot),
+            "1" => Some(EditorMessage::Value(NoteValue::Whole)),
+            "2" => Some(EditorMessage::Value(NoteValue::Half)),
+            "3" => Some(EditorMessage::Value(NoteValue::Quarter)),
+            "4" => Some(EditorMessage::Value(NoteValue::Eighth)),
+            "5" => Some(EditorMessage::Value(NoteValue::Sixteenth)),
+            _ => key_to_pitch(key).map(|pitch| {
+                if modifiers.shift() {
+                    EditorMessage::Chord(pitch)
+                } else {
+                    EditorMessage::Note(pitch)
+                }
+            }),
+        },
+        _ => None,
+    }
+}
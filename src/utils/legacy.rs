@@ -0,0 +1,158 @@
+use {
+    crate::{
+        models::{
+            note::Note,
+            partiture::Hand,
+            score::{Meter, Piece, ScoreFile, ScoreMetadata, Section},
+            score_error::{Diagnostic, Issue, ScoreLocation},
+        },
+        utils::note_names::parse_note_name,
+    },
+    serde::{Deserialize, Serialize},
+    std::collections::BTreeMap,
+};
+
+// Sección única en la que se guardan las notas de las piezas antiguas
+pub const LEGACY_SECTION: &str = "A";
+
+/// Formato antiguo de `notes.json`: `[{ "nombre": { "right": [...], "left": [...] } }]`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LegacyFile(pub Vec<BTreeMap<String, LegacyPiece>>);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LegacyPiece {
+    #[serde(default)]
+    pub right: Vec<LegacyNote>,
+    #[serde(default)]
+    pub left: Vec<LegacyNote>,
+}
+
+/// Nota del formato antiguo, con el nombre además del número MIDI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegacyNote {
+    pub name: String, // Ej: "D#5"
+    pub start: f32,
+    pub pitch: u8,
+    pub duration: f32,
+}
+
+impl LegacyPiece {
+    fn notes(&self, hand: &Hand) -> &[LegacyNote] {
+        match hand {
+            Hand::Left => &self.left,
+            Hand::Right => &self.right,
+        }
+    }
+}
+
+impl From<&LegacyNote> for Note {
+    fn from(legacy: &LegacyNote) -> Self {
+        Note {
+            start: legacy.start,
+            ..Note::new(legacy.pitch, legacy.duration, false, Default::default())
+        }
+    }
+}
+
+/// Comprueba que el nombre de cada nota ("D#5") coincide con su número MIDI, y avisa de que
+/// cada pieza se convertirá con el compás y el tempo por defecto
+pub fn validate_legacy_file(legacy: &LegacyFile) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+
+    for (name, piece) in legacy.0.iter().flat_map(|entry| entry.iter()) {
+        diagnostics.push(Diagnostic {
+            location: ScoreLocation {
+                piece: Some(name.clone()),
+                ..ScoreLocation::default()
+            },
+            issue: Issue::AssumedTiming,
+        });
+        for hand in [Hand::Right, Hand::Left] {
+            for (i, note) in piece.notes(&hand).iter().enumerate() {
+                let location: ScoreLocation = ScoreLocation {
+                    piece: Some(name.clone()),
+                    hand: Some(hand),
+                    note: Some(i),
+                    ..ScoreLocation::default()
+                };
+
+                match parse_note_name(&note.name) {
+                    None => diagnostics.push(Diagnostic {
+                        location,
+                        issue: Issue::InvalidNoteName(note.name.clone()),
+                    }),
+                    Some(expected) if expected != note.pitch => diagnostics.push(Diagnostic {
+                        location,
+                        issue: Issue::NameMismatch {
+                            name: note.name.clone(),
+                            pitch: note.pitch,
+                            expected,
+                        },
+                    }),
+                    Some(_) => {}
+                }
+            }
+        }
+    }
+
+    diagnostics
+}
+
+/// Convierte una pieza antigua al formato actual con una única sección.
+/// El formato antiguo no guarda compás ni tempo, así que se usan 4/4 y 1 segundo por unidad
+/// (`validate_legacy_file` avisa de ello).
+pub fn convert_legacy_piece(name: &str, legacy: &LegacyPiece) -> Piece {
+    let section: Section = Section {
+        start_time: 0.0,
+        right: legacy.right.iter().map(Note::from).collect(),
+        left: legacy.left.iter().map(Note::from).collect(),
+        ..Section::default()
+    };
+
+    Piece {
+        metadata: ScoreMetadata {
+            title: title_from_name(name),
+            composer: None,
+            source: Some("notes.json".to_string()),
+            meter: Meter {
+                beats: 4,
+                beat_unit: 4,
+            },
+            tempo: None,
+            key: None,
+            note_duration_unit_seconds: 1.0,
+            structure: vec![LEGACY_SECTION.to_string()],
+            playback_instructions: None,
+            exercise: false,
+        },
+        sections: BTreeMap::from([(LEGACY_SECTION.to_string(), section)]),
+    }
+}
+
+/// Convierte un archivo antiguo completo al formato actual
+pub fn convert_legacy_file(legacy: &LegacyFile) -> ScoreFile {
+    ScoreFile(
+        legacy
+            .0
+            .iter()
+            .map(|entry| {
+                entry
+                    .iter()
+                    .map(|(name, piece)| (name.clone(), convert_legacy_piece(name, piece)))
+                    .collect()
+            })
+            .collect(),
+    )
+}
+
+// "where-is-my-mind" -> "Where is my mind"
+fn title_from_name(name: &str) -> String {
+    let spaced: String = name.replace('-', " ");
+    let mut chars = spaced.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,394 @@
+use {
+    crate::{
+        models::{
+            markings::{
+                Articulation, Dynamic, DynamicMark, Hairpin, HairpinKind, Markings, Pedal, Slur,
+            },
+            note::Note,
+            partiture::Hand,
+            score::{Meter, Section},
+        },
+        utils::import::ImportedScore,
+    },
+    roxmltree::{Document, Node, ParsingOptions},
+    std::collections::HashMap,
+};
+
+// Tempo de una partitura sin indicación de tempo: 120 negras por minuto
+const DEFAULT_QUARTER_SECONDS: f32 = 0.5;
+
+// Tonalidades mayores y menores según el número de alteraciones, de 7 bemoles a 7 sostenidos
+const MAJOR_KEYS: [&str; 15] = [
+    "Cb", "Gb", "Db", "Ab", "Eb", "Bb", "F", "C", "G", "D", "A", "E", "B", "F#", "C#",
+];
+const MINOR_KEYS: [&str; 15] = [
+    "Ab", "Eb", "Bb", "F", "C", "G", "D", "A", "E", "B", "F#", "C#", "G#", "D#", "A#",
+];
+
+// Estado de la lectura de una parte, con las posiciones en negras desde el principio
+#[derive(Default)]
+struct PartReader {
+    divisions: f32,                      // Divisiones por negra
+    position: f32,                       // Posición actual
+    last_start: f32,                     // Inicio de la última nota, para los acordes
+    slurs: HashMap<(Hand, String), f32>, // Ligaduras abiertas por mano y número
+    wedge: Option<(f32, HairpinKind)>,   // Regulador abierto
+    pedal: Option<f32>,                  // Pedal pisado desde
+    right: Vec<Note>,                    // Notas en negras, se pasan a segundos al final
+    left: Vec<Note>,
+    markings: Markings,
+}
+
+/// Lee un MusicXML sin comprimir (`score-partwise`). Con una parte de dos pentagramas el primero
+/// es la mano derecha y el segundo la izquierda; si no, la primera parte es la derecha y la
+/// segunda la izquierda. Además de las notas se leen los matices, los reguladores, el staccato y
+/// los acentos, las ligaduras de expresión y el pedal. Las notas ligadas se unen en una.
+pub fn musicxml_to_section(text: &str) -> Result<ImportedScore, String> {
+    // Los programas de notación escriben siempre el DOCTYPE de MusicXML
+    let options: ParsingOptions = ParsingOptions {
+        allow_dtd: true,
+        ..ParsingOptions::default()
+    };
+    let document: Document =
+        Document::parse_with_options(text, options).map_err(|e| format!("XML inválido: {}", e))?;
+    let root: Node = document.root_element();
+    if root.tag_name().name() != "score-partwise" {
+        return Err(format!(
+            "se esperaba <score-partwise> y el archivo empieza por <{}>",
+            root.tag_name().name()
+        ));
+    }
+
+    let title: Option<String> =
+        find_text(root, &["work", "work-title"]).or_else(|| find_text(root, &["movement-title"]));
+    let composer: Option<String> = child(root, "identification").and_then(|identification| {
+        children(identification, "creator")
+            .find(|creator| creator.attribute("type") == Some("composer"))
+            .and_then(|creator| creator.text())
+            .map(|text| text.trim().to_string())
+    });
+
+    // Compás, tonalidad y tempo se toman de su primera aparición
+    let meter: Meter = root
+        .descendants()
+        .find(|node| node.has_tag_name("time"))
+        .and_then(|time| {
+            let beats: u8 = find_text(time, &["beats"])?.parse().ok()?;
+            let beat_unit: u8 = find_text(time, &["beat-type"])?.parse().ok()?;
+            (beats > 0 && beat_unit.is_power_of_two()).then_some(Meter { beats, beat_unit })
+        })
+        .unwrap_or(Meter {
+            beats: 4,
+            beat_unit: 4,
+        });
+    let key: Option<String> = root
+        .descendants()
+        .find(|node| node.has_tag_name("key"))
+        .and_then(key_name);
+    let quarter_seconds: f32 = root
+        .descendants()
+        .filter(|node| node.has_tag_name("sound"))
+        .find_map(|sound| sound.attribute("tempo")?.parse::<f32>().ok())
+        .filter(|bpm| *bpm > 0.0)
+        .map_or(DEFAULT_QUARTER_SECONDS, |bpm| 60.0 / bpm);
+
+    let parts: Vec<Node> = children(root, "part").collect();
+    if parts.is_empty() {
+        return Err("el archivo no tiene ninguna parte".to_string());
+    }
+    let grand_staff: bool = parts[0]
+        .descendants()
+        .find(|node| node.has_tag_name("staves"))
+        .and_then(|staves| staves.text())
+        .is_some_and(|staves| staves.trim() != "1");
+
+    let mut section: Section = Section::default();
+    let mut markings: Markings = Markings::default();
+    let used: usize = if grand_staff { 1 } else { 2 };
+    for (index, part) in parts.iter().take(used).enumerate() {
+        let default_hand: Hand = if index == 0 { Hand::Right } else { Hand::Left };
+        let reader: PartReader = read_part(*part, grand_staff, default_hand);
+        section.right.extend(reader.right);
+        section.left.extend(reader.left);
+        markings.append(reader.markings);
+    }
+
+    // Los matices repetidos en las dos partes solo se quedan una vez
+    markings
+        .dynamics
+        .sort_by(|a, b| a.start.total_cmp(&b.start));
+    markings
+        .dynamics
+        .dedup_by(|a, b| (a.start - b.start).abs() < 1e-4);
+
+    // De negras a segundos, en orden aunque las voces vinieran por separado
+    for notes in [&mut section.right, &mut section.left] {
+        notes.sort_by(|a, b| a.start.total_cmp(&b.start).then(a.pitch.cmp(&b.pitch)));
+    }
+    for note in section.right.iter_mut().chain(section.left.iter_mut()) {
+        note.start *= quarter_seconds;
+        note.duration *= quarter_seconds;
+    }
+    section.markings = markings.scaled(quarter_seconds);
+    if section.right.is_empty() && section.left.is_empty() {
+        return Err("el archivo no tiene notas".to_string());
+    }
+
+    Ok(ImportedScore {
+        title,
+        composer,
+        key,
+        meter,
+        quarter_seconds,
+        section,
+    })
+}
+
+// Notas y marcas de una parte, compás a compás
+fn read_part(part: Node, grand_staff: bool, default_hand: Hand) -> PartReader {
+    let mut reader: PartReader = PartReader {
+        divisions: 1.0,
+        ..PartReader::default()
+    };
+    let hand_of = |node: Node| -> Hand {
+        match find_text(node, &["staff"]).as_deref() {
+            Some("2") if grand_staff => Hand::Left,
+            _ if grand_staff => Hand::Right,
+            _ => default_hand,
+        }
+    };
+
+    for measure in children(part, "measure") {
+        let measure_start: f32 = reader.position;
+        let mut measure_end: f32 = reader.position;
+
+        for element in measure.children().filter(Node::is_element) {
+            match element.tag_name().name() {
+                "attributes" => {
+                    if let Some(divisions) = find_text(element, &["divisions"])
+                        .and_then(|text| text.parse::<f32>().ok())
+                        .filter(|divisions| *divisions > 0.0)
+                    {
+                        reader.divisions = divisions;
+                    }
+                }
+                "backup" => reader.position -= reader.duration(element),
+                "forward" => reader.position += reader.duration(element),
+                "note" => reader.read_note(element, hand_of(element)),
+                "direction" => reader.read_direction(element),
+                _ => {}
+            }
+            measure_end = measure_end.max(reader.position);
+        }
+
+        // El siguiente compás empieza donde acaba la voz más larga
+        reader.position = measure_end.max(measure_start);
+    }
+
+    // Lo que queda abierto al final se cierra con la pieza
+    let end: f32 = reader.position;
+    if let Some((start, kind)) = reader.wedge.take() {
+        reader.markings.hairpins.push(Hairpin { start, end, kind });
+    }
+    if let Some(start) = reader.pedal.take() {
+        reader.markings.pedal.push(Pedal { start, end });
+    }
+    reader
+}
+
+impl PartReader {
+    // Duración de un elemento en negras
+    fn duration(&self, element: Node) -> f32 {
+        find_text(element, &["duration"])
+            .and_then(|text| text.parse::<f32>().ok())
+            .unwrap_or(0.0)
+            / self.divisions
+    }
+
+    fn read_note(&mut self, element: Node, hand: Hand) {
+        // Las notas de adorno no ocupan tiempo y no se tocan en el juego
+        if child(element, "grace").is_some() {
+            return;
+        }
+        let duration: f32 = self.duration(element);
+        let start: f32 = if child(element, "chord").is_some() {
+            self.last_start
+        } else {
+            self.position
+        };
+        if child(element, "chord").is_none() {
+            self.position += duration;
+        }
+        self.last_start = start;
+
+        let notations: Vec<Node> = children(element, "notations").collect();
+        for slur in notations
+            .iter()
+            .flat_map(|notations| children(*notations, "slur"))
+        {
+            let number: String = slur.attribute("number").unwrap_or("1").to_string();
+            match slur.attribute("type") {
+                Some("start") => {
+                    self.slurs.insert((hand, number), start);
+                }
+                Some("stop") => {
+                    if let Some(from) = self.slurs.remove(&(hand, number)) {
+                        self.markings.slurs.push(Slur {
+                            hand,
+                            start: from,
+                            end: start,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let Some(pitch) = child(element, "pitch").and_then(midi_pitch) else {
+            return; // Silencio
+        };
+
+        let notes: &mut Vec<Note> = match hand {
+            Hand::Right => &mut self.right,
+            Hand::Left => &mut self.left,
+        };
+
+        // Una nota ligada a la anterior del mismo tono solo la alarga
+        let tied: bool = children(element, "tie").any(|tie| tie.attribute("type") == Some("stop"));
+        if tied
+            && let Some(previous) = notes.iter_mut().rev().find(|note| {
+                note.pitch == pitch && (note.start + note.duration - start).abs() < 1e-3
+            })
+        {
+            previous.duration += duration;
+            return;
+        }
+
+        let articulations: Vec<Articulation> = notations
+            .iter()
+            .flat_map(|notations| children(*notations, "articulations"))
+            .flat_map(|articulations| articulations.children().filter(Node::is_element))
+            .filter_map(|articulation| match articulation.tag_name().name() {
+                "staccato" | "staccatissimo" => Some(Articulation::Staccato),
+                "accent" | "strong-accent" => Some(Articulation::Accent),
+                _ => None,
+            })
+            .collect();
+
+        notes.push(Note {
+            start,
+            articulations,
+            ..Note::new(pitch, duration, false, Default::default())
+        });
+    }
+
+    // Matices, reguladores y pedal, que valen para las dos manos
+    fn read_direction(&mut self, element: Node) {
+        // El desplazamiento va en divisiones desde la posición actual
+        let at: f32 = self.position
+            + find_text(element, &["offset"])
+                .and_then(|text| text.parse::<f32>().ok())
+                .unwrap_or(0.0)
+                / self.divisions;
+
+        for kind in children(element, "direction-type")
+            .flat_map(|direction| direction.children().filter(Node::is_element))
+        {
+            match kind.tag_name().name() {
+                "dynamics" => {
+                    let level: Option<Dynamic> = kind
+                        .children()
+                        .filter(Node::is_element)
+                        .find_map(|dynamic| Dynamic::from_name(dynamic.tag_name().name()));
+                    if let Some(level) = level {
+                        self.markings
+                            .dynamics
+                            .push(DynamicMark { start: at, level });
+                    }
+                }
+                "wedge" => {
+                    let kind: Option<HairpinKind> = match kind.attribute("type") {
+                        Some("crescendo") => Some(HairpinKind::Crescendo),
+                        Some("diminuendo") => Some(HairpinKind::Diminuendo),
+                        _ => None,
+                    };
+                    // Un regulador nuevo cierra el anterior
+                    if let Some((start, open)) = self.wedge.take() {
+                        self.markings.hairpins.push(Hairpin {
+                            start,
+                            end: at,
+                            kind: open,
+                        });
+                    }
+                    self.wedge = kind.map(|kind| (at, kind));
+                }
+                "pedal" => {
+                    let kind: Option<&str> = kind.attribute("type");
+                    if matches!(kind, Some("stop" | "change"))
+                        && let Some(start) = self.pedal.take()
+                    {
+                        self.markings.pedal.push(Pedal { start, end: at });
+                    }
+                    if matches!(kind, Some("start" | "change")) {
+                        self.pedal = Some(at);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+// Número MIDI de un elemento <pitch>
+fn midi_pitch(pitch: Node) -> Option<u8> {
+    let step: i32 = match find_text(pitch, &["step"])?.as_str() {
+        "C" => 0,
+        "D" => 2,
+        "E" => 4,
+        "F" => 5,
+        "G" => 7,
+        "A" => 9,
+        "B" => 11,
+        _ => return None,
+    };
+    let alter: i32 = find_text(pitch, &["alter"])
+        .and_then(|text| text.parse::<f32>().ok())
+        .map_or(0, |alter| alter.round() as i32);
+    let octave: i32 = find_text(pitch, &["octave"])?.parse().ok()?;
+    u8::try_from((octave + 1) * 12 + step + alter).ok()
+}
+
+// Tonalidad de un elemento <key>, como "A minor"
+fn key_name(key: Node) -> Option<String> {
+    let fifths: i32 = find_text(key, &["fifths"])?.parse().ok()?;
+    let index: usize = usize::try_from(fifths + 7).ok()?;
+    match find_text(key, &["mode"]).as_deref() {
+        Some("minor") => MINOR_KEYS
+            .get(index)
+            .map(|tonic| format!("{} minor", tonic)),
+        _ => MAJOR_KEYS
+            .get(index)
+            .map(|tonic| format!("{} major", tonic)),
+    }
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(name))
+}
+
+fn children<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children()
+        .filter(move |child| child.has_tag_name(name))
+}
+
+// Texto del elemento al final de la ruta de hijos indicada
+fn find_text(node: Node, path: &[&str]) -> Option<String> {
+    let mut current: Node = node;
+    for name in path {
+        current = child(current, name)?;
+    }
+    current.text().map(|text| text.trim().to_string())
+}
@@ -0,0 +1,41 @@
+// Nombres de las 12 notas usando sostenidos
+const SHARP_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// Nombre científico de un tono MIDI, con sostenidos (60 -> "C4", 75 -> "D#5")
+pub fn note_name(pitch: u8) -> String {
+    let octave: i32 = pitch as i32 / 12 - 1;
+    format!("{}{}", SHARP_NAMES[pitch as usize % 12], octave)
+}
+
+/// Tono MIDI a partir de un nombre como "C4", "D#5", "Bb3" o "F##2".
+/// Devuelve `None` si el nombre no es válido o queda fuera del rango MIDI.
+pub fn parse_note_name(name: &str) -> Option<u8> {
+    let name: &str = name.trim();
+    let mut chars = name.chars();
+
+    let base: i32 = match chars.next()?.to_ascii_uppercase() {
+        'C' => 0,
+        'D' => 2,
+        'E' => 4,
+        'F' => 5,
+        'G' => 7,
+        'A' => 9,
+        'B' => 11,
+        _ => return None,
+    };
+
+    // Alteraciones: '#' sube un semitono, 'b' lo baja
+    let rest: &str = chars.as_str();
+    let accidentals: usize = rest.find(|c: char| c != '#' && c != 'b' && c != '♯' && c != '♭')?;
+    let alteration: i32 = rest[..accidentals]
+        .chars()
+        .map(|c| if c == '#' || c == '♯' { 1 } else { -1 })
+        .sum();
+
+    let octave: i32 = rest[accidentals..].parse().ok()?;
+    let pitch: i32 = (octave + 1) * 12 + base + alteration;
+
+    u8::try_from(pitch).ok().filter(|p| *p <= 127)
+}
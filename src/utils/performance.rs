@@ -0,0 +1,157 @@
+use crate::models::{
+    note::Note,
+    partiture::Hand,
+    take::{JudgedNote, Judgement, PlayedNote, Take},
+};
+
+// Margen en segundos para considerar una nota a tiempo
+pub const ON_TIME_WINDOW: f32 = 0.08;
+
+// Distancia máxima en segundos para emparejar una nota tocada con la esperada
+pub const MATCH_WINDOW: f32 = 0.35;
+
+/// Resumen de una interpretación
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PerformanceSummary {
+    pub on_time: usize,
+    pub early: usize,
+    pub late: usize,
+    pub wrong: usize,
+    pub missed: usize, // Notas esperadas que no se tocaron
+    pub accuracy: f32, // 0.0 a 1.0
+}
+
+/// Empareja cada nota tocada con la nota esperada más cercana del mismo tono
+/// que aún no se haya usado, y la valora según su desfase
+pub fn judge_take(take: &Take, right: &[Note], left: &[Note]) -> Vec<JudgedNote> {
+    let mut used_r: Vec<bool> = vec![false; right.len()];
+    let mut used_l: Vec<bool> = vec![false; left.len()];
+
+    let mut played: Vec<&PlayedNote> = take.notes.iter().collect();
+    played.sort_by(|a, b| a.on.total_cmp(&b.on));
+
+    played
+        .into_iter()
+        .map(|note| {
+            let candidate_r = closest(note, right, &used_r).map(|(i, d)| (Hand::Right, i, d));
+            let candidate_l = closest(note, left, &used_l).map(|(i, d)| (Hand::Left, i, d));
+
+            let best = match (candidate_r, candidate_l) {
+                (Some(r), Some(l)) => Some(if r.2.abs() <= l.2.abs() { r } else { l }),
+                (r, l) => r.or(l),
+            };
+
+            match best {
+                Some((hand, index, offset)) => {
+                    match hand {
+                        Hand::Right => used_r[index] = true,
+                        Hand::Left => used_l[index] = true,
+                    }
+                    JudgedNote {
+                        played: *note,
+                        judgement: judgement_for(offset),
+                        offset,
+                        expected: Some((hand, index)),
+                    }
+                }
+                None => JudgedNote {
+                    played: *note,
+                    judgement: Judgement::Wrong,
+                    offset: 0.0,
+                    expected: None,
+                },
+            }
+        })
+        .collect()
+}
+
+/// Notas que hay que tocar con las dos manos: todas menos los silencios
+pub fn expected_notes(right: &[Note], left: &[Note]) -> usize {
+    right
+        .iter()
+        .chain(left)
+        .filter(|note| note.pitch != 0 && !note.is_rest)
+        .count()
+}
+
+/// Cuenta las valoraciones y calcula la precisión.
+/// Las notas adelantadas o retrasadas cuentan como media nota acertada.
+pub fn summarize(judged: &[JudgedNote], expected_total: usize) -> PerformanceSummary {
+    let mut summary: PerformanceSummary = PerformanceSummary::default();
+    for note in judged {
+        match note.judgement {
+            Judgement::OnTime => summary.on_time += 1,
+            Judgement::Early => summary.early += 1,
+            Judgement::Late => summary.late += 1,
+            Judgement::Wrong => summary.wrong += 1,
+        }
+    }
+
+    let matched: usize = summary.on_time + summary.early + summary.late;
+    summary.missed = expected_total.saturating_sub(matched);
+
+    let attempts: usize = expected_total + summary.wrong;
+    if attempts > 0 {
+        let score: f32 = summary.on_time as f32 + 0.5 * (summary.early + summary.late) as f32;
+        summary.accuracy = (score / attempts as f32).clamp(0.0, 1.0);
+    }
+    summary
+}
+
+/// Regularidad de una interpretación: desviación típica, en segundos, de la diferencia entre
+/// el tiempo que pasó entre dos notas seguidas de la misma mano y el que estaba escrito.
+/// Solo cuentan las parejas de notas esperadas consecutivas que se tocaron las dos.
+/// `None` si no hay al menos dos intervalos que comparar.
+pub fn evenness(judged: &[JudgedNote], right: &[Note], left: &[Note]) -> Option<f32> {
+    let mut deviations: Vec<f32> = Vec::new();
+
+    for (hand, notes) in [(Hand::Right, right), (Hand::Left, left)] {
+        let mut onsets: Vec<(usize, f32)> = judged
+            .iter()
+            .filter_map(|note| match note.expected {
+                Some((h, index)) if h == hand => Some((index, note.played.on)),
+                _ => None,
+            })
+            .collect();
+        onsets.sort_by_key(|(index, _)| *index);
+
+        for pair in onsets.windows(2) {
+            let ((a, on_a), (b, on_b)) = (pair[0], pair[1]);
+            let written: f32 = notes[b].start - notes[a].start;
+            // Notas a la vez (acordes) o con otras en medio que no se tocaron
+            if b != a + 1 || written <= 0.0 {
+                continue;
+            }
+            deviations.push((on_b - on_a) - written);
+        }
+    }
+
+    if deviations.len() < 2 {
+        return None;
+    }
+    let mean: f32 = deviations.iter().sum::<f32>() / deviations.len() as f32;
+    let variance: f32 =
+        deviations.iter().map(|d| (d - mean).powi(2)).sum::<f32>() / deviations.len() as f32;
+    Some(variance.sqrt())
+}
+
+fn judgement_for(offset: f32) -> Judgement {
+    if offset.abs() <= ON_TIME_WINDOW {
+        Judgement::OnTime
+    } else if offset < 0.0 {
+        Judgement::Early
+    } else {
+        Judgement::Late
+    }
+}
+
+// Índice y desfase de la nota esperada libre más cercana dentro de la ventana
+fn closest(played: &PlayedNote, expected: &[Note], used: &[bool]) -> Option<(usize, f32)> {
+    expected
+        .iter()
+        .enumerate()
+        .filter(|(i, note)| !used[*i] && note.pitch == played.pitch)
+        .map(|(i, note)| (i, played.on - note.start))
+        .filter(|(_, offset)| offset.abs() <= MATCH_WINDOW)
+        .min_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))
+}
@@ -0,0 +1,96 @@
+use core::fmt;
+
+/// Swing máximo: la corchea de después del pulso cae a tres cuartos del par
+pub const MAX_SWING: u8 = 75;
+
+/// Figura más corta a la que se ajustan inicios y duraciones
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuantizeGrid {
+    Quarter,
+    Eighth,
+    Sixteenth,
+}
+
+impl QuantizeGrid {
+    pub const ALL: [QuantizeGrid; 3] = [
+        QuantizeGrid::Quarter,
+        QuantizeGrid::Eighth,
+        QuantizeGrid::Sixteenth,
+    ];
+
+    // Divisiones de la negra sin tresillos
+    fn per_quarter(self) -> f32 {
+        match self {
+            QuantizeGrid::Quarter => 1.0,
+            QuantizeGrid::Eighth => 2.0,
+            QuantizeGrid::Sixteenth => 4.0,
+        }
+    }
+}
+
+impl fmt::Display for QuantizeGrid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            QuantizeGrid::Quarter => "Negra",
+            QuantizeGrid::Eighth => "Corchea",
+            QuantizeGrid::Sixteenth => "Semicorchea",
+        })
+    }
+}
+
+/// Ajusta a la rejilla los segundos de lo que se ha tocado.
+/// Con tresillos la rejilla divide en tres lo que normalmente se divide en dos. Con swing la
+/// segunda figura de cada par se espera tarde (a `swing` del par en vez de a la mitad) y se
+/// escribe recta, como en una partitura de jazz.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quantizer {
+    step: f32,  // Segundos de una división de la rejilla
+    swing: f32, // Fracción del par donde cae la segunda división, 0.5 sin swing
+}
+
+impl Quantizer {
+    /// `swing` en porcentaje, de 50 (recto) a `MAX_SWING`; con tresillos no se aplica
+    pub fn new(quarter_seconds: f32, grid: QuantizeGrid, triplets: bool, swing: u8) -> Self {
+        let divisions: f32 = if triplets {
+            grid.per_quarter() * 1.5
+        } else {
+            grid.per_quarter()
+        };
+        let swing: f32 = if triplets {
+            0.5
+        } else {
+            swing.clamp(50, MAX_SWING) as f32 / 100.0
+        };
+        Quantizer {
+            step: quarter_seconds.max(f32::EPSILON) / divisions,
+            swing,
+        }
+    }
+
+    /// Segundos de una división de la rejilla
+    pub fn step(&self) -> f32 {
+        self.step
+    }
+
+    /// Posición escrita más cercana a un segundo tocado; lo tocado antes del 0 va al 0
+    pub fn onset(&self, time: f32) -> f32 {
+        let pair: f32 = self.step * 2.0;
+        let first: f32 = (time.max(0.0) / pair).floor() * pair;
+        let offset: f32 = time.max(0.0) - first;
+
+        // Principio del par, segunda división (tocada con swing) o principio del siguiente
+        let candidates: [(f32, f32); 3] =
+            [(0.0, 0.0), (self.swing * pair, self.step), (pair, pair)];
+        let (_, written) = candidates
+            .into_iter()
+            .min_by(|(a, _), (b, _)| (offset - a).abs().total_cmp(&(offset - b).abs()))
+            .unwrap_or_default();
+        first + written
+    }
+
+    /// Duración escrita de una nota tocada entre `on` y `off`: el final también se ajusta
+    /// a la rejilla, y nunca queda más corta que una división
+    pub fn duration(&self, on: f32, off: f32) -> f32 {
+        (self.onset(off) - self.onset(on)).max(self.step)
+    }
+}
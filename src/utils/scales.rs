@@ -0,0 +1,381 @@
+use {
+    crate::{
+        models::{
+            note::Note,
+            partiture::{Hand, Hands},
+            score::{Meter, Piece, ScoreMetadata, Section},
+        },
+        utils::sight_reading::{Key, MAJOR_SCALE, MINOR_SCALE, NoteValue},
+    },
+    core::fmt,
+    std::collections::BTreeMap,
+};
+
+/// Nombre con el que se juega el ejercicio de escalas
+pub const SCALE_EXERCISE: &str = "scale-exercise";
+
+/// Octavas como máximo. En movimiento contrario las manos se separan desde el centro
+/// del teclado, así que caben menos.
+pub const MAX_OCTAVES: u8 = 4;
+pub const MAX_CONTRARY_OCTAVES: u8 = 3;
+
+// Menores armónica y melódica (subiendo; bajando la melódica es la natural)
+const HARMONIC_MINOR_SCALE: [u8; 7] = [0, 2, 3, 5, 7, 8, 11];
+const MELODIC_MINOR_SCALE: [u8; 7] = [0, 2, 3, 5, 7, 9, 11];
+
+/// Forma de la escala menor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MinorForm {
+    Natural,
+    Harmonic,
+    Melodic, // Sube con la sexta y la séptima elevadas y baja como la natural
+}
+
+impl MinorForm {
+    pub const ALL: [MinorForm; 3] = [MinorForm::Natural, MinorForm::Harmonic, MinorForm::Melodic];
+}
+
+impl fmt::Display for MinorForm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MinorForm::Natural => "Natural",
+            MinorForm::Harmonic => "Armónica",
+            MinorForm::Melodic => "Melódica",
+        })
+    }
+}
+
+/// Qué se toca
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScalePattern {
+    Scale,
+    Arpeggio,       // Acorde de tónica en estado fundamental
+    ContraryMotion, // Escala con las dos manos desde la misma tónica, una sube y otra baja
+}
+
+impl ScalePattern {
+    pub const ALL: [ScalePattern; 3] = [
+        ScalePattern::Scale,
+        ScalePattern::Arpeggio,
+        ScalePattern::ContraryMotion,
+    ];
+}
+
+impl fmt::Display for ScalePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ScalePattern::Scale => "Escala",
+            ScalePattern::Arpeggio => "Arpegio",
+            ScalePattern::ContraryMotion => "Movimiento contrario",
+        })
+    }
+}
+
+/// Parámetros del ejercicio de escalas
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScaleConfig {
+    pub key: Key,
+    pub minor_form: MinorForm, // Solo cuenta en tonalidades menores
+    pub pattern: ScalePattern,
+    pub octaves: u8,
+    pub hands: Hands,
+    pub note_value: NoteValue, // Figura de todas las notas
+}
+
+impl Default for ScaleConfig {
+    fn default() -> Self {
+        ScaleConfig {
+            key: Key::new(0, false),
+            minor_form: MinorForm::Harmonic,
+            pattern: ScalePattern::Scale,
+            octaves: 1,
+            hands: Hands::Together,
+            note_value: NoteValue::Quarter,
+        }
+    }
+}
+
+impl ScaleConfig {
+    /// Octavas que se tocan de verdad, dentro de lo que cabe en el teclado
+    pub fn octaves(&self) -> u8 {
+        let max: u8 = match self.pattern {
+            ScalePattern::ContraryMotion => MAX_CONTRARY_OCTAVES,
+            _ => MAX_OCTAVES,
+        };
+        self.octaves.clamp(1, max)
+    }
+
+    /// Si toca esa mano
+    pub fn plays(&self, hand: Hand) -> bool {
+        self.pattern == ScalePattern::ContraryMotion || self.hands.plays(hand)
+    }
+
+    /// Nombre del ejercicio: "A minor armónica · Escala · 2 octavas"
+    pub fn title(&self) -> String {
+        let key: String = if self.key.minor && self.pattern != ScalePattern::Arpeggio {
+            format!(
+                "{} {}",
+                self.key,
+                self.minor_form.to_string().to_lowercase()
+            )
+        } else {
+            self.key.to_string()
+        };
+        let octaves: u8 = self.octaves();
+        format!(
+            "{} · {} · {} {}",
+            key,
+            self.pattern,
+            octaves,
+            if octaves == 1 { "octava" } else { "octavas" }
+        )
+    }
+
+    // Semitonos desde la tónica de cada nota de una octava, subiendo y bajando
+    fn steps(&self) -> (Vec<u8>, Vec<u8>) {
+        let natural: &[u8; 7] = if self.key.minor {
+            &MINOR_SCALE
+        } else {
+            &MAJOR_SCALE
+        };
+        let up: &[u8; 7] = match (self.key.minor, self.minor_form) {
+            (false, _) | (true, MinorForm::Natural) => natural,
+            (true, MinorForm::Harmonic) => &HARMONIC_MINOR_SCALE,
+            (true, MinorForm::Melodic) => &MELODIC_MINOR_SCALE,
+        };
+        let down: &[u8; 7] = match self.minor_form {
+            MinorForm::Melodic => natural,
+            _ => up,
+        };
+
+        match self.pattern {
+            ScalePattern::Arpeggio => {
+                let triad: Vec<u8> = vec![0, natural[2], natural[4]];
+                (triad.clone(), triad)
+            }
+            _ => (up.to_vec(), down.to_vec()),
+        }
+    }
+}
+
+// Digitación de una octava: el dedo de cada nota en las octavas de en medio, el de la
+// primera nota (la más grave) y el de la última (la más aguda). Dedos del 1 (pulgar)
+// al 5 (meñique).
+struct Fingering {
+    inner: &'static [u8],
+    first: u8,
+    last: u8,
+}
+
+impl Fingering {
+    // Dedo de la nota `position` contando desde la más grave, con `top` la más aguda
+    fn finger(&self, position: usize, top: usize) -> u8 {
+        if position == 0 {
+            self.first
+        } else if position == top {
+            self.last
+        } else {
+            self.inner[position % self.inner.len()]
+        }
+    }
+}
+
+const fn fingering(inner: &'static [u8], first: u8, last: u8) -> Fingering {
+    Fingering { inner, first, last }
+}
+
+// Digitaciones habituales de las escalas, con el pulgar siempre en tecla blanca
+const RH_WHITE: Fingering = fingering(&[1, 2, 3, 1, 2, 3, 4], 1, 5);
+const RH_FOURTH_FINGER_ON_SEVENTH: Fingering = fingering(&[1, 2, 3, 4, 1, 2, 3], 1, 4);
+const LH_WHITE: Fingering = fingering(&[1, 4, 3, 2, 1, 3, 2], 5, 1);
+const LH_B: Fingering = fingering(&[1, 3, 2, 1, 4, 3, 2], 4, 1);
+const LH_FROM_THIRD_FINGER: Fingering = fingering(&[3, 2, 1, 4, 3, 2, 1], 3, 3);
+const LH_F_SHARP: Fingering = fingering(&[4, 3, 2, 1, 3, 2, 1], 4, 4);
+
+// Mano derecha y mano izquierda de cada tónica (0 = Do) en mayor
+const MAJOR_FINGERINGS: [(Fingering, Fingering); 12] = [
+    (RH_WHITE, LH_WHITE),
+    (
+        fingering(&[2, 3, 1, 2, 3, 4, 1], 2, 2),
+        LH_FROM_THIRD_FINGER,
+    ),
+    (RH_WHITE, LH_WHITE),
+    (
+        fingering(&[3, 1, 2, 3, 4, 1, 2], 3, 3),
+        LH_FROM_THIRD_FINGER,
+    ),
+    (RH_WHITE, LH_WHITE),
+    (RH_FOURTH_FINGER_ON_SEVENTH, LH_WHITE),
+    (fingering(&[2, 3, 4, 1, 2, 3, 1], 2, 2), LH_F_SHARP),
+    (RH_WHITE, LH_WHITE),
+    (
+        fingering(&[3, 4, 1, 2, 3, 1, 2], 3, 3),
+        LH_FROM_THIRD_FINGER,
+    ),
+    (RH_WHITE, LH_WHITE),
+    (
+        fingering(&[4, 1, 2, 3, 1, 2, 3], 4, 4),
+        LH_FROM_THIRD_FINGER,
+    ),
+    (RH_WHITE, LH_B),
+];
+
+// Lo mismo en menor, con la digitación de la menor armónica para las tres formas
+const MINOR_FINGERINGS: [(Fingering, Fingering); 12] = [
+    (RH_WHITE, LH_WHITE),
+    (
+        fingering(&[3, 4, 1, 2, 3, 1, 2], 3, 3),
+        LH_FROM_THIRD_FINGER,
+    ),
+    (RH_WHITE, LH_WHITE),
+    (
+        fingering(&[3, 1, 2, 3, 4, 1, 2], 3, 3),
+        fingering(&[2, 1, 4, 3, 2, 1, 3], 2, 2),
+    ),
+    (RH_WHITE, LH_WHITE),
+    (RH_FOURTH_FINGER_ON_SEVENTH, LH_WHITE),
+    (fingering(&[3, 4, 1, 2, 3, 1, 2], 3, 3), LH_F_SHARP),
+    (RH_WHITE, LH_WHITE),
+    (
+        fingering(&[3, 4, 1, 2, 3, 1, 2], 3, 3),
+        LH_FROM_THIRD_FINGER,
+    ),
+    (RH_WHITE, LH_WHITE),
+    (
+        fingering(&[4, 1, 2, 3, 1, 2, 3], 4, 4),
+        fingering(&[2, 1, 3, 2, 1, 4, 3], 2, 2),
+    ),
+    (RH_WHITE, LH_B),
+];
+
+// Arpegios: con la tónica en tecla blanca empieza el pulgar (o el meñique en la izquierda);
+// en tecla negra, el segundo dedo
+const ARPEGGIO_WHITE: (Fingering, Fingering) =
+    (fingering(&[1, 2, 3], 1, 5), fingering(&[1, 4, 2], 5, 1));
+const ARPEGGIO_BLACK: (Fingering, Fingering) =
+    (fingering(&[4, 1, 2], 2, 4), fingering(&[2, 1, 4], 2, 2));
+
+fn fingering_for(config: &ScaleConfig, hand: Hand) -> &'static Fingering {
+    let tonic: usize = (config.key.tonic % 12) as usize;
+    let (right, left): &'static (Fingering, Fingering) = match config.pattern {
+        ScalePattern::Arpeggio if matches!(tonic, 1 | 3 | 6 | 8 | 10) => &ARPEGGIO_BLACK,
+        ScalePattern::Arpeggio => &ARPEGGIO_WHITE,
+        _ if config.key.minor => &MINOR_FINGERINGS[tonic],
+        _ => &MAJOR_FINGERINGS[tonic],
+    };
+    match hand {
+        Hand::Right => right,
+        Hand::Left => left,
+    }
+}
+
+// Tono y dedo de cada nota de una mano, en orden
+type Line = Vec<(u8, u8)>;
+
+/// Generar el ejercicio como una pieza de una sola sección, con la digitación en cada nota
+pub fn generate_piece(config: &ScaleConfig) -> Piece {
+    let octaves: usize = config.octaves() as usize;
+    let tonic: u8 = config.key.tonic % 12;
+
+    let (right, left): (Line, Line) = match config.pattern {
+        // Las dos manos empiezan en la misma tónica, la más cercana al Do central
+        ScalePattern::ContraryMotion => {
+            let start: u8 = 57 + (tonic + 3) % 12;
+            (
+                up_and_down(config, Hand::Right, start, octaves),
+                down_and_up(config, Hand::Left, start - 12 * octaves as u8, octaves),
+            )
+        }
+        // Las manos a una octava, más abajo cuantas más octavas para no salir del teclado
+        _ => {
+            let start: u8 = 60 + tonic - 12 * ((octaves as u8 - 1) / 2);
+            let line = |hand: Hand, bottom: u8| {
+                if config.plays(hand) {
+                    up_and_down(config, hand, bottom, octaves)
+                } else {
+                    Vec::new()
+                }
+            };
+            (line(Hand::Right, start), line(Hand::Left, start - 12))
+        }
+    };
+
+    let duration: f32 = config.note_value.seconds();
+    let meter: Meter = match config.pattern {
+        ScalePattern::Arpeggio => Meter {
+            beats: 3,
+            beat_unit: 4,
+        },
+        _ => Meter {
+            beats: 4,
+            beat_unit: 4,
+        },
+    };
+
+    Piece {
+        metadata: ScoreMetadata {
+            title: config.title(),
+            composer: None,
+            source: Some("OctaRust".to_string()),
+            meter,
+            tempo: None,
+            key: Some(config.key.to_string()),
+            note_duration_unit_seconds: 4.0 / meter.beat_unit as f32,
+            structure: vec!["A".to_string()],
+            playback_instructions: None,
+            exercise: true,
+        },
+        sections: BTreeMap::from([(
+            "A".to_string(),
+            Section {
+                start_time: 0.0,
+                right: to_notes(&right, duration),
+                left: to_notes(&left, duration),
+                ..Section::default()
+            },
+        )]),
+    }
+}
+
+// Subir desde `bottom` las octavas indicadas y volver a bajar. Cada nota con su dedo.
+fn up_and_down(config: &ScaleConfig, hand: Hand, bottom: u8, octaves: usize) -> Line {
+    let (up, down) = config.steps();
+    let top: usize = up.len() * octaves;
+    let fingering: &Fingering = fingering_for(config, hand);
+
+    (0..=top)
+        .map(|position| (pitch_at(bottom, &up, position), position))
+        .chain((0..top).rev().map(|p| (pitch_at(bottom, &down, p), p)))
+        .map(|(pitch, position)| (pitch, fingering.finger(position, top)))
+        .collect()
+}
+
+// Bajar hasta `bottom` desde las octavas indicadas por encima y volver a subir
+fn down_and_up(config: &ScaleConfig, hand: Hand, bottom: u8, octaves: usize) -> Line {
+    let (up, down) = config.steps();
+    let top: usize = up.len() * octaves;
+    let fingering: &Fingering = fingering_for(config, hand);
+
+    (0..=top)
+        .rev()
+        .map(|position| (pitch_at(bottom, &down, position), position))
+        .chain((1..=top).map(|p| (pitch_at(bottom, &up, p), p)))
+        .map(|(pitch, position)| (pitch, fingering.finger(position, top)))
+        .collect()
+}
+
+// Tono de la nota `position` de la escala contando desde `bottom`
+fn pitch_at(bottom: u8, steps: &[u8], position: usize) -> u8 {
+    bottom + 12 * (position / steps.len()) as u8 + steps[position % steps.len()]
+}
+
+fn to_notes(line: &[(u8, u8)], duration: f32) -> Vec<Note> {
+    line.iter()
+        .enumerate()
+        .map(|(i, &(pitch, finger))| Note {
+            start: i as f32 * duration,
+            finger: Some(finger),
+            ..Note::new(pitch, duration, false, Default::default())
+        })
+        .collect()
+}
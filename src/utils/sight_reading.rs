@@ -0,0 +1,608 @@
+use {
+    crate::{
+        models::{
+            markings::Markings,
+            note::Note,
+            partiture::Partiture,
+            playback::PlaybackClock,
+            score::{Meter, Piece, ScoreFile, ScoreMetadata, Section},
+            score_error::ScoreError,
+            settings::CustomSettings,
+        },
+        utils::{
+            helper_json::{build_partitures, load_piece_hands},
+            note_names::parse_note_name,
+        },
+    },
+    core::fmt,
+    std::collections::BTreeMap,
+};
+
+/// Nombre con el que se juega el ejercicio generado
+pub const GENERATED_PIECE: &str = "sight-reading";
+
+// Margen para comparar posiciones dentro del compás
+const EPSILON: f32 = 1e-4;
+
+// Probabilidades de los adornos
+const HARMONY_CHANCE: f32 = 0.5;
+const CHROMATIC_CHANCE: f32 = 0.15;
+
+// Grados de la escala (desde 0) de la raíz de cada compás. El penúltimo compás es
+// siempre la dominante y el último la tónica.
+const PROGRESSIONS: [[usize; 4]; 4] = [[0, 3, 4, 0], [0, 5, 3, 4], [0, 4, 5, 3], [0, 3, 0, 4]];
+
+/// Semitonos desde la tónica de las escalas mayor y menor natural
+pub const MAJOR_SCALE: [u8; 7] = [0, 2, 4, 5, 7, 9, 11];
+pub const MINOR_SCALE: [u8; 7] = [0, 2, 3, 5, 7, 8, 10];
+
+// Nombres de las tónicas, con bemoles en las tonalidades que los usan
+const SHARP_TONICS: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+const FLAT_TONICS: [&str; 12] = [
+    "C", "Db", "D", "Eb", "E", "F", "Gb", "G", "Ab", "A", "Bb", "B",
+];
+
+/// Tonalidad: tónica (clase de nota, 0 = Do) y modo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    pub tonic: u8,
+    pub minor: bool,
+}
+
+impl Key {
+    pub const fn new(tonic: u8, minor: bool) -> Self {
+        Key { tonic, minor }
+    }
+
+    /// Tonalidad escrita como en los metadatos de las piezas: "C major", "Bb minor"
+    pub fn parse(name: &str) -> Option<Key> {
+        let (tonic, mode) = name.trim().split_once(' ')?;
+        let minor: bool = match mode.trim().to_lowercase().as_str() {
+            "major" => false,
+            "minor" => true,
+            _ => return None,
+        };
+        let pitch: u8 = parse_note_name(&format!("{}4", tonic))?;
+        Some(Key {
+            tonic: pitch % 12,
+            minor,
+        })
+    }
+
+    // Semitonos de cada grado desde la tónica
+    fn intervals(&self) -> &'static [u8; 7] {
+        if self.minor {
+            &MINOR_SCALE
+        } else {
+            &MAJOR_SCALE
+        }
+    }
+
+    /// Si el tono pertenece a la escala
+    pub fn contains(&self, pitch: u8) -> bool {
+        self.degree_of(pitch).is_some()
+    }
+
+    /// Grado (0 a 6) de un tono de la escala
+    pub fn degree_of(&self, pitch: u8) -> Option<usize> {
+        let offset: u8 = (pitch % 12 + 12 - self.tonic) % 12;
+        self.intervals().iter().position(|&i| i == offset)
+    }
+
+    /// Tonos de la escala entre `low` y `high`, ambos incluidos
+    pub fn pitches_in(&self, low: u8, high: u8) -> Vec<u8> {
+        (low..=high.min(127))
+            .filter(|&pitch| self.contains(pitch))
+            .collect()
+    }
+
+    // Las tonalidades con bemoles en la armadura se escriben con bemol
+    fn uses_flats(&self) -> bool {
+        let relative_major: u8 = if self.minor {
+            (self.tonic + 3) % 12
+        } else {
+            self.tonic
+        };
+        matches!(relative_major, 1 | 3 | 5 | 8 | 10)
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: &[&str; 12] = if self.uses_flats() {
+            &FLAT_TONICS
+        } else {
+            &SHARP_TONICS
+        };
+        let mode: &str = if self.minor { "minor" } else { "major" };
+        write!(f, "{} {}", names[self.tonic as usize], mode)
+    }
+}
+
+/// Figuras que puede usar el ejercicio
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum NoteValue {
+    Whole,
+    DottedHalf,
+    Half,
+    DottedQuarter,
+    Quarter,
+    Eighth,
+    Sixteenth,
+}
+
+impl NoteValue {
+    pub const ALL: [NoteValue; 7] = [
+        NoteValue::Whole,
+        NoteValue::DottedHalf,
+        NoteValue::Half,
+        NoteValue::DottedQuarter,
+        NoteValue::Quarter,
+        NoteValue::Eighth,
+        NoteValue::Sixteenth,
+    ];
+
+    /// Duración en segundos, con la negra a un segundo como en el resto de partituras
+    pub fn seconds(&self) -> f32 {
+        match self {
+            NoteValue::Whole => 4.0,
+            NoteValue::DottedHalf => 3.0,
+            NoteValue::Half => 2.0,
+            NoteValue::DottedQuarter => 1.5,
+            NoteValue::Quarter => 1.0,
+            NoteValue::Eighth => 0.5,
+            NoteValue::Sixteenth => 0.25,
+        }
+    }
+}
+
+impl fmt::Display for NoteValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            NoteValue::Whole => "Redondas",
+            NoteValue::DottedHalf => "Blancas con puntillo",
+            NoteValue::Half => "Blancas",
+            NoteValue::DottedQuarter => "Negras con puntillo",
+            NoteValue::Quarter => "Negras",
+            NoteValue::Eighth => "Corcheas",
+            NoteValue::Sixteenth => "Semicorcheas",
+        })
+    }
+}
+
+/// Parámetros del ejercicio. Con la misma semilla y los mismos parámetros sale siempre
+/// el mismo ejercicio.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SightReadingConfig {
+    pub seed: u64,
+    pub key: Key,
+    pub meter: Meter,
+    pub bars: u32,
+    pub right_range: (u8, u8), // Tonos MIDI más grave y más agudo de la mano derecha
+    pub left_range: (u8, u8),  // Tonos MIDI más grave y más agudo de la mano izquierda
+    pub note_values: Vec<NoteValue>,
+    pub intervals: Vec<u8>, // Saltos permitidos en grados de la escala: 0 repite, 1 por grado, 2 terceras...
+    pub chords: bool,       // Terceras y sextas en la derecha y acordes en la izquierda
+    pub accidentals: bool,  // Notas cromáticas que resuelven por semitono
+}
+
+impl SightReadingConfig {
+    /// Parámetros de un nivel de 1 a 3
+    pub fn level(level: u8, seed: u64) -> Self {
+        let (note_values, intervals, right_range, left_range): (Vec<NoteValue>, Vec<u8>, _, _) =
+            match level {
+                0 | 1 => (
+                    vec![NoteValue::Whole, NoteValue::Half, NoteValue::Quarter],
+                    vec![0, 1],
+                    (60, 67),
+                    (48, 55),
+                ),
+                2 => (
+                    vec![
+                        NoteValue::Half,
+                        NoteValue::DottedQuarter,
+                        NoteValue::Quarter,
+                        NoteValue::Eighth,
+                    ],
+                    vec![0, 1, 2],
+                    (60, 74),
+                    (43, 55),
+                ),
+                _ => (
+                    vec![
+                        NoteValue::Half,
+                        NoteValue::DottedQuarter,
+                        NoteValue::Quarter,
+                        NoteValue::Eighth,
+                        NoteValue::Sixteenth,
+                    ],
+                    vec![0, 1, 2, 3, 4],
+                    (57, 81),
+                    (36, 57),
+                ),
+            };
+
+        SightReadingConfig {
+            seed,
+            key: Key {
+                tonic: 0,
+                minor: false,
+            },
+            meter: Meter {
+                beats: 4,
+                beat_unit: 4,
+            },
+            bars: 8,
+            right_range,
+            left_range,
+            note_values,
+            intervals,
+            chords: level >= 3,
+            accidentals: level >= 3,
+        }
+    }
+
+    /// Duración de un compás en segundos
+    pub fn bar_seconds(&self) -> f32 {
+        self.meter.beats as f32 * 4.0 / self.meter.beat_unit as f32
+    }
+
+    // Pulso en segundos: en 6/8, 9/8 y 12/8 se cuenta por negras con puntillo
+    fn beat_seconds(&self) -> f32 {
+        let unit: f32 = 4.0 / self.meter.beat_unit as f32;
+        if self.meter.beat_unit == 8 && self.meter.beats.is_multiple_of(3) && self.meter.beats > 3 {
+            unit * 3.0
+        } else {
+            unit
+        }
+    }
+}
+
+impl Default for SightReadingConfig {
+    fn default() -> Self {
+        SightReadingConfig::level(1, 1)
+    }
+}
+
+/// Generador pseudoaleatorio con semilla (splitmix64). No depende de ninguna versión
+/// de una biblioteca, así que una semilla da el mismo ejercicio en cualquier versión.
+#[derive(Debug, Clone)]
+pub struct SeededRng(u64);
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        SeededRng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z: u64 = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Entero entre 0 y `n - 1` (0 si `n` es 0)
+    pub fn below(&mut self, n: usize) -> usize {
+        if n == 0 {
+            0
+        } else {
+            (self.next_u64() % n as u64) as usize
+        }
+    }
+
+    /// Decimal entre 0 y 1
+    pub fn unit(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.unit() < probability
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        (!items.is_empty()).then(|| &items[self.below(items.len())])
+    }
+}
+
+/// Generar el ejercicio como una pieza de una sola sección, igual que las de `partitures.json`
+pub fn generate_piece(config: &SightReadingConfig) -> Piece {
+    let mut rng: SeededRng = SeededRng::new(config.seed);
+    let roots: Vec<usize> = progression(config, &mut rng);
+
+    let right: Vec<Note> = right_hand(config, &roots, &mut rng);
+    let left: Vec<Note> = left_hand(config, &roots, &mut rng);
+
+    Piece {
+        metadata: ScoreMetadata {
+            title: format!("Lectura a primera vista #{}", config.seed),
+            composer: None,
+            source: Some("OctaRust".to_string()),
+            meter: config.meter,
+            tempo: None,
+            key: Some(config.key.to_string()),
+            note_duration_unit_seconds: 4.0 / config.meter.beat_unit as f32,
+            structure: vec!["A".to_string()],
+            playback_instructions: None,
+            exercise: true,
+        },
+        sections: BTreeMap::from([(
+            "A".to_string(),
+            Section {
+                start_time: 0.0,
+                right,
+                left,
+                ..Section::default()
+            },
+        )]),
+    }
+}
+
+/// Generar el ejercicio y cargarlo en las dos manos como una pieza de la biblioteca
+pub fn generate_partitures(
+    config: &SightReadingConfig,
+    settings: &CustomSettings,
+    clock: &PlaybackClock,
+) -> Result<(Partiture, Partiture), ScoreError> {
+    let library: ScoreFile = ScoreFile(vec![BTreeMap::from([(
+        GENERATED_PIECE.to_string(),
+        generate_piece(config),
+    )])]);
+    let (notes_l, notes_r, metadata) = load_piece_hands(&library, GENERATED_PIECE)?;
+    Ok(build_partitures(
+        notes_l,
+        notes_r,
+        Markings::default(),
+        metadata,
+        settings,
+        clock,
+    ))
+}
+
+// Raíz de cada compás, terminando en dominante y tónica
+fn progression(config: &SightReadingConfig, rng: &mut SeededRng) -> Vec<usize> {
+    let pattern: &[usize; 4] = rng.pick(&PROGRESSIONS).unwrap_or(&PROGRESSIONS[0]);
+    let bars: usize = config.bars.max(1) as usize;
+    let mut roots: Vec<usize> = (0..bars).map(|bar| pattern[bar % 4]).collect();
+    if bars >= 2 {
+        roots[bars - 2] = 4;
+    }
+    roots[bars - 1] = 0;
+    roots
+}
+
+// Ritmo de un compás: inicio y figura de cada nota. Las figuras de un pulso o más
+// empiezan en un pulso y las más cortas no cruzan al pulso siguiente. Donde no cabe
+// ninguna queda un silencio hasta el pulso siguiente.
+fn bar_rhythm(
+    config: &SightReadingConfig,
+    values: &[NoteValue],
+    rng: &mut SeededRng,
+) -> Vec<(f32, NoteValue)> {
+    let bar: f32 = config.bar_seconds();
+    let beat: f32 = config.beat_seconds();
+    let mut rhythm: Vec<(f32, NoteValue)> = Vec::new();
+    let mut position: f32 = 0.0;
+
+    while position < bar - EPSILON {
+        let fitting: Vec<NoteValue> = values
+            .iter()
+            .copied()
+            .filter(|value| fits(position, value.seconds(), bar, beat))
+            .collect();
+        let Some(&value) = rng.pick(&fitting) else {
+            // Silencio hasta el pulso siguiente
+            position = ((position / beat + EPSILON).floor() + 1.0) * beat;
+            continue;
+        };
+        rhythm.push((position, value));
+        position += value.seconds();
+    }
+    rhythm
+}
+
+fn fits(position: f32, length: f32, bar: f32, beat: f32) -> bool {
+    if position + length > bar + EPSILON {
+        return false;
+    }
+    let beat_index: f32 = (position / beat + EPSILON).floor();
+    if length >= beat - EPSILON {
+        (position - beat_index * beat).abs() < EPSILON
+    } else {
+        position + length <= (beat_index + 1.0) * beat + EPSILON
+    }
+}
+
+// Melodía de la mano derecha: se mueve por la escala con los saltos permitidos, cae en
+// notas del acorde al empezar cada compás y termina en la tónica
+fn right_hand(config: &SightReadingConfig, roots: &[usize], rng: &mut SeededRng) -> Vec<Note> {
+    let (low, high) = config.right_range;
+    let scale: Vec<u8> = config.key.pitches_in(low, high);
+    if scale.is_empty() || config.note_values.is_empty() {
+        return Vec::new();
+    }
+    let bar_seconds: f32 = config.bar_seconds();
+
+    // Empezar en la tónica más cercana al centro del registro
+    let center: u8 = ((low as u16 + high as u16) / 2) as u8;
+    let mut index: usize = nearest_degree(&scale, config.key, 0, center);
+    let mut pending: Option<usize> = None; // Nota de escala a la que resuelve una cromática
+    let mut notes: Vec<Note> = Vec::new();
+
+    let rhythms: Vec<Vec<(f32, NoteValue)>> = roots
+        .iter()
+        .map(|_| bar_rhythm(config, &config.note_values, rng))
+        .collect();
+    let total: usize = rhythms.iter().map(Vec::len).sum();
+    let mut count: usize = 0;
+
+    for (bar, (root, rhythm)) in roots.iter().zip(&rhythms).enumerate() {
+        let last_bar: bool = bar + 1 == roots.len();
+        let chord: [usize; 3] = [*root, (root + 2) % 7, (root + 4) % 7];
+
+        for (position, value) in rhythm {
+            count += 1;
+            let start: f32 = bar as f32 * bar_seconds + position;
+            let duration: f32 = value.seconds();
+
+            // La última nota es la tónica más cercana
+            if count == total {
+                index = nearest_degree(&scale, config.key, 0, scale[index]);
+                notes.push(note(scale[index], start, duration));
+                continue;
+            }
+
+            // Nota cromática que lleva por semitono a una nota de la escala cercana
+            let resolving: bool = pending.is_some();
+            if config.accidentals
+                && !resolving
+                && !last_bar
+                && count + 1 < total
+                && rng.chance(CHROMATIC_CHANCE)
+            {
+                let candidates: Vec<usize> = step_candidates(&scale, index, &config.intervals);
+                if let Some(&target) = rng.pick(&candidates) {
+                    let pitch: u8 = if rng.chance(0.5) {
+                        scale[target].saturating_sub(1)
+                    } else {
+                        scale[target] + 1
+                    };
+                    if !config.key.contains(pitch) && (low..=high).contains(&pitch) {
+                        notes.push(note(pitch, start, duration));
+                        pending = Some(target);
+                        continue;
+                    }
+                }
+            }
+
+            index = match pending.take() {
+                Some(target) => target,
+                None => {
+                    let candidates: Vec<usize> = step_candidates(&scale, index, &config.intervals);
+                    let preferred: Vec<usize> = if *position < EPSILON {
+                        // En la parte fuerte, notas del acorde
+                        filter_or_all(&candidates, |i| {
+                            config
+                                .key
+                                .degree_of(scale[*i])
+                                .is_some_and(|d| chord.contains(&d))
+                        })
+                    } else if bar + 2 >= roots.len() {
+                        // Al final, acercarse a una tónica
+                        let tonic: usize = nearest_degree(&scale, config.key, 0, scale[index]);
+                        filter_or_all(&candidates, |i| i.abs_diff(tonic) < index.abs_diff(tonic))
+                    } else {
+                        candidates
+                    };
+                    *rng.pick(&preferred).unwrap_or(&index)
+                }
+            };
+
+            notes.push(note(scale[index], start, duration));
+
+            // Tercera o sexta por debajo en las partes fuertes
+            if config.chords && *position < EPSILON && rng.chance(HARMONY_CHANCE) {
+                let below: usize = if rng.chance(0.5) { 2 } else { 5 };
+                if let Some(harmony) = index.checked_sub(below) {
+                    notes.push(note(scale[harmony], start, duration));
+                }
+            }
+        }
+    }
+    notes
+}
+
+// Bajo de la mano izquierda: la raíz de cada compás, o el acorde entero con `chords`,
+// con figuras de un pulso o más
+fn left_hand(config: &SightReadingConfig, roots: &[usize], rng: &mut SeededRng) -> Vec<Note> {
+    let (low, high) = config.left_range;
+    let scale: Vec<u8> = config.key.pitches_in(low, high);
+    if scale.is_empty() || config.note_values.is_empty() {
+        return Vec::new();
+    }
+    let bar_seconds: f32 = config.bar_seconds();
+    let beat: f32 = config.beat_seconds();
+
+    // Figuras de un pulso o más; si no hay ninguna, todas las permitidas
+    let mut values: Vec<NoteValue> = config
+        .note_values
+        .iter()
+        .copied()
+        .filter(|value| value.seconds() >= beat - EPSILON)
+        .collect();
+    if values.is_empty() {
+        values = config.note_values.clone();
+    }
+
+    let mut notes: Vec<Note> = Vec::new();
+    for (bar, root) in roots.iter().enumerate() {
+        // Raíz más grave que deja sitio para la quinta
+        let Some(base) = (0..scale.len()).find(|&i| {
+            config.key.degree_of(scale[i]) == Some(*root) && (!config.chords || i + 4 < scale.len())
+        }) else {
+            continue;
+        };
+
+        for (n, (position, value)) in bar_rhythm(config, &values, rng).into_iter().enumerate() {
+            let start: f32 = bar as f32 * bar_seconds + position;
+            let duration: f32 = value.seconds();
+
+            if config.chords {
+                for i in [base, base + 2, base + 4] {
+                    notes.push(note(scale[i], start, duration));
+                }
+            } else {
+                // Raíz y quinta alternadas para que el bajo se mueva
+                let i: usize = if n % 2 == 1 && base + 4 < scale.len() {
+                    base + 4
+                } else {
+                    base
+                };
+                notes.push(note(scale[i], start, duration));
+            }
+        }
+    }
+    notes
+}
+
+// Índices alcanzables desde `index` con los saltos permitidos, sin salir del registro
+fn step_candidates(scale: &[u8], index: usize, intervals: &[u8]) -> Vec<usize> {
+    let mut candidates: Vec<usize> = intervals
+        .iter()
+        .flat_map(|&step| {
+            let step: usize = step as usize;
+            [index.checked_sub(step), Some(index + step)]
+        })
+        .flatten()
+        .filter(|&i| i < scale.len())
+        .collect();
+    candidates.sort_unstable();
+    candidates.dedup();
+    candidates
+}
+
+fn filter_or_all(candidates: &[usize], keep: impl Fn(&usize) -> bool) -> Vec<usize> {
+    let kept: Vec<usize> = candidates.iter().copied().filter(|i| keep(i)).collect();
+    if kept.is_empty() {
+        candidates.to_vec()
+    } else {
+        kept
+    }
+}
+
+// Índice del tono del grado `degree` más cercano a `pitch`
+fn nearest_degree(scale: &[u8], key: Key, degree: usize, pitch: u8) -> usize {
+    (0..scale.len())
+        .filter(|&i| key.degree_of(scale[i]) == Some(degree))
+        .min_by_key(|&i| scale[i].abs_diff(pitch))
+        .unwrap_or(0)
+}
+
+fn note(pitch: u8, start: f32, duration: f32) -> Note {
+    Note {
+        start,
+        ..Note::new(pitch, duration, false, Default::default())
+    }
+}
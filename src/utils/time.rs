@@ -0,0 +1,10 @@
+use std::time::Instant;
+
+/// Segundos de `from` a `to`, negativos si `to` es anterior
+pub fn seconds_between(from: Instant, to: Instant) -> f32 {
+    if to >= from {
+        to.duration_since(from).as_secs_f32()
+    } else {
+        -from.duration_since(to).as_secs_f32()
+    }
+}
@@ -0,0 +1,188 @@
+use {
+    crate::models::{
+        markings::Markings,
+        note::Note,
+        partiture::Hand,
+        score::{Piece, ScoreFile, Section},
+        score_error::{Diagnostic, Issue, ScoreLocation, Severity},
+    },
+    std::collections::HashSet,
+};
+
+// Rango MIDI de un piano de 88 teclas (A0 a C8)
+pub const LOWEST_PIANO_PITCH: u8 = 21;
+pub const HIGHEST_PIANO_PITCH: u8 = 108;
+
+// Margen para comparar tiempos en coma flotante
+const TIME_EPSILON: f32 = 1e-4;
+
+/// Valida todas las piezas de un archivo
+pub fn validate_score(score: &ScoreFile) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    let mut seen: HashSet<&str> = HashSet::new();
+
+    for (name, piece) in score.pieces() {
+        if !seen.insert(name.as_str()) {
+            diagnostics.push(Diagnostic {
+                location: ScoreLocation::piece(name),
+                issue: Issue::DuplicatePiece,
+            });
+        }
+        diagnostics.extend(validate_piece(name, piece));
+    }
+
+    diagnostics
+}
+
+/// Valida una pieza: metadatos, `structure` y las notas de cada sección
+pub fn validate_piece(name: &str, piece: &Piece) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    let piece_location: ScoreLocation = ScoreLocation::piece(name);
+
+    if piece.metadata.note_duration_unit_seconds <= 0.0 {
+        diagnostics.push(Diagnostic {
+            location: piece_location.clone(),
+            issue: Issue::InvalidNoteUnit(piece.metadata.note_duration_unit_seconds),
+        });
+    }
+
+    if piece.metadata.structure.is_empty() {
+        diagnostics.push(Diagnostic {
+            location: piece_location.clone(),
+            issue: Issue::EmptyStructure,
+        });
+    }
+
+    for label in &piece.metadata.structure {
+        if !piece.sections.contains_key(label) {
+            diagnostics.push(Diagnostic {
+                location: piece_location.clone(),
+                issue: Issue::UnknownSection(label.clone()),
+            });
+        }
+    }
+
+    for (label, section) in &piece.sections {
+        let section_location: ScoreLocation = ScoreLocation {
+            section: Some(label.clone()),
+            ..piece_location.clone()
+        };
+
+        if !piece.metadata.structure.contains(label) {
+            diagnostics.push(Diagnostic {
+                location: section_location.clone(),
+                issue: Issue::UnusedSection,
+            });
+        }
+
+        diagnostics.extend(validate_markings(&section.markings, &section_location));
+
+        for hand in [Hand::Right, Hand::Left] {
+            let hand_location: ScoreLocation = ScoreLocation {
+                hand: Some(hand),
+                ..section_location.clone()
+            };
+            diagnostics.extend(validate_notes(
+                section,
+                section.notes(&hand),
+                &hand_location,
+            ));
+        }
+    }
+
+    diagnostics
+}
+
+// Valida que los reguladores, las ligaduras y el pedal de una sección terminen después de empezar
+fn validate_markings(markings: &Markings, location: &ScoreLocation) -> Vec<Diagnostic> {
+    let ranges = markings
+        .hairpins
+        .iter()
+        .map(|hairpin| ("regulador", hairpin.start, hairpin.end))
+        .chain(
+            markings
+                .slurs
+                .iter()
+                .map(|slur| ("ligadura", slur.start, slur.end)),
+        )
+        .chain(
+            markings
+                .pedal
+                .iter()
+                .map(|pedal| ("pedal", pedal.start, pedal.end)),
+        );
+
+    ranges
+        .filter(|(_, start, end)| end - start < TIME_EPSILON)
+        .map(|(kind, start, end)| Diagnostic {
+            location: location.clone(),
+            issue: Issue::EmptyMarking { kind, start, end },
+        })
+        .collect()
+}
+
+// Valida las notas de una mano dentro de una sección
+fn validate_notes(section: &Section, notes: &[Note], location: &ScoreLocation) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    let at = |index: usize, issue: Issue| Diagnostic {
+        location: ScoreLocation {
+            note: Some(index),
+            ..location.clone()
+        },
+        issue,
+    };
+
+    for (i, note) in notes.iter().enumerate() {
+        if note.start < 0.0 {
+            diagnostics.push(at(i, Issue::NegativeStart(note.start)));
+        } else if note.start + TIME_EPSILON < section.start_time {
+            diagnostics.push(at(
+                i,
+                Issue::NoteBeforeSection {
+                    start: note.start,
+                    section_start: section.start_time,
+                },
+            ));
+        }
+
+        if note.duration <= 0.0 {
+            diagnostics.push(at(i, Issue::NegativeDuration(note.duration)));
+        }
+
+        // El tono 0 se usa para los silencios
+        if note.pitch != 0 && !(LOWEST_PIANO_PITCH..=HIGHEST_PIANO_PITCH).contains(&note.pitch) {
+            diagnostics.push(at(i, Issue::PitchOutOfRange(note.pitch)));
+        }
+
+        if let Some(finger) = note.finger
+            && !(1..=5).contains(&finger)
+        {
+            diagnostics.push(at(i, Issue::InvalidFinger(finger)));
+        }
+
+        if let Some(velocity) = note.velocity
+            && !(1..=127).contains(&velocity)
+        {
+            diagnostics.push(at(i, Issue::InvalidVelocity(velocity)));
+        }
+
+        // Dos notas del mismo tono no pueden sonar a la vez en la misma mano
+        if note.pitch != 0 {
+            let overlapping = notes.iter().enumerate().skip(i + 1).find(|(_, other)| {
+                other.pitch == note.pitch
+                    && other.start < note.start + note.duration - TIME_EPSILON
+                    && note.start < other.start + other.duration - TIME_EPSILON
+            });
+            if let Some((j, _)) = overlapping {
+                diagnostics.push(at(i, Issue::Overlap { other: j }));
+            }
+        }
+    }
+
+    diagnostics
+}
+
+/// Indica si alguno de los diagnósticos es un error (y no solo un aviso)
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics.iter().any(|d| d.severity() == Severity::Error)
+}
@@ -1,4 +1,17 @@
+pub mod calibration;
+pub mod capture;
+pub mod ear_training;
+pub mod editor;
+pub mod flashcards;
 pub mod game;
+pub mod latency;
+pub mod lessons;
 pub mod menu;
+pub mod replay;
+pub mod results;
+pub mod rhythm;
+pub mod scales;
 pub mod selection;
 pub mod settings;
+pub mod sight_reading;
+pub mod trophies;
@@ -0,0 +1,122 @@
+use {
+    crate::{
+        message::states::{AppMessage, CalibrationMessage},
+        models::{
+            calibration::{CalibrationStep, CalibrationWizard, REFERENCE_NOTES, SILENCE_SECONDS},
+            settings::MicrophoneSettings,
+        },
+        styles::custom_style,
+        utils::{note_names::note_name, reusable},
+    },
+    iced::{
+        Element, Length,
+        alignment::{Horizontal, Vertical},
+        widget::{Button, Column, Container, Text, column},
+    },
+};
+
+// Asistente de calibración del micrófono. Sin asistente es que no se pudo abrir el micrófono.
+pub fn calibration_view(wizard: Option<&CalibrationWizard>) -> Element<'static, AppMessage> {
+    let mut content: Column<AppMessage> = column![Text::new("Calibrar micrófono").size(32)]
+        .spacing(20)
+        .align_x(Horizontal::Center);
+
+    match wizard {
+        None => {
+            content = content.push(
+                Text::new("No se ha podido abrir el micrófono. Comprueba que está conectado.")
+                    .size(20),
+            );
+        }
+        Some(wizard) => {
+            content = content.push(Text::new(instructions(wizard)).size(20));
+
+            if let Some(countdown) = wizard.countdown() {
+                content = content.push(Text::new(countdown).size(64));
+            }
+            if let Some(message) = wizard.message() {
+                content = content.push(Text::new(message.to_string()).size(18));
+            }
+            for reading in wizard.readings() {
+                content = content.push(
+                    Text::new(format!(
+                        "{}: {:.1} Hz, {:.0} ms",
+                        note_name(reading.pitch),
+                        reading.frequency,
+                        reading.offset * 1000.0
+                    ))
+                    .size(16),
+                );
+            }
+
+            if let Some(result) = wizard.result() {
+                content = content.push(summary(&result));
+                let save: Button<AppMessage> = reusable::create_button(
+                    AppMessage::Calibration(CalibrationMessage::Save),
+                    Some("Guardar"),
+                    None,
+                    Some(20.0),
+                );
+                content = content.push(save);
+            } else if wizard.waiting() {
+                let next: Button<AppMessage> = reusable::create_button(
+                    AppMessage::Calibration(CalibrationMessage::Next),
+                    Some("Empezar"),
+                    None,
+                    Some(20.0),
+                );
+                content = content.push(next);
+            }
+        }
+    }
+
+    let cancel: Button<AppMessage> = reusable::create_button(
+        AppMessage::Calibration(CalibrationMessage::Cancel),
+        Some("Cancelar"),
+        None,
+        Some(20.0),
+    );
+    content = content.push(cancel);
+
+    Container::new(content)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .padding(40)
+        .align_x(Horizontal::Center)
+        .align_y(Vertical::Center)
+        .style(custom_style::background)
+        .into()
+}
+
+/// Valores de la calibración en texto, también para la pantalla de ajustes
+pub fn summary(settings: &MicrophoneSettings) -> Text<'static> {
+    let threshold: String = match settings.noise_threshold {
+        Some(threshold) => format!("{:.1}", threshold),
+        None => "sin calibrar".to_string(),
+    };
+    Text::new(format!(
+        "A4 = {:.1} Hz · Latencia {:.0} ms · Umbral de ruido {}",
+        settings.a4,
+        settings.latency * 1000.0,
+        threshold
+    ))
+    .size(18)
+}
+
+// Qué tiene que hacer el usuario en cada paso
+fn instructions(wizard: &CalibrationWizard) -> String {
+    match wizard.step() {
+        CalibrationStep::Intro => format!(
+            "Primero se mide el ruido de la habitación: pulsa Empezar y no toques nada durante {} segundos.",
+            SILENCE_SECONDS
+        ),
+        CalibrationStep::Silence => "Escuchando el silencio...".to_string(),
+        CalibrationStep::Reference(index) => format!(
+            "Nota {} de {}: toca el {} una sola vez justo con el último click de la cuenta.",
+            index + 1,
+            REFERENCE_NOTES.len(),
+            wizard.reference_name().unwrap_or_default()
+        ),
+        CalibrationStep::Done => "Calibración terminada.".to_string(),
+    }
+}
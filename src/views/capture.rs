@@ -0,0 +1,285 @@
+use {
+    crate::{
+        message::states::{AppMessage, CaptureMessage},
+        models::{
+            capture::{CaptureConfig, CaptureSession, HandSplit, RIGHT_CHANNEL},
+            partiture::PieceMetadata,
+            rhythm::RHYTHM_METERS,
+            score::{Piece, Section},
+        },
+        styles::custom_style,
+        utils::{
+            note_names::note_name,
+            quantize::{MAX_SWING, QuantizeGrid},
+            reusable,
+        },
+        views::game::{beat_in_bar, beat_indicator},
+    },
+    iced::{
+        Element, Length,
+        alignment::{Horizontal, Vertical},
+        widget::{
+            Column, Container, Text, column, pick_list, row, scrollable, slider, text_input,
+            toggler,
+        },
+    },
+};
+
+// Grabar una pieza: ajustes antes de empezar, el pulso mientras se toca y la pieza resultante
+pub fn capture_view(
+    config: &CaptureConfig,
+    session: Option<&CaptureSession>,
+) -> Element<'static, AppMessage> {
+    let content: Column<AppMessage> = match session {
+        None => setup(config),
+        Some(session) if session.is_recording() => recording(session),
+        Some(session) => results(session),
+    };
+
+    Container::new(scrollable(
+        Container::new(content)
+            .width(Length::Fill)
+            .padding(40)
+            .align_x(Horizontal::Center),
+    ))
+    .width(Length::Fill)
+    .height(Length::Fill)
+    .align_x(Horizontal::Center)
+    .align_y(Vertical::Center)
+    .style(custom_style::background)
+    .into()
+}
+
+// Compás y tempo del metrónomo, rejilla y cómo se separan las manos
+fn setup(config: &CaptureConfig) -> Column<'static, AppMessage> {
+    // En los compuestos el pulso es la negra con puntillo
+    let pulse: &str = if config.metadata().is_compound() {
+        "negras con puntillo"
+    } else {
+        "pulsos"
+    };
+    let swing: String = if config.triplets {
+        "Sin swing con tresillos".to_string()
+    } else if config.swing <= 50 {
+        "Sin swing".to_string()
+    } else {
+        format!("Swing {}%", config.swing)
+    };
+
+    let mut content: Column<AppMessage> = column![
+        Text::new("Grabar partitura").size(32),
+        Text::new(
+            "Toca con el teclado del ordenador siguiendo el pulso; al parar, lo tocado se ajusta a la rejilla."
+        )
+        .size(18),
+        row![
+            Text::new("Compás").size(20),
+            pick_list(RHYTHM_METERS, Some(config.meter), |meter| {
+                AppMessage::Capture(CaptureMessage::Meter(meter))
+            }),
+            Text::new("Rejilla").size(20),
+            pick_list(QuantizeGrid::ALL, Some(config.grid), |grid| {
+                AppMessage::Capture(CaptureMessage::Grid(grid))
+            }),
+            toggler(config.triplets)
+                .label("Tresillos")
+                .text_size(20)
+                .style(custom_style::toogle_theme)
+                .on_toggle(|triplets| AppMessage::Capture(CaptureMessage::Triplets(triplets))),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center),
+        row![
+            Text::new(format!("{} {} por minuto", config.tempo, pulse))
+                .size(20)
+                .width(320),
+            slider(40..=160, config.tempo, |tempo| {
+                AppMessage::Capture(CaptureMessage::Tempo(tempo))
+            })
+            .width(300),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center),
+        row![
+            Text::new(swing).size(20).width(320),
+            slider(50..=MAX_SWING, config.swing, |swing| {
+                AppMessage::Capture(CaptureMessage::Swing(swing))
+            })
+            .width(300),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center),
+        row![
+            Text::new("Manos").size(20),
+            pick_list(HandSplit::ALL, Some(config.split), |split| {
+                AppMessage::Capture(CaptureMessage::Split(split))
+            }),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center),
+    ]
+    .spacing(20)
+    .align_x(Horizontal::Center);
+
+    content = content.push(match config.split {
+        HandSplit::Pitch => row![
+            Text::new(format!(
+                "Mano derecha desde {}",
+                note_name(config.split_pitch)
+            ))
+            .size(20)
+            .width(320),
+            slider(36..=84, config.split_pitch, |pitch| {
+                AppMessage::Capture(CaptureMessage::SplitPitch(pitch))
+            })
+            .width(300),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center),
+        HandSplit::Channel => row![
+            Text::new(
+                "Tab cambia de canal mientras grabas: canal 1 mano derecha, canal 2 izquierda"
+            )
+            .size(18)
+        ],
+    });
+
+    content.push(
+        row![
+            reusable::create_button(
+                AppMessage::Capture(CaptureMessage::Start),
+                Some("Grabar"),
+                None,
+                Some(24.0),
+            ),
+            reusable::create_button(
+                AppMessage::Capture(CaptureMessage::Back),
+                Some("Volver"),
+                None,
+                Some(20.0),
+            ),
+        ]
+        .spacing(20),
+    )
+}
+
+// La cuenta, el pulso visual, el canal y las notas que se llevan tocadas
+fn recording(session: &CaptureSession) -> Column<'static, AppMessage> {
+    let position: f32 = session.position();
+    let metadata: PieceMetadata = session.config.metadata();
+    let bar: f32 = metadata.bar_duration().max(f32::EPSILON);
+    let status: String = if position < 0.0 {
+        format!("Empieza en {:.1} s", -position)
+    } else {
+        format!("Compás {}", (position / bar).floor() as u32 + 1)
+    };
+    let hand: &str = if session.channel == RIGHT_CHANNEL {
+        "mano derecha"
+    } else {
+        "mano izquierda"
+    };
+
+    let mut content: Column<AppMessage> =
+        column![Text::new("Grabando").size(32), Text::new(status).size(20),]
+            .spacing(20)
+            .align_x(Horizontal::Center);
+
+    if let Some((current, beats)) = beat_in_bar(&metadata, position) {
+        content = content.push(beat_indicator(current, beats));
+    }
+    if session.config.split == HandSplit::Channel {
+        content = content.push(Text::new(format!("Canal {} ({})", session.channel, hand)).size(20));
+    }
+
+    let last: Vec<String> = session
+        .notes
+        .iter()
+        .rev()
+        .take(8)
+        .rev()
+        .map(|note| note_name(note.pitch))
+        .collect();
+    content
+        .push(Text::new(format!("{} notas: {}", session.notes.len(), last.join(" "))).size(20))
+        .push(reusable::create_button(
+            AppMessage::Capture(CaptureMessage::Stop),
+            Some("Parar"),
+            None,
+            Some(20.0),
+        ))
+}
+
+// Lo que ha quedado tras ajustar a la rejilla, con el nombre y el título para guardarlo
+fn results(session: &CaptureSession) -> Column<'static, AppMessage> {
+    let piece: Piece = session.to_piece();
+    let (right, left): (usize, usize) = piece
+        .sections
+        .values()
+        .fold((0, 0), |(right, left), section| {
+            (right + section.right.len(), left + section.left.len())
+        });
+    let bar: f32 = session.config.metadata().bar_duration().max(f32::EPSILON);
+    let length: f32 = piece
+        .sections
+        .values()
+        .map(Section::length)
+        .fold(0.0, f32::max);
+    let bars: u32 = (length / bar).ceil() as u32;
+
+    let mut content: Column<AppMessage> = column![
+        Text::new("Grabación terminada").size(32),
+        Text::new(format!(
+            "{} compases de {} · {} notas en la mano derecha y {} en la izquierda",
+            bars, piece.metadata.meter, right, left
+        ))
+        .size(20),
+        row![
+            Text::new("Nombre").size(18),
+            text_input("Nombre", &session.name)
+                .on_input(|name| AppMessage::Capture(CaptureMessage::Name(name)))
+                .width(220),
+            Text::new("Título").size(18),
+            text_input("Título", &session.title)
+                .on_input(|title| AppMessage::Capture(CaptureMessage::Title(title)))
+                .width(220),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center),
+    ]
+    .spacing(20)
+    .align_x(Horizontal::Center);
+
+    if let Some(status) = &session.status {
+        content = content.push(Text::new(status.clone()).size(18));
+    }
+
+    content.push(
+        row![
+            reusable::create_button(
+                AppMessage::Capture(CaptureMessage::Save),
+                Some("Guardar"),
+                None,
+                Some(20.0),
+            ),
+            reusable::create_button(
+                AppMessage::Capture(CaptureMessage::Edit),
+                Some("Abrir en el editor"),
+                None,
+                Some(20.0),
+            ),
+            reusable::create_button(
+                AppMessage::Capture(CaptureMessage::Retry),
+                Some("Grabar otra vez"),
+                None,
+                Some(20.0),
+            ),
+            reusable::create_button(
+                AppMessage::Capture(CaptureMessage::Back),
+                Some("Volver"),
+                None,
+                Some(20.0),
+            ),
+        ]
+        .spacing(20),
+    )
+}
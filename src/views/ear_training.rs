@@ -0,0 +1,229 @@
+use {
+    crate::{
+        message::states::{AppMessage, EarTrainingMessage},
+        models::ear_training::{
+            CategoryStats, EarCategory, EarTrainingSession, LevelScore, MAX_LEVEL,
+        },
+        styles::custom_style,
+        utils::{note_names::note_name, reusable},
+    },
+    iced::{
+        Element, Length,
+        alignment::{Horizontal, Vertical},
+        widget::{Column, Container, Row, Text, column, pick_list, row, text::Shaping, text_input},
+    },
+};
+
+// Botones de las notas del dictado: clase de nota y nombre
+const NOTES: [(u8, &str); 12] = [
+    (0, "Do"),
+    (1, "Do#"),
+    (2, "Re"),
+    (3, "Re#"),
+    (4, "Mi"),
+    (5, "Fa"),
+    (6, "Fa#"),
+    (7, "Sol"),
+    (8, "Sol#"),
+    (9, "La"),
+    (10, "La#"),
+    (11, "Si"),
+];
+
+const LEVELS: [(u8, &str); MAX_LEVEL as usize] = [(1, "Nivel 1"), (2, "Nivel 2"), (3, "Nivel 3")];
+
+// Botones de respuesta por fila
+const CHOICES_PER_ROW: usize = 6;
+
+// Entrenamiento auditivo: escuchar, contestar y ver los aciertos de cada categoría
+pub fn ear_training_view(session: &EarTrainingSession) -> Element<'static, AppMessage> {
+    let mut content: Column<AppMessage> = column![Text::new("Entrenamiento auditivo").size(32)]
+        .spacing(16)
+        .align_x(Horizontal::Center);
+
+    let levels: Row<AppMessage> = LEVELS.iter().fold(
+        row![pick_list(
+            EarCategory::ALL,
+            Some(session.category),
+            |category| AppMessage::EarTraining(EarTrainingMessage::Category(category)),
+        )]
+        .spacing(10)
+        .align_y(Vertical::Center),
+        |levels, (level, label)| {
+            levels.push(reusable::create_button(
+                AppMessage::EarTraining(EarTrainingMessage::Level(*level)),
+                Some(label),
+                None,
+                Some(18.0),
+            ))
+        },
+    );
+    content = content.push(levels);
+
+    content = content.push(
+        row![
+            reusable::create_button(
+                AppMessage::EarTraining(EarTrainingMessage::Play),
+                Some("Escuchar"),
+                None,
+                Some(24.0),
+            ),
+            Text::new(prompt(session)).size(20),
+        ]
+        .spacing(20)
+        .align_y(Vertical::Center),
+    );
+
+    content = content.push(answer_buttons(session));
+
+    content = content.push(
+        row![
+            Text::new("O escribe las notas (E G, mi sol…)").size(18),
+            text_input("", &session.typed)
+                .on_input(|text| AppMessage::EarTraining(EarTrainingMessage::Typed(text)))
+                .on_submit(AppMessage::EarTraining(EarTrainingMessage::Submit))
+                .width(200),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center),
+    );
+
+    // Cómo fue la pregunta anterior y la racha
+    let mut status: Vec<String> = Vec::new();
+    if let Some(feedback) = &session.feedback {
+        status.push(feedback.clone());
+    }
+    status.push(format!("Racha: {}", session.streak));
+    content = content.push(
+        Text::new(status.join("  ·  "))
+            .size(20)
+            .shaping(Shaping::Advanced),
+    );
+
+    content = content.push(stats_table(session));
+
+    content = content.push(reusable::create_button(
+        AppMessage::EarTraining(EarTrainingMessage::Back),
+        Some("Volver al menú"),
+        None,
+        Some(20.0),
+    ));
+
+    Container::new(content)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .padding(30)
+        .align_x(Horizontal::Center)
+        .align_y(Vertical::Center)
+        .style(custom_style::background)
+        .into()
+}
+
+// Qué hay que contestar, con la nota de referencia
+fn prompt(session: &EarTrainingSession) -> String {
+    let given: String = note_name(session.question.given());
+    match session.category {
+        EarCategory::Interval => format!("¿Qué intervalo es? Empieza en {}", given),
+        EarCategory::Chord => format!("¿Qué acorde es? La nota más grave es {}", given),
+        EarCategory::Melody => {
+            let answered: Vec<String> = session
+                .notes
+                .iter()
+                .map(|&class| NOTES[class as usize % 12].1.to_string())
+                .collect();
+            format!(
+                "Empieza en {}. Notas: {}/{} {}",
+                given,
+                session.notes.len(),
+                session.question.answer_len(),
+                answered.join(" ")
+            )
+        }
+    }
+}
+
+// Botones del intervalo o del acorde, o de las notas en el dictado
+fn answer_buttons(session: &EarTrainingSession) -> Column<'static, AppMessage> {
+    let buttons: Vec<(AppMessage, &'static str)> = match session.category {
+        EarCategory::Melody => NOTES
+            .iter()
+            .map(|(class, label)| {
+                (
+                    AppMessage::EarTraining(EarTrainingMessage::Note(*class)),
+                    *label,
+                )
+            })
+            .chain([(AppMessage::EarTraining(EarTrainingMessage::Undo), "Borrar")])
+            .collect(),
+        category => category
+            .choices(session.level())
+            .into_iter()
+            .map(|choice| {
+                (
+                    AppMessage::EarTraining(EarTrainingMessage::Choice(choice)),
+                    category.choice_name(choice),
+                )
+            })
+            .collect(),
+    };
+
+    buttons.chunks(CHOICES_PER_ROW).fold(
+        column![].spacing(10).align_x(Horizontal::Center),
+        |rows, chunk| {
+            let cells: Row<AppMessage> =
+                chunk
+                    .iter()
+                    .fold(row![].spacing(10), |cells, (message, label)| {
+                        cells.push(reusable::create_button(
+                            message.clone(),
+                            Some(*label),
+                            None,
+                            Some(18.0),
+                        ))
+                    });
+            rows.push(cells)
+        },
+    )
+}
+
+// Aciertos de cada categoría en su nivel elegido y en total
+fn stats_table(session: &EarTrainingSession) -> Column<'static, AppMessage> {
+    EarCategory::ALL
+        .iter()
+        .fold(column![].spacing(6), |table, &category| {
+            let stats: CategoryStats = session.stats.category(category);
+            let level: u8 = stats.level();
+            table.push(
+                row![
+                    Text::new(category.to_string())
+                        .size(16)
+                        .width(Length::Fixed(180.0)),
+                    Text::new(format!(
+                        "Nivel {}: {}",
+                        level,
+                        score_label(stats.score(level))
+                    ))
+                    .size(16)
+                    .width(Length::Fixed(220.0)),
+                    Text::new(format!("Total: {}", score_label(stats.total())))
+                        .size(16)
+                        .width(Length::Fixed(220.0)),
+                    Text::new(format!("Mejor racha: {}", stats.best_streak)).size(16),
+                ]
+                .spacing(10),
+            )
+        })
+}
+
+// "7/10 (70 %)", o una raya si aún no se ha contestado nada
+fn score_label(score: LevelScore) -> String {
+    match score.accuracy() {
+        Some(accuracy) => format!(
+            "{}/{} ({:.0} %)",
+            score.correct,
+            score.asked,
+            accuracy * 100.0
+        ),
+        None => "—".to_string(),
+    }
+}
@@ -0,0 +1,299 @@
+use {
+    crate::{
+        asset_path,
+        message::states::{AppMessage, EditorMessage},
+        models::{
+            editor::{ScoreEditor, TextField},
+            markings::Markings,
+            partiture::{Hand, Partiture, PieceMetadata},
+            playback::PlaybackClock,
+            rhythm::RHYTHM_METERS,
+            settings::CustomSettings,
+        },
+        styles::custom_style,
+        utils::{
+            helper_json::build_partitures,
+            reusable::{self, create_image},
+            sight_reading::{Key, NoteValue},
+        },
+        widgets::editor_staff::EditorStaff,
+    },
+    iced::{
+        Element, Length, Padding,
+        alignment::{Horizontal, Vertical},
+        widget::{
+            Canvas, Column, Container, Row, Stack, Text, column, pick_list, row, scrollable,
+            text_input,
+        },
+    },
+};
+
+// Alto de cada pentagrama, el mismo que en el juego
+const STAFF_HEIGHT: f32 = 200.0;
+
+// Atajos de teclado, mientras no se está escribiendo en un campo de texto
+const SHORTCUTS: &str = "Teclas a s d f... (negras w e t y u...): escribir nota · Mayús: añadir al acorde · r: silencio\n\
+1-5: redonda a semicorchea · .: puntillo · ←/→: mover el cursor · ↑/↓: subir o bajar un semitono\n\
+Re Pág/Av Pág: octava · Tab: cambiar de mano · Retroceso/Supr: borrar · Ctrl+Z/Ctrl+Y: deshacer/rehacer · Ctrl+S: guardar";
+
+// Editor de partituras: metadatos, secciones, la figura elegida y el gran pentagrama con el cursor
+pub fn editor_view(
+    editor: &ScoreEditor,
+    settings: &CustomSettings,
+) -> Element<'static, AppMessage> {
+    let (left, right) = partitures(editor, settings);
+
+    let content: Column<AppMessage> = column![
+        Text::new("Editor de partituras").size(32),
+        metadata_rows(editor),
+        section_row(editor),
+        palette_row(editor),
+        hand_staff(right, editor, "clave-de-sol.png", 180.0, 10.0),
+        hand_staff(left, editor, "clave-de-fa.png", 150.0, 20.0),
+        Text::new(status(editor)).size(18),
+        Text::new(SHORTCUTS).size(14),
+        row![
+            button(EditorMessage::Undo, "Deshacer", editor.can_undo()),
+            button(EditorMessage::Redo, "Rehacer", editor.can_redo()),
+            reusable::create_button(
+                AppMessage::Editor(EditorMessage::Save),
+                Some("Guardar"),
+                None,
+                Some(20.0),
+            ),
+            reusable::create_button(
+                AppMessage::Editor(EditorMessage::Back),
+                Some("Volver"),
+                None,
+                Some(20.0),
+            ),
+        ]
+        .spacing(20),
+    ]
+    .spacing(16)
+    .align_x(Horizontal::Center);
+
+    Container::new(scrollable(
+        Container::new(content)
+            .width(Length::Fill)
+            .padding(30)
+            .align_x(Horizontal::Center),
+    ))
+    .width(Length::Fill)
+    .height(Length::Fill)
+    .align_x(Horizontal::Center)
+    .align_y(Vertical::Center)
+    .style(custom_style::background)
+    .into()
+}
+
+// Botón que solo se puede pulsar si hay algo que deshacer o rehacer
+fn button(
+    message: EditorMessage,
+    label: &str,
+    enabled: bool,
+) -> iced::widget::Button<'_, AppMessage> {
+    let button =
+        reusable::create_button(AppMessage::Editor(message), Some(label), None, Some(20.0));
+    if enabled {
+        button
+    } else {
+        button.on_press_maybe(None)
+    }
+}
+
+// Nombre en la biblioteca, título, compositor, compás, tonalidad y tempo
+fn metadata_rows(editor: &ScoreEditor) -> Column<'static, AppMessage> {
+    let text_field = |label: &'static str, field: TextField, value: &str| -> Row<AppMessage> {
+        row![
+            Text::new(label).size(18),
+            text_input(label, value)
+                .on_input(move |text| AppMessage::Editor(EditorMessage::Text(field, text)))
+                .width(220),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center)
+    };
+
+    // Todas las tonalidades en el orden del círculo de quintas, primero las mayores
+    let keys: Vec<Key> = [false, true]
+        .into_iter()
+        .flat_map(|minor| (0..12).map(move |i| Key::new(i * 7 % 12, minor)))
+        .collect();
+    let metadata = &editor.piece.metadata;
+
+    column![
+        row![
+            text_field("Nombre", TextField::Name, &editor.name),
+            text_field("Título", TextField::Title, &metadata.title),
+            text_field(
+                "Compositor",
+                TextField::Composer,
+                metadata.composer.as_deref().unwrap_or_default()
+            ),
+        ]
+        .spacing(20),
+        row![
+            Text::new("Compás").size(18),
+            pick_list(RHYTHM_METERS, Some(metadata.meter), |meter| {
+                AppMessage::Editor(EditorMessage::Meter(meter))
+            }),
+            Text::new("Tonalidad").size(18),
+            pick_list(keys, editor.key(), |key| {
+                AppMessage::Editor(EditorMessage::Key(key))
+            }),
+            text_field(
+                "Tempo",
+                TextField::Tempo,
+                metadata.tempo.as_deref().unwrap_or_default()
+            ),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center),
+    ]
+    .spacing(10)
+    .align_x(Horizontal::Center)
+}
+
+// Sección que se edita y orden de reproducción (`structure`)
+fn section_row(editor: &ScoreEditor) -> Row<'static, AppMessage> {
+    let labels: Vec<String> = editor.piece.sections.keys().cloned().collect();
+    let structure: String = editor.piece.metadata.structure.join(" ");
+
+    row![
+        Text::new("Sección").size(18),
+        pick_list(labels, Some(editor.section.clone()), |label| {
+            AppMessage::Editor(EditorMessage::Section(label))
+        }),
+        small_button(EditorMessage::AddSection, "Nueva"),
+        small_button(EditorMessage::RemoveSection, "Quitar"),
+        Text::new(format!("Estructura: {}", structure)).size(18),
+        small_button(EditorMessage::PushStructure, "Repetir sección"),
+        small_button(EditorMessage::PopStructure, "Quitar la última"),
+    ]
+    .spacing(10)
+    .align_y(Vertical::Center)
+}
+
+fn small_button(message: EditorMessage, label: &str) -> iced::widget::Button<'_, AppMessage> {
+    iced::widget::Button::new(Text::new(label).size(16))
+        .on_press(AppMessage::Editor(message))
+        .padding(8)
+        .style(custom_style::button_selection)
+}
+
+// Figura de las notas, mano y octava del teclado
+fn palette_row(editor: &ScoreEditor) -> Row<'static, AppMessage> {
+    let hand: &str = match editor.hand {
+        Hand::Right => "Mano derecha",
+        Hand::Left => "Mano izquierda",
+    };
+
+    row![
+        Text::new("Figura").size(18),
+        pick_list(NoteValue::ALL, Some(editor.value), |value| {
+            AppMessage::Editor(EditorMessage::Value(value))
+        }),
+        small_button(EditorMessage::Rest, "Silencio"),
+        small_button(EditorMessage::ToggleHand, hand),
+        Text::new(format!("Octava {:+}", editor.octave)).size(18),
+    ]
+    .spacing(10)
+    .align_y(Vertical::Center)
+}
+
+// Compás del cursor, cambios sin guardar y el resultado de la última acción
+fn status(editor: &ScoreEditor) -> String {
+    let bar: f32 = editor.bar_seconds();
+    let position: String = if bar > 0.0 {
+        let beat: f32 = editor.cursor.rem_euclid(bar) / editor.quarter_seconds().max(f32::EPSILON);
+        format!(
+            "Compás {}, negra {:.2}",
+            (editor.cursor / bar + 1e-3).floor() as u32 + 1,
+            beat + 1.0
+        )
+    } else {
+        format!("{:.2} s", editor.cursor)
+    };
+
+    let mut status: String = format!("Sección {} · {}", editor.section, position);
+    if editor.dirty {
+        status.push_str(" · Cambios sin guardar");
+    }
+    if let Some(message) = &editor.status {
+        status.push_str(&format!(" · {}", message));
+    }
+    status
+}
+
+/// Las dos manos de la sección actual como partituras paradas un compás antes del cursor,
+/// para ver lo último que se ha escrito
+fn partitures(editor: &ScoreEditor, settings: &CustomSettings) -> (Partiture, Partiture) {
+    let metadata: PieceMetadata = PieceMetadata::from(&editor.piece.metadata);
+    let bar: f32 = metadata.bar_duration().max(f32::EPSILON);
+    let markings: Markings = editor
+        .current_section()
+        .map(|section| section.markings.shifted(-section.start_time))
+        .unwrap_or_default();
+
+    let clock: PlaybackClock = PlaybackClock::new();
+    clock.seek(((editor.cursor / bar + 1e-3).floor() - 1.0).max(0.0) * bar);
+
+    let (mut left, mut right): (Partiture, Partiture) = build_partitures(
+        editor.section_notes(Hand::Left),
+        editor.section_notes(Hand::Right),
+        markings,
+        metadata,
+        settings,
+        &clock,
+    );
+    // Sin cuenta previa, y con compases dibujados hasta pasado el cursor
+    for partiture in [&mut left, &mut right] {
+        partiture.settings.timer = 0.0;
+        partiture.time = partiture.time.max(editor.cursor) + bar;
+    }
+    (left, right)
+}
+
+// Pentagrama de una mano con su clave, igual que `create_grand_staff`
+fn hand_staff(
+    partiture: Partiture,
+    editor: &ScoreEditor,
+    clef: &str,
+    clef_height: f32,
+    clef_top: f32,
+) -> Container<'static, AppMessage> {
+    let active: bool = partiture.hand == editor.hand;
+    let staff: Canvas<EditorStaff, AppMessage> = Canvas::new(EditorStaff {
+        partiture,
+        cursor: editor.cursor,
+        active,
+    })
+    .width(Length::Fill)
+    .height(Length::Fixed(STAFF_HEIGHT));
+
+    let clef_image: Container<AppMessage> =
+        Container::new(create_image(&asset_path!(clef), clef_height, 80.0))
+            .height(Length::Fixed(STAFF_HEIGHT))
+            .padding(Padding {
+                top: clef_top,
+                right: 20.0,
+                bottom: 0.0,
+                left: 20.0,
+            });
+
+    Container::new(
+        Stack::new()
+            .push(staff)
+            .push(clef_image)
+            .height(Length::Fixed(STAFF_HEIGHT)),
+    )
+    .height(Length::Fixed(STAFF_HEIGHT))
+    .width(Length::Fill)
+    .padding(Padding {
+        top: 0.0,
+        right: 20.0,
+        bottom: 0.0,
+        left: 20.0,
+    })
+}
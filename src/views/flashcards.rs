@@ -0,0 +1,176 @@
+use {
+    crate::{
+        asset_path,
+        message::states::{AppMessage, FlashcardMessage},
+        models::flashcards::{Clef, FlashcardSession, MAX_CLUSTER, PitchStats},
+        styles::custom_style,
+        utils::{
+            note_names::note_name,
+            reusable::{self, create_image},
+        },
+        widgets::flashcard::{FLASHCARD_HEIGHT, FLASHCARD_STAFF_TOP, FlashcardStaff},
+    },
+    iced::{
+        Element, Length, Padding,
+        alignment::{Horizontal, Vertical},
+        widget::{
+            Canvas, Column, Container, Row, Stack, Text, column, pick_list, row, text::Shaping,
+            text_input,
+        },
+    },
+};
+
+// Botones de respuesta: clase de nota y nombre en solfeo y en letra
+const ANSWERS: [(u8, &str); 7] = [
+    (0, "Do · C"),
+    (2, "Re · D"),
+    (4, "Mi · E"),
+    (5, "Fa · F"),
+    (7, "Sol · G"),
+    (9, "La · A"),
+    (11, "Si · B"),
+];
+
+const CLUSTERS: [(usize, &str); MAX_CLUSTER] = [(1, "1 nota"), (2, "2 notas"), (3, "3 notas")];
+
+// Notas por fila en la tabla de estadísticas
+const STATS_PER_ROW: usize = 6;
+
+// Tarjetas de lectura de notas: la tarjeta, las respuestas y lo aprendido de cada nota
+pub fn flashcards_view(session: &FlashcardSession) -> Element<'static, AppMessage> {
+    let mut content: Column<AppMessage> = column![Text::new("Lectura de notas").size(32)]
+        .spacing(16)
+        .align_x(Horizontal::Center);
+
+    let clusters: Row<AppMessage> = CLUSTERS.iter().fold(
+        row![pick_list(Clef::ALL, Some(session.clef), |clef| {
+            AppMessage::Flashcards(FlashcardMessage::Clef(clef))
+        })]
+        .spacing(10)
+        .align_y(Vertical::Center),
+        |clusters, (size, label)| {
+            clusters.push(reusable::create_button(
+                AppMessage::Flashcards(FlashcardMessage::Cluster(*size)),
+                Some(label),
+                None,
+                Some(18.0),
+            ))
+        },
+    );
+    content = content.push(clusters);
+
+    content = content.push(card(session));
+
+    let answers: Row<AppMessage> =
+        ANSWERS
+            .iter()
+            .fold(row![].spacing(10), |answers, (pitch_class, label)| {
+                answers.push(reusable::create_button(
+                    AppMessage::Flashcards(FlashcardMessage::Answer(*pitch_class)),
+                    Some(label),
+                    None,
+                    Some(20.0),
+                ))
+            });
+    content = content.push(answers);
+
+    content = content.push(
+        row![
+            Text::new("O escribe la nota (C, do, C4…)").size(18),
+            text_input("", &session.typed)
+                .on_input(|text| AppMessage::Flashcards(FlashcardMessage::Typed(text)))
+                .on_submit(AppMessage::Flashcards(FlashcardMessage::Submit))
+                .width(160),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center),
+    );
+
+    // Cuántas notas del grupo quedan y cómo fue la tarjeta anterior
+    let mut status: Vec<String> = Vec::new();
+    if session.card.pitches.len() > 1 {
+        status.push(format!(
+            "Faltan {} de {}",
+            session.remaining.len(),
+            session.card.pitches.len()
+        ));
+    }
+    if let Some(feedback) = &session.feedback {
+        status.push(feedback.clone());
+    }
+    status.push(format!("Racha: {}", session.streak));
+    content = content.push(
+        Text::new(status.join("  ·  "))
+            .size(20)
+            .shaping(Shaping::Advanced),
+    );
+
+    content = content.push(stats_table(session));
+
+    content = content.push(reusable::create_button(
+        AppMessage::Flashcards(FlashcardMessage::Back),
+        Some("Volver al menú"),
+        None,
+        Some(20.0),
+    ));
+
+    Container::new(content)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .padding(30)
+        .align_x(Horizontal::Center)
+        .align_y(Vertical::Center)
+        .style(custom_style::background)
+        .into()
+}
+
+// La tarjeta: el pentagrama con las notas y la clave encima, como en el juego
+fn card(session: &FlashcardSession) -> Container<'static, AppMessage> {
+    let (clef_image, width, top): (String, f32, f32) = match session.clef {
+        Clef::Treble => (asset_path!("clave-de-sol.png"), 180.0, 10.0),
+        Clef::Bass => (asset_path!("clave-de-fa.png"), 150.0, 20.0),
+    };
+    let clef: Container<AppMessage> = Container::new(create_image(&clef_image, width, 80.0))
+        .padding(Padding {
+            top: FLASHCARD_STAFF_TOP + top,
+            right: 20.0,
+            bottom: 0.0,
+            left: 20.0,
+        });
+
+    let staff: Canvas<FlashcardStaff, AppMessage> = Canvas::new(FlashcardStaff {
+        card: session.card.clone(),
+    })
+    .width(Length::Fill)
+    .height(Length::Fixed(FLASHCARD_HEIGHT));
+
+    Container::new(Stack::new().push(staff).push(clef))
+        .width(Length::Fixed(700.0))
+        .height(Length::Fixed(FLASHCARD_HEIGHT))
+}
+
+// Aciertos y tiempo medio de cada nota de la clave, en filas
+fn stats_table(session: &FlashcardSession) -> Column<'static, AppMessage> {
+    let pitches: Vec<u8> = session.clef.pitches();
+    pitches
+        .chunks(STATS_PER_ROW)
+        .fold(column![].spacing(6), |table, chunk| {
+            let cells: Row<AppMessage> = chunk.iter().fold(row![].spacing(10), |cells, &pitch| {
+                let stats: PitchStats = session.stats.pitch(pitch);
+                let label: String = match (stats.accuracy(), stats.mean_seconds()) {
+                    (Some(accuracy), Some(seconds)) => format!(
+                        "{} {:.0} % {:.1} s",
+                        note_name(pitch),
+                        accuracy * 100.0,
+                        seconds
+                    ),
+                    (Some(accuracy), None) => {
+                        format!("{} {:.0} %", note_name(pitch), accuracy * 100.0)
+                    }
+                    _ => format!("{} —", note_name(pitch)),
+                };
+                cells.push(Text::new(label).size(14).width(Length::Fixed(110.0)))
+            });
+            table.push(cells)
+        })
+}
@@ -1,7 +1,10 @@
 use {
     crate::{
-        message::states::AppMessage,
-        models::{partiture::Partiture, settings::CustomSettings},
+        message::states::{AppMessage, GameMessage},
+        models::{
+            partiture::{Partiture, PieceMetadata},
+            settings::CustomSettings,
+        },
         styles::custom_style::{self, ColorPalette},
         utils::utils,
         widgets::intro_overlay::IntroOverlay,
@@ -9,7 +12,7 @@ use {
     iced::{
         Color, Element, Length,
         alignment::{Horizontal, Vertical},
-        widget::{Canvas, Column, Container, Stack, Text, column, text::Shaping},
+        widget::{Canvas, Column, Container, Row, Stack, Text, column, row, slider, text::Shaping},
     },
 };
 
@@ -17,9 +20,12 @@ use {
 pub fn game_view<'a>(
     partiture: (&'a Partiture, &'a Partiture),
     settings: &CustomSettings,
+    count_in_target: Option<f32>,
+    rewards: Vec<String>,
 ) -> Element<'a, AppMessage> {
     // Extraer elapsed antes de mover partiture
-    let elapsed: f32 = partiture.0.elapsed;
+    let elapsed: f32 = partiture.0.elapsed();
+    let progress: Row<AppMessage> = progress_bar(partiture.0, settings);
 
     // Crear imagen de el gran pentagrama, calve de sol y clave de fa para ambas partituras
     let (partiture_r_overlay, partiture_l_overlay) = utils::create_grand_staff(partiture);
@@ -28,12 +34,22 @@ pub fn game_view<'a>(
         .width(Length::Fill)
         .height(Length::Fill);
 
+    // Pulso actual dentro del compás, si la pieza tiene compás
+    let beat: Option<(u32, u32)> = partiture
+        .0
+        .metadata
+        .as_ref()
+        .and_then(|metadata| beat_in_bar(metadata, elapsed - settings.timer));
+
     // Crear la columna principal del juego
-    let game_column: Column<AppMessage> = column![
-        partiture_r_overlay, // Parte mano derecha de la partitura
-        partiture_l_overlay, // Parte mano izquierda de la partitura
-    ]
-    .spacing(20);
+    let mut game_column: Column<AppMessage> = column![].spacing(20);
+    if let (true, Some((current, beats))) = (settings.metronome.visual_beat, beat) {
+        game_column = game_column.push(beat_indicator(current, beats));
+    }
+    game_column = game_column
+        .push(partiture_r_overlay) // Parte mano derecha de la partitura
+        .push(partiture_l_overlay) // Parte mano izquierda de la partitura
+        .push(progress); // Barra de progreso para avanzar o retroceder
 
     // Contenedor base del juego
     let game_container = Container::new(game_column)
@@ -45,13 +61,27 @@ pub fn game_view<'a>(
     // Crear el stack base
     let mut stack: Stack<AppMessage> = Stack::new();
 
-    // Mostramos el timer inicial
-    if elapsed < settings.timer {
-        stack = stack.push(draw_intro_overlay(elapsed));
+    // Durante la cuenta previa se muestra el número de pulso del compás,
+    // o los segundos si la pieza no tiene compás
+    let counter: String = match beat {
+        Some((current, _)) => current.to_string(),
+        None => (elapsed.ceil() as i32).max(1).to_string(),
+    };
+
+    // Mostramos la cuenta previa al saltar a un compás, o la del inicio
+    if count_in_target.is_some() {
+        stack = stack.push(game_container).push(draw_intro_overlay(counter));
+    } else if elapsed < settings.timer {
+        stack = stack.push(draw_intro_overlay(counter));
     } else {
         stack = stack.push(game_container)
     }
 
+    // Las recompensas recién ganadas se avisan encima de todo
+    if !rewards.is_empty() {
+        stack = stack.push(draw_reward_notice(rewards));
+    }
+
     Container::new(stack)
         .width(Length::Fill)
         .height(Length::Fill)
@@ -59,11 +89,90 @@ pub fn game_view<'a>(
         .into()
 }
 
+// Barra de progreso con el compás y la sección actuales
+fn progress_bar<'a>(partiture: &Partiture, settings: &CustomSettings) -> Row<'a, AppMessage> {
+    let position: f32 = (partiture.elapsed() - settings.timer).clamp(0.0, partiture.time);
+
+    let (bar, total_bars, section): (usize, usize, &str) = match &partiture.metadata {
+        Some(metadata) if metadata.bar_duration() > 0.0 => {
+            let bar_duration: f32 = metadata.bar_duration();
+            (
+                (position / bar_duration).floor() as usize + 1,
+                (partiture.time / bar_duration).ceil() as usize,
+                current_section(metadata, position),
+            )
+        }
+        _ => (1, 1, ""),
+    };
+
+    let scrubber = slider(0.0..=partiture.time.max(0.01), position, |value| {
+        AppMessage::Game(GameMessage::Scrub(value))
+    })
+    .on_release(AppMessage::Game(GameMessage::ScrubRelease))
+    .step(0.01)
+    .width(Length::Fill);
+
+    let label: Text = Text::new(format!(
+        "Compás {}/{}  {}",
+        bar.min(total_bars),
+        total_bars,
+        section
+    ))
+    .size(18);
+
+    row![scrubber, label]
+        .spacing(20)
+        .padding([0, 20])
+        .align_y(Vertical::Center)
+}
+
+// Etiqueta de la sección que suena en ese segundo
+fn current_section(metadata: &PieceMetadata, position: f32) -> &str {
+    metadata
+        .sections
+        .iter()
+        .rev()
+        .find(|(_, start)| *start <= position)
+        .map(|(label, _)| label.as_str())
+        .unwrap_or("")
+}
+
+// Pulso dentro del compás (empezando en 1) y pulsos por compás en un segundo de la partitura
+pub fn beat_in_bar(metadata: &PieceMetadata, score_time: f32) -> Option<(u32, u32)> {
+    let (beats, pulse_seconds) = metadata.pulses();
+    if pulse_seconds <= 0.0 || beats == 0 {
+        return None;
+    }
+    let beat: i64 = (score_time / pulse_seconds).floor() as i64;
+    Some((beat.rem_euclid(beats as i64) as u32 + 1, beats))
+}
+
+// Indicador visual del pulso: un punto por pulso, resaltando el actual
+pub fn beat_indicator<'a>(current: u32, beats: u32) -> Row<'a, AppMessage> {
+    let mut indicator: Row<AppMessage> = Row::new().spacing(12).padding([0, 20]);
+    for beat in 1..=beats {
+        let color: Color = if beat == current {
+            ColorPalette::ACCENT_ORANGE
+        } else {
+            ColorPalette::SHADOW_DARK
+        };
+        // El primer pulso del compás es más grande, como el click acentuado
+        let size: f32 = if beat == 1 { 30.0 } else { 22.0 };
+        indicator = indicator.push(
+            Text::new("●")
+                .size(size)
+                .color(color)
+                .shaping(Shaping::Advanced),
+        );
+    }
+    indicator.align_y(Vertical::Center)
+}
+
 // Dibujar el temporizador de introducción
 // Renderiza overlay de cuenta regresiva pre-partitura con animaciones fluidas
-fn draw_intro_overlay<'a>(elapsed: f32) -> Container<'a, AppMessage> {
+fn draw_intro_overlay<'a>(label: String) -> Container<'a, AppMessage> {
     // Solo opacidad, tamaño fijo para evitar temblor
-    let counter = Text::new((elapsed.ceil() as i32).max(1).to_string())
+    let counter = Text::new(label)
         .size(250.0)
         .color(Color::from_rgba(
             ColorPalette::ACCENT_ORANGE.r,
@@ -92,3 +201,35 @@ fn draw_intro_overlay<'a>(elapsed: f32) -> Container<'a, AppMessage> {
             ..Default::default()
         })
 }
+
+// Aviso de las recompensas recién ganadas, arriba a la derecha
+fn draw_reward_notice<'a>(rewards: Vec<String>) -> Container<'a, AppMessage> {
+    let lines: Column<AppMessage> =
+        rewards
+            .into_iter()
+            .fold(column![].spacing(6), |lines, reward| {
+                lines.push(
+                    Text::new(reward)
+                        .size(20)
+                        .color(ColorPalette::DARK_TEXT)
+                        .shaping(Shaping::Advanced),
+                )
+            });
+
+    Container::new(
+        Container::new(lines)
+            .padding(16)
+            .style(|_| iced::widget::container::Style {
+                background: Some(Color::from_rgba(0.0, 0.0, 0.0, 0.75).into()),
+                border: iced::Border {
+                    color: ColorPalette::ACCENT_ORANGE,
+                    width: 2.0,
+                    radius: 8.0.into(),
+                },
+                ..Default::default()
+            }),
+    )
+    .width(Length::Fill)
+    .align_x(Horizontal::Right)
+    .padding(20)
+}
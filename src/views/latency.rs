@@ -0,0 +1,167 @@
+use {
+    crate::{
+        message::states::{AppMessage, LatencyMessage},
+        models::{
+            latency::{TAPS_NEEDED, TapTest},
+            settings::CustomSettings,
+            take::InputSource,
+        },
+        styles::custom_style,
+        utils::reusable,
+    },
+    iced::{
+        Element, Length,
+        alignment::{Horizontal, Vertical},
+        widget::{Column, Container, Row, Text, column, row, slider, text::Shaping},
+    },
+};
+
+// Rango de los ajustes manuales, en segundos
+const MIN_OFFSET: f32 = -0.1;
+const MAX_OFFSET: f32 = 0.4;
+
+// Segundos que se ilumina el indicador en cada pulso
+const FLASH_SECONDS: f32 = 0.12;
+
+// Prueba de latencia: seguir el pulso con una entrada y ajustar el retraso de cada una.
+// El teclado MIDI solo aparece si hay uno conectado (`midi`).
+pub fn latency_view(
+    test: Option<&TapTest>,
+    settings: &CustomSettings,
+    midi: bool,
+) -> Element<'static, AppMessage> {
+    let mut content: Column<AppMessage> = column![
+        Text::new("Latencia de entrada").size(32),
+        Text::new(
+            "Pulsa al ritmo del click y del círculo: con el teclado cualquier tecla, con el teclado MIDI o el micrófono toca una nota, o da una palmada al micrófono."
+        )
+        .size(18),
+    ]
+    .spacing(20)
+    .align_x(Horizontal::Center);
+
+    let mut sources: Row<AppMessage> = row![reusable::create_button(
+        AppMessage::Latency(LatencyMessage::Start(InputSource::Keyboard)),
+        Some("Probar teclado"),
+        None,
+        Some(18.0),
+    )]
+    .spacing(20);
+    if midi {
+        sources = sources.push(reusable::create_button(
+            AppMessage::Latency(LatencyMessage::Start(InputSource::Midi)),
+            Some("Probar teclado MIDI"),
+            None,
+            Some(18.0),
+        ));
+    }
+    sources = sources.push(reusable::create_button(
+        AppMessage::Latency(LatencyMessage::Start(InputSource::Microphone)),
+        Some("Probar micrófono"),
+        None,
+        Some(18.0),
+    ));
+    content = content.push(sources);
+
+    if let Some(test) = test {
+        content = content.push(beat_indicator(test));
+
+        if let Some(result) = test.result() {
+            content = content.push(
+                Text::new(format!(
+                    "{}: {:.0} ms de retraso (± {:.0} ms)",
+                    source_name(result.source),
+                    result.offset * 1000.0,
+                    result.spread * 1000.0
+                ))
+                .size(20),
+            );
+            content = content.push(reusable::create_button(
+                AppMessage::Latency(LatencyMessage::Save),
+                Some("Guardar"),
+                None,
+                Some(18.0),
+            ));
+        }
+    }
+
+    // Ajuste fino a mano de cada entrada
+    for source in [
+        InputSource::Keyboard,
+        InputSource::Midi,
+        InputSource::Microphone,
+    ]
+    .into_iter()
+    .filter(|source| midi || *source != InputSource::Midi)
+    {
+        content = content.push(offset_slider(source, settings.input_latency(source)));
+    }
+
+    content = content.push(reusable::create_button(
+        AppMessage::Latency(LatencyMessage::Back),
+        Some("Back"),
+        None,
+        Some(20.0),
+    ));
+
+    Container::new(content)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .padding(40)
+        .align_x(Horizontal::Center)
+        .align_y(Vertical::Center)
+        .style(custom_style::background)
+        .into()
+}
+
+// Círculo que se ilumina en cada pulso, con la cuenta y las pulsaciones registradas
+fn beat_indicator(test: &TapTest) -> Element<'static, AppMessage> {
+    let Some((beat, since)) = test.beat().filter(|_| test.is_running()) else {
+        return Text::new(format!("Pulsaciones: {}/{}", test.taps(), TAPS_NEEDED))
+            .size(20)
+            .into();
+    };
+
+    let flash: &str = if since < FLASH_SECONDS { "●" } else { "○" };
+    let label: String = if beat < 0 {
+        format!("Cuenta: {}", -beat)
+    } else {
+        format!("Pulsaciones: {}/{}", test.taps(), TAPS_NEEDED)
+    };
+
+    column![
+        Text::new(flash).size(72).shaping(Shaping::Advanced),
+        Text::new(label).size(20),
+    ]
+    .spacing(10)
+    .align_x(Horizontal::Center)
+    .into()
+}
+
+fn offset_slider(source: InputSource, offset: f32) -> Element<'static, AppMessage> {
+    row![
+        Text::new(format!(
+            "{}: {:.0} ms",
+            source_name(source),
+            offset * 1000.0
+        ))
+        .size(18)
+        .width(200),
+        slider(MIN_OFFSET..=MAX_OFFSET, offset, move |value| {
+            AppMessage::Latency(LatencyMessage::Adjust(source, value))
+        })
+        .step(0.005)
+        .width(300),
+    ]
+    .spacing(10)
+    .align_y(Vertical::Center)
+    .into()
+}
+
+fn source_name(source: InputSource) -> &'static str {
+    match source {
+        InputSource::Keyboard => "Teclado",
+        InputSource::Midi => "MIDI",
+        InputSource::Microphone => "Micrófono",
+    }
+}
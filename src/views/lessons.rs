@@ -0,0 +1,146 @@
+use {
+    crate::{
+        message::states::{AppMessage, LessonMessage},
+        models::curriculum::{Curriculum, Lesson, LessonProgress},
+        styles::custom_style,
+        utils::reusable,
+    },
+    iced::{
+        Element, Length,
+        alignment::{Horizontal, Vertical},
+        widget::{Button, Column, Container, Text, column, scrollable, text::Shaping},
+    },
+};
+
+// Mapa de lecciones: los cursos en orden con lo aprobado, lo abierto y lo bloqueado
+pub fn lesson_map_view(
+    curriculum: &Curriculum,
+    progress: &LessonProgress,
+) -> Element<'static, AppMessage> {
+    let mut content: Column<AppMessage> = column![Text::new("Lecciones").size(32)]
+        .spacing(20)
+        .align_x(Horizontal::Center);
+
+    if curriculum.courses.is_empty() {
+        content = content.push(Text::new("No hay cursos en assets/courses.").size(20));
+    }
+
+    for course in &curriculum.courses {
+        let passed: usize = course
+            .lessons
+            .iter()
+            .filter(|lesson| progress.is_passed(&lesson.id))
+            .count();
+        content = content.push(
+            Text::new(format!(
+                "{}  ·  {}/{}",
+                course.title,
+                passed,
+                course.lessons.len()
+            ))
+            .size(24),
+        );
+        if !course.description.is_empty() {
+            content = content.push(Text::new(course.description.clone()).size(16));
+        }
+
+        for lesson in &course.lessons {
+            let unlocked: bool = curriculum.is_unlocked(&lesson.id, progress);
+            let mark: &str = if progress.is_passed(&lesson.id) {
+                "✓"
+            } else if unlocked {
+                "○"
+            } else {
+                "🔒"
+            };
+
+            // Las lecciones bloqueadas se ven pero no se pueden abrir
+            let button: Button<AppMessage> = Button::new(
+                Text::new(format!("{}  {}", mark, lesson.title)).shaping(Shaping::Advanced),
+            )
+            .on_press_maybe(
+                unlocked.then(|| AppMessage::Lesson(LessonMessage::Select(lesson.id.clone()))),
+            )
+            .width(Length::Fixed(500.0))
+            .padding(10)
+            .style(custom_style::button_selection);
+            content = content.push(button);
+        }
+    }
+
+    content = content.push(reusable::create_button(
+        AppMessage::Lesson(LessonMessage::BackToMenu),
+        Some("Volver al menú"),
+        None,
+        Some(20.0),
+    ));
+
+    Container::new(scrollable(
+        Container::new(content).width(Length::Fill).padding(40),
+    ))
+    .width(Length::Fill)
+    .height(Length::Fill)
+    .align_x(Horizontal::Center)
+    .align_y(Vertical::Center)
+    .style(custom_style::background)
+    .into()
+}
+
+// Página de una lección: explicación, requisitos y el mejor intento
+pub fn lesson_view(lesson: &Lesson, progress: &LessonProgress) -> Element<'static, AppMessage> {
+    let mut content: Column<AppMessage> = column![Text::new(lesson.title.clone()).size(32)]
+        .spacing(20)
+        .align_x(Horizontal::Center)
+        .max_width(700);
+
+    for paragraph in &lesson.explanation {
+        content = content.push(Text::new(paragraph.clone()).size(18));
+    }
+
+    content = content.push(
+        Text::new(format!(
+            "Para aprobar: {:.0} % de precisión al {:.0} % del tempo",
+            lesson.required_accuracy * 100.0,
+            lesson.playback_rate() * 100.0
+        ))
+        .size(20),
+    );
+
+    if let Some(record) = progress.record(&lesson.id) {
+        let status: &str = if record.passed_at.is_some() {
+            "aprobada"
+        } else {
+            "sin aprobar"
+        };
+        content = content.push(
+            Text::new(format!(
+                "Mejor intento: {:.0} % ({})",
+                record.best_accuracy * 100.0,
+                status
+            ))
+            .size(18),
+        );
+    }
+
+    content = content.push(reusable::create_button(
+        AppMessage::Lesson(LessonMessage::Start),
+        Some("Empezar"),
+        None,
+        Some(24.0),
+    ));
+    content = content.push(reusable::create_button(
+        AppMessage::Lesson(LessonMessage::BackToMap),
+        Some("Volver a las lecciones"),
+        None,
+        Some(20.0),
+    ));
+
+    Container::new(content)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .padding(40)
+        .align_x(Horizontal::Center)
+        .align_y(Vertical::Center)
+        .style(custom_style::background)
+        .into()
+}
@@ -29,6 +29,30 @@ pub fn main_menu_view() -> Element<'static, AppMessage> {
                 None,
                 Some(24.0)
             ),
+            reusable::create_button(
+                AppMessage::MainMenu(MainMenuMessage::OpenLessons),
+                Some("Lessons"),
+                None,
+                Some(24.0)
+            ),
+            reusable::create_button(
+                AppMessage::MainMenu(MainMenuMessage::OpenFlashcards),
+                Some("Note Reading"),
+                None,
+                Some(24.0)
+            ),
+            reusable::create_button(
+                AppMessage::MainMenu(MainMenuMessage::OpenEarTraining),
+                Some("Ear Training"),
+                None,
+                Some(24.0)
+            ),
+            reusable::create_button(
+                AppMessage::MainMenu(MainMenuMessage::OpenTrophies),
+                Some("Trophies"),
+                None,
+                Some(24.0)
+            ),
             reusable::create_button(
                 AppMessage::MainMenu(MainMenuMessage::Exit),
                 Some("Exit"),
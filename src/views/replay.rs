@@ -0,0 +1,122 @@
+use {
+    crate::{
+        message::states::{AppMessage, ReplayMessage},
+        models::{
+            partiture::Partiture,
+            take::{JudgedNote, Judgement},
+        },
+        styles::custom_style,
+        utils::{
+            performance::{PerformanceSummary, expected_notes, summarize},
+            reusable, utils,
+        },
+        widgets::all_notes_overlay::AllNotesOverlay,
+    },
+    iced::{
+        Element, Length,
+        alignment::{Horizontal, Vertical},
+        widget::{Button, Column, Container, Row, Text, column, row, slider, text::Shaping},
+    },
+};
+
+// Vista del modo repetición: la grabación sobre la partitura, con barra para avanzar o retroceder
+pub fn replay_view<'a>(
+    partiture: (&'a Partiture, &'a Partiture),
+    playing: bool,
+) -> Element<'a, AppMessage> {
+    let (first, second) = partiture;
+
+    // Posición actual en segundos de partitura (sin la cuenta atrás)
+    let position: f32 = (first.elapsed() - first.settings.timer).clamp(0.0, first.time);
+
+    let (partiture_r_overlay, partiture_l_overlay) = utils::create_grand_staff(partiture);
+
+    // Barra para desplazarse por la grabación
+    let scrubber = slider(0.0..=first.time.max(0.01), position, |value| {
+        AppMessage::Replay(ReplayMessage::Seek(value))
+    })
+    .step(0.01)
+    .width(Length::Fill);
+
+    let time_label: Text = Text::new(format!("{:.1}s / {:.1}s", position, first.time)).size(18);
+
+    let play_button: Button<AppMessage> = reusable::create_button(
+        AppMessage::Replay(ReplayMessage::TogglePlay),
+        Some(if playing { "Pause" } else { "Play" }),
+        None,
+        Some(20.0),
+    );
+    let export_button: Button<AppMessage> = reusable::create_button(
+        AppMessage::Replay(ReplayMessage::Export),
+        Some("Export Recording"),
+        None,
+        Some(20.0),
+    );
+    let back_button: Button<AppMessage> = reusable::create_button(
+        AppMessage::Replay(ReplayMessage::Back),
+        Some("Back"),
+        None,
+        Some(20.0),
+    );
+
+    // Resumen de la interpretación con las dos manos
+    let judged: Vec<JudgedNote> = first
+        .replay
+        .iter()
+        .chain(second.replay.iter())
+        .copied()
+        .collect();
+    let summary: PerformanceSummary =
+        summarize(&judged, expected_notes(&first.notes, &second.notes));
+
+    let controls: Row<AppMessage> = row![
+        play_button,
+        scrubber,
+        time_label,
+        export_button,
+        back_button
+    ]
+    .spacing(20)
+    .align_y(Vertical::Center);
+
+    let replay_column: Column<AppMessage> = column![
+        partiture_r_overlay,
+        partiture_l_overlay,
+        controls,
+        summary_legend(&summary),
+    ]
+    .spacing(20)
+    .padding(20);
+
+    Container::new(replay_column)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .align_x(Horizontal::Center)
+        .align_y(Vertical::Center)
+        .style(custom_style::background)
+        .into()
+}
+
+// Leyenda con los colores de cada valoración y la precisión
+fn summary_legend<'a>(summary: &PerformanceSummary) -> Row<'a, AppMessage> {
+    let entries: [(Judgement, &str, usize); 4] = [
+        (Judgement::OnTime, "A tiempo", summary.on_time),
+        (Judgement::Early, "Adelantadas", summary.early),
+        (Judgement::Late, "Retrasadas", summary.late),
+        (Judgement::Wrong, "Incorrectas", summary.wrong),
+    ];
+
+    let mut legend: Row<AppMessage> = Row::new().spacing(30).align_y(Vertical::Center);
+    for (judgement, label, count) in entries {
+        legend = legend.push(
+            Text::new(format!("■ {}: {}", label, count))
+                .size(18)
+                .color(AllNotesOverlay::judgement_color(judgement).scale_alpha(1.6))
+                .shaping(Shaping::Advanced),
+        );
+    }
+
+    legend
+        .push(Text::new(format!("Sin tocar: {}", summary.missed)).size(18))
+        .push(Text::new(format!("Precisión: {:.0}%", summary.accuracy * 100.0)).size(18))
+}
@@ -0,0 +1,172 @@
+use {
+    crate::{
+        message::states::{
+            AppMessage, GameMessage, LessonMessage, ReplayMessage, ResultsMessage, SettingsMessage,
+        },
+        models::results::SongResults,
+        styles::custom_style,
+        utils::reusable,
+        widgets::score_overview::{SCORE_OVERVIEW_WIDTH, ScoreOverview},
+    },
+    iced::{
+        Element, Length,
+        alignment::{Horizontal, Vertical},
+        widget::{
+            Button, Canvas, Column, Container, Row, Text, column, row, scrollable, text::Shaping,
+        },
+    },
+};
+
+// Botones de sección por fila
+const SECTIONS_PER_ROW: usize = 8;
+
+// Resultados al terminar la pieza. `lesson` es el resultado de la lección, `report` el del
+// ejercicio de escalas y `rewards` lo ganado en la partida.
+pub fn results_view(
+    results: &SongResults,
+    can_replay: bool,
+    lesson: Option<String>,
+    report: Option<String>,
+    rewards: Vec<String>,
+) -> Element<'static, AppMessage> {
+    let mut content: Column<AppMessage> = column![Text::new(results.info.title.clone()).size(32)]
+        .spacing(16)
+        .align_x(Horizontal::Center);
+
+    if let Some(composer) = &results.info.composer {
+        content = content.push(Text::new(composer.clone()).size(20));
+    }
+
+    // Compás, tempo y tonalidad, los que tenga la pieza
+    let mut details: Vec<String> = vec![format!("Compás {}", results.info.meter)];
+    if let Some(tempo) = &results.info.tempo {
+        details.push(tempo.clone());
+    }
+    if let Some(key) = &results.info.key {
+        details.push(key.clone());
+    }
+    content = content.push(Text::new(details.join("  ·  ")).size(18));
+
+    content = content.push(
+        Text::new(format!(
+            "Tiempo de partida: {} con las pausas  ·  Partitura: {}",
+            clock(results.wall_seconds),
+            clock(results.score_seconds)
+        ))
+        .size(18),
+    );
+
+    let in_lesson: bool = lesson.is_some();
+    for line in lesson.into_iter().chain(report) {
+        content = content.push(Text::new(line).size(22));
+    }
+    if !rewards.is_empty() {
+        let notice: Column<AppMessage> = rewards
+            .into_iter()
+            .fold(column![].spacing(4), |notice, reward| {
+                notice.push(Text::new(reward).size(18).shaping(Shaping::Advanced))
+            });
+        content = content.push(notice);
+    }
+
+    let overview: ScoreOverview = ScoreOverview::new(results);
+    let height: f32 = overview.height();
+    content = content.push(Text::new(results.metric.clone()).size(20));
+    content = content.push(
+        Canvas::new(overview)
+            .width(Length::Fixed(SCORE_OVERVIEW_WIDTH))
+            .height(Length::Fixed(height)),
+    );
+
+    if results.sections.len() > 1 {
+        content = content.push(Text::new("Empezar desde una sección").size(20));
+        content = content.push(section_buttons(results));
+    }
+
+    content = content.push(actions(can_replay, in_lesson));
+
+    Container::new(scrollable(
+        Container::new(content)
+            .width(Length::Fill)
+            .padding(40)
+            .align_x(Horizontal::Center),
+    ))
+    .width(Length::Fill)
+    .height(Length::Fill)
+    .align_x(Horizontal::Center)
+    .align_y(Vertical::Center)
+    .style(custom_style::background)
+    .into()
+}
+
+// Minutos y segundos, como 2:05
+fn clock(seconds: f32) -> String {
+    let seconds: u32 = seconds.max(0.0).round() as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+// Un botón por entrada de `structure`, con el compás donde empieza
+fn section_buttons(results: &SongResults) -> Column<'static, AppMessage> {
+    let buttons: Vec<Button<AppMessage>> = results
+        .sections
+        .iter()
+        .zip(&results.section_bars)
+        .map(|((label, start), bar)| {
+            Button::new(Text::new(format!("{}  ·  c. {}", label, bar + 1)))
+                .on_press(AppMessage::Results(ResultsMessage::FromSection(*start)))
+                .padding(10)
+                .style(custom_style::button_selection)
+        })
+        .collect();
+
+    let mut rows: Column<AppMessage> = column![].spacing(10).align_x(Horizontal::Center);
+    let mut cells: Row<AppMessage> = row![].spacing(10);
+    for (index, button) in buttons.into_iter().enumerate() {
+        if index > 0 && index % SECTIONS_PER_ROW == 0 {
+            rows = rows.push(cells);
+            cells = row![].spacing(10);
+        }
+        cells = cells.push(button);
+    }
+    rows.push(cells)
+}
+
+// Volver a tocar, elegir otra pieza, la repetición, exportar y volver al menú
+fn actions(can_replay: bool, in_lesson: bool) -> Row<'static, AppMessage> {
+    let mut buttons: Vec<(AppMessage, &'static str)> = vec![
+        (AppMessage::Results(ResultsMessage::Retry), "Retry"),
+        (
+            AppMessage::Results(ResultsMessage::ChoosePiece),
+            "Choose Another Piece",
+        ),
+    ];
+    if can_replay {
+        buttons.push((AppMessage::Replay(ReplayMessage::Open), "Replay"));
+    }
+    buttons.push((AppMessage::Game(GameMessage::ExportMidi), "Export MIDI"));
+    if can_replay {
+        buttons.push((
+            AppMessage::Replay(ReplayMessage::Export),
+            "Export Recording",
+        ));
+    }
+    // Desde una lección se vuelve al mapa de lecciones
+    if in_lesson {
+        buttons.push((AppMessage::Lesson(LessonMessage::BackToMap), "Lecciones"));
+    }
+    buttons.push((
+        AppMessage::Settings(SettingsMessage::BackToMenu),
+        "Back to Main Menu",
+    ));
+
+    buttons
+        .into_iter()
+        .fold(row![].spacing(16), |actions, (message, label)| {
+            actions.push(reusable::create_button(
+                message,
+                Some(label),
+                None,
+                Some(20.0),
+            ))
+        })
+}
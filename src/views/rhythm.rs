@@ -0,0 +1,235 @@
+use {
+    crate::{
+        message::states::{AppMessage, RhythmMessage},
+        models::rhythm::{
+            MAX_RHYTHM_BARS, MAX_RHYTHM_LEVEL, RHYTHM_METERS, RhythmConfig, RhythmRun, RhythmSource,
+        },
+        styles::custom_style,
+        utils::reusable,
+        widgets::{
+            rhythm_staff::{BARS_PER_LINE, RHYTHM_STAFF_HEIGHT, RhythmStaff},
+            timing_graph::{TIMING_GRAPH_HEIGHT, TimingGraph},
+        },
+    },
+    iced::{
+        Element, Length,
+        alignment::{Horizontal, Vertical},
+        widget::{
+            Canvas, Column, Container, Row, Text, column, pick_list, row, scrollable, slider,
+        },
+    },
+};
+
+const LEVELS: [(u8, &str); MAX_RHYTHM_LEVEL as usize] =
+    [(1, "Nivel 1"), (2, "Nivel 2"), (3, "Nivel 3")];
+
+// Ejercicio de ritmo: ajustes antes de empezar, la línea mientras se toca y el resultado
+pub fn rhythm_view(config: &RhythmConfig, run: Option<&RhythmRun>) -> Element<'static, AppMessage> {
+    let content: Column<AppMessage> = match run {
+        None => setup(config),
+        Some(run) if run.is_finished() => results(run),
+        Some(run) => playing(run),
+    };
+
+    Container::new(scrollable(
+        Container::new(content)
+            .width(Length::Fill)
+            .padding(40)
+            .align_x(Horizontal::Center),
+    ))
+    .width(Length::Fill)
+    .height(Length::Fill)
+    .align_x(Horizontal::Center)
+    .align_y(Vertical::Center)
+    .style(custom_style::background)
+    .into()
+}
+
+// De dónde sale el ritmo y, si es generado, compás, tempo, compases y nivel
+fn setup(config: &RhythmConfig) -> Column<'static, AppMessage> {
+    let mut content: Column<AppMessage> = column![
+        Text::new("Ritmo").size(32),
+        Text::new(
+            "Solo cuenta cuándo pulsas: cualquier tecla o la barra espaciadora en cada nota."
+        )
+        .size(18),
+        pick_list(
+            config.sources.clone(),
+            Some(config.source.clone()),
+            |source| { AppMessage::Rhythm(RhythmMessage::Source(source)) }
+        ),
+    ]
+    .spacing(20)
+    .align_x(Horizontal::Center);
+
+    if config.source == RhythmSource::Generated {
+        let levels: Row<AppMessage> = LEVELS.iter().fold(
+            row![
+                Text::new("Compás").size(20),
+                pick_list(RHYTHM_METERS, Some(config.meter), |meter| {
+                    AppMessage::Rhythm(RhythmMessage::Meter(meter))
+                }),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            |levels, (level, label)| {
+                levels.push(reusable::create_button(
+                    AppMessage::Rhythm(RhythmMessage::Level(*level)),
+                    Some(label),
+                    None,
+                    Some(18.0),
+                ))
+            },
+        );
+        content = content.push(levels);
+
+        // En los compuestos el pulso es la negra con puntillo
+        let pulse: &str = if config.metadata().is_compound() {
+            "negras con puntillo"
+        } else {
+            "pulsos"
+        };
+        content = content.push(
+            row![
+                Text::new(format!("{} {} por minuto", config.tempo, pulse))
+                    .size(20)
+                    .width(320),
+                slider(40..=160, config.tempo, |tempo| {
+                    AppMessage::Rhythm(RhythmMessage::Tempo(tempo))
+                })
+                .width(300),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+        );
+        content = content.push(
+            row![
+                Text::new(format!("{} compases", config.bars))
+                    .size(20)
+                    .width(320),
+                slider(1..=MAX_RHYTHM_BARS, config.bars, |bars| {
+                    AppMessage::Rhythm(RhythmMessage::Bars(bars))
+                })
+                .width(300),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+        );
+        content = content.push(Text::new(format!("Nivel {}", config.level)).size(18));
+    } else {
+        content = content.push(
+            Text::new(format!(
+                "El ritmo de la mano derecha, hasta {} compases",
+                MAX_RHYTHM_BARS
+            ))
+            .size(18),
+        );
+    }
+
+    content.push(
+        row![
+            reusable::create_button(
+                AppMessage::Rhythm(RhythmMessage::Start),
+                Some("Empezar"),
+                None,
+                Some(24.0),
+            ),
+            reusable::create_button(
+                AppMessage::Rhythm(RhythmMessage::Back),
+                Some("Volver"),
+                None,
+                Some(20.0),
+            ),
+        ]
+        .spacing(20),
+    )
+}
+
+// La línea de los compases que suenan, con la cuenta antes de empezar
+fn playing(run: &RhythmRun) -> Column<'static, AppMessage> {
+    let position: f32 = run.position();
+    let first_bar: usize = run.line.bar_of(position.max(0.0)) / BARS_PER_LINE * BARS_PER_LINE;
+    let status: String = if position < 0.0 {
+        format!("Empieza en {:.1} s", -position)
+    } else {
+        format!(
+            "Compás {} de {}",
+            run.line.bar_of(position) + 1,
+            run.line.bars()
+        )
+    };
+
+    column![
+        Text::new(run.line.title.clone()).size(28),
+        Text::new(status).size(20),
+        staff(run, first_bar, Some(position)),
+        reusable::create_button(
+            AppMessage::Rhythm(RhythmMessage::Stop),
+            Some("Parar"),
+            None,
+            Some(20.0),
+        ),
+    ]
+    .spacing(20)
+    .align_x(Horizontal::Center)
+}
+
+// Todas las líneas con las pulsaciones, el gráfico por compás y el resumen
+fn results(run: &RhythmRun) -> Column<'static, AppMessage> {
+    let mut content: Column<AppMessage> = column![Text::new(run.line.title.clone()).size(28)]
+        .spacing(16)
+        .align_x(Horizontal::Center);
+
+    for first_bar in (0..run.line.bars()).step_by(BARS_PER_LINE) {
+        content = content.push(staff(run, first_bar, None));
+    }
+
+    content = content.push(
+        Canvas::new(TimingGraph {
+            bars: run.bar_timings(),
+        })
+        .width(Length::Fixed(900.0))
+        .height(Length::Fixed(TIMING_GRAPH_HEIGHT)),
+    );
+    content = content.push(Text::new(run.summary()).size(20));
+
+    content.push(
+        row![
+            reusable::create_button(
+                AppMessage::Rhythm(RhythmMessage::Retry),
+                Some("Otra vez"),
+                None,
+                Some(20.0),
+            ),
+            reusable::create_button(
+                AppMessage::Rhythm(RhythmMessage::Stop),
+                Some("Cambiar ejercicio"),
+                None,
+                Some(20.0),
+            ),
+            reusable::create_button(
+                AppMessage::Rhythm(RhythmMessage::Back),
+                Some("Volver"),
+                None,
+                Some(20.0),
+            ),
+        ]
+        .spacing(20),
+    )
+}
+
+fn staff(
+    run: &RhythmRun,
+    first_bar: usize,
+    position: Option<f32>,
+) -> Canvas<RhythmStaff, AppMessage> {
+    Canvas::new(RhythmStaff {
+        line: run.line.clone(),
+        first_bar,
+        position,
+        taps: run.taps.clone(),
+        deviations: run.deviations().to_vec(),
+    })
+    .width(Length::Fixed(900.0))
+    .height(Length::Fixed(RHYTHM_STAFF_HEIGHT))
+}
@@ -0,0 +1,121 @@
+use {
+    crate::{
+        message::states::{AppMessage, ScaleMessage},
+        models::partiture::Hands,
+        styles::custom_style,
+        utils::{
+            reusable,
+            scales::{MAX_CONTRARY_OCTAVES, MAX_OCTAVES, MinorForm, ScaleConfig, ScalePattern},
+            sight_reading::{Key, NoteValue},
+        },
+    },
+    iced::{
+        Element, Length,
+        alignment::{Horizontal, Vertical},
+        widget::{Column, Container, Row, Text, column, pick_list, row, slider},
+    },
+};
+
+const NOTE_VALUES: [NoteValue; 3] = [NoteValue::Quarter, NoteValue::Eighth, NoteValue::Sixteenth];
+
+// Ejercicios de escalas, arpegios y movimiento contrario en cualquier tonalidad
+pub fn scales_view(config: &ScaleConfig) -> Element<'static, AppMessage> {
+    let mut content: Column<AppMessage> = column![
+        Text::new("Escalas y arpegios").size(32),
+        Text::new("Con la digitación habitual encima (derecha) y debajo (izquierda) de cada nota.")
+            .size(18),
+    ]
+    .spacing(20)
+    .align_x(Horizontal::Center);
+
+    // Todas las tonalidades en el orden del círculo de quintas, primero las mayores
+    let keys: Vec<Key> = [false, true]
+        .into_iter()
+        .flat_map(|minor| (0..12).map(move |i| Key::new(i * 7 % 12, minor)))
+        .collect();
+
+    let mut key_row: Row<AppMessage> = row![
+        Text::new("Tonalidad").size(20),
+        pick_list(keys, Some(config.key), |key| {
+            AppMessage::Scales(ScaleMessage::Key(key))
+        }),
+    ]
+    .spacing(10)
+    .align_y(Vertical::Center);
+    if config.key.minor && config.pattern != ScalePattern::Arpeggio {
+        key_row = key_row.push(pick_list(MinorForm::ALL, Some(config.minor_form), |form| {
+            AppMessage::Scales(ScaleMessage::MinorForm(form))
+        }));
+    }
+    content = content.push(key_row);
+
+    let mut pattern_row: Row<AppMessage> = row![pick_list(
+        ScalePattern::ALL,
+        Some(config.pattern),
+        |pattern| AppMessage::Scales(ScaleMessage::Pattern(pattern)),
+    )]
+    .spacing(10)
+    .align_y(Vertical::Center);
+    // El movimiento contrario es siempre con las dos manos
+    if config.pattern != ScalePattern::ContraryMotion {
+        pattern_row = pattern_row.push(pick_list(Hands::ALL, Some(config.hands), |hands| {
+            AppMessage::Scales(ScaleMessage::Hands(hands))
+        }));
+    }
+    pattern_row = pattern_row.push(pick_list(NOTE_VALUES, Some(config.note_value), |value| {
+        AppMessage::Scales(ScaleMessage::NoteValue(value))
+    }));
+    content = content.push(pattern_row);
+
+    let max_octaves: u8 = match config.pattern {
+        ScalePattern::ContraryMotion => MAX_CONTRARY_OCTAVES,
+        _ => MAX_OCTAVES,
+    };
+    let octaves: u8 = config.octaves();
+    content = content.push(
+        row![
+            Text::new(format!(
+                "{} {}",
+                octaves,
+                if octaves == 1 { "octava" } else { "octavas" }
+            ))
+            .size(20)
+            .width(160),
+            slider(1..=max_octaves, octaves, |octaves| {
+                AppMessage::Scales(ScaleMessage::Octaves(octaves))
+            })
+            .width(300),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center),
+    );
+
+    content = content.push(Text::new(config.title()).size(20));
+
+    content = content.push(
+        row![
+            reusable::create_button(
+                AppMessage::Scales(ScaleMessage::Start),
+                Some("Empezar"),
+                None,
+                Some(24.0),
+            ),
+            reusable::create_button(
+                AppMessage::Scales(ScaleMessage::Back),
+                Some("Volver"),
+                None,
+                Some(20.0),
+            ),
+        ]
+        .spacing(20),
+    );
+
+    Container::new(content)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .padding(40)
+        .align_x(Horizontal::Center)
+        .align_y(Vertical::Center)
+        .style(custom_style::background)
+        .into()
+}
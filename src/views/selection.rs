@@ -1,55 +1,106 @@
 use {
     crate::{
-        asset_path,
         message::states::{AppMessage, SelectionMessage},
         styles::custom_style,
-        utils::{helper_json, reusable::create_button},
+        utils::{difficulty::DifficultyRating, reusable::create_button},
     },
     iced::{
         Element, Length,
         alignment::{Horizontal, Vertical},
-        widget::{Button, Column, Container, Text, column},
+        widget::{Button, Column, Container, Text, column, row},
     },
-    serde_json::Value,
 };
 
-pub fn select_partiture_view() -> Element<'static, AppMessage> {
-    // Cargar las partituras desde el archivo JSON
-    let partitures: Vec<Value> =
-        helper_json::load_partiture(&asset_path!("partitures.json")).unwrap();
-
+// Lista de piezas con la dificultad calculada al cargar la biblioteca
+pub fn select_partiture_view(
+    songs: &[(String, Option<DifficultyRating>)],
+) -> Element<'static, AppMessage> {
     // Vista de las partituras
     let mut partiture_column: Column<AppMessage> = column![].spacing(20);
 
     // Iterar sobre las partituras y crear un contenedor para cada una
-    for partiture in &partitures {
-        if let Some((name, _data)) = partiture.as_object().and_then(|obj| obj.iter().next()) {
-            let name_to_show: String = name
-                .replace("-", " ")
-                .chars()
-                .enumerate()
-                .map(|(i, c)| {
-                    if i == 0 {
-                        c.to_uppercase().collect::<String>()
-                    } else {
-                        c.to_string()
-                    }
-                })
-                .collect::<String>();
+    for (name, rating) in songs {
+        let name_to_show: String = name
+            .replace("-", " ")
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                if i == 0 {
+                    c.to_uppercase().collect::<String>()
+                } else {
+                    c.to_string()
+                }
+            })
+            .collect::<String>();
+
+        // Añadir la dificultad calculada a partir de las notas de la pieza
+        let label: String = match rating {
+            Some(rating) => format!(
+                "{}  ·  Dificultad {}/10 ({})",
+                name_to_show,
+                rating.level(),
+                rating.label()
+            ),
+            None => name_to_show,
+        };
 
-            let partiture_button: Button<AppMessage> = Button::new(Text::new(name_to_show))
-                // Al pulsar el botón, enviar el mensaje para iniciar el juego con la partitura seleccionada
-                .on_press(AppMessage::Selection(SelectionMessage::StartGame(
-                    name.clone().leak(),
-                )))
-                .width(Length::Fixed(500.0))
-                .padding(10)
-                .style(custom_style::button_selection);
+        let partiture_button: Button<AppMessage> = Button::new(Text::new(label))
+            // Al pulsar el botón, enviar el mensaje para iniciar el juego con la partitura seleccionada
+            .on_press(AppMessage::Selection(SelectionMessage::StartGame(
+                name.clone(),
+            )))
+            .width(Length::Fixed(500.0))
+            .padding(10)
+            .style(custom_style::button_selection);
 
-            partiture_column = partiture_column.push(partiture_button);
-        }
+        // Abrir la pieza en el editor de partituras
+        let edit_button: Button<AppMessage> = Button::new(Text::new("Editar"))
+            .on_press(AppMessage::Selection(SelectionMessage::EditPiece(
+                name.clone(),
+            )))
+            .padding(10)
+            .style(custom_style::button_selection);
+
+        partiture_column = partiture_column.push(row![partiture_button, edit_button].spacing(10));
     }
 
+    // Ejercicio generado en lugar de una pieza de la biblioteca
+    let sight_reading_button: Button<AppMessage> =
+        Button::new(Text::new("Lectura a primera vista"))
+            .on_press(AppMessage::Selection(SelectionMessage::OpenSightReading))
+            .width(Length::Fixed(500.0))
+            .padding(10)
+            .style(custom_style::button_selection);
+    partiture_column = partiture_column.push(sight_reading_button);
+
+    let scales_button: Button<AppMessage> = Button::new(Text::new("Escalas y arpegios"))
+        .on_press(AppMessage::Selection(SelectionMessage::OpenScales))
+        .width(Length::Fixed(500.0))
+        .padding(10)
+        .style(custom_style::button_selection);
+    partiture_column = partiture_column.push(scales_button);
+
+    let rhythm_button: Button<AppMessage> = Button::new(Text::new("Ritmo"))
+        .on_press(AppMessage::Selection(SelectionMessage::OpenRhythm))
+        .width(Length::Fixed(500.0))
+        .padding(10)
+        .style(custom_style::button_selection);
+    partiture_column = partiture_column.push(rhythm_button);
+
+    let editor_button: Button<AppMessage> = Button::new(Text::new("Nueva partitura"))
+        .on_press(AppMessage::Selection(SelectionMessage::NewPiece))
+        .width(Length::Fixed(500.0))
+        .padding(10)
+        .style(custom_style::button_selection);
+    partiture_column = partiture_column.push(editor_button);
+
+    let capture_button: Button<AppMessage> = Button::new(Text::new("Grabar partitura"))
+        .on_press(AppMessage::Selection(SelectionMessage::OpenCapture))
+        .width(Length::Fixed(500.0))
+        .padding(10)
+        .style(custom_style::button_selection);
+    partiture_column = partiture_column.push(capture_button);
+
     // Añadir boton de volver al menú principal
     let back_button: Button<AppMessage> = create_button(
         AppMessage::Selection(SelectionMessage::BackToMenu),
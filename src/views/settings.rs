@@ -1,23 +1,38 @@
 use {
     crate::{
-        message::states::{AppMessage, GameMessage, SettingsMessage},
+        message::states::{
+            AppMessage, CalibrationMessage, GameMessage, LatencyMessage, LessonMessage,
+            SettingsMessage,
+        },
+        models::{
+            partiture::{Hand, Hands},
+            settings::{CustomSettings, MidiOutputSettings},
+        },
         styles::custom_style,
         utils::reusable,
+        views::calibration,
     },
     iced::{
         Element, Length, Theme,
         alignment::{Horizontal, Vertical},
-        widget::{Button, Column, Container, column, toggler},
-    },
-    std::sync::{
-        Arc,
-        atomic::{AtomicBool, Ordering},
+        widget::{
+            Button, Column, Container, Text, column, pick_list, row, scrollable, slider,
+            text::Shaping, toggler,
+        },
     },
 };
 
-// Vista de configuración
-pub fn settings_view(theme: &Theme) -> Element<'static, AppMessage> {
-    let theme_toggle = toggler(theme == &Theme::Dark)
+/// Opción de la lista de puertos para no usar ningún dispositivo MIDI
+pub const NO_MIDI_DEVICE: &str = "Ninguno";
+
+// Vista de configuración. `input_ports` y `output_ports` son los puertos MIDI encontrados.
+pub fn settings_view(
+    settings: &CustomSettings,
+    input_ports: &[String],
+    output_ports: &[String],
+) -> Element<'static, AppMessage> {
+    let theme: Theme = settings.get_iced_theme();
+    let theme_toggle = toggler(theme == Theme::Dark)
         .label("Cambiar Tema")
         .text_size(24)
         .size(30)
@@ -31,6 +46,85 @@ pub fn settings_view(theme: &Theme) -> Element<'static, AppMessage> {
             }))
         });
 
+    // Ajustes del metrónomo
+    let metronome_toggle = toggler(settings.metronome.enabled)
+        .label("Metrónomo")
+        .text_size(24)
+        .size(30)
+        .style(custom_style::toogle_theme)
+        .on_toggle(|enabled| AppMessage::Settings(SettingsMessage::ToggleMetronome(enabled)));
+
+    let volume = row![
+        Text::new(format!("Volumen {:.0}%", settings.metronome.volume * 100.0))
+            .size(20)
+            .width(160),
+        slider(0.0..=1.0, settings.metronome.volume, |volume| {
+            AppMessage::Settings(SettingsMessage::MetronomeVolume(volume))
+        })
+        .step(0.05),
+    ]
+    .spacing(10)
+    .align_y(Vertical::Center);
+
+    let count_in = row![
+        Text::new(format!(
+            "Cuenta previa: {} {}",
+            settings.metronome.count_in_bars,
+            if settings.metronome.count_in_bars == 1 {
+                "compás"
+            } else {
+                "compases"
+            }
+        ))
+        .size(20)
+        .width(Length::Fill),
+        reusable::create_button(
+            AppMessage::Settings(SettingsMessage::CountInBars(
+                if settings.metronome.count_in_bars == 1 {
+                    2
+                } else {
+                    1
+                },
+            )),
+            Some("Cambiar"),
+            None,
+            Some(18.0),
+        ),
+    ]
+    .spacing(10)
+    .align_y(Vertical::Center);
+
+    let visual_beat_toggle = toggler(settings.metronome.visual_beat)
+        .label("Indicador visual del pulso")
+        .text_size(24)
+        .size(30)
+        .style(custom_style::toogle_theme)
+        .on_toggle(|visible| AppMessage::Settings(SettingsMessage::ToggleVisualBeat(visible)));
+
+    // Calibración del micrófono para el piano acústico
+    let microphone = column![
+        toggler(settings.microphone.use_in_game)
+            .label("Usar micrófono en la partida")
+            .text_size(24)
+            .size(30)
+            .style(custom_style::toogle_theme)
+            .on_toggle(|enabled| AppMessage::Settings(SettingsMessage::ToggleMicrophone(enabled))),
+        calibration::summary(&settings.microphone),
+        reusable::create_button(
+            AppMessage::Calibration(CalibrationMessage::Open),
+            Some("Calibrar micrófono"),
+            None,
+            Some(18.0),
+        ),
+        reusable::create_button(
+            AppMessage::Latency(LatencyMessage::Open),
+            Some("Latencia de entrada"),
+            None,
+            Some(18.0),
+        ),
+    ]
+    .spacing(10);
+
     let back_to_menu: Button<AppMessage> = reusable::create_button(
         AppMessage::Settings(SettingsMessage::BackToMenu),
         Some("Back to Main Menu"),
@@ -38,38 +132,167 @@ pub fn settings_view(theme: &Theme) -> Element<'static, AppMessage> {
         Some(20.0),
     );
 
-    let content_view = Container::new(column![theme_toggle, back_to_menu].spacing(20))
-        .align_x(Horizontal::Center)
-        .align_y(Vertical::Center)
-        .width(400)
-        .max_width(400);
+    let content_view = Container::new(
+        column![
+            theme_toggle,
+            metronome_toggle,
+            volume,
+            count_in,
+            visual_beat_toggle,
+            microphone,
+            midi_input(settings.midi_input.as_ref(), input_ports),
+            midi_output(&settings.midi_output, output_ports),
+            back_to_menu
+        ]
+        .spacing(20),
+    )
+    .align_x(Horizontal::Center)
+    .align_y(Vertical::Center)
+    .width(400)
+    .max_width(400);
 
-    Container::new(content_view)
-        .width(Length::Fill)
-        .height(Length::Fill)
-        .align_x(Horizontal::Center)
+    Container::new(scrollable(
+        Container::new(content_view)
+            .width(Length::Fill)
+            .padding(30)
+            .align_x(Horizontal::Center),
+    ))
+    .width(Length::Fill)
+    .height(Length::Fill)
+    .align_x(Horizontal::Center)
+    .align_y(Vertical::Center)
+    .style(custom_style::background)
+    .into()
+}
+
+// Opciones de la lista de puertos y la elegida. El puerto guardado aparece aunque ahora no
+// esté conectado.
+fn midi_devices(device: Option<&String>, ports: &[String]) -> (Vec<String>, String) {
+    let mut devices: Vec<String> = vec![NO_MIDI_DEVICE.to_string()];
+    devices.extend(ports.iter().cloned());
+    if let Some(device) = device.filter(|d| !ports.contains(d)) {
+        devices.push(device.clone());
+    }
+    let selected: String = device
+        .cloned()
+        .unwrap_or_else(|| NO_MIDI_DEVICE.to_string());
+    (devices, selected)
+}
+
+// Teclado MIDI con el que se toca en la partida, las tarjetas, el ritmo y la grabación
+fn midi_input(device: Option<&String>, ports: &[String]) -> Column<'static, AppMessage> {
+    let (devices, selected) = midi_devices(device, ports);
+    column![
+        Text::new("Teclado MIDI").size(24),
+        row![
+            pick_list(devices, Some(selected), |device| {
+                AppMessage::Settings(SettingsMessage::MidiInputDevice(device))
+            }),
+            reusable::create_button(
+                AppMessage::Settings(SettingsMessage::RefreshMidiPorts),
+                Some("Buscar"),
+                None,
+                Some(18.0),
+            ),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center),
+    ]
+    .spacing(10)
+}
+
+// Piano digital o sintetizador: puerto, manos que suenan y canales de las luces de guía
+fn midi_output(settings: &MidiOutputSettings, ports: &[String]) -> Column<'static, AppMessage> {
+    let (devices, selected) = midi_devices(settings.device.as_ref(), ports);
+
+    let channel = |label: String, value: u8, message: fn(u8) -> SettingsMessage| {
+        row![
+            Text::new(label).size(20).width(200),
+            slider(1..=16, value, move |channel| {
+                AppMessage::Settings(message(channel))
+            }),
+        ]
+        .spacing(10)
         .align_y(Vertical::Center)
-        .style(custom_style::background)
-        .into()
+    };
+
+    column![
+        Text::new("Salida MIDI").size(24),
+        row![
+            pick_list(devices, Some(selected), |device| {
+                AppMessage::Settings(SettingsMessage::MidiDevice(device))
+            }),
+            reusable::create_button(
+                AppMessage::Settings(SettingsMessage::RefreshMidiPorts),
+                Some("Buscar"),
+                None,
+                Some(18.0),
+            ),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center),
+        toggler(settings.sound)
+            .label("Sonar en el dispositivo")
+            .text_size(20)
+            .style(custom_style::toogle_theme)
+            .on_toggle(|sound| AppMessage::Settings(SettingsMessage::MidiSound(sound))),
+        pick_list(Hands::ALL, Some(settings.hands), |hands| {
+            AppMessage::Settings(SettingsMessage::MidiHands(hands))
+        }),
+        channel(
+            format!("Canal {}", settings.channel),
+            settings.channel,
+            SettingsMessage::MidiChannel
+        ),
+        toggler(settings.guide)
+            .label("Luces de guía")
+            .text_size(20)
+            .style(custom_style::toogle_theme)
+            .on_toggle(|guide| AppMessage::Settings(SettingsMessage::MidiGuide(guide))),
+        channel(
+            format!("Luces derecha: canal {}", settings.guide_right),
+            settings.guide_right,
+            |channel| SettingsMessage::GuideChannel(Hand::Right, channel)
+        ),
+        channel(
+            format!("Luces izquierda: canal {}", settings.guide_left),
+            settings.guide_left,
+            |channel| SettingsMessage::GuideChannel(Hand::Left, channel)
+        ),
+    ]
+    .spacing(10)
 }
 
-// Menú de pausa
-pub fn paused_view(finished: Arc<AtomicBool>) -> Element<'static, AppMessage> {
+// Menú de pausa. `lesson` es el objetivo de la lección en curso, si se está tocando una,
+// y `rewards` lo ganado en esta partida. Al terminar la pieza se pasa a los resultados.
+pub fn paused_view(lesson: Option<String>, rewards: Vec<String>) -> Element<'static, AppMessage> {
     // Crear columna para el menú de pausa
     let mut pause_column: Column<AppMessage> = column![].spacing(20);
 
-    // Si el juego no ha terminado, añadir el botón de reanudar
-    if !finished.load(Ordering::SeqCst) {
-        // Crear botón para reanudar el juego
-        let resume_button: Button<AppMessage> = reusable::create_button(
-            AppMessage::Game(GameMessage::ResumeGame),
-            Some("Resume Game"),
-            None,
-            Some(24.0),
-        );
-        pause_column = pause_column.push(resume_button);
+    // XP, racha y logros de la partida
+    if !rewards.is_empty() {
+        let notice: Column<AppMessage> = rewards
+            .into_iter()
+            .fold(column![].spacing(4), |notice, reward| {
+                notice.push(Text::new(reward).size(18).shaping(Shaping::Advanced))
+            });
+        pause_column = pause_column.push(notice);
     }
 
+    let in_lesson: bool = lesson.is_some();
+    if let Some(result) = lesson {
+        pause_column = pause_column.push(Text::new(result).size(24));
+    }
+
+    // Crear botón para reanudar el juego
+    let resume_button: Button<AppMessage> = reusable::create_button(
+        AppMessage::Game(GameMessage::ResumeGame),
+        Some("Resume Game"),
+        None,
+        Some(24.0),
+    );
+    pause_column = pause_column.push(resume_button);
+
     // Crear botón para reiniciar el juego
     let restart_button: Button<AppMessage> = reusable::create_button(
         // Llama al evento Restar Game
@@ -80,6 +303,26 @@ pub fn paused_view(finished: Arc<AtomicBool>) -> Element<'static, AppMessage> {
     );
     pause_column = pause_column.push(restart_button);
 
+    // Crear botón para exportar la partitura a MIDI
+    let export_button: Button<AppMessage> = reusable::create_button(
+        AppMessage::Game(GameMessage::ExportMidi),
+        Some("Export MIDI"),
+        None,
+        Some(24.0),
+    );
+    pause_column = pause_column.push(export_button);
+
+    // Desde una lección se vuelve al mapa de lecciones
+    if in_lesson {
+        let lessons_button: Button<AppMessage> = reusable::create_button(
+            AppMessage::Lesson(LessonMessage::BackToMap),
+            Some("Lecciones"),
+            None,
+            Some(24.0),
+        );
+        pause_column = pause_column.push(lessons_button);
+    }
+
     // Crear botón para volver al menú principal
     let back_to_menu_button: Button<AppMessage> = reusable::create_button(
         // Llama al evento back to menu
@@ -96,6 +339,6 @@ pub fn paused_view(finished: Arc<AtomicBool>) -> Element<'static, AppMessage> {
         .height(Length::Fill)
         .align_x(Horizontal::Center)
         .align_y(Vertical::Center)
-        .style(|theme| custom_style::background(theme))
+        .style(custom_style::background)
         .into()
 }
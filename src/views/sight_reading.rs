@@ -0,0 +1,198 @@
+use {
+    crate::{
+        message::states::{AppMessage, SightReadingMessage},
+        models::score::Meter,
+        styles::custom_style,
+        utils::{
+            note_names::note_name,
+            reusable,
+            sight_reading::{Key, SightReadingConfig},
+        },
+    },
+    iced::{
+        Element, Length,
+        alignment::{Horizontal, Vertical},
+        widget::{
+            Column, Container, Row, Text, column, pick_list, row, slider, text_input, toggler,
+        },
+    },
+};
+
+// Tonalidades que se pueden elegir: hasta tres alteraciones en la armadura
+const KEYS: [Key; 14] = [
+    Key::new(0, false),
+    Key::new(7, false),
+    Key::new(2, false),
+    Key::new(9, false),
+    Key::new(5, false),
+    Key::new(10, false),
+    Key::new(3, false),
+    Key::new(9, true),
+    Key::new(4, true),
+    Key::new(11, true),
+    Key::new(6, true),
+    Key::new(2, true),
+    Key::new(7, true),
+    Key::new(0, true),
+];
+
+const METERS: [Meter; 5] = [
+    Meter {
+        beats: 2,
+        beat_unit: 4,
+    },
+    Meter {
+        beats: 3,
+        beat_unit: 4,
+    },
+    Meter {
+        beats: 4,
+        beat_unit: 4,
+    },
+    Meter {
+        beats: 3,
+        beat_unit: 8,
+    },
+    Meter {
+        beats: 6,
+        beat_unit: 8,
+    },
+];
+
+const LEVELS: [(u8, &str); 3] = [(1, "Nivel 1"), (2, "Nivel 2"), (3, "Nivel 3")];
+
+// Generador de ejercicios de lectura a primera vista
+pub fn sight_reading_view(config: &SightReadingConfig) -> Element<'static, AppMessage> {
+    let mut content: Column<AppMessage> = column![
+        Text::new("Lectura a primera vista").size(32),
+        Text::new("Un ejercicio nuevo cada vez. Con la misma semilla sale el mismo ejercicio.")
+            .size(18),
+    ]
+    .spacing(20)
+    .align_x(Horizontal::Center);
+
+    let levels: Row<AppMessage> =
+        LEVELS
+            .iter()
+            .fold(row![].spacing(10), |levels, (level, label)| {
+                levels.push(reusable::create_button(
+                    AppMessage::SightReading(SightReadingMessage::Level(*level)),
+                    Some(label),
+                    None,
+                    Some(18.0),
+                ))
+            });
+    content = content.push(levels);
+
+    content = content.push(
+        row![
+            Text::new("Tonalidad").size(20),
+            pick_list(KEYS, Some(config.key), |key| {
+                AppMessage::SightReading(SightReadingMessage::Key(key))
+            }),
+            Text::new("Compás").size(20),
+            pick_list(METERS, Some(config.meter), |meter| {
+                AppMessage::SightReading(SightReadingMessage::Meter(meter))
+            }),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center),
+    );
+
+    content = content.push(
+        row![
+            Text::new(format!("{} compases", config.bars))
+                .size(20)
+                .width(160),
+            slider(4..=16, config.bars, |bars| {
+                AppMessage::SightReading(SightReadingMessage::Bars(bars))
+            })
+            .width(300),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center),
+    );
+
+    content = content.push(
+        row![
+            toggler(config.chords)
+                .label("Acordes")
+                .text_size(20)
+                .style(custom_style::toogle_theme)
+                .on_toggle(|chords| {
+                    AppMessage::SightReading(SightReadingMessage::ToggleChords(chords))
+                }),
+            toggler(config.accidentals)
+                .label("Alteraciones")
+                .text_size(20)
+                .style(custom_style::toogle_theme)
+                .on_toggle(|accidentals| {
+                    AppMessage::SightReading(SightReadingMessage::ToggleAccidentals(accidentals))
+                }),
+        ]
+        .spacing(40),
+    );
+
+    // Resumen de lo que fija el nivel
+    let values: Vec<String> = config
+        .note_values
+        .iter()
+        .map(|value| format!("{:?}", value))
+        .collect();
+    content = content.push(
+        Text::new(format!(
+            "Derecha {}–{} · Izquierda {}–{} · Figuras: {} · Saltos de hasta {} grados",
+            note_name(config.right_range.0),
+            note_name(config.right_range.1),
+            note_name(config.left_range.0),
+            note_name(config.left_range.1),
+            values.join(", "),
+            config.intervals.iter().max().copied().unwrap_or(0)
+        ))
+        .size(16),
+    );
+
+    content = content.push(
+        row![
+            Text::new("Semilla").size(20),
+            text_input("0", &config.seed.to_string())
+                .on_input(|text| AppMessage::SightReading(SightReadingMessage::Seed(text)))
+                .width(160),
+            reusable::create_button(
+                AppMessage::SightReading(SightReadingMessage::NewSeed),
+                Some("Otra"),
+                None,
+                Some(18.0),
+            ),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center),
+    );
+
+    content = content.push(
+        row![
+            reusable::create_button(
+                AppMessage::SightReading(SightReadingMessage::Start),
+                Some("Empezar"),
+                None,
+                Some(24.0),
+            ),
+            reusable::create_button(
+                AppMessage::SightReading(SightReadingMessage::Back),
+                Some("Volver"),
+                None,
+                Some(20.0),
+            ),
+        ]
+        .spacing(20),
+    );
+
+    Container::new(content)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .padding(40)
+        .align_x(Horizontal::Center)
+        .align_y(Vertical::Center)
+        .style(custom_style::background)
+        .into()
+}
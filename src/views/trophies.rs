@@ -0,0 +1,121 @@
+use {
+    crate::{
+        message::states::{AppMessage, TrophyMessage},
+        models::rewards::{PlayerProgress, RewardRules},
+        styles::custom_style,
+        utils::reusable,
+    },
+    iced::{
+        Element, Length,
+        alignment::{Horizontal, Vertical},
+        widget::{Column, Container, Text, column, progress_bar, row, scrollable, text::Shaping},
+    },
+};
+
+// Trofeos: nivel y XP, racha de días, lo tocado y los logros desbloqueados o pendientes
+pub fn trophies_view(
+    rules: &RewardRules,
+    player: &PlayerProgress,
+    now: u64,
+) -> Element<'static, AppMessage> {
+    let level: u32 = rules.level(player.xp);
+    let mut content: Column<AppMessage> = column![
+        Text::new("Trofeos").size(32),
+        Text::new(format!("Nivel {}  ·  {} XP", level, player.xp)).size(24),
+    ]
+    .spacing(16)
+    .align_x(Horizontal::Center);
+
+    // Lo que falta para el siguiente nivel
+    if let Some((current, next)) = rules.level_range(player.xp) {
+        content = content.push(
+            row![
+                progress_bar(current as f32..=next as f32, player.xp as f32)
+                    .width(Length::Fixed(400.0))
+                    .height(Length::Fixed(14.0)),
+                Text::new(format!(
+                    "{} XP para el nivel {}",
+                    next - player.xp,
+                    level + 1
+                ))
+                .size(16),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+        );
+    }
+
+    let minutes: u32 = (player.practice_seconds / 60.0) as u32;
+    content = content.push(
+        Text::new(format!(
+            "Racha: {} días  ·  Mejor racha: {} días",
+            player.current_streak(now),
+            player.best_streak
+        ))
+        .size(18),
+    );
+    content = content.push(
+        Text::new(format!(
+            "{} partidas terminadas de {}  ·  {} piezas distintas  ·  {} h {} min de práctica",
+            player.pieces_finished,
+            player.pieces_started,
+            player.finished_pieces.len(),
+            minutes / 60,
+            minutes % 60
+        ))
+        .size(18),
+    );
+
+    let unlocked: usize = rules
+        .achievements
+        .iter()
+        .filter(|achievement| player.is_unlocked(&achievement.id))
+        .count();
+    content = content
+        .push(Text::new(format!("Logros: {}/{}", unlocked, rules.achievements.len())).size(24));
+
+    for achievement in &rules.achievements {
+        let mark: &str = if player.is_unlocked(&achievement.id) {
+            "🏆"
+        } else {
+            "🔒"
+        };
+        let xp: String = if achievement.xp > 0 {
+            format!("  ·  +{} XP", achievement.xp)
+        } else {
+            String::new()
+        };
+        content = content.push(
+            column![
+                Text::new(format!("{}  {}", mark, achievement.title))
+                    .size(20)
+                    .shaping(Shaping::Advanced),
+                Text::new(format!("{}{}", achievement.description, xp))
+                    .size(16)
+                    .shaping(Shaping::Advanced),
+            ]
+            .spacing(2)
+            .width(Length::Fixed(500.0)),
+        );
+    }
+
+    content = content.push(reusable::create_button(
+        AppMessage::Trophies(TrophyMessage::Back),
+        Some("Volver al menú"),
+        None,
+        Some(20.0),
+    ));
+
+    Container::new(scrollable(
+        Container::new(content)
+            .width(Length::Fill)
+            .padding(40)
+            .align_x(Horizontal::Center),
+    ))
+    .width(Length::Fill)
+    .height(Length::Fill)
+    .align_x(Horizontal::Center)
+    .align_y(Vertical::Center)
+    .style(custom_style::background)
+    .into()
+}
@@ -1,4 +1,10 @@
 pub mod all_notes_overlay;
+pub mod editor_staff;
+pub mod flashcard;
 pub mod intro_overlay;
+pub mod markings_overlay;
 pub mod notes;
 pub mod partiture;
+pub mod rhythm_staff;
+pub mod score_overview;
+pub mod timing_graph;
@@ -1,8 +1,29 @@
 use {
-    crate::models::{note::Note, partiture::Partiture},
-    iced::{Point, Rectangle, widget::canvas::Frame},
+    crate::models::{
+        markings::Articulation,
+        note::Note,
+        partiture::{Hand, Partiture},
+        take::{JudgedNote, Judgement},
+    },
+    iced::{
+        Color, Point, Rectangle, Size,
+        alignment::{Horizontal, Vertical},
+        widget::canvas::{Frame, Path, Stroke, Style, Text},
+    },
 };
 
+// Altura de las barras de las notas grabadas
+const REPLAY_BAR_HEIGHT: f32 = 8.0;
+
+// Distancia de la digitación a la cabeza de la nota, por encima de las plicas
+const FINGER_OFFSET: f32 = 42.0;
+
+// Separación entre los dedos apilados de un acorde
+const FINGER_STACK: f32 = 16.0;
+
+// Distancia de la articulación a la cabeza, en el lado contrario a la plica
+const ARTICULATION_OFFSET: f32 = 12.0;
+
 // Estructura de overlay para mostrar todas las notas y compas de la partitura
 pub struct AllNotesOverlay<'a> {
     pub partiture: &'a Partiture, // Referencia a la partitura
@@ -13,9 +34,16 @@ impl<'a> AllNotesOverlay<'a> {
     pub fn draw(&self, frame: &mut Frame, layout_bounds: Rectangle) {
         let mut last_position: Point = Point::default();
 
+        // Las notas grabadas van debajo de las esperadas
+        for judged in self.partiture.replay.iter() {
+            self.draw_replay_note(judged, frame, layout_bounds);
+        }
+
         for note in self.partiture.notes.iter() {
             self.draw_note_in_overlay(note, frame, layout_bounds, &mut last_position);
         }
+
+        self.draw_fingers(frame, layout_bounds);
     }
 
     // Método para dibujar una nota en el overlay
@@ -34,7 +62,7 @@ impl<'a> AllNotesOverlay<'a> {
         };
 
         // Tiempo actual ajustado por el timer inicial
-        let current_time: f32 = self.partiture.elapsed - self.partiture.settings.timer;
+        let current_time: f32 = self.partiture.elapsed() - self.partiture.settings.timer;
         let pixels_per_second: f32 = self.partiture.calculate_pixels_per_second();
 
         let note_end_time: f32 = note.start + note.duration;
@@ -49,7 +77,7 @@ impl<'a> AllNotesOverlay<'a> {
 
         let is_currently_active = current_time >= note.start && current_time < note_end_time;
 
-        let note_y = self.calculate_note_y_in_staff(note, &layout_bounds);
+        let note_y = self.calculate_note_y_in_staff(note.pitch, &layout_bounds);
         let mut actual_position = Point::new(note_x, note_y);
 
         let new_note: Note = Note {
@@ -58,11 +86,158 @@ impl<'a> AllNotesOverlay<'a> {
             ..note.clone()
         };
 
-        new_note.draw(frame, actual_position.clone());
+        new_note.draw(frame, actual_position);
+        Self::draw_articulations(&new_note, frame, actual_position);
         Self::draw_plicas(&new_note, &mut actual_position);
 
         *last_position = Point::new(actual_position.x + 8.0, actual_position.y);
     }
+
+    // Método para dibujar una nota grabada como una barra desde que se pulsó hasta que se soltó
+    fn draw_replay_note(&self, judged: &JudgedNote, frame: &mut Frame, layout_bounds: Rectangle) {
+        let work_x: f32 = layout_bounds.x + self.partiture.img_width;
+        let work_end: f32 = layout_bounds.x + layout_bounds.width;
+
+        let current_time: f32 = self.partiture.elapsed() - self.partiture.settings.timer;
+        let pixels_per_second: f32 = self.partiture.calculate_pixels_per_second();
+
+        let start_x: f32 = work_x + (judged.played.on - current_time) * pixels_per_second;
+        let end_x: f32 = work_x + (judged.played.off - current_time) * pixels_per_second;
+
+        // Culling: solo dibujar las barras visibles
+        if end_x < work_x || start_x > work_end {
+            return;
+        }
+
+        let x: f32 = start_x.max(work_x);
+        let width: f32 = (end_x.min(work_end) - x).max(4.0);
+        let y: f32 = self.calculate_note_y_in_staff(judged.played.pitch, &layout_bounds)
+            - REPLAY_BAR_HEIGHT / 2.0;
+
+        let bar: Path = Path::rectangle(Point::new(x, y), Size::new(width, REPLAY_BAR_HEIGHT));
+        frame.fill(&bar, Self::judgement_color(judged.judgement));
+    }
+
+    // Digitación de las notas visibles. En los acordes los dedos se apilan con el de la nota
+    // más aguda arriba: encima del acorde en la mano derecha y debajo en la izquierda.
+    fn draw_fingers(&self, frame: &mut Frame, layout_bounds: Rectangle) {
+        let work_x: f32 = layout_bounds.x + self.partiture.img_width;
+        let work_end: f32 = layout_bounds.x + layout_bounds.width;
+        let current_time: f32 = self.partiture.elapsed() - self.partiture.settings.timer;
+        let pixels_per_second: f32 = self.partiture.calculate_pixels_per_second();
+
+        // Notas con dedo agrupadas por inicio, de la más grave a la más aguda
+        let mut chords: Vec<(f32, Vec<&Note>)> = Vec::new();
+        for note in self
+            .partiture
+            .notes
+            .iter()
+            .filter(|note| note.finger.is_some())
+        {
+            let note_x: f32 = work_x + (note.start - current_time) * pixels_per_second;
+            if note_x < work_x - 50.0 || note_x > work_end + 50.0 {
+                continue;
+            }
+            match chords
+                .iter_mut()
+                .find(|(start, _)| (start - note.start).abs() < 1e-3)
+            {
+                Some((_, chord)) => chord.push(note),
+                None => chords.push((note.start, vec![note])),
+            }
+        }
+
+        for (start, mut chord) in chords {
+            chord.sort_by_key(|note| note.pitch);
+            let x: f32 = work_x + (start - current_time) * pixels_per_second;
+            let heads: Vec<f32> = chord
+                .iter()
+                .map(|note| self.calculate_note_y_in_staff(note.pitch, &layout_bounds))
+                .collect();
+
+            for (index, note) in chord.iter().enumerate() {
+                let Some(finger) = note.finger else {
+                    continue;
+                };
+                let y: f32 = match self.partiture.hand {
+                    Hand::Right => {
+                        let top: f32 = heads.iter().copied().fold(f32::INFINITY, f32::min);
+                        top - FINGER_OFFSET - (chord.len() - 1 - index) as f32 * FINGER_STACK
+                    }
+                    Hand::Left => {
+                        let bottom: f32 = heads.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+                        bottom + FINGER_OFFSET + (chord.len() - 1 - index) as f32 * FINGER_STACK
+                    }
+                };
+                Self::draw_finger(finger, frame, Point::new(x, y));
+            }
+        }
+    }
+
+    // Número del dedo centrado en la posición
+    fn draw_finger(finger: u8, frame: &mut Frame, position: Point) {
+        frame.fill_text(Text {
+            content: finger.to_string(),
+            position: Point::new(position.x + 4.0, position.y),
+            color: Color::from_rgb(0.2, 0.2, 0.6),
+            size: 16.0.into(),
+            horizontal_alignment: Horizontal::Center,
+            vertical_alignment: Vertical::Center,
+            ..Text::default()
+        });
+    }
+
+    // Staccato y acento junto a la cabeza, en el lado contrario a la plica; el acento va más lejos
+    fn draw_articulations(note: &Note, frame: &mut Frame, head: Point) {
+        let direction: f32 = if note.stem_up() { 1.0 } else { -1.0 };
+        // La cabeza se dibuja desde su esquina superior izquierda
+        let center: Point = Point::new(head.x + 5.0, head.y + 2.5);
+        let color: Color = if note.is_active {
+            Color::from_rgb(0.94, 0.35, 0.25)
+        } else {
+            Color::BLACK
+        };
+
+        let mut distance: f32 = ARTICULATION_OFFSET;
+        for articulation in [Articulation::Staccato, Articulation::Accent] {
+            if !note.articulations.contains(&articulation) {
+                continue;
+            }
+            let y: f32 = center.y + direction * distance;
+            match articulation {
+                Articulation::Staccato => {
+                    frame.fill(&Path::circle(Point::new(center.x, y), 2.5), color);
+                }
+                Articulation::Accent => {
+                    let accent: Path = Path::new(|builder| {
+                        builder.move_to(Point::new(center.x - 7.0, y - 4.0));
+                        builder.line_to(Point::new(center.x + 7.0, y));
+                        builder.line_to(Point::new(center.x - 7.0, y + 4.0));
+                    });
+                    frame.stroke(
+                        &accent,
+                        Stroke {
+                            style: Style::Solid(color),
+                            width: 2.0,
+                            ..Stroke::default()
+                        },
+                    );
+                }
+            }
+            distance += ARTICULATION_OFFSET;
+        }
+    }
+
+    // Color de cada valoración: verde a tiempo, azul adelantada, naranja retrasada, rojo incorrecta
+    pub fn judgement_color(judgement: Judgement) -> Color {
+        match judgement {
+            Judgement::OnTime => Color::from_rgba(0.15, 0.65, 0.25, 0.6),
+            Judgement::Early => Color::from_rgba(0.15, 0.40, 0.90, 0.6),
+            Judgement::Late => Color::from_rgba(0.90, 0.49, 0.12, 0.6),
+            Judgement::Wrong => Color::from_rgba(0.90, 0.10, 0.10, 0.6),
+        }
+    }
+
     fn draw_plicas(new_note: &Note, actual_position: &mut Point) {
         // Plicas
         if new_note.pitch < 54 {
@@ -83,11 +258,10 @@ impl<'a> AllNotesOverlay<'a> {
     }
 
     // Método para calcular la posición Y de la nota en el pentagrama
-    fn calculate_note_y_in_staff(&self, note: &Note, staff_area: &Rectangle) -> f32 {
+    pub fn calculate_note_y_in_staff(&self, pitch: u8, staff_area: &Rectangle) -> f32 {
         let line_spacing: f32 = staff_area.height / 6.0;
 
         // Cada nota tiene una posición en el pentagrama según su nombre y octava
-        let pitch: u8 = note.pitch;
         let staff_y_offset: f32 = staff_area.y + staff_area.height;
 
         // Calcular cuántos "pasos" está por encima del Do4 (MIDI 60)
@@ -120,6 +294,6 @@ impl<'a> AllNotesOverlay<'a> {
         };
 
         // Cada paso son medio espacio de pentagrama (línea o espacio)
-        staff_y_offset - (steps_from_c4 as f32 * (line_spacing / 2.0)) - 5.0
+        staff_y_offset - (steps_from_c4 * (line_spacing / 2.0)) - 5.0
     }
 }
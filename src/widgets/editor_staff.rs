@@ -0,0 +1,134 @@
+use {
+    crate::{
+        message::states::{AppMessage, EditorMessage},
+        models::partiture::{Hand, Partiture},
+        styles::custom_style::ColorPalette,
+        widgets::all_notes_overlay::AllNotesOverlay,
+    },
+    iced::{
+        Color, Point, Rectangle, Renderer, Size, Theme,
+        event::Status,
+        mouse::{self, Cursor},
+        widget::canvas::{Event, Frame, Geometry, Path, Program},
+    },
+};
+
+// Teclas blancas dentro de una octava, en semitonos desde el Do
+const NATURALS: [u8; 7] = [0, 2, 4, 5, 7, 9, 11];
+
+// Pasos de pentagrama entre la clave de sol y la de fa: del Mi4 de la primera línea al Sol2
+const BASS_CLEF_STEPS: i32 = 12;
+
+// Pentagrama de una mano en el editor: la partitura parada en el compás del cursor,
+// con el cursor encima. Un click escribe o borra la nota de esa línea o espacio.
+pub struct EditorStaff {
+    pub partiture: Partiture,
+    pub cursor: f32,  // Segundo del cursor desde el inicio de la sección
+    pub active: bool, // Si es la mano en la que se escribe
+}
+
+// Tono de una tecla blanca contando pasos de pentagrama desde el Do-1
+fn natural_pitch(steps: i32) -> u8 {
+    let octave: i32 = steps.div_euclid(7);
+    (octave * 12 + NATURALS[steps.rem_euclid(7) as usize] as i32).clamp(21, 108) as u8
+}
+
+impl EditorStaff {
+    // Segundo de la sección en una posición horizontal del lienzo
+    fn time_at(&self, x: f32) -> f32 {
+        let view_start: f32 = self.partiture.elapsed() - self.partiture.settings.timer;
+        view_start + (x - self.partiture.img_width) / self.partiture.calculate_pixels_per_second()
+    }
+
+    fn time_x(&self, time: f32) -> f32 {
+        let view_start: f32 = self.partiture.elapsed() - self.partiture.settings.timer;
+        self.partiture.img_width
+            + (time - view_start) * self.partiture.calculate_pixels_per_second()
+    }
+
+    /// Tecla blanca más cercana a una altura del pentagrama. La posición de cada nota la da
+    /// la mano derecha; en la izquierda se baja de la clave de sol a la de fa.
+    fn pitch_at(&self, y: f32, bounds: Size) -> u8 {
+        let overlay: AllNotesOverlay = AllNotesOverlay {
+            partiture: &self.partiture,
+        };
+        let area: Rectangle = Rectangle::new(Point::ORIGIN, bounds);
+        let steps: i32 = (35..=48)
+            .min_by(|a, b| {
+                let distance = |steps: i32| -> f32 {
+                    (overlay.calculate_note_y_in_staff(natural_pitch(steps), &area) - y).abs()
+                };
+                distance(*a).total_cmp(&distance(*b))
+            })
+            .unwrap_or(35);
+
+        match self.partiture.hand {
+            Hand::Right => natural_pitch(steps),
+            Hand::Left => natural_pitch(steps - BASS_CLEF_STEPS),
+        }
+    }
+
+    // Línea del cursor, más clara en la mano en la que no se escribe
+    fn draw_cursor(&self, frame: &mut Frame, bounds: Size) {
+        let x: f32 = self.time_x(self.cursor) + 4.0;
+        if x < self.partiture.img_width || x > bounds.width {
+            return;
+        }
+        let alpha: f32 = if self.active { 1.0 } else { 0.35 };
+        frame.fill(
+            &Path::rectangle(Point::new(x - 1.5, 0.0), Size::new(3.0, bounds.height)),
+            Color {
+                a: alpha,
+                ..ColorPalette::ACCENT_ORANGE
+            },
+        );
+    }
+}
+
+impl Program<AppMessage> for EditorStaff {
+    type State = ();
+
+    fn update(
+        &self,
+        _state: &mut Self::State,
+        event: Event,
+        bounds: Rectangle,
+        cursor: Cursor,
+    ) -> (Status, Option<AppMessage>) {
+        let Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) = event else {
+            return (Status::Ignored, None);
+        };
+        let Some(position) = cursor.position_in(bounds) else {
+            return (Status::Ignored, None);
+        };
+        // La zona de la clave no es parte de la partitura
+        if position.x < self.partiture.img_width {
+            return (Status::Ignored, None);
+        }
+
+        let message: EditorMessage = EditorMessage::Click(
+            self.partiture.hand,
+            self.time_at(position.x - 4.0).max(0.0),
+            self.pitch_at(position.y, bounds.size()),
+        );
+        (Status::Captured, Some(AppMessage::Editor(message)))
+    }
+
+    fn draw(
+        &self,
+        state: &Self::State,
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        cursor: Cursor,
+    ) -> Vec<Geometry> {
+        // El pentagrama, las notas y las marcas se dibujan igual que en el juego
+        let mut layers: Vec<Geometry> =
+            Program::<AppMessage>::draw(&self.partiture, state, renderer, theme, bounds, cursor);
+
+        let mut frame: Frame<Renderer> = Frame::new(renderer, bounds.size());
+        self.draw_cursor(&mut frame, bounds.size());
+        layers.push(frame.into_geometry());
+        layers
+    }
+}
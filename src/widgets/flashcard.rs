@@ -0,0 +1,104 @@
+use {
+    crate::models::{
+        flashcards::{Flashcard, diatonic_position},
+        note::Note,
+        partiture::Partiture,
+    },
+    iced::{
+        Color, Point, Rectangle, Renderer, Size, Theme,
+        mouse::Cursor,
+        widget::canvas::{Frame, Geometry, Path, Program},
+    },
+};
+
+/// Alto del lienzo de la tarjeta y del pentagrama dentro de él. Queda sitio por encima y
+/// por debajo para las líneas adicionales.
+pub const FLASHCARD_HEIGHT: f32 = 320.0;
+pub const FLASHCARD_STAFF_TOP: f32 = 60.0;
+const STAFF_HEIGHT: f32 = 200.0;
+
+// Espacio reservado a la izquierda para la clave, como en la partitura del juego
+const CLEF_WIDTH: f32 = 200.0;
+
+// Ancho de las líneas adicionales y desplazamiento de la nota de una segunda
+const LEDGER_WIDTH: f32 = 26.0;
+const SECOND_OFFSET: f32 = 14.0;
+
+/// Pentagrama de una tarjeta de lectura con sus notas, sin desplazamiento en el tiempo
+pub struct FlashcardStaff {
+    pub card: Flashcard,
+}
+
+impl FlashcardStaff {
+    // Dibujar cada nota con sus líneas adicionales. En una segunda la nota de arriba
+    // se aparta a la derecha para que no se monten las cabezas.
+    fn draw_notes(&self, frame: &mut Frame, staff: Rectangle) {
+        let step: f32 = staff.height / 10.0;
+        let bottom_line: f32 = staff.y + staff.height * 0.8;
+        let x: f32 = (staff.x + staff.width / 2.0).max(staff.x + CLEF_WIDTH + 60.0);
+
+        let mut previous: Option<(i32, bool)> = None;
+        for &pitch in &self.card.pitches {
+            let position: i32 = diatonic_position(pitch) - self.card.clef.bottom_line();
+            let shifted: bool = matches!(previous, Some((p, false)) if position - p == 1);
+            previous = Some((position, shifted));
+
+            let head_x: f32 = if shifted { x + SECOND_OFFSET } else { x };
+            let y: f32 = bottom_line - position as f32 * step;
+
+            self.draw_ledger_lines(frame, head_x, bottom_line, step, position);
+            let note: Note = Note::new(pitch, 1.0, false, Default::default());
+            // La cabeza se dibuja desde su esquina superior
+            note.draw(frame, Point::new(head_x, y - 2.5));
+        }
+    }
+
+    // Líneas adicionales entre el pentagrama y la nota
+    fn draw_ledger_lines(
+        &self,
+        frame: &mut Frame,
+        head_x: f32,
+        bottom_line: f32,
+        step: f32,
+        position: i32,
+    ) {
+        let below = (position..=-2).filter(|p| p % 2 == 0);
+        let above = (10..=position).filter(|p| p % 2 == 0);
+        for line in below.chain(above) {
+            let y: f32 = bottom_line - line as f32 * step;
+            let ledger: Path = Path::rectangle(
+                Point::new(head_x + 5.0 - LEDGER_WIDTH / 2.0, y - 1.0),
+                Size::new(LEDGER_WIDTH, 2.0),
+            );
+            frame.fill(&ledger, Color::BLACK);
+        }
+    }
+}
+
+impl<AppMessage> Program<AppMessage> for FlashcardStaff {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame: Frame<Renderer> = Frame::new(renderer, bounds.size());
+        frame.fill(&Path::rectangle(Point::ORIGIN, bounds.size()), Color::WHITE);
+
+        // Las mismas líneas que la partitura del juego
+        let staff: Rectangle = Rectangle {
+            x: 0.0,
+            y: FLASHCARD_STAFF_TOP,
+            width: bounds.width,
+            height: STAFF_HEIGHT,
+        };
+        Partiture::draw_staff_lines(&mut frame, staff);
+        self.draw_notes(&mut frame, staff);
+
+        vec![frame.into_geometry()]
+    }
+}
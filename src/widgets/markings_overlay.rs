@@ -0,0 +1,247 @@
+use {
+    crate::{
+        models::{
+            markings::{Dynamic, HairpinKind},
+            partiture::{Hand, Partiture},
+        },
+        widgets::all_notes_overlay::AllNotesOverlay,
+    },
+    iced::{
+        Color, Font, Point, Rectangle,
+        alignment::{Horizontal, Vertical},
+        font::{Style as FontStyle, Weight},
+        widget::canvas::{Frame, Path, Stroke, Style, Text},
+    },
+};
+
+// Distancia de la ligadura a la nota más alejada, por fuera de las plicas
+const SLUR_OFFSET: f32 = 34.0;
+
+// Curvatura de las ligaduras
+const SLUR_HEIGHT: f32 = 14.0;
+
+// Distancia al borde inferior de los matices, reguladores y pedal
+const BOTTOM_MARGIN: f32 = 14.0;
+
+// Apertura de los reguladores
+const HAIRPIN_WIDTH: f32 = 10.0;
+
+// Hueco que deja un matiz antes del regulador que empieza con él
+const DYNAMIC_GAP: f32 = 26.0;
+
+// Dinámicas, ligaduras y pedal de la partitura. Las ligaduras van en el pentagrama de su mano;
+// los matices y reguladores debajo de la mano derecha, entre los dos pentagramas, y el pedal
+// debajo de la mano izquierda.
+pub struct MarkingsOverlay<'a> {
+    pub partiture: &'a Partiture,
+}
+
+impl<'a> MarkingsOverlay<'a> {
+    pub fn draw(&self, frame: &mut Frame, layout_bounds: Rectangle) {
+        self.draw_slurs(frame, layout_bounds);
+        match self.partiture.hand {
+            Hand::Right => {
+                self.draw_dynamics(frame, layout_bounds);
+                self.draw_hairpins(frame, layout_bounds);
+            }
+            Hand::Left => self.draw_pedal(frame, layout_bounds),
+        }
+    }
+
+    // Posición horizontal de un segundo de la partitura
+    fn time_x(&self, time: f32, layout_bounds: Rectangle) -> f32 {
+        let current_time: f32 = self.partiture.elapsed() - self.partiture.settings.timer;
+        layout_bounds.x
+            + self.partiture.img_width
+            + (time - current_time) * self.partiture.calculate_pixels_per_second()
+    }
+
+    // Si algo entre esas dos posiciones se ve en la pantalla
+    fn visible(&self, from: f32, to: f32, layout_bounds: Rectangle) -> bool {
+        to >= layout_bounds.x + self.partiture.img_width - 50.0
+            && from <= layout_bounds.x + layout_bounds.width + 50.0
+    }
+
+    // Arco por encima de las notas en la mano derecha y por debajo en la izquierda
+    fn draw_slurs(&self, frame: &mut Frame, layout_bounds: Rectangle) {
+        let overlay: AllNotesOverlay = AllNotesOverlay {
+            partiture: self.partiture,
+        };
+        let direction: f32 = match self.partiture.hand {
+            Hand::Right => -1.0,
+            Hand::Left => 1.0,
+        };
+
+        for slur in self
+            .partiture
+            .markings
+            .slurs
+            .iter()
+            .filter(|slur| slur.hand == self.partiture.hand)
+        {
+            let (from, to): (f32, f32) = (
+                self.time_x(slur.start, layout_bounds) + 5.0,
+                self.time_x(slur.end, layout_bounds) + 5.0,
+            );
+            if !self.visible(from, to, layout_bounds) {
+                continue;
+            }
+
+            // Altura de la nota más alejada del centro del pentagrama
+            let heads: Vec<f32> = self
+                .partiture
+                .notes
+                .iter()
+                .filter(|note| {
+                    !note.is_rest
+                        && note.start >= slur.start - 1e-3
+                        && note.start <= slur.end + 1e-3
+                })
+                .map(|note| overlay.calculate_note_y_in_staff(note.pitch, &layout_bounds))
+                .collect();
+            let Some(edge) = heads
+                .iter()
+                .copied()
+                .reduce(|a, b| match self.partiture.hand {
+                    Hand::Right => a.min(b),
+                    Hand::Left => a.max(b),
+                })
+            else {
+                continue;
+            };
+            let y: f32 = (edge + direction * SLUR_OFFSET).clamp(
+                layout_bounds.y + SLUR_HEIGHT,
+                layout_bounds.y + layout_bounds.height - SLUR_HEIGHT,
+            );
+
+            let slur_path: Path = Path::new(|builder| {
+                builder.move_to(Point::new(from, y));
+                builder.quadratic_curve_to(
+                    Point::new((from + to) / 2.0, y + direction * SLUR_HEIGHT * 2.0),
+                    Point::new(to, y),
+                );
+            });
+            frame.stroke(
+                &slur_path,
+                Stroke {
+                    style: Style::Solid(Color::BLACK),
+                    width: 2.0,
+                    ..Stroke::default()
+                },
+            );
+        }
+    }
+
+    fn draw_dynamics(&self, frame: &mut Frame, layout_bounds: Rectangle) {
+        let y: f32 = layout_bounds.y + layout_bounds.height - BOTTOM_MARGIN;
+        for mark in &self.partiture.markings.dynamics {
+            let x: f32 = self.time_x(mark.start, layout_bounds);
+            if self.visible(x, x, layout_bounds) {
+                Self::draw_dynamic(frame, mark.level, Point::new(x, y));
+            }
+        }
+    }
+
+    // Matiz en negrita y cursiva, como en la partitura impresa
+    fn draw_dynamic(frame: &mut Frame, level: Dynamic, position: Point) {
+        frame.fill_text(Text {
+            content: level.to_string(),
+            position,
+            color: Color::BLACK,
+            size: 22.0.into(),
+            font: Font {
+                weight: Weight::Bold,
+                style: FontStyle::Italic,
+                ..Font::DEFAULT
+            },
+            horizontal_alignment: Horizontal::Left,
+            vertical_alignment: Vertical::Center,
+            ..Text::default()
+        });
+    }
+
+    // Reguladores abiertos hacia el final (crescendo) o hacia el principio (diminuendo)
+    fn draw_hairpins(&self, frame: &mut Frame, layout_bounds: Rectangle) {
+        let y: f32 = layout_bounds.y + layout_bounds.height - BOTTOM_MARGIN;
+        let markings = &self.partiture.markings;
+
+        for hairpin in &markings.hairpins {
+            let mut from: f32 = self.time_x(hairpin.start, layout_bounds);
+            let to: f32 = self.time_x(hairpin.end, layout_bounds) - 4.0;
+            if !self.visible(from, to, layout_bounds) {
+                continue;
+            }
+            // Si empieza con un matiz, el regulador va después del texto
+            if markings
+                .dynamics
+                .iter()
+                .any(|mark| (mark.start - hairpin.start).abs() < 1e-3)
+            {
+                from += DYNAMIC_GAP;
+            }
+            if to <= from {
+                continue;
+            }
+
+            let (point, open): (f32, f32) = match hairpin.kind {
+                HairpinKind::Crescendo => (from, to),
+                HairpinKind::Diminuendo => (to, from),
+            };
+            let hairpin_path: Path = Path::new(|builder| {
+                builder.move_to(Point::new(open, y - HAIRPIN_WIDTH / 2.0));
+                builder.line_to(Point::new(point, y));
+                builder.line_to(Point::new(open, y + HAIRPIN_WIDTH / 2.0));
+            });
+            frame.stroke(
+                &hairpin_path,
+                Stroke {
+                    style: Style::Solid(Color::BLACK),
+                    width: 2.0,
+                    ..Stroke::default()
+                },
+            );
+        }
+    }
+
+    // "Ped." al pisar y un corchete hasta que se levanta
+    fn draw_pedal(&self, frame: &mut Frame, layout_bounds: Rectangle) {
+        let y: f32 = layout_bounds.y + layout_bounds.height - BOTTOM_MARGIN;
+
+        for pedal in &self.partiture.markings.pedal {
+            let from: f32 = self.time_x(pedal.start, layout_bounds);
+            let to: f32 = self.time_x(pedal.end, layout_bounds);
+            if !self.visible(from, to, layout_bounds) {
+                continue;
+            }
+
+            frame.fill_text(Text {
+                content: "Ped.".to_string(),
+                position: Point::new(from + 4.0, y),
+                color: Color::BLACK,
+                size: 16.0.into(),
+                font: Font {
+                    style: FontStyle::Italic,
+                    ..Font::DEFAULT
+                },
+                horizontal_alignment: Horizontal::Left,
+                vertical_alignment: Vertical::Bottom,
+                ..Text::default()
+            });
+
+            let bracket: Path = Path::new(|builder| {
+                builder.move_to(Point::new(from, y - 14.0));
+                builder.line_to(Point::new(from, y + 4.0));
+                builder.line_to(Point::new(to, y + 4.0));
+                builder.line_to(Point::new(to, y - 6.0));
+            });
+            frame.stroke(
+                &bracket,
+                Stroke {
+                    style: Style::Solid(Color::BLACK),
+                    width: 1.5,
+                    ..Stroke::default()
+                },
+            );
+        }
+    }
+}
@@ -18,6 +18,9 @@ impl Note {
             joined,
             last_position,
             is_rest: pitch == 0,
+            finger: None,
+            velocity: None,
+            articulations: Vec::new(),
         }
     }
 
@@ -117,20 +120,17 @@ impl Note {
         frame.fill(&head, palette.secondary);
     }
 
+    // Plica hacia arriba en la parte baja de cada pentagrama (hasta el Si4 en la mano derecha
+    // y por debajo del Fa#3 en la izquierda) y hacia abajo en la alta
+    pub fn stem_up(&self) -> bool {
+        self.pitch < 54 || (60..=71).contains(&self.pitch)
+    }
+
     // Dibujar plica vertical, negras y blancas
     fn draw_stem(&self, frame: &mut Frame, mut center: Point, color: Color) {
-        if self.pitch < 54 {
-            // Mano izquierda - plica hacia arriba
-            center.y -= 25.0;
-        } else if self.pitch < 60 {
-            // Mano izquierda - plica hacia abajo
-            center.y += 5.0;
-            center.x -= 9.0;
-        } else if self.pitch <= 71 {
-            // Mano derecha - plica hacia arriba
+        if self.stem_up() {
             center.y -= 25.0;
         } else {
-            // Mano derecha - plica hacia abajo
             center.y += 5.0;
             center.x -= 9.0;
         }
@@ -1,7 +1,7 @@
 use {
     crate::{
         models::partiture::{Partiture, PieceMetadata},
-        widgets::all_notes_overlay::AllNotesOverlay,
+        widgets::{all_notes_overlay::AllNotesOverlay, markings_overlay::MarkingsOverlay},
     },
     iced::{
         Color, Point, Rectangle, Renderer, Size, Theme,
@@ -28,7 +28,7 @@ impl Partiture {
     }
 
     // Dibujar las líneas del pentagrama
-    fn draw_staff_lines(&self, frame: &mut Frame, bounds: iced::Rectangle) {
+    pub fn draw_staff_lines(frame: &mut Frame, bounds: iced::Rectangle) {
         let line_height: f32 = 2.0;
         let line_spacing: f32 = (bounds.height - (5.0 * line_height)) / 5.0;
 
@@ -42,25 +42,19 @@ impl Partiture {
         }
     }
 
-    // Calcular duración de un compás completo
-    fn calculate_bar_duration(metadata: &PieceMetadata) -> f32 {
-        let (beats, _) = metadata.time_signature;
-        beats as f32 * metadata.base_note_value
-    }
-
     // Dibujar línea divisoria de compás
     // Dibujar todas las líneas de compás
     fn draw_bar_lines(&self, frame: &mut Frame, layout_bounds: iced::Rectangle) {
         let bar_duration: f32 = self
             .metadata
             .as_ref()
-            .map(|metadata| Self::calculate_bar_duration(metadata))
+            .map(PieceMetadata::bar_duration)
             .unwrap_or(0.0);
 
         let pixels_per_second: f32 = self.calculate_pixels_per_second();
         let start_x: f32 = layout_bounds.x + self.img_width;
 
-        let current_time: f32 = self.elapsed - self.settings.timer;
+        let current_time: f32 = self.elapsed() - self.settings.timer;
         let scroll_offset: f32 = current_time * pixels_per_second;
 
         let num_bars: usize = (self.time / bar_duration).ceil() as usize;
@@ -94,7 +88,7 @@ impl Partiture {
         let screen_size = frame.size();
         frame.fill(&Path::rectangle(Point::ORIGIN, screen_size), Color::WHITE);
 
-        self.draw_staff_lines(frame, relative_bounds);
+        Self::draw_staff_lines(frame, relative_bounds);
         self.draw_bar_lines(frame, relative_bounds);
     }
 }
@@ -125,9 +119,13 @@ impl<AppMessage> Program<AppMessage> for Partiture {
         self.draw_partiture(&mut frame, relative_bounds);
 
         // Dibuja todas las notas usando AllNotesOverlay
-        let overlay: AllNotesOverlay = AllNotesOverlay { partiture: &self };
+        let overlay: AllNotesOverlay = AllNotesOverlay { partiture: self };
         overlay.draw(&mut frame, relative_bounds);
 
+        // Dinámicas, ligaduras y pedal por encima de las notas
+        let markings: MarkingsOverlay = MarkingsOverlay { partiture: self };
+        markings.draw(&mut frame, relative_bounds);
+
         // Retorna el frame como geometría
         vec![frame.into_geometry()]
     }
@@ -0,0 +1,382 @@
+use {
+    crate::{
+        models::rhythm::{RhythmLine, RhythmTap, TAP_WINDOW},
+        styles::custom_style::ColorPalette,
+    },
+    iced::{
+        Color, Point, Rectangle, Renderer, Size, Theme,
+        alignment::{Horizontal, Vertical},
+        mouse::Cursor,
+        widget::canvas::{self, Frame, Geometry, Path, Program, Stroke},
+    },
+};
+
+/// Alto del lienzo de una línea de ritmo
+pub const RHYTHM_STAFF_HEIGHT: f32 = 150.0;
+
+/// Compases que caben en una línea
+pub const BARS_PER_LINE: usize = 4;
+
+/// Desviaciones que se consideran a tiempo y casi a tiempo, en segundos
+pub const GOOD_TIMING: f32 = 0.04;
+pub const FAIR_TIMING: f32 = 0.09;
+
+// Altura de la línea, margen para el compás y largo de las plicas
+const LINE_Y: f32 = 85.0;
+const LEFT_MARGIN: f32 = 60.0;
+const RIGHT_MARGIN: f32 = 20.0;
+const STEM_HEIGHT: f32 = 32.0;
+const HEAD_WIDTH: f32 = 10.0;
+const HEAD_HEIGHT: f32 = 7.0;
+const BEAM_HEIGHT: f32 = 4.0;
+const BEAM_GAP: f32 = 7.0;
+
+// Figuras desde la redonda, en negras
+const VALUES: [f32; 6] = [4.0, 2.0, 1.0, 0.5, 0.25, 0.125];
+
+/// Color de una pulsación según lo lejos que cayó de su nota
+pub fn timing_color(deviation: f32) -> Color {
+    match deviation.abs() {
+        d if d <= GOOD_TIMING => Color::from_rgb(0.2, 0.65, 0.3),
+        d if d <= FAIR_TIMING => ColorPalette::ACCENT_ORANGE,
+        _ => ColorPalette::ACCENT_RED,
+    }
+}
+
+/// Línea de ritmo de `BARS_PER_LINE` compases, con las pulsaciones encima
+pub struct RhythmStaff {
+    pub line: RhythmLine,
+    pub first_bar: usize,
+    pub position: Option<f32>,        // Segundo actual, mientras se toca
+    pub taps: Vec<RhythmTap>,         // Pulsaciones con su segundo en la línea
+    pub deviations: Vec<Option<f32>>, // Desviación de cada ataque, `None` si no se pulsó
+}
+
+// Figura de una duración: valor en negras y si lleva puntillo
+fn figure(quarters: f32) -> (f32, bool) {
+    for value in VALUES {
+        if (quarters - value * 1.5).abs() < 0.01 {
+            return (value, true);
+        }
+        if quarters >= value - 0.01 {
+            return (value, false);
+        }
+    }
+    (VALUES[VALUES.len() - 1], false)
+}
+
+// Barras o corchetes de una figura: 1 en la corchea, 2 en la semicorchea...
+fn beams(value: f32) -> usize {
+    match value {
+        v if v <= 0.125 => 3,
+        v if v <= 0.25 => 2,
+        v if v <= 0.5 => 1,
+        _ => 0,
+    }
+}
+
+impl RhythmStaff {
+    fn bar_seconds(&self) -> f32 {
+        self.line.metadata.bar_duration().max(f32::EPSILON)
+    }
+
+    // Duración de una negra: la unidad del compás escalada a cuartos
+    fn quarter_seconds(&self) -> f32 {
+        let (_, beat_unit) = self.line.metadata.time_signature;
+        self.line.metadata.base_note_value * beat_unit as f32 / 4.0
+    }
+
+    // Duración de cada grupo de corcheas unidas por la barra: el pulso, o el compás en 3/8
+    fn beam_group_seconds(&self) -> f32 {
+        match self.line.metadata.time_signature {
+            (3, 8) => self.bar_seconds(),
+            _ => self.line.metadata.pulses().1,
+        }
+    }
+
+    fn page(&self) -> (f32, f32) {
+        let start: f32 = self.first_bar as f32 * self.bar_seconds();
+        (start, start + BARS_PER_LINE as f32 * self.bar_seconds())
+    }
+
+    fn x_of(&self, time: f32, bounds: Size) -> f32 {
+        let (start, end) = self.page();
+        let width: f32 = bounds.width - LEFT_MARGIN - RIGHT_MARGIN;
+        LEFT_MARGIN + (time - start) / (end - start) * width
+    }
+
+    fn draw_line(&self, frame: &mut Frame, bounds: Size) {
+        frame.fill(
+            &Path::rectangle(
+                Point::new(LEFT_MARGIN - 10.0, LINE_Y - 1.0),
+                Size::new(bounds.width - LEFT_MARGIN - RIGHT_MARGIN + 10.0, 2.0),
+            ),
+            Color::BLACK,
+        );
+
+        // Barras de compás y número de cada compás
+        let bars: usize = self.line.bars();
+        for bar in self.first_bar..=(self.first_bar + BARS_PER_LINE).min(bars) {
+            let x: f32 = self.x_of(bar as f32 * self.bar_seconds(), bounds) - 8.0;
+            let width: f32 = if bar == bars { 4.0 } else { 2.0 };
+            frame.fill(
+                &Path::rectangle(Point::new(x, LINE_Y - 20.0), Size::new(width, 40.0)),
+                Color::BLACK,
+            );
+            if bar < bars {
+                frame.fill_text(canvas::Text {
+                    content: (bar + 1).to_string(),
+                    position: Point::new(x + 6.0, LINE_Y + 30.0),
+                    color: ColorPalette::SHADOW_DARK,
+                    size: 12.0.into(),
+                    ..canvas::Text::default()
+                });
+            }
+        }
+
+        // El compás al principio de la línea
+        let (beats, beat_unit) = self.line.metadata.time_signature;
+        for (content, y) in [(beats, LINE_Y - 12.0), (beat_unit, LINE_Y + 12.0)] {
+            frame.fill_text(canvas::Text {
+                content: content.to_string(),
+                position: Point::new(LEFT_MARGIN / 2.0, y),
+                color: Color::BLACK,
+                size: 22.0.into(),
+                horizontal_alignment: Horizontal::Center,
+                vertical_alignment: Vertical::Center,
+                ..canvas::Text::default()
+            });
+        }
+    }
+
+    // Color de la cabeza: según la pulsación, en rojo si ya pasó sin pulsarse
+    fn head_color(&self, onset: usize, start: f32) -> Color {
+        match self.deviations.get(onset).copied().flatten() {
+            Some(deviation) => timing_color(deviation),
+            None if self.position.is_none_or(|p| p > start + TAP_WINDOW) => {
+                ColorPalette::ACCENT_RED
+            }
+            None => Color::BLACK,
+        }
+    }
+
+    fn draw_notes(&self, frame: &mut Frame, bounds: Size) {
+        let (page_start, page_end) = self.page();
+        let quarter: f32 = self.quarter_seconds().max(f32::EPSILON);
+        let group: f32 = self.beam_group_seconds().max(f32::EPSILON);
+
+        // Notas que llevan barra, para unirlas por grupos: (x de la plica, barras, grupo)
+        let mut beamed: Vec<(f32, usize, i64)> = Vec::new();
+        let mut onset: usize = 0;
+        for note in &self.line.notes {
+            let in_page: bool = note.start >= page_start - 1e-3 && note.start < page_end - 1e-3;
+            let (value, dotted) = figure(note.duration / quarter);
+            if note.rest {
+                if in_page {
+                    self.draw_rest(frame, self.x_of(note.start, bounds), value, dotted);
+                }
+                beamed.push((0.0, 0, -1));
+                continue;
+            }
+            let index: usize = onset;
+            onset += 1;
+            if !in_page {
+                continue;
+            }
+
+            let x: f32 = self.x_of(note.start, bounds);
+            let color: Color = self.head_color(index, note.start);
+            self.draw_head(frame, x, value, dotted, color);
+            beamed.push((
+                x + HEAD_WIDTH - 2.0,
+                beams(value),
+                ((note.start + 1e-3) / group).floor() as i64,
+            ));
+        }
+        self.draw_beams(frame, &beamed);
+    }
+
+    fn draw_head(&self, frame: &mut Frame, x: f32, value: f32, dotted: bool, color: Color) {
+        let head: Path = Path::rectangle(
+            Point::new(x, LINE_Y - HEAD_HEIGHT / 2.0),
+            Size::new(HEAD_WIDTH, HEAD_HEIGHT),
+        );
+        if value >= 2.0 {
+            frame.fill(&head, Color::WHITE);
+            frame.stroke(&head, Stroke::default().with_color(color).with_width(2.5));
+        } else {
+            frame.fill(&head, color);
+        }
+        if value < 4.0 {
+            frame.fill(
+                &Path::rectangle(
+                    Point::new(x + HEAD_WIDTH - 2.0, LINE_Y - STEM_HEIGHT),
+                    Size::new(2.0, STEM_HEIGHT),
+                ),
+                Color::BLACK,
+            );
+        }
+        if dotted {
+            frame.fill(
+                &Path::circle(Point::new(x + HEAD_WIDTH + 6.0, LINE_Y + 2.0), 2.5),
+                Color::BLACK,
+            );
+        }
+    }
+
+    // Une con barras las corcheas seguidas de un mismo grupo; las sueltas llevan corchete
+    fn draw_beams(&self, frame: &mut Frame, notes: &[(f32, usize, i64)]) {
+        let top: f32 = LINE_Y - STEM_HEIGHT;
+        let mut i: usize = 0;
+        while i < notes.len() {
+            let (_, count, group) = notes[i];
+            if count == 0 {
+                i += 1;
+                continue;
+            }
+            let mut j: usize = i + 1;
+            while j < notes.len() && notes[j].1 > 0 && notes[j].2 == group {
+                j += 1;
+            }
+            let run: &[(f32, usize, i64)] = &notes[i..j];
+
+            if run.len() == 1 {
+                let (x, count, _) = run[0];
+                for level in 0..count {
+                    let y: f32 = top + level as f32 * BEAM_GAP;
+                    let flag: Path = Path::line(Point::new(x, y), Point::new(x + 9.0, y + 10.0));
+                    frame.stroke(&flag, Stroke::default().with_width(3.0));
+                }
+            } else {
+                // Cada barra une las notas vecinas que la tienen; la que queda sola lleva
+                // un trozo hacia la nota anterior, o hacia la siguiente si es la primera
+                for (k, &(x, count, _)) in run.iter().enumerate() {
+                    for level in 0..count {
+                        let y: f32 = top + level as f32 * BEAM_GAP;
+                        let previous: bool = k > 0 && run[k - 1].1 > level;
+                        let (left, right): (f32, f32) = match run.get(k + 1) {
+                            Some(&(next, next_count, _)) if next_count > level => (x, next + 2.0),
+                            _ if previous => continue,
+                            _ if k > 0 => (x - 8.0, x + 2.0),
+                            _ => (x, x + 10.0),
+                        };
+                        frame.fill(
+                            &Path::rectangle(
+                                Point::new(left, y),
+                                Size::new(right - left, BEAM_HEIGHT),
+                            ),
+                            Color::BLACK,
+                        );
+                    }
+                }
+            }
+            i = j;
+        }
+    }
+
+    fn draw_rest(&self, frame: &mut Frame, x: f32, value: f32, dotted: bool) {
+        match beams(value) {
+            0 if value >= 4.0 => frame.fill(
+                &Path::rectangle(Point::new(x, LINE_Y - 10.0), Size::new(14.0, 6.0)),
+                Color::BLACK,
+            ),
+            0 if value >= 2.0 => frame.fill(
+                &Path::rectangle(Point::new(x, LINE_Y - 6.0), Size::new(14.0, 6.0)),
+                Color::BLACK,
+            ),
+            // Silencio de negra: un zigzag
+            0 => {
+                let zigzag: Path = Path::new(|path| {
+                    path.move_to(Point::new(x + 2.0, LINE_Y - 16.0));
+                    path.line_to(Point::new(x + 9.0, LINE_Y - 7.0));
+                    path.line_to(Point::new(x + 3.0, LINE_Y + 1.0));
+                    path.line_to(Point::new(x + 9.0, LINE_Y + 9.0));
+                    path.line_to(Point::new(x + 3.0, LINE_Y + 14.0));
+                });
+                frame.stroke(&zigzag, Stroke::default().with_width(3.0));
+            }
+            // Silencio de corchea o menor: una diagonal con un punto por barra
+            count => {
+                let slash: Path = Path::line(
+                    Point::new(x + 10.0, LINE_Y - 10.0),
+                    Point::new(x + 4.0, LINE_Y + 12.0),
+                );
+                frame.stroke(&slash, Stroke::default().with_width(2.5));
+                for level in 0..count {
+                    let y: f32 = LINE_Y - 8.0 + level as f32 * 7.0;
+                    frame.fill(
+                        &Path::circle(Point::new(x + 4.0 - level as f32 * 2.0, y), 3.0),
+                        Color::BLACK,
+                    );
+                }
+            }
+        }
+        if dotted {
+            frame.fill(
+                &Path::circle(Point::new(x + 18.0, LINE_Y - 4.0), 2.5),
+                Color::BLACK,
+            );
+        }
+    }
+
+    // Una marca por pulsación encima de la línea; las que sobran en gris
+    fn draw_taps(&self, frame: &mut Frame, bounds: Size) {
+        let (page_start, page_end) = self.page();
+        let onsets: Vec<f32> = self.line.onsets();
+        for tap in &self.taps {
+            if tap.time < page_start - TAP_WINDOW || tap.time >= page_end {
+                continue;
+            }
+            let color: Color = match tap.onset {
+                Some(onset) => timing_color(tap.time - onsets[onset]),
+                None => ColorPalette::SHADOW_DARK,
+            };
+            let x: f32 = self.x_of(tap.time, bounds) + HEAD_WIDTH / 2.0;
+            let marker: Path = Path::new(|path| {
+                path.move_to(Point::new(x - 5.0, LINE_Y - 62.0));
+                path.line_to(Point::new(x + 5.0, LINE_Y - 62.0));
+                path.line_to(Point::new(x, LINE_Y - 52.0));
+                path.close();
+            });
+            frame.fill(&marker, color);
+        }
+    }
+
+    fn draw_position(&self, frame: &mut Frame, bounds: Size) {
+        let Some(position) = self.position else {
+            return;
+        };
+        let (page_start, page_end) = self.page();
+        if position < page_start || position >= page_end {
+            return;
+        }
+        let x: f32 = self.x_of(position, bounds) + HEAD_WIDTH / 2.0;
+        frame.fill(
+            &Path::rectangle(Point::new(x - 1.5, LINE_Y - 70.0), Size::new(3.0, 100.0)),
+            ColorPalette::ACCENT_ORANGE,
+        );
+    }
+}
+
+impl<AppMessage> Program<AppMessage> for RhythmStaff {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame: Frame<Renderer> = Frame::new(renderer, bounds.size());
+        frame.fill(&Path::rectangle(Point::ORIGIN, bounds.size()), Color::WHITE);
+
+        self.draw_line(&mut frame, bounds.size());
+        self.draw_notes(&mut frame, bounds.size());
+        self.draw_taps(&mut frame, bounds.size());
+        self.draw_position(&mut frame, bounds.size());
+
+        vec![frame.into_geometry()]
+    }
+}
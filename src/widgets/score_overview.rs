@@ -0,0 +1,150 @@
+use {
+    crate::{models::results::SongResults, styles::custom_style::ColorPalette},
+    iced::{
+        Color, Point, Rectangle, Renderer, Size, Theme,
+        alignment::{Horizontal, Vertical},
+        mouse::Cursor,
+        widget::canvas::{self, Frame, Geometry, Path, Program},
+    },
+};
+
+/// Ancho del lienzo del resumen
+pub const SCORE_OVERVIEW_WIDTH: f32 = 900.0;
+
+// Compases por fila, margen para el número de compás y alto de cada fila
+const BARS_PER_ROW: usize = 16;
+const LEFT_MARGIN: f32 = 40.0;
+const LABEL_HEIGHT: f32 = 18.0;
+const CELL_HEIGHT: f32 = 28.0;
+const ROW_GAP: f32 = 8.0;
+
+// Color de los compases sin nada que medir
+const EMPTY: Color = Color::from_rgb(0.8, 0.8, 0.8);
+
+/// Color de un compás: rojo en 0, naranja en la mitad y verde en 1
+pub fn metric_color(value: f32) -> Color {
+    let green: Color = Color::from_rgb(0.2, 0.65, 0.3);
+    let (from, to, t): (Color, Color, f32) = if value < 0.5 {
+        (
+            ColorPalette::ACCENT_RED,
+            ColorPalette::ACCENT_ORANGE,
+            value * 2.0,
+        )
+    } else {
+        (ColorPalette::ACCENT_ORANGE, green, value * 2.0 - 1.0)
+    };
+    let t: f32 = t.clamp(0.0, 1.0);
+    Color::from_rgb(
+        from.r + (to.r - from.r) * t,
+        from.g + (to.g - from.g) * t,
+        from.b + (to.b - from.b) * t,
+    )
+}
+
+/// Toda la partitura en filas de compases, coloreados con la medida de cada uno
+/// y con la etiqueta de cada sección de `structure` donde empieza
+pub struct ScoreOverview {
+    pub bars: Vec<Option<f32>>,
+    pub sections: Vec<(String, usize)>, // Etiqueta y compás donde empieza, desde 0
+}
+
+impl ScoreOverview {
+    pub fn new(results: &SongResults) -> Self {
+        ScoreOverview {
+            bars: results.bars.clone(),
+            sections: results
+                .sections
+                .iter()
+                .map(|(label, _)| label.clone())
+                .zip(results.section_bars.iter().copied())
+                .collect(),
+        }
+    }
+
+    /// Alto del lienzo para que quepan todas las filas
+    pub fn height(&self) -> f32 {
+        self.rows() as f32 * (LABEL_HEIGHT + CELL_HEIGHT + ROW_GAP)
+    }
+
+    fn rows(&self) -> usize {
+        self.bars.len().div_ceil(BARS_PER_ROW).max(1)
+    }
+
+    // Esquina superior izquierda de la casilla de un compás
+    fn cell(&self, bar: usize, width: f32) -> Point {
+        let row: usize = bar / BARS_PER_ROW;
+        Point::new(
+            LEFT_MARGIN + (bar % BARS_PER_ROW) as f32 * width,
+            row as f32 * (LABEL_HEIGHT + CELL_HEIGHT + ROW_GAP) + LABEL_HEIGHT,
+        )
+    }
+
+    fn text(frame: &mut Frame, content: String, position: Point, horizontal: Horizontal) {
+        frame.fill_text(canvas::Text {
+            content,
+            position,
+            color: ColorPalette::SHADOW_DARK,
+            size: 12.0.into(),
+            horizontal_alignment: horizontal,
+            vertical_alignment: Vertical::Center,
+            ..canvas::Text::default()
+        });
+    }
+}
+
+impl<AppMessage> Program<AppMessage> for ScoreOverview {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame: Frame<Renderer> = Frame::new(renderer, bounds.size());
+        let width: f32 = (bounds.width - LEFT_MARGIN) / BARS_PER_ROW as f32;
+
+        for (bar, value) in self.bars.iter().enumerate() {
+            let corner: Point = self.cell(bar, width);
+            frame.fill(
+                &Path::rectangle(
+                    Point::new(corner.x + 1.0, corner.y),
+                    Size::new(width - 2.0, CELL_HEIGHT),
+                ),
+                value.map_or(EMPTY, metric_color),
+            );
+
+            // Número del primer compás de cada fila
+            if bar % BARS_PER_ROW == 0 {
+                Self::text(
+                    &mut frame,
+                    (bar + 1).to_string(),
+                    Point::new(LEFT_MARGIN - 6.0, corner.y + CELL_HEIGHT / 2.0),
+                    Horizontal::Right,
+                );
+            }
+        }
+
+        // Raya y etiqueta al empezar cada sección
+        for (label, bar) in &self.sections {
+            let corner: Point = self.cell(*bar, width);
+            frame.fill(
+                &Path::rectangle(
+                    Point::new(corner.x - 1.0, corner.y - LABEL_HEIGHT + 2.0),
+                    Size::new(3.0, CELL_HEIGHT + LABEL_HEIGHT - 2.0),
+                ),
+                Color::BLACK,
+            );
+            Self::text(
+                &mut frame,
+                label.clone(),
+                Point::new(corner.x + 4.0, corner.y - LABEL_HEIGHT / 2.0),
+                Horizontal::Left,
+            );
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
@@ -0,0 +1,140 @@
+use {
+    crate::{
+        models::rhythm::{BarTiming, TAP_WINDOW},
+        styles::custom_style::ColorPalette,
+        widgets::rhythm_staff::timing_color,
+    },
+    iced::{
+        Color, Point, Rectangle, Renderer, Size, Theme,
+        alignment::{Horizontal, Vertical},
+        mouse::Cursor,
+        widget::canvas::{self, Frame, Geometry, Path, Program},
+    },
+};
+
+/// Alto del lienzo del gráfico
+pub const TIMING_GRAPH_HEIGHT: f32 = 220.0;
+
+// Margen para las etiquetas del eje y alto de la zona de cada compás
+const LEFT_MARGIN: f32 = 90.0;
+const TOP: f32 = 30.0;
+const PLOT_HEIGHT: f32 = 160.0;
+
+/// Desviación de cada pulsación por compás: arriba tarde, abajo antes.
+/// La raya de cada compás es la desviación media; encima, las notas sin pulsar y las de más.
+pub struct TimingGraph {
+    pub bars: Vec<BarTiming>,
+}
+
+impl TimingGraph {
+    fn y_of(deviation: f32) -> f32 {
+        let clamped: f32 = deviation.clamp(-TAP_WINDOW, TAP_WINDOW);
+        TOP + PLOT_HEIGHT / 2.0 - clamped / TAP_WINDOW * PLOT_HEIGHT / 2.0
+    }
+
+    fn draw_axis(&self, frame: &mut Frame, bounds: Size) {
+        let width: f32 = bounds.width - LEFT_MARGIN;
+        for (deviation, label) in [
+            (TAP_WINDOW, format!("+{:.0} ms tarde", TAP_WINDOW * 1000.0)),
+            (0.0, "0".to_string()),
+            (-TAP_WINDOW, format!("-{:.0} ms antes", TAP_WINDOW * 1000.0)),
+        ] {
+            let y: f32 = Self::y_of(deviation);
+            let thickness: f32 = if deviation == 0.0 { 2.0 } else { 1.0 };
+            frame.fill(
+                &Path::rectangle(Point::new(LEFT_MARGIN, y), Size::new(width, thickness)),
+                ColorPalette::SHADOW_DARK,
+            );
+            frame.fill_text(canvas::Text {
+                content: label,
+                position: Point::new(LEFT_MARGIN - 6.0, y),
+                color: ColorPalette::SHADOW_DARK,
+                size: 12.0.into(),
+                horizontal_alignment: Horizontal::Right,
+                vertical_alignment: Vertical::Center,
+                ..canvas::Text::default()
+            });
+        }
+    }
+
+    fn draw_bar(&self, frame: &mut Frame, index: usize, bar: &BarTiming, x: f32, width: f32) {
+        // Fondo alterno para distinguir los compases
+        if index % 2 == 1 {
+            frame.fill(
+                &Path::rectangle(Point::new(x, TOP), Size::new(width, PLOT_HEIGHT)),
+                Color::from_rgba(0.0, 0.0, 0.0, 0.05),
+            );
+        }
+
+        // Una marca por pulsación, repartidas a lo ancho del compás
+        let count: usize = bar.deviations.len();
+        for (i, deviation) in bar.deviations.iter().enumerate() {
+            let dot_x: f32 = x + width * (i as f32 + 1.0) / (count as f32 + 1.0);
+            frame.fill(
+                &Path::circle(Point::new(dot_x, Self::y_of(*deviation)), 4.0),
+                timing_color(*deviation),
+            );
+        }
+
+        if let Some(mean) = bar.mean() {
+            frame.fill(
+                &Path::rectangle(
+                    Point::new(x + width * 0.15, Self::y_of(mean) - 1.5),
+                    Size::new(width * 0.7, 3.0),
+                ),
+                Color::BLACK,
+            );
+        }
+
+        let mut notes: Vec<String> = Vec::new();
+        if bar.missed > 0 {
+            notes.push(format!("x{}", bar.missed));
+        }
+        if bar.extra > 0 {
+            notes.push(format!("+{}", bar.extra));
+        }
+        for (content, y, color) in [
+            (notes.join(" "), TOP - 14.0, ColorPalette::ACCENT_RED),
+            (
+                (index + 1).to_string(),
+                TOP + PLOT_HEIGHT + 14.0,
+                ColorPalette::SHADOW_DARK,
+            ),
+        ] {
+            frame.fill_text(canvas::Text {
+                content,
+                position: Point::new(x + width / 2.0, y),
+                color,
+                size: 12.0.into(),
+                horizontal_alignment: Horizontal::Center,
+                vertical_alignment: Vertical::Center,
+                ..canvas::Text::default()
+            });
+        }
+    }
+}
+
+impl<AppMessage> Program<AppMessage> for TimingGraph {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame: Frame<Renderer> = Frame::new(renderer, bounds.size());
+        frame.fill(&Path::rectangle(Point::ORIGIN, bounds.size()), Color::WHITE);
+
+        let width: f32 = (bounds.width - LEFT_MARGIN) / self.bars.len().max(1) as f32;
+        for (index, bar) in self.bars.iter().enumerate() {
+            let x: f32 = LEFT_MARGIN + index as f32 * width;
+            self.draw_bar(&mut frame, index, bar, x, width);
+        }
+        self.draw_axis(&mut frame, bounds.size());
+
+        vec![frame.into_geometry()]
+    }
+}
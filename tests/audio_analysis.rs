@@ -0,0 +1,352 @@
+use {
+    octarust::audio::{
+        analyser::{AnalyserConfig, DetectedOnset, NoiseProfile, StreamingAnalyser, analyse},
+        wav::{Wav, parse_wav, read_wav, write_wav},
+    },
+    serde::{Deserialize, Serialize},
+    std::{
+        f32::consts::TAU,
+        fs,
+        path::{Path, PathBuf},
+    },
+};
+
+// Frecuencia de muestreo de los fixtures sintéticos
+const SAMPLE_RATE: u32 = 22_050;
+
+// Tolerancia en segundos para emparejar un ataque detectado con uno esperado
+const ONSET_TOLERANCE: f32 = 0.05;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GroundTruth {
+    fixtures: Vec<Fixture>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Fixture {
+    file: String,
+    calibration_seconds: f32, // Silencio inicial usado para medir el ruido
+    onsets: Vec<ExpectedOnset>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ExpectedOnset {
+    time: f32,
+    pitches: Vec<u8>, // Notas nuevas en ese ataque
+}
+
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/audio")
+}
+
+fn load_ground_truth() -> GroundTruth {
+    let text: String = fs::read_to_string(fixtures_dir().join("ground_truth.json"))
+        .expect("falta tests/fixtures/audio/ground_truth.json");
+    serde_json::from_str(&text).expect("ground_truth.json no válido")
+}
+
+// Analiza un fixture calibrando el ruido con su silencio inicial
+fn analyse_fixture(fixture: &Fixture) -> Vec<DetectedOnset> {
+    let wav: Wav = read_wav(&fixtures_dir().join(&fixture.file)).expect("fixture ilegible");
+    let config: AnalyserConfig = AnalyserConfig::default();
+    let silence_len: usize = (fixture.calibration_seconds * wav.sample_rate as f32) as usize;
+    let noise: NoiseProfile =
+        NoiseProfile::measure(&wav.samples[..silence_len], wav.sample_rate, &config);
+    analyse(&wav.samples, wav.sample_rate, config, Some(noise))
+}
+
+// Empareja ataques por tiempo: (esperado, detectado) y cuántos sobran de cada lado
+fn match_onsets<'a>(
+    expected: &'a [ExpectedOnset],
+    detected: &'a [DetectedOnset],
+) -> Vec<(&'a ExpectedOnset, Option<&'a DetectedOnset>)> {
+    let mut used: Vec<bool> = vec![false; detected.len()];
+    expected
+        .iter()
+        .map(|onset| {
+            let best: Option<usize> = detected
+                .iter()
+                .enumerate()
+                .filter(|(i, d)| !used[*i] && (d.time - onset.time).abs() <= ONSET_TOLERANCE)
+                .min_by(|a, b| {
+                    (a.1.time - onset.time)
+                        .abs()
+                        .total_cmp(&(b.1.time - onset.time).abs())
+                })
+                .map(|(i, _)| i);
+            if let Some(i) = best {
+                used[i] = true;
+            }
+            (onset, best.map(|i| &detected[i]))
+        })
+        .collect()
+}
+
+#[test]
+fn wav_round_trip_keeps_samples() {
+    let samples: Vec<f32> = (0..1000)
+        .map(|i| 0.5 * (TAU * 440.0 * i as f32 / 8000.0).sin())
+        .collect();
+    let wav: Wav = Wav {
+        sample_rate: 8000,
+        samples,
+    };
+    let path: PathBuf = std::env::temp_dir().join("octarust-wav-round-trip.wav");
+    write_wav(&path, &wav).unwrap();
+    let read: Wav = read_wav(&path).unwrap();
+    let _ = fs::remove_file(&path);
+
+    assert_eq!(read.sample_rate, 8000);
+    assert_eq!(read.samples.len(), wav.samples.len());
+    for (a, b) in read.samples.iter().zip(&wav.samples) {
+        assert!((a - b).abs() < 1.0 / 16_000.0);
+    }
+}
+
+#[test]
+fn wav_rejects_non_riff_data() {
+    assert!(parse_wav(b"not a wav file at all").is_err());
+}
+
+#[test]
+fn fixtures_match_ground_truth() {
+    let truth: GroundTruth = load_ground_truth();
+    assert!(!truth.fixtures.is_empty());
+
+    for fixture in &truth.fixtures {
+        let detected: Vec<DetectedOnset> = analyse_fixture(fixture);
+
+        // Cada ataque y cada acorde deben ser exactos
+        assert_eq!(
+            detected.len(),
+            fixture.onsets.len(),
+            "{}: ataques detectados {:?}",
+            fixture.file,
+            detected
+                .iter()
+                .map(|d| (d.time, d.pitches.clone()))
+                .collect::<Vec<_>>()
+        );
+        for (expected, found) in match_onsets(&fixture.onsets, &detected) {
+            let found: &DetectedOnset = found.unwrap_or_else(|| {
+                panic!(
+                    "{}: falta el ataque de {:.2} s",
+                    fixture.file, expected.time
+                )
+            });
+            assert_eq!(
+                found.pitches, expected.pitches,
+                "{}: tonos del ataque de {:.2} s (suenan {:?})",
+                fixture.file, expected.time, found.sounding
+            );
+        }
+    }
+}
+
+#[test]
+fn streaming_in_small_blocks_matches_offline_analysis() {
+    let truth: GroundTruth = load_ground_truth();
+    let fixture: &Fixture = &truth.fixtures[0];
+    let wav: Wav = read_wav(&fixtures_dir().join(&fixture.file)).unwrap();
+
+    let offline: Vec<DetectedOnset> = analyse(
+        &wav.samples,
+        wav.sample_rate,
+        AnalyserConfig::default(),
+        None,
+    );
+
+    // Bloques de tamaño irregular, como los que entrega una tarjeta de sonido
+    let mut analyser: StreamingAnalyser =
+        StreamingAnalyser::new(wav.sample_rate, AnalyserConfig::default());
+    let mut streamed: Vec<DetectedOnset> = Vec::new();
+    let mut pos: usize = 0;
+    let mut block: usize = 37;
+    while pos < wav.samples.len() {
+        let end: usize = (pos + block).min(wav.samples.len());
+        streamed.extend(analyser.push(&wav.samples[pos..end]));
+        pos = end;
+        block = block * 7 % 500 + 1;
+    }
+    streamed.extend(analyser.flush());
+
+    assert_eq!(streamed, offline);
+}
+
+#[test]
+fn calibration_suppresses_onsets_in_noise() {
+    let noise: Vec<f32> = white_noise(SAMPLE_RATE as usize * 3, 0.02, 99);
+    let config: AnalyserConfig = AnalyserConfig::default();
+    let mut analyser: StreamingAnalyser = StreamingAnalyser::new(SAMPLE_RATE, config);
+    analyser.calibrate(&noise[..SAMPLE_RATE as usize]);
+
+    let mut detected: Vec<DetectedOnset> = analyser.push(&noise[SAMPLE_RATE as usize..]);
+    detected.extend(analyser.flush());
+    assert!(detected.is_empty(), "ataques en ruido: {:?}", detected);
+}
+
+// Genera los fixtures sintéticos y su ground truth:
+// cargo test --test audio_analysis -- --ignored regenerate_fixtures
+#[test]
+#[ignore]
+fn regenerate_fixtures() {
+    let mut fixtures: Vec<Fixture> = Vec::new();
+
+    let single: Vec<(f32, Vec<u8>, f32)> = [60, 64, 67, 72, 45, 88]
+        .iter()
+        .enumerate()
+        .map(|(i, &p)| (0.5 + i as f32 * 0.5, vec![p], 0.4))
+        .collect();
+    fixtures.push(synthesize("single_notes.wav", &single, 0.0));
+
+    let mut repeated: Vec<(f32, Vec<u8>, f32)> = (0..4)
+        .map(|i| (0.5 + i as f32 * 0.3, vec![69], 0.25))
+        .collect();
+    repeated.extend((0..4).map(|i| (1.9 + i as f32 * 0.25, vec![64], 0.2)));
+    fixtures.push(synthesize("repeated_notes.wav", &repeated, 0.0));
+
+    let chords: Vec<(f32, Vec<u8>, f32)> = [
+        vec![60, 64, 67],
+        vec![53, 57, 60],
+        vec![55, 59, 62, 65],
+        vec![57, 60, 64],
+        vec![43, 59, 62, 67],
+    ]
+    .into_iter()
+    .enumerate()
+    .map(|(i, chord)| (0.5 + i as f32 * 0.7, chord, 0.6))
+    .collect();
+    fixtures.push(synthesize("chords.wav", &chords, 0.0));
+
+    // Un Do grave mantenido bajo una melodía: solo cuentan las notas nuevas
+    let melody: Vec<(f32, Vec<u8>, f32)> = vec![
+        (0.5, vec![48], 2.3),
+        (0.5, vec![64], 0.45),
+        (1.0, vec![67], 0.45),
+        (1.5, vec![72], 0.45),
+        (2.0, vec![53, 69], 0.6),
+    ];
+    fixtures.push(synthesize("melody_with_bass.wav", &melody, 0.0));
+
+    let noisy: Vec<(f32, Vec<u8>, f32)> = [62, 66, 69, 74]
+        .iter()
+        .enumerate()
+        .map(|(i, &p)| (0.5 + i as f32 * 0.5, vec![p], 0.4))
+        .collect();
+    fixtures.push(synthesize("noisy.wav", &noisy, 0.01));
+
+    let truth: GroundTruth = GroundTruth { fixtures };
+    fs::write(
+        fixtures_dir().join("ground_truth.json"),
+        serde_json::to_string_pretty(&truth).unwrap(),
+    )
+    .unwrap();
+}
+
+// Escribe un fixture a partir de (inicio, tonos, duración) y devuelve su ground truth
+fn synthesize(file: &str, notes: &[(f32, Vec<u8>, f32)], noise: f32) -> Fixture {
+    let end: f32 = notes
+        .iter()
+        .map(|(start, _, duration)| start + duration)
+        .fold(0.0, f32::max)
+        + 0.8;
+    let len: usize = (end * SAMPLE_RATE as f32) as usize;
+    let mut samples: Vec<f32> = white_noise(len, noise, file.len() as u64);
+
+    for (i, (start, pitches, duration)) in notes.iter().enumerate() {
+        for (j, pitch) in pitches.iter().enumerate() {
+            let seed: u64 = (i * 16 + j) as u64 + 1;
+            add_piano_tone(&mut samples, *pitch, *start, *duration, seed);
+        }
+    }
+
+    let peak: f32 = samples.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+    if peak > 0.95 {
+        samples.iter_mut().for_each(|s| *s *= 0.95 / peak);
+    }
+    write_wav(
+        &fixtures_dir().join(file),
+        &Wav {
+            sample_rate: SAMPLE_RATE,
+            samples,
+        },
+    )
+    .unwrap();
+
+    // Ataques agrupados por tiempo
+    let mut onsets: Vec<ExpectedOnset> = Vec::new();
+    for (start, pitches, _) in notes {
+        match onsets.iter_mut().find(|o| (o.time - start).abs() < 1e-4) {
+            Some(onset) => onset.pitches.extend(pitches),
+            None => onsets.push(ExpectedOnset {
+                time: *start,
+                pitches: pitches.clone(),
+            }),
+        }
+    }
+    onsets.iter_mut().for_each(|o| o.pitches.sort_unstable());
+
+    Fixture {
+        file: file.to_string(),
+        calibration_seconds: 0.5,
+        onsets,
+    }
+}
+
+// Tono de piano aproximado: parciales inarmónicos que decaen a distinto ritmo,
+// ataque de 3 ms, golpe del martillo con ruido y apagado al soltar la tecla
+fn add_piano_tone(samples: &mut [f32], pitch: u8, start: f32, duration: f32, seed: u64) {
+    let sr: f32 = SAMPLE_RATE as f32;
+    let f0: f32 = 440.0 * 2f32.powf((pitch as f32 - 69.0) / 12.0);
+    let inharmonicity: f32 = 0.0003;
+    let release: f32 = 0.06;
+    let hammer: Vec<f32> = white_noise((0.01 * sr) as usize, 0.04, seed);
+
+    let first: usize = (start * sr) as usize;
+    let last: usize = (((start + duration + 6.0 * release) * sr) as usize).min(samples.len());
+
+    for h in 1..=12 {
+        let h_f: f32 = h as f32;
+        let frequency: f32 = h_f * f0 * (1.0 + inharmonicity * h_f * h_f).sqrt();
+        if frequency > sr * 0.45 {
+            break;
+        }
+        let amplitude: f32 = 0.12 / h_f.powf(0.9);
+        let decay: f32 = 1.2 + 0.5 * h_f + f0 / 400.0;
+        let phase: f32 = seed as f32 * 0.37 * h_f;
+
+        for (n, sample) in samples[first..last].iter_mut().enumerate() {
+            let t: f32 = n as f32 / sr;
+            let attack: f32 = (t / 0.003).min(1.0);
+            let damper: f32 = if t > duration {
+                (-(t - duration) / release).exp()
+            } else {
+                1.0
+            };
+            *sample += amplitude
+                * attack
+                * damper
+                * (-decay * t).exp()
+                * (TAU * frequency * t + phase).sin();
+        }
+    }
+
+    for (n, noise) in hammer.iter().enumerate() {
+        if let Some(sample) = samples.get_mut(first + n) {
+            *sample += noise * (1.0 - n as f32 / hammer.len() as f32);
+        }
+    }
+}
+
+// Ruido blanco reproducible (xorshift)
+fn white_noise(len: usize, amplitude: f32, seed: u64) -> Vec<f32> {
+    let mut state: u64 = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            amplitude * ((state >> 40) as f32 / (1u64 << 24) as f32 * 2.0 - 1.0)
+        })
+        .collect()
+}
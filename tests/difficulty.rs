@@ -0,0 +1,135 @@
+use octarust::{
+    DifficultyRating,
+    models::{note::Note, partiture::PieceMetadata},
+    rate_difficulty,
+};
+
+// Compás de 4/4 con la negra de un segundo
+fn metadata() -> PieceMetadata {
+    PieceMetadata {
+        time_signature: (4, 4),
+        base_note_value: 1.0,
+        sections: Vec::new(),
+    }
+}
+
+fn note(start: f32, pitch: u8, duration: f32) -> Note {
+    Note {
+        start,
+        ..Note::new(pitch, duration, false, Default::default())
+    }
+}
+
+// Negras por grados conjuntos alrededor del Do central, una por pulso
+fn melody(pitches: &[u8]) -> Vec<Note> {
+    pitches
+        .iter()
+        .enumerate()
+        .map(|(i, &pitch)| note(i as f32, pitch, 1.0))
+        .collect()
+}
+
+fn rate(right: &[Note], left: &[Note]) -> DifficultyRating {
+    rate_difficulty(right, left, &metadata())
+}
+
+fn simple() -> Vec<Note> {
+    melody(&[60, 62, 64, 65, 67, 65, 64, 62])
+}
+
+#[test]
+fn an_empty_piece_is_the_easiest() {
+    let rating: DifficultyRating = rate(&[], &[]);
+    assert_eq!(rating.score, 1.0);
+    assert_eq!(rating.level(), 1);
+    assert_eq!(rating.label(), "Principiante");
+}
+
+#[test]
+fn denser_pieces_rate_higher() {
+    // Las mismas notas en corcheas: el doble de ataques por segundo
+    let dense: Vec<Note> = simple()
+        .into_iter()
+        .map(|n| note(n.start / 2.0, n.pitch, 0.5))
+        .collect();
+
+    let easy: DifficultyRating = rate(&simple(), &[]);
+    let hard: DifficultyRating = rate(&dense, &[]);
+    assert!(hard.density > easy.density);
+    assert!(hard.score > easy.score);
+}
+
+#[test]
+fn wider_pieces_rate_higher() {
+    // Mismo ritmo, pero saltando entre tres octavas
+    let wide: Vec<Note> = melody(&[36, 60, 84, 48, 72, 40, 79, 60]);
+
+    let easy: DifficultyRating = rate(&simple(), &[]);
+    let hard: DifficultyRating = rate(&wide, &[]);
+    assert!(hard.range > easy.range);
+    assert!(hard.jumps > easy.jumps);
+    assert!(hard.score > easy.score);
+}
+
+#[test]
+fn chordal_pieces_rate_higher() {
+    // Cada nota de la melodía con su tercera y su quinta
+    let chords: Vec<Note> = simple()
+        .into_iter()
+        .flat_map(|n| {
+            [0, 4, 7]
+                .into_iter()
+                .map(move |interval| note(n.start, n.pitch + interval, n.duration))
+        })
+        .collect();
+
+    let easy: DifficultyRating = rate(&simple(), &[]);
+    let hard: DifficultyRating = rate(&chords, &[]);
+    assert_eq!(easy.chords, 0.0);
+    assert!(hard.chords > easy.chords);
+    assert!(hard.score > easy.score);
+}
+
+#[test]
+fn syncopated_pieces_rate_higher() {
+    // Corchea, negra a caballo del pulso y corchea, compás tras compás
+    let syncopated: Vec<Note> = (0..4)
+        .flat_map(|bar| {
+            let start: f32 = bar as f32 * 2.0;
+            [
+                note(start, 60, 0.5),
+                note(start + 0.5, 62, 1.0),
+                note(start + 1.5, 64, 0.5),
+            ]
+        })
+        .collect();
+    // Las mismas notas en el pulso, con las mismas figuras
+    let straight: Vec<Note> = (0..4)
+        .flat_map(|bar| {
+            let start: f32 = bar as f32 * 2.0;
+            [
+                note(start, 60, 0.5),
+                note(start + 0.5, 62, 0.5),
+                note(start + 1.0, 64, 1.0),
+            ]
+        })
+        .collect();
+
+    let easy: DifficultyRating = rate(&straight, &[]);
+    let hard: DifficultyRating = rate(&syncopated, &[]);
+    assert!(hard.rhythm > easy.rhythm);
+    assert!(hard.score > easy.score);
+}
+
+#[test]
+fn rests_do_not_count() {
+    let mut with_rests: Vec<Note> = simple();
+    with_rests.push(Note {
+        is_rest: true,
+        ..note(8.0, 0, 1.0)
+    });
+
+    let rating: DifficultyRating = rate(&with_rests, &[]);
+    assert_eq!(rating.range, rate(&simple(), &[]).range);
+    assert_eq!(rating.chords, 0.0);
+}
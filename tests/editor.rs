@@ -0,0 +1,162 @@
+use octarust::models::{
+    editor::{ScoreEditor, TextField},
+    note::Note,
+    partiture::Hand,
+};
+
+// Pieza nueva en 4/4 con la negra de un segundo: cada nota escrita avanza un segundo
+fn editor() -> ScoreEditor {
+    ScoreEditor::new_piece("prueba")
+}
+
+fn pitches(editor: &ScoreEditor) -> Vec<(u8, f32)> {
+    editor
+        .section_notes(Hand::Right)
+        .iter()
+        .map(|note: &Note| (note.pitch, note.start))
+        .collect()
+}
+
+fn structure(editor: &ScoreEditor) -> Vec<&str> {
+    editor
+        .piece
+        .metadata
+        .structure
+        .iter()
+        .map(String::as_str)
+        .collect()
+}
+
+#[test]
+fn edits_are_undone_and_redone_in_order() {
+    let mut editor: ScoreEditor = editor();
+    editor.insert(60, false);
+    editor.insert(62, false);
+    assert_eq!(pitches(&editor), vec![(60, 0.0), (62, 1.0)]);
+
+    editor.undo();
+    assert_eq!(pitches(&editor), vec![(60, 0.0)]);
+    editor.undo();
+    assert!(pitches(&editor).is_empty());
+    assert!(!editor.can_undo());
+
+    editor.redo();
+    editor.redo();
+    assert_eq!(pitches(&editor), vec![(60, 0.0), (62, 1.0)]);
+    assert!(!editor.can_redo());
+}
+
+#[test]
+fn a_new_edit_forgets_what_was_undone() {
+    let mut editor: ScoreEditor = editor();
+    editor.insert(60, false);
+    editor.undo();
+    assert!(editor.can_redo());
+
+    // Deshacer no mueve el cursor: la nota nueva va detrás de la deshecha
+    editor.insert(64, false);
+    assert!(!editor.can_redo());
+    assert_eq!(pitches(&editor), vec![(64, 1.0)]);
+}
+
+#[test]
+fn typing_in_a_field_is_undone_at_once() {
+    let mut editor: ScoreEditor = editor();
+    for text in ["S", "So", "Son", "Sonata"] {
+        editor.set_text(TextField::Title, text.to_string());
+    }
+    editor.set_text(TextField::Composer, "Clementi".to_string());
+
+    editor.undo();
+    assert_eq!(editor.piece.metadata.title, "Sonata");
+    assert_eq!(editor.piece.metadata.composer, None);
+    editor.undo();
+    assert_eq!(editor.piece.metadata.title, "Nueva pieza");
+    assert!(!editor.can_undo());
+}
+
+#[test]
+fn renaming_is_not_part_of_the_history() {
+    let mut editor: ScoreEditor = editor();
+    editor.insert(60, false);
+    editor.set_text(TextField::Name, "otra".to_string());
+    assert!(editor.dirty);
+
+    // Deshacer quita la nota y deja el nombre, que no cambiaría con la pieza guardada
+    editor.undo();
+    assert!(pitches(&editor).is_empty());
+    assert_eq!(editor.name, "otra");
+    assert!(!editor.can_undo());
+}
+
+#[test]
+fn new_sections_start_after_the_last_bar_and_join_the_structure() {
+    let mut editor: ScoreEditor = editor();
+    editor.insert(60, false);
+    editor.add_section();
+
+    assert_eq!(editor.section, "B");
+    assert_eq!(structure(&editor), vec!["A", "B"]);
+    // La nota de A ocupa el primer compás de cuatro segundos
+    assert_eq!(editor.current_section().unwrap().start_time, 4.0);
+
+    // Las notas de B se escriben desde su propio inicio
+    editor.insert(67, false);
+    assert_eq!(pitches(&editor), vec![(67, 0.0)]);
+    editor.select_section("A".to_string());
+    assert_eq!(pitches(&editor), vec![(60, 0.0)]);
+}
+
+#[test]
+fn undoing_a_new_section_goes_back_to_the_previous_one() {
+    let mut editor: ScoreEditor = editor();
+    editor.add_section();
+    editor.undo();
+
+    assert_eq!(editor.section, "A");
+    assert_eq!(structure(&editor), vec!["A"]);
+    assert!(!editor.piece.sections.contains_key("B"));
+
+    editor.redo();
+    assert_eq!(editor.section, "B");
+    assert_eq!(structure(&editor), vec!["A", "B"]);
+}
+
+#[test]
+fn removing_a_section_keeps_at_least_one() {
+    let mut editor: ScoreEditor = editor();
+    editor.add_section();
+    editor.push_structure();
+    assert_eq!(structure(&editor), vec!["A", "B", "B"]);
+
+    // Se quitan también todas sus repeticiones
+    editor.remove_section();
+    assert_eq!(editor.section, "A");
+    assert_eq!(structure(&editor), vec!["A"]);
+
+    editor.remove_section();
+    assert_eq!(editor.piece.sections.len(), 1);
+    assert_eq!(structure(&editor), vec!["A"]);
+}
+
+#[test]
+fn structure_grows_and_shrinks_from_the_end() {
+    let mut editor: ScoreEditor = editor();
+    editor.push_structure();
+    editor.push_structure();
+    assert_eq!(structure(&editor), vec!["A", "A", "A"]);
+
+    editor.pop_structure();
+    assert_eq!(structure(&editor), vec!["A", "A"]);
+    editor.undo();
+    assert_eq!(structure(&editor), vec!["A", "A", "A"]);
+
+    // Quitar de una estructura vacía no es una edición
+    for _ in 0..3 {
+        editor.pop_structure();
+    }
+    assert!(structure(&editor).is_empty());
+    editor.pop_structure();
+    editor.undo();
+    assert_eq!(structure(&editor), vec!["A"]);
+}
@@ -0,0 +1,102 @@
+use octarust::{
+    models::{note::Note, partiture::Hand},
+    utils::fingering::suggest_fingering,
+};
+
+fn note(start: f32, pitch: u8) -> Note {
+    Note {
+        start,
+        ..Note::new(pitch, 0.5, false, Default::default())
+    }
+}
+
+// Corcheas seguidas, una nota detrás de otra
+fn melody(pitches: &[u8]) -> Vec<Note> {
+    pitches
+        .iter()
+        .enumerate()
+        .map(|(i, &pitch)| note(i as f32 * 0.5, pitch))
+        .collect()
+}
+
+fn fingers(notes: &[Note]) -> Vec<Option<u8>> {
+    notes.iter().map(|note| note.finger).collect()
+}
+
+fn fingered(pitches: &[u8], hand: Hand) -> Vec<Option<u8>> {
+    let mut notes: Vec<Note> = melody(pitches);
+    suggest_fingering(&mut notes, hand);
+    fingers(&notes)
+}
+
+#[test]
+fn five_finger_position_uses_one_finger_per_key() {
+    // Posición de Do: un dedo por tecla, del pulgar al meñique en la derecha
+    assert_eq!(
+        fingered(&[60, 62, 64, 65, 67, 65, 64, 62, 60], Hand::Right),
+        [1, 2, 3, 4, 5, 4, 3, 2, 1].map(Some)
+    );
+    // Y del meñique al pulgar en la izquierda, una octava más abajo
+    assert_eq!(
+        fingered(&[48, 50, 52, 53, 55], Hand::Left),
+        [5, 4, 3, 2, 1].map(Some)
+    );
+}
+
+#[test]
+fn scales_pass_the_thumb_under() {
+    // Do mayor de una octava: el pulgar pasa por debajo para tocar el Fa
+    assert_eq!(
+        fingered(&[60, 62, 64, 65, 67, 69, 71, 72], Hand::Right),
+        [1, 2, 3, 1, 2, 3, 4, 5].map(Some)
+    );
+    // En la izquierda bajando: el pulgar pasa por debajo hacia el Sol
+    assert_eq!(
+        fingered(&[72, 71, 69, 67, 65, 64, 62, 60], Hand::Left),
+        [1, 2, 3, 1, 2, 3, 4, 5].map(Some)
+    );
+}
+
+#[test]
+fn written_fingers_are_kept() {
+    // Con el segundo dedo escrito en el Do, el resto se coloca a partir de él
+    let mut notes: Vec<Note> = melody(&[60, 62, 64, 65]);
+    notes[0].finger = Some(2);
+    suggest_fingering(&mut notes, Hand::Right);
+
+    assert_eq!(fingers(&notes), [2, 3, 4, 5].map(Some));
+}
+
+#[test]
+fn written_fingers_override_the_comfortable_choice() {
+    // Un dedo escrito que la búsqueda no elegiría se queda tal cual
+    let mut notes: Vec<Note> = melody(&[60, 62, 64, 65, 67]);
+    notes[2].finger = Some(1);
+    suggest_fingering(&mut notes, Hand::Right);
+
+    assert_eq!(notes[2].finger, Some(1));
+    assert!(notes.iter().all(|note| note.finger.is_some()));
+}
+
+#[test]
+fn chords_of_more_than_five_notes_stay_unfingered() {
+    let mut notes: Vec<Note> = [60, 64, 67, 72, 76, 79]
+        .into_iter()
+        .map(|pitch| note(0.0, pitch))
+        .collect();
+    // Una nota suelta después del acorde sí se digita
+    notes.push(note(2.0, 60));
+    suggest_fingering(&mut notes, Hand::Right);
+
+    assert!(notes[..6].iter().all(|note| note.finger.is_none()));
+    assert!(notes[6].finger.is_some());
+}
+
+#[test]
+fn rests_are_not_fingered() {
+    let mut notes: Vec<Note> = melody(&[60, 0, 64]);
+    suggest_fingering(&mut notes, Hand::Right);
+
+    assert_eq!(notes[1].finger, None);
+    assert!(notes[0].finger.is_some() && notes[2].finger.is_some());
+}
@@ -0,0 +1,218 @@
+use {
+    octarust::{
+        midi::{
+            import::smf_to_section,
+            smf::{MidiNote, MidiTrack, write_smf},
+        },
+        models::{
+            markings::{Articulation, Dynamic, Hairpin, HairpinKind, Markings, Pedal, Slur},
+            note::Note,
+            partiture::{Hand, PieceMetadata},
+            score::Section,
+        },
+        utils::{import::ImportedScore, musicxml::musicxml_to_section},
+    },
+    std::{
+        fs,
+        path::{Path, PathBuf},
+    },
+};
+
+// 4/4 con la negra de medio segundo
+fn metadata() -> PieceMetadata {
+    PieceMetadata {
+        time_signature: (4, 4),
+        base_note_value: 0.5,
+        sections: Vec::new(),
+    }
+}
+
+fn midi_note(start: f32, duration: f32, pitch: u8, velocity: u8) -> MidiNote {
+    MidiNote {
+        start,
+        duration,
+        pitch,
+        velocity,
+    }
+}
+
+fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() < 1e-3
+}
+
+// Tono, inicio y duración de cada nota
+fn timing(notes: &[Note]) -> Vec<(u8, f32, f32)> {
+    notes
+        .iter()
+        .map(|note| (note.pitch, note.start, note.duration))
+        .collect()
+}
+
+fn assert_timing(notes: &[Note], expected: &[(u8, f32, f32)]) {
+    let actual: Vec<(u8, f32, f32)> = timing(notes);
+    assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+    for (note, expected) in actual.iter().zip(expected) {
+        assert!(
+            note.0 == expected.0 && close(note.1, expected.1) && close(note.2, expected.2),
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+}
+
+// Dos compases: el primero piano y el segundo forte, con pedal en la mano izquierda
+fn two_hands() -> Vec<u8> {
+    let right: MidiTrack = MidiTrack {
+        name: "Right hand".to_string(),
+        channel: 0,
+        notes: (0..8)
+            .map(|i| {
+                let velocity: u8 = if i < 4 { 49 } else { 96 };
+                midi_note(i as f32 * 0.5, 0.5, [60, 62, 64, 65][i % 4], velocity)
+            })
+            .collect(),
+        pedal: Vec::new(),
+    };
+    let left: MidiTrack = MidiTrack {
+        name: "Left hand".to_string(),
+        channel: 1,
+        notes: vec![midi_note(0.0, 2.0, 48, 49), midi_note(2.0, 2.0, 43, 96)],
+        pedal: vec![Pedal {
+            start: 0.0,
+            end: 1.5,
+        }],
+    };
+    write_smf(&[right, left], &metadata())
+}
+
+#[test]
+fn written_files_read_back() {
+    let imported: ImportedScore = smf_to_section(&two_hands()).unwrap();
+
+    assert!(close(imported.quarter_seconds, 0.5));
+    assert_eq!((imported.meter.beats, imported.meter.beat_unit), (4, 4));
+    assert_timing(
+        &imported.section.right,
+        &[
+            (60, 0.0, 0.5),
+            (62, 0.5, 0.5),
+            (64, 1.0, 0.5),
+            (65, 1.5, 0.5),
+            (60, 2.0, 0.5),
+            (62, 2.5, 0.5),
+            (64, 3.0, 0.5),
+            (65, 3.5, 0.5),
+        ],
+    );
+    assert_timing(&imported.section.left, &[(48, 0.0, 2.0), (43, 2.0, 2.0)]);
+
+    // La velocidad de cada compás vuelve como matiz
+    let markings: &Markings = &imported.section.markings;
+    let dynamics: Vec<(f32, Dynamic)> = markings
+        .dynamics
+        .iter()
+        .map(|mark| (mark.start, mark.level))
+        .collect();
+    assert_eq!(dynamics, vec![(0.0, Dynamic::P), (2.0, Dynamic::F)]);
+
+    // Y el controlador 64 como pedal
+    assert_eq!(markings.pedal.len(), 1);
+    assert!(close(markings.pedal[0].start, 0.0) && close(markings.pedal[0].end, 1.5));
+}
+
+#[test]
+fn running_status_repeats_the_previous_command() {
+    // Tipo 0, una pista, 480 ticks por negra. Tras el primer note-on los mensajes no repiten el
+    // byte de estado, y los note-off son note-on con velocidad 0.
+    let track: Vec<u8> = vec![
+        0x00, 0x90, 60, 100, // Do
+        0x83, 0x60, 60, 0, // Se suelta una negra después
+        0x00, 62, 90, // Re, sin byte de estado
+        0x83, 0x60, 62, 0, // Se suelta
+        0x00, 0xFF, 0x2F, 0x00, // Fin de pista
+    ];
+    let mut bytes: Vec<u8> = b"MThd".to_vec();
+    bytes.extend(6u32.to_be_bytes());
+    bytes.extend([0, 0, 0, 1, 0x01, 0xE0]);
+    bytes.extend(b"MTrk");
+    bytes.extend((track.len() as u32).to_be_bytes());
+    bytes.extend(&track);
+
+    let imported: ImportedScore = smf_to_section(&bytes).unwrap();
+    assert_timing(&imported.section.right, &[(60, 0.0, 0.5), (62, 0.5, 0.5)]);
+    assert!(imported.section.left.is_empty());
+}
+
+#[test]
+fn truncated_files_are_errors() {
+    let bytes: Vec<u8> = two_hands();
+
+    // Ningún corte hace que la lectura entre en pánico
+    for len in 0..bytes.len() {
+        let _ = smf_to_section(&bytes[..len]);
+    }
+    // A medias de la cabecera o de la última pista no se puede leer
+    assert!(smf_to_section(&bytes[..10]).is_err());
+    assert!(smf_to_section(&bytes[..bytes.len() - 1]).is_err());
+    assert!(smf_to_section(&[]).is_err());
+}
+
+#[test]
+fn smpte_division_is_rejected() {
+    let mut bytes: Vec<u8> = two_hands();
+    // -25 fotogramas por segundo y 40 ticks por fotograma
+    bytes[12..14].copy_from_slice(&[0xE7, 0x28]);
+
+    let error: String = smf_to_section(&bytes).err().unwrap();
+    assert!(error.contains("SMPTE"), "{}", error);
+}
+
+#[test]
+fn musicxml_markings_are_read() {
+    let path: PathBuf =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/musicxml/markings.musicxml");
+    let text: String = fs::read_to_string(path).expect("falta el fixture de MusicXML");
+    let imported: ImportedScore = musicxml_to_section(&text).unwrap();
+
+    assert_eq!(imported.title.as_deref(), Some("Marcas de prueba"));
+    assert_eq!(imported.composer.as_deref(), Some("OctaRust"));
+    assert_eq!(imported.key.as_deref(), Some("C major"));
+    assert!(close(imported.quarter_seconds, 0.5));
+
+    let section: &Section = &imported.section;
+    assert_timing(
+        &section.right,
+        &[
+            (72, 0.0, 0.5),
+            (74, 0.5, 0.5),
+            (76, 1.0, 0.5),
+            (77, 1.5, 0.5),
+            (79, 2.0, 2.0),
+        ],
+    );
+    assert_timing(&section.left, &[(48, 0.0, 2.0)]);
+    assert_eq!(section.right[0].articulations, vec![Articulation::Staccato]);
+    assert!(section.right[1].articulations.is_empty());
+
+    let markings: &Markings = &section.markings;
+    let dynamics: Vec<(f32, Dynamic)> = markings
+        .dynamics
+        .iter()
+        .map(|mark| (mark.start, mark.level))
+        .collect();
+    assert_eq!(dynamics, vec![(0.0, Dynamic::P), (2.0, Dynamic::F)]);
+
+    assert_eq!(markings.hairpins.len(), 1);
+    let hairpin: &Hairpin = &markings.hairpins[0];
+    assert_eq!(hairpin.kind, HairpinKind::Crescendo);
+    assert!(close(hairpin.start, 0.0) && close(hairpin.end, 2.0));
+
+    assert_eq!(markings.slurs.len(), 1);
+    let slur: &Slur = &markings.slurs[0];
+    assert_eq!(slur.hand, Hand::Right);
+    assert!(close(slur.start, 0.0) && close(slur.end, 1.5));
+
+    assert_eq!(markings.pedal.len(), 1);
+    assert!(close(markings.pedal[0].start, 0.0) && close(markings.pedal[0].end, 2.0));
+}
@@ -0,0 +1,146 @@
+use octarust::{
+    audio::{
+        metronome::{CLICK_SECONDS, Click, ClickScheduler},
+        output::AudioSource,
+    },
+    models::partiture::PieceMetadata,
+};
+
+const SAMPLE_RATE: u32 = 48_000;
+
+// Metrónomo en 3/8 con la corchea a 0.5 segundos, como Für Elise
+fn scheduler_3_8() -> ClickScheduler {
+    let mut scheduler: ClickScheduler = ClickScheduler::new(SAMPLE_RATE);
+    scheduler.configure(&PieceMetadata {
+        time_signature: (3, 8),
+        base_note_value: 0.5,
+        sections: Vec::new(),
+    });
+    scheduler
+}
+
+fn render(scheduler: &mut ClickScheduler, len: usize) -> Vec<f32> {
+    let mut out: Vec<f32> = vec![0.0; len];
+    scheduler.render(&mut out);
+    out
+}
+
+#[test]
+fn clicks_fall_on_exact_samples() {
+    let mut scheduler: ClickScheduler = scheduler_3_8();
+    scheduler.start(0.0);
+
+    let samples: Vec<u64> = scheduler
+        .clicks_between(0, 3 * SAMPLE_RATE as u64)
+        .iter()
+        .map(|click| click.sample)
+        .collect();
+    assert_eq!(samples, vec![0, 24_000, 48_000, 72_000, 96_000, 120_000]);
+}
+
+#[test]
+fn downbeat_is_accented() {
+    let mut scheduler: ClickScheduler = scheduler_3_8();
+    scheduler.start(0.0);
+
+    let accents: Vec<bool> = scheduler
+        .clicks_between(0, 3 * SAMPLE_RATE as u64)
+        .iter()
+        .map(|click| click.accent)
+        .collect();
+    assert_eq!(accents, vec![true, false, false, true, false, false]);
+}
+
+#[test]
+fn count_in_keeps_the_bar_grid() {
+    // Dos compases de cuenta previa: se empieza en el segundo -3.0
+    let mut scheduler: ClickScheduler = scheduler_3_8();
+    scheduler.start(-3.0);
+
+    let clicks: Vec<Click> = scheduler.clicks_between(0, 3 * SAMPLE_RATE as u64 + 1);
+    assert_eq!(clicks.first().map(|c| (c.beat, c.accent)), Some((-6, true)));
+    assert_eq!(clicks.get(1).map(|c| (c.beat, c.accent)), Some((-5, false)));
+    // El primer pulso de la partitura cae justo al acabar la cuenta previa
+    let downbeat: &Click = clicks.iter().find(|c| c.beat == 0).unwrap();
+    assert_eq!(downbeat.sample, 3 * SAMPLE_RATE as u64);
+    assert!(downbeat.accent);
+}
+
+#[test]
+fn starting_between_beats_waits_for_the_next_one() {
+    let mut scheduler: ClickScheduler = scheduler_3_8();
+    scheduler.start(0.2);
+
+    let first: Click = scheduler.clicks_between(0, SAMPLE_RATE as u64)[0];
+    assert_eq!(first.beat, 1);
+    assert_eq!(first.sample, 14_400); // (0.5 - 0.2) * 48000
+}
+
+#[test]
+fn rendering_in_blocks_matches_rendering_at_once() {
+    let total: usize = 2 * SAMPLE_RATE as usize;
+
+    let mut whole: ClickScheduler = scheduler_3_8();
+    whole.start(-0.75);
+    let expected: Vec<f32> = render(&mut whole, total);
+
+    // Bloques de tamaños irregulares, como los que pide el dispositivo
+    let mut blocks: ClickScheduler = scheduler_3_8();
+    blocks.start(-0.75);
+    let mut rendered: Vec<f32> = Vec::new();
+    let sizes: [usize; 4] = [64, 511, 1024, 37];
+    let mut i: usize = 0;
+    while rendered.len() < total {
+        let len: usize = sizes[i % sizes.len()].min(total - rendered.len());
+        rendered.extend(render(&mut blocks, len));
+        i += 1;
+    }
+
+    assert_eq!(rendered, expected);
+    assert_eq!(blocks.position(), total as u64);
+}
+
+#[test]
+fn rendered_click_starts_at_its_sample() {
+    let mut scheduler: ClickScheduler = scheduler_3_8();
+    scheduler.start(0.2);
+    let out: Vec<f32> = render(&mut scheduler, SAMPLE_RATE as usize);
+
+    let click_len: usize = (CLICK_SECONDS * SAMPLE_RATE as f32).round() as usize;
+    assert!(out[..14_400].iter().all(|s| *s == 0.0));
+    assert!(
+        out[14_400..14_400 + click_len]
+            .iter()
+            .any(|s| s.abs() > 0.1)
+    );
+    // Silencio hasta el siguiente pulso, en el segundo 1.0
+    assert!(out[14_400 + click_len..38_400].iter().all(|s| *s == 0.0));
+    assert!(
+        out[38_400..38_400 + click_len]
+            .iter()
+            .any(|s| s.abs() > 0.1)
+    );
+}
+
+#[test]
+fn volume_and_stop_silence_the_output() {
+    let mut scheduler: ClickScheduler = scheduler_3_8();
+    scheduler.start(0.0);
+    scheduler.set_volume(0.0);
+    assert!(render(&mut scheduler, 4_800).iter().all(|s| *s == 0.0));
+
+    scheduler.set_volume(0.5);
+    scheduler.start(0.0);
+    let half: f32 = render(&mut scheduler, 4_800)
+        .iter()
+        .fold(0.0, |max, s| max.max(s.abs()));
+    scheduler.set_volume(1.0);
+    scheduler.start(0.0);
+    let full: f32 = render(&mut scheduler, 4_800)
+        .iter()
+        .fold(0.0, |max, s| max.max(s.abs()));
+    assert!((full - 2.0 * half).abs() < 1e-5);
+
+    scheduler.stop();
+    assert!(render(&mut scheduler, 4_800).iter().all(|s| *s == 0.0));
+}
@@ -0,0 +1,63 @@
+use {
+    octarust::{
+        midi::input::parse_message,
+        models::{
+            input::{InputAction, TimedInput},
+            take::InputSource,
+        },
+    },
+    std::time::Instant,
+};
+
+fn action(bytes: &[u8]) -> Option<InputAction> {
+    parse_message(bytes, Instant::now()).map(|input| input.action)
+}
+
+#[test]
+fn note_on_keeps_pitch_velocity_and_arrival() {
+    let at: Instant = Instant::now();
+    let input: TimedInput = parse_message(&[0x90, 60, 100], at).unwrap();
+
+    assert_eq!(input.at, at);
+    assert_eq!(input.source, InputSource::Midi);
+    assert_eq!(
+        input.action,
+        InputAction::NoteOn {
+            pitch: 60,
+            velocity: 100
+        }
+    );
+}
+
+#[test]
+fn any_channel_is_read() {
+    assert_eq!(
+        action(&[0x9F, 72, 1]),
+        Some(InputAction::NoteOn {
+            pitch: 72,
+            velocity: 1
+        })
+    );
+    assert_eq!(
+        action(&[0x83, 72, 64]),
+        Some(InputAction::NoteOff { pitch: 72 })
+    );
+}
+
+#[test]
+fn note_on_with_zero_velocity_is_a_note_off() {
+    assert_eq!(
+        action(&[0x90, 64, 0]),
+        Some(InputAction::NoteOff { pitch: 64 })
+    );
+}
+
+#[test]
+fn other_messages_are_ignored() {
+    // Pedal, cambio de programa, reloj y mensajes cortados
+    assert_eq!(action(&[0xB0, 64, 127]), None);
+    assert_eq!(action(&[0xC0, 5]), None);
+    assert_eq!(action(&[0xF8]), None);
+    assert_eq!(action(&[0x90, 60]), None);
+    assert_eq!(action(&[]), None);
+}
@@ -0,0 +1,316 @@
+use {
+    octarust::{
+        midi::output::{
+            GUIDE_VELOCITY, MidiEvent, MidiMessage, MidiOutput, MidiScheduler, MidiSink,
+            output_events,
+        },
+        models::{
+            markings::{Markings, Pedal},
+            note::Note,
+            partiture::{Hand, Hands, Partiture},
+            playback::{FakeTime, PlaybackClock},
+            settings::{CustomSettings, MidiOutputSettings},
+        },
+    },
+    std::{
+        sync::{Arc, Mutex},
+        thread,
+        time::{Duration, Instant},
+    },
+};
+
+// Cuenta previa de las partituras de prueba
+const TIMER: f32 = 2.0;
+
+// Guarda los mensajes que salen, en orden; las copias comparten la lista
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<MidiMessage>>>);
+
+impl Capture {
+    fn take(&self) -> Vec<MidiMessage> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+impl MidiSink for Capture {
+    fn send(&mut self, message: MidiMessage) -> Result<(), String> {
+        self.0.lock().unwrap().push(message);
+        Ok(())
+    }
+}
+
+fn note(start: f32, pitch: u8, duration: f32) -> Note {
+    Note {
+        start,
+        ..Note::new(pitch, duration, false, Default::default())
+    }
+}
+
+fn partiture(hand: Hand, notes: Vec<Note>, markings: &Markings) -> Partiture {
+    Partiture::new(
+        hand,
+        notes,
+        markings.clone(),
+        4.0,
+        PlaybackClock::new(),
+        CustomSettings {
+            timer: TIMER,
+            ..CustomSettings::default()
+        },
+    )
+}
+
+// Mano derecha Do-Re y mano izquierda un Do grave que dura dos segundos
+fn hands(markings: &Markings) -> (Partiture, Partiture) {
+    (
+        partiture(Hand::Left, vec![note(0.0, 48, 2.0)], markings),
+        partiture(
+            Hand::Right,
+            vec![note(0.0, 60, 1.0), note(1.0, 62, 0.5)],
+            markings,
+        ),
+    )
+}
+
+fn events(settings: &MidiOutputSettings) -> Vec<MidiEvent> {
+    let (left, right) = hands(&Markings::default());
+    output_events((&left, &right), settings)
+}
+
+fn sound(hands: Hands) -> MidiOutputSettings {
+    MidiOutputSettings {
+        sound: true,
+        hands,
+        channel: 1,
+        guide: false,
+        ..MidiOutputSettings::default()
+    }
+}
+
+fn on(channel: u8, pitch: u8, velocity: u8) -> MidiMessage {
+    MidiMessage::NoteOn {
+        channel,
+        pitch,
+        velocity,
+    }
+}
+
+fn off(channel: u8, pitch: u8) -> MidiMessage {
+    MidiMessage::NoteOff { channel, pitch }
+}
+
+#[test]
+fn messages_follow_elapsed_after_the_count_in() {
+    let mut scheduler: MidiScheduler = MidiScheduler::new(events(&sound(Hands::Right)));
+    let mut capture: Capture = Capture::default();
+
+    // Durante la cuenta no sale nada
+    for elapsed in [0.0, 0.5, 1.0, 1.5, 1.99] {
+        scheduler.update(elapsed, &mut capture);
+    }
+    assert!(capture.take().is_empty());
+
+    // Sin dinámicas las notas suenan en mf
+    scheduler.update(TIMER, &mut capture);
+    assert_eq!(capture.take(), vec![on(0, 60, 80)]);
+
+    // La nota que acaba se apaga antes de que empiece la siguiente
+    scheduler.update(TIMER + 0.2, &mut capture);
+    scheduler.update(TIMER + 0.4, &mut capture);
+    scheduler.update(TIMER + 0.6, &mut capture);
+    scheduler.update(TIMER + 0.8, &mut capture);
+    scheduler.update(TIMER + 1.0, &mut capture);
+    assert_eq!(capture.take(), vec![off(0, 60), on(0, 62, 80)]);
+
+    scheduler.update(TIMER + 1.2, &mut capture);
+    scheduler.update(TIMER + 1.4, &mut capture);
+    scheduler.update(TIMER + 1.5, &mut capture);
+    assert_eq!(capture.take(), vec![off(0, 62)]);
+}
+
+#[test]
+fn only_the_chosen_hand_sounds() {
+    let pitches = |hands: Hands| -> Vec<u8> {
+        events(&sound(hands))
+            .iter()
+            .filter_map(|event| match event.message {
+                MidiMessage::NoteOn { pitch, .. } => Some(pitch),
+                _ => None,
+            })
+            .collect()
+    };
+
+    assert_eq!(pitches(Hands::Right), vec![60, 62]);
+    assert_eq!(pitches(Hands::Left), vec![48]);
+    assert_eq!(pitches(Hands::Together), vec![48, 60, 62]);
+}
+
+#[test]
+fn sound_uses_the_chosen_channel_and_note_velocity() {
+    let (left, mut right) = hands(&Markings::default());
+    right.notes[1].velocity = Some(100);
+    let settings: MidiOutputSettings = MidiOutputSettings {
+        channel: 10,
+        ..sound(Hands::Right)
+    };
+
+    let messages: Vec<MidiMessage> = output_events((&left, &right), &settings)
+        .into_iter()
+        .map(|event| event.message)
+        .collect();
+    assert_eq!(
+        messages,
+        vec![on(9, 60, 80), off(9, 60), on(9, 62, 100), off(9, 62)]
+    );
+}
+
+#[test]
+fn guide_lights_use_a_channel_per_hand() {
+    let settings: MidiOutputSettings = MidiOutputSettings {
+        sound: false,
+        guide: true,
+        guide_right: 4,
+        guide_left: 3,
+        ..MidiOutputSettings::default()
+    };
+    let mut scheduler: MidiScheduler = MidiScheduler::new(events(&settings));
+    let mut capture: Capture = Capture::default();
+
+    scheduler.update(TIMER, &mut capture);
+    let mut lit: Vec<MidiMessage> = capture.take();
+    lit.sort_by_key(|message| message.bytes());
+    assert_eq!(
+        lit,
+        vec![on(2, 48, GUIDE_VELOCITY), on(3, 60, GUIDE_VELOCITY)]
+    );
+    assert_eq!(on(3, 60, GUIDE_VELOCITY).bytes(), [0x93, 60, 1]);
+}
+
+#[test]
+fn guide_lights_and_sound_go_out_together() {
+    let settings: MidiOutputSettings = MidiOutputSettings {
+        guide: true,
+        ..sound(Hands::Left)
+    };
+    let mut scheduler: MidiScheduler = MidiScheduler::new(events(&settings));
+    let mut capture: Capture = Capture::default();
+
+    scheduler.update(TIMER, &mut capture);
+    let mut messages: Vec<MidiMessage> = capture.take();
+    messages.sort_by_key(|message| message.bytes());
+    // La izquierda suena por el canal 1 y se enciende por el 3; la derecha solo se enciende
+    assert_eq!(
+        messages,
+        vec![
+            on(0, 48, 80),
+            on(2, 48, GUIDE_VELOCITY),
+            on(3, 60, GUIDE_VELOCITY)
+        ]
+    );
+}
+
+#[test]
+fn a_jump_silences_and_skips_what_was_jumped_over() {
+    let mut scheduler: MidiScheduler = MidiScheduler::new(events(&sound(Hands::Together)));
+    let mut capture: Capture = Capture::default();
+
+    scheduler.update(TIMER, &mut capture);
+    assert_eq!(capture.take(), vec![on(0, 48, 80), on(0, 60, 80)]);
+
+    // Adelante más de lo que avanza el reloj entre lecturas: se apaga todo y no se toca el Re
+    scheduler.update(TIMER + 1.8, &mut capture);
+    let mut silenced: Vec<MidiMessage> = capture.take();
+    silenced.sort_by_key(|message| message.bytes());
+    assert_eq!(silenced, vec![off(0, 48), off(0, 60)]);
+
+    // Los note-off de lo ya apagado no vuelven a salir
+    scheduler.update(TIMER + 2.0, &mut capture);
+    assert!(capture.take().is_empty());
+
+    // Hacia atrás, justo al principio de una nota, la nota vuelve a sonar
+    scheduler.update(TIMER + 1.0, &mut capture);
+    assert_eq!(capture.take(), vec![on(0, 62, 80)]);
+}
+
+#[test]
+fn silence_turns_off_notes_and_pedal_once() {
+    let markings: Markings = Markings {
+        pedal: vec![Pedal {
+            start: 0.0,
+            end: 2.0,
+        }],
+        ..Markings::default()
+    };
+    let (left, right) = hands(&markings);
+    let mut scheduler: MidiScheduler =
+        MidiScheduler::new(output_events((&left, &right), &sound(Hands::Right)));
+    let mut capture: Capture = Capture::default();
+
+    scheduler.update(TIMER, &mut capture);
+    assert_eq!(
+        capture.take(),
+        vec![
+            on(0, 60, 80),
+            MidiMessage::Sustain {
+                channel: 0,
+                down: true
+            }
+        ]
+    );
+
+    // Pausa: el reloj no avanza y el piano se calla
+    scheduler.silence(&mut capture);
+    assert_eq!(
+        capture.take(),
+        vec![
+            off(0, 60),
+            MidiMessage::Sustain {
+                channel: 0,
+                down: false
+            }
+        ]
+    );
+    scheduler.update(TIMER, &mut capture);
+    assert!(capture.take().is_empty());
+
+    // Al seguir, solo sale lo nuevo
+    scheduler.update(TIMER + 0.2, &mut capture);
+    scheduler.update(TIMER + 0.4, &mut capture);
+    scheduler.update(TIMER + 0.6, &mut capture);
+    scheduler.update(TIMER + 0.8, &mut capture);
+    scheduler.update(TIMER + 1.0, &mut capture);
+    assert_eq!(capture.take(), vec![on(0, 62, 80)]);
+    assert_eq!(
+        MidiMessage::Sustain {
+            channel: 0,
+            down: true
+        }
+        .bytes(),
+        [0xB0, 64, 127]
+    );
+}
+
+#[test]
+fn output_follows_the_playback_clock() {
+    let time: FakeTime = FakeTime::new();
+    let clock: PlaybackClock = PlaybackClock::with_source(Arc::new(time.clone()));
+    let capture: Capture = Capture::default();
+    let output: MidiOutput = MidiOutput::with_sink(Box::new(capture.clone()));
+
+    output.play(events(&sound(Hands::Right)), &clock);
+    clock.play();
+    time.advance(Duration::from_secs_f32(TIMER));
+
+    // El hilo de la salida lee el reloj por su cuenta
+    let deadline: Instant = Instant::now() + Duration::from_secs(2);
+    let mut sent: Vec<MidiMessage> = Vec::new();
+    while sent.is_empty() && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(5));
+        sent = capture.take();
+    }
+    assert_eq!(sent, vec![on(0, 60, 80)]);
+
+    // Al parar se apaga lo que sonaba
+    output.stop();
+    assert_eq!(capture.take(), vec![off(0, 60)]);
+}
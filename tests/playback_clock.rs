@@ -0,0 +1,197 @@
+use {
+    octarust::models::{
+        markings::Markings,
+        partiture::{Hand, Partiture},
+        playback::{FakeTime, PlaybackClock},
+        settings::CustomSettings,
+    },
+    std::{sync::Arc, time::Duration},
+};
+
+// Reloj con tiempo falso y el tiempo para avanzarlo
+fn fake_clock() -> (PlaybackClock, FakeTime) {
+    let time: FakeTime = FakeTime::new();
+    (PlaybackClock::with_source(Arc::new(time.clone())), time)
+}
+
+fn advance(time: &FakeTime, seconds: f32) {
+    time.advance(Duration::from_secs_f32(seconds));
+}
+
+fn assert_near(actual: f32, expected: f32) {
+    assert!(
+        (actual - expected).abs() < 1e-4,
+        "esperado {}, obtenido {}",
+        expected,
+        actual
+    );
+}
+
+// Partitura sin notas de `time` segundos con `timer` segundos de cuenta previa
+fn partiture(clock: &PlaybackClock, time: f32, timer: f32) -> Partiture {
+    Partiture::new(
+        Hand::Right,
+        Vec::new(),
+        Markings::default(),
+        time,
+        clock.clone(),
+        CustomSettings {
+            timer,
+            ..CustomSettings::default()
+        },
+    )
+}
+
+#[test]
+fn stopped_clock_does_not_advance() {
+    let (clock, time) = fake_clock();
+    advance(&time, 5.0);
+    assert!(!clock.is_playing());
+    assert_near(clock.position(), 0.0);
+}
+
+#[test]
+fn playing_clock_follows_time_not_ticks() {
+    let (clock, time) = fake_clock();
+    clock.play();
+    advance(&time, 1.25);
+    assert_near(clock.position(), 1.25);
+
+    // Leer muchas veces no cambia la posición
+    for _ in 0..100 {
+        assert_near(clock.position(), 1.25);
+    }
+}
+
+#[test]
+fn pause_holds_position_and_resume_continues() {
+    let (clock, time) = fake_clock();
+    clock.play();
+    advance(&time, 2.0);
+    clock.pause();
+
+    // Una pausa larga no cuenta
+    advance(&time, 30.0);
+    assert_near(clock.position(), 2.0);
+
+    clock.play();
+    advance(&time, 0.5);
+    assert_near(clock.position(), 2.5);
+}
+
+#[test]
+fn play_and_pause_twice_are_harmless() {
+    let (clock, time) = fake_clock();
+    clock.play();
+    advance(&time, 1.0);
+    clock.play();
+    advance(&time, 1.0);
+    clock.pause();
+    clock.pause();
+    assert_near(clock.position(), 2.0);
+}
+
+#[test]
+fn seek_keeps_playing_or_paused_state() {
+    let (clock, time) = fake_clock();
+    clock.play();
+    advance(&time, 3.0);
+    clock.seek(10.0);
+    advance(&time, 1.0);
+    assert_near(clock.position(), 11.0);
+
+    clock.pause();
+    clock.seek(4.0);
+    advance(&time, 1.0);
+    assert!(!clock.is_playing());
+    assert_near(clock.position(), 4.0);
+}
+
+#[test]
+fn rate_scales_time_from_the_current_position() {
+    let (clock, time) = fake_clock();
+    clock.play();
+    advance(&time, 2.0);
+    clock.set_rate(0.5);
+    assert_near(clock.position(), 2.0);
+
+    advance(&time, 2.0);
+    assert_near(clock.position(), 3.0);
+
+    // Cambiar la velocidad parado se aplica al seguir
+    clock.pause();
+    clock.set_rate(2.0);
+    advance(&time, 1.0);
+    clock.play();
+    advance(&time, 1.0);
+    assert_near(clock.position(), 5.0);
+}
+
+#[test]
+fn position_at_places_earlier_inputs() {
+    let (clock, time) = fake_clock();
+    clock.play();
+    advance(&time, 1.0);
+    let captured = clock.now();
+    advance(&time, 0.2);
+
+    // Una nota capturada antes del tick se sitúa donde se tocó
+    assert_near(clock.position_at(captured), 1.0);
+    assert_near(clock.position(), 1.2);
+}
+
+#[test]
+fn clones_share_the_same_clock() {
+    let (clock, time) = fake_clock();
+    let left: Partiture = partiture(&clock, 10.0, 3.0);
+    let right: Partiture = partiture(&clock, 10.0, 3.0);
+    clock.play();
+    advance(&time, 4.0);
+    assert_near(left.elapsed(), 4.0);
+    assert_near(right.elapsed(), 4.0);
+
+    clock.pause();
+    advance(&time, 1.0);
+    assert_near(left.elapsed(), right.elapsed());
+}
+
+#[test]
+fn song_ends_after_time_plus_two_count_ins() {
+    let (clock, time) = fake_clock();
+    let piece: Partiture = partiture(&clock, 10.0, 3.0);
+    clock.play();
+
+    advance(&time, 15.9);
+    assert!(!piece.has_ended());
+    advance(&time, 0.2);
+    assert!(piece.has_ended());
+}
+
+#[test]
+fn pause_delays_the_end_of_the_song() {
+    let (clock, time) = fake_clock();
+    let piece: Partiture = partiture(&clock, 10.0, 3.0);
+    clock.play();
+    advance(&time, 8.0);
+    clock.pause();
+    advance(&time, 60.0);
+    assert!(!piece.has_ended());
+
+    clock.play();
+    advance(&time, 7.9);
+    assert!(!piece.has_ended());
+    advance(&time, 0.2);
+    assert!(piece.has_ended());
+}
+
+#[test]
+fn seeking_back_undoes_the_end() {
+    let (clock, time) = fake_clock();
+    let piece: Partiture = partiture(&clock, 10.0, 3.0);
+    clock.play();
+    advance(&time, 20.0);
+    assert!(piece.has_ended());
+
+    clock.seek(5.0);
+    assert!(!piece.has_ended());
+}
@@ -0,0 +1,108 @@
+use octarust::utils::quantize::{MAX_SWING, QuantizeGrid, Quantizer};
+
+// Negra de un segundo: una corchea dura medio segundo
+const QUARTER: f32 = 1.0;
+
+fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() < 1e-4
+}
+
+fn assert_onsets(quantizer: &Quantizer, cases: &[(f32, f32)]) {
+    for &(played, written) in cases {
+        let onset: f32 = quantizer.onset(played);
+        assert!(
+            close(onset, written),
+            "{} se escribe en {} y no en {}",
+            played,
+            onset,
+            written
+        );
+    }
+}
+
+#[test]
+fn straight_eighths_snap_to_the_nearest_half_beat() {
+    let quantizer: Quantizer = Quantizer::new(QUARTER, QuantizeGrid::Eighth, false, 50);
+    assert!(close(quantizer.step(), 0.5));
+    assert_onsets(
+        &quantizer,
+        &[
+            (0.0, 0.0),
+            (0.2, 0.0),
+            (0.3, 0.5),
+            (0.55, 0.5),
+            (0.8, 1.0),
+            (1.74, 1.5),
+            (3.9, 4.0),
+        ],
+    );
+}
+
+#[test]
+fn swung_eighths_are_written_straight() {
+    // Con un 66 % la segunda corchea del par se toca a dos tercios de la negra
+    let quantizer: Quantizer = Quantizer::new(QUARTER, QuantizeGrid::Eighth, false, 66);
+    assert_onsets(
+        &quantizer,
+        &[
+            (0.0, 0.0),
+            (0.66, 0.5),
+            (1.0, 1.0),
+            (1.667, 1.5),
+            (2.62, 2.5),
+        ],
+    );
+    // La frontera está a medio camino entre el pulso y la corchea con swing, en 0.33
+    assert_onsets(&quantizer, &[(0.32, 0.0), (0.34, 0.5)]);
+}
+
+#[test]
+fn swing_is_clamped_and_ignored_with_triplets() {
+    let swung: Quantizer = Quantizer::new(QUARTER, QuantizeGrid::Eighth, false, 100);
+    let max: Quantizer = Quantizer::new(QUARTER, QuantizeGrid::Eighth, false, MAX_SWING);
+    assert_eq!(swung, max);
+
+    let triplets: Quantizer = Quantizer::new(QUARTER, QuantizeGrid::Eighth, true, 66);
+    let straight: Quantizer = Quantizer::new(QUARTER, QuantizeGrid::Eighth, true, 50);
+    assert_eq!(triplets, straight);
+}
+
+#[test]
+fn eighth_triplets_divide_the_beat_in_three() {
+    let quantizer: Quantizer = Quantizer::new(QUARTER, QuantizeGrid::Eighth, true, 50);
+    assert!(close(quantizer.step(), QUARTER / 3.0));
+    assert_onsets(
+        &quantizer,
+        &[
+            (0.0, 0.0),
+            (0.3, 1.0 / 3.0),
+            (0.7, 2.0 / 3.0),
+            (0.98, 1.0),
+            (1.36, 4.0 / 3.0),
+        ],
+    );
+}
+
+#[test]
+fn notes_played_before_the_start_go_to_zero() {
+    let quantizer: Quantizer = Quantizer::new(QUARTER, QuantizeGrid::Sixteenth, false, 50);
+    assert_onsets(&quantizer, &[(-0.3, 0.0), (-5.0, 0.0)]);
+    assert!(close(quantizer.duration(-0.5, 0.5), 0.5));
+}
+
+#[test]
+fn durations_snap_both_ends() {
+    let quantizer: Quantizer = Quantizer::new(QUARTER, QuantizeGrid::Eighth, false, 50);
+    // De 0.1 a 0.9: de 0 a 1, una negra
+    assert!(close(quantizer.duration(0.1, 0.9), 1.0));
+    // De 0.45 a 2.1: de 0.5 a 2, negra con puntillo
+    assert!(close(quantizer.duration(0.45, 2.1), 1.5));
+}
+
+#[test]
+fn durations_are_at_least_one_step() {
+    let quantizer: Quantizer = Quantizer::new(QUARTER, QuantizeGrid::Sixteenth, false, 50);
+    // Un golpe seco que empieza y acaba en la misma semicorchea dura una semicorchea
+    assert!(close(quantizer.duration(1.0, 1.05), 0.25));
+    assert!(close(quantizer.duration(2.0, 2.0), 0.25));
+}
@@ -0,0 +1,201 @@
+use {
+    octarust::{
+        models::{
+            score::ScoreFile,
+            score_error::{Issue, ScoreError, Severity},
+        },
+        utils::helper_json::{
+            LoadedScore, ScoreFormat, detect_format, load_any_score_file, load_library,
+            merge_legacy,
+        },
+    },
+    serde_json::Value,
+    std::{
+        env, fs,
+        path::{Path, PathBuf},
+        process::{self, Command, Output},
+    },
+};
+
+fn asset(name: &str) -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("assets")
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+// Archivo temporal propio de cada prueba
+fn temp_file(name: &str, json: &str) -> String {
+    let path: PathBuf = env::temp_dir().join(format!("octarust-{}-{}.json", process::id(), name));
+    fs::write(&path, json).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+// `partitures.json` con un cambio en los metadatos de la primera pieza
+fn broken_library(name: &str, edit: impl FnOnce(&mut serde_json::Map<String, Value>)) -> String {
+    let mut json: Value =
+        serde_json::from_str(&fs::read_to_string(asset("partitures.json")).unwrap()).unwrap();
+    let piece: &mut Value = json[0]
+        .as_object_mut()
+        .unwrap()
+        .values_mut()
+        .next()
+        .unwrap();
+    edit(piece["metadata"].as_object_mut().unwrap());
+    temp_file(name, &json.to_string())
+}
+
+fn parse_error(path: &str) -> String {
+    match load_any_score_file(path) {
+        Err(e @ ScoreError::Parse { .. }) => e.to_string(),
+        Err(e) => panic!("se esperaba un error de lectura y no {}", e),
+        Ok(loaded) => panic!("se ha leído como {:?}", loaded.format),
+    }
+}
+
+// Pieza serializada, para comparar de dónde viene
+fn piece_json(score: &ScoreFile, name: &str) -> Value {
+    serde_json::to_value(score.piece(name).unwrap()).unwrap()
+}
+
+fn cli(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_octarust-cli"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn formats_are_told_apart_by_their_keys() {
+    assert_eq!(
+        detect_format(r#"[{"a": {"metadata": {}, "sections": {}}}]"#),
+        ScoreFormat::Current
+    );
+    assert_eq!(
+        detect_format(r#"[{"a": {"right": [], "left": []}}]"#),
+        ScoreFormat::Legacy
+    );
+    // Lo que no es ninguno de los dos se lee como el actual
+    assert_eq!(detect_format(r#"[{"a": {}}]"#), ScoreFormat::Current);
+    assert_eq!(detect_format("no es json"), ScoreFormat::Current);
+}
+
+#[test]
+fn both_bundled_files_load_in_their_format() {
+    let current: LoadedScore = load_any_score_file(&asset("partitures.json")).unwrap();
+    assert_eq!(current.format, ScoreFormat::Current);
+    assert!(current.score.pieces().count() > 0);
+    assert!(current.warnings.is_empty());
+
+    let legacy: LoadedScore = load_any_score_file(&asset("notes.json")).unwrap();
+    assert_eq!(legacy.format, ScoreFormat::Legacy);
+}
+
+#[test]
+fn legacy_pieces_warn_that_meter_and_tempo_were_assumed() {
+    let path: String = temp_file(
+        "assumed",
+        r#"[{"a": {"right": [], "left": []}}, {"b": {"right": []}}]"#,
+    );
+    let loaded: LoadedScore = load_any_score_file(&path).unwrap();
+
+    let pieces: Vec<Option<&str>> = loaded
+        .warnings
+        .iter()
+        .filter(|d| d.issue == Issue::AssumedTiming)
+        .map(|d| d.location.piece.as_deref())
+        .collect();
+    assert_eq!(pieces, vec![Some("a"), Some("b")]);
+    assert!(
+        loaded
+            .warnings
+            .iter()
+            .all(|d| d.severity() == Severity::Warning)
+    );
+
+    // validate los muestra como avisos: no falla salvo con --strict
+    let output: Output = cli(&["validate", &path]);
+    assert_eq!(output.status.code(), Some(0));
+    let stdout: String = String::from_utf8_lossy(&output.stdout).into_owned();
+    assert!(stdout.contains("4/4"), "{}", stdout);
+    assert!(stdout.contains("2 aviso(s)"), "{}", stdout);
+    assert_eq!(cli(&["validate", "--strict", &path]).status.code(), Some(1));
+}
+
+#[test]
+fn the_library_wins_over_legacy_pieces_with_the_same_name() {
+    let library: String = fs::read_to_string(asset("partitures.json")).unwrap();
+    let mut score: ScoreFile = serde_json::from_str(&library).unwrap();
+    let legacy: LoadedScore = load_any_score_file(&asset("notes.json")).unwrap();
+    let before: ScoreFile = score.clone();
+    merge_legacy(&mut score, &legacy.score);
+
+    // for-elise está en los dos archivos y se queda la de partitures.json
+    assert_eq!(
+        piece_json(&score, "for-elise"),
+        piece_json(&before, "for-elise")
+    );
+    assert_ne!(
+        piece_json(&score, "for-elise"),
+        piece_json(&legacy.score, "for-elise")
+    );
+    // Las que solo están en el antiguo se añaden una vez
+    assert_eq!(
+        piece_json(&score, "where-is-my-mind"),
+        piece_json(&legacy.score, "where-is-my-mind")
+    );
+    assert_eq!(
+        score
+            .pieces()
+            .filter(|(name, _)| name.as_str() == "for-elise")
+            .count(),
+        1
+    );
+
+    // La biblioteca que usa la aplicación hace la misma mezcla
+    let loaded: ScoreFile = load_library().unwrap();
+    assert_eq!(
+        piece_json(&loaded, "for-elise"),
+        piece_json(&before, "for-elise")
+    );
+    assert!(loaded.piece("where-is-my-mind").is_ok());
+}
+
+#[test]
+fn a_broken_current_file_is_not_read_as_legacy() {
+    let meter: String = broken_library("meter", |metadata| {
+        metadata.insert("meter".to_string(), Value::from("3/7"));
+    });
+    let error: String = parse_error(&meter);
+    assert!(error.contains("3/7"), "{}", error);
+
+    let title: String = broken_library("title", |metadata| {
+        metadata.remove("title");
+    });
+    let error: String = parse_error(&title);
+    assert!(error.contains("title"), "{}", error);
+}
+
+#[test]
+fn unknown_keys_in_a_legacy_piece_are_errors() {
+    let path: String = temp_file("legacy", r#"[{"a": {"right": [], "rigth": []}}]"#);
+    let error: String = parse_error(&path);
+    assert!(error.contains("rigth"), "{}", error);
+}
+
+#[test]
+fn cli_validate_fails_with_the_parse_error() {
+    let path: String = broken_library("cli", |metadata| {
+        metadata.remove("title");
+    });
+    let output: Output = cli(&["validate", &path]);
+
+    // Código 3: el archivo no se puede interpretar
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("title"));
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("ok:"));
+
+    let output: Output = cli(&["info", &path]);
+    assert_eq!(output.status.code(), Some(3));
+}
@@ -0,0 +1,278 @@
+use {
+    octarust::{
+        models::{
+            note::Note,
+            partiture::Partiture,
+            playback::PlaybackClock,
+            score::{Meter, Piece},
+            settings::CustomSettings,
+        },
+        utils::{
+            sight_reading::{
+                Key, NoteValue, SeededRng, SightReadingConfig, generate_partitures, generate_piece,
+            },
+            validation::{has_errors, validate_piece},
+        },
+    },
+    std::collections::BTreeMap,
+};
+
+// Ejercicios generados por cada propiedad
+const CASES: u64 = 300;
+
+const METERS: [(u8, u8); 6] = [(2, 4), (3, 4), (4, 4), (3, 8), (6, 8), (12, 8)];
+
+const EPSILON: f32 = 1e-3;
+
+// Parámetros al azar pero válidos: registros de al menos una octava y siempre algún salto
+fn random_config(case: u64) -> SightReadingConfig {
+    let mut rng: SeededRng = SeededRng::new(case.wrapping_mul(7919));
+    let (beats, beat_unit) = METERS[rng.below(METERS.len())];
+
+    let mut note_values: Vec<NoteValue> = NoteValue::ALL
+        .iter()
+        .copied()
+        .filter(|_| rng.chance(0.5))
+        .collect();
+    // Alguna figura de un pulso o menos para que ningún compás quede vacío
+    if !note_values.iter().any(|value| value.seconds() <= 0.5) {
+        note_values.push(NoteValue::Eighth);
+    }
+    let mut intervals: Vec<u8> = (0..=4).filter(|_| rng.chance(0.5)).collect();
+    if !intervals.iter().any(|&step| step > 0) {
+        intervals.push(1);
+    }
+
+    let right_low: u8 = 55 + rng.below(13) as u8;
+    let left_low: u8 = 36 + rng.below(13) as u8;
+    SightReadingConfig {
+        seed: rng.next_u64(),
+        key: Key::new(rng.below(12) as u8, rng.chance(0.5)),
+        meter: Meter { beats, beat_unit },
+        bars: 1 + rng.below(16) as u32,
+        right_range: (right_low, right_low + 12 + rng.below(13) as u8),
+        left_range: (left_low, left_low + 12 + rng.below(8) as u8),
+        note_values,
+        intervals,
+        chords: rng.chance(0.5),
+        accidentals: rng.chance(0.5),
+    }
+}
+
+fn hands(piece: &Piece) -> (&[Note], &[Note]) {
+    let section = &piece.sections["A"];
+    (&section.right, &section.left)
+}
+
+// Nota más aguda de cada inicio: la melodía, con las terceras y sextas por debajo
+fn melody(notes: &[Note]) -> Vec<&Note> {
+    let mut by_start: BTreeMap<i64, &Note> = BTreeMap::new();
+    for note in notes {
+        let key: i64 = (note.start * 1000.0).round() as i64;
+        let entry = by_start.entry(key).or_insert(note);
+        if note.pitch > entry.pitch {
+            *entry = note;
+        }
+    }
+    by_start.into_values().collect()
+}
+
+fn fingerprint(piece: &Piece) -> Vec<(u8, f32, f32)> {
+    let (right, left) = hands(piece);
+    right
+        .iter()
+        .chain(left)
+        .map(|note| (note.pitch, note.start, note.duration))
+        .collect()
+}
+
+#[test]
+fn same_seed_gives_the_same_exercise() {
+    for case in 0..CASES {
+        let config: SightReadingConfig = random_config(case);
+        assert_eq!(
+            fingerprint(&generate_piece(&config)),
+            fingerprint(&generate_piece(&config)),
+            "{:?}",
+            config
+        );
+    }
+}
+
+#[test]
+fn different_seeds_give_different_exercises() {
+    let base: SightReadingConfig = SightReadingConfig::level(2, 0);
+    let first: Vec<(u8, f32, f32)> = fingerprint(&generate_piece(&base));
+    let different: usize = (1..20)
+        .filter(|&seed| {
+            let config: SightReadingConfig = SightReadingConfig {
+                seed,
+                ..base.clone()
+            };
+            fingerprint(&generate_piece(&config)) != first
+        })
+        .count();
+    assert!(
+        different >= 18,
+        "solo {} de 19 semillas cambian el ejercicio",
+        different
+    );
+}
+
+#[test]
+fn notes_stay_in_each_hand_range() {
+    for case in 0..CASES {
+        let config: SightReadingConfig = random_config(case);
+        let piece: Piece = generate_piece(&config);
+        let (right, left) = hands(&piece);
+        let (right_low, right_high) = config.right_range;
+        let (left_low, left_high) = config.left_range;
+        assert!(
+            right
+                .iter()
+                .all(|n| (right_low..=right_high).contains(&n.pitch))
+        );
+        assert!(
+            left.iter()
+                .all(|n| (left_low..=left_high).contains(&n.pitch))
+        );
+        assert!(!right.is_empty(), "{:?}", config);
+    }
+}
+
+#[test]
+fn durations_are_allowed_and_no_note_crosses_a_barline() {
+    for case in 0..CASES {
+        let config: SightReadingConfig = random_config(case);
+        let piece: Piece = generate_piece(&config);
+        let (right, left) = hands(&piece);
+        let bar: f32 = config.bar_seconds();
+        let allowed: Vec<f32> = config.note_values.iter().map(NoteValue::seconds).collect();
+
+        for note in right.iter().chain(left) {
+            assert!(
+                allowed.iter().any(|d| (d - note.duration).abs() < EPSILON),
+                "duración {} no permitida en {:?}",
+                note.duration,
+                config
+            );
+            let bar_index: f32 = (note.start / bar + EPSILON).floor();
+            assert!(note.start + note.duration <= (bar_index + 1.0) * bar + EPSILON);
+            assert!(note.start + note.duration <= config.bars as f32 * bar + EPSILON);
+        }
+    }
+}
+
+#[test]
+fn melody_notes_do_not_overlap() {
+    for case in 0..CASES {
+        let config: SightReadingConfig = random_config(case);
+        let piece: Piece = generate_piece(&config);
+        let line: Vec<&Note> = melody(hands(&piece).0);
+        for pair in line.windows(2) {
+            assert!(pair[0].start + pair[0].duration <= pair[1].start + EPSILON);
+        }
+    }
+}
+
+#[test]
+fn without_accidentals_every_pitch_is_in_the_key() {
+    for case in 0..CASES {
+        let config: SightReadingConfig = SightReadingConfig {
+            accidentals: false,
+            ..random_config(case)
+        };
+        let piece: Piece = generate_piece(&config);
+        let (right, left) = hands(&piece);
+        assert!(
+            right
+                .iter()
+                .chain(left)
+                .all(|n| config.key.contains(n.pitch)),
+            "{:?}",
+            config
+        );
+    }
+}
+
+#[test]
+fn accidentals_resolve_by_semitone_to_the_key() {
+    for case in 0..CASES {
+        let config: SightReadingConfig = SightReadingConfig {
+            accidentals: true,
+            ..random_config(case)
+        };
+        let piece: Piece = generate_piece(&config);
+        let (right, left) = hands(&piece);
+        assert!(left.iter().all(|n| config.key.contains(n.pitch)));
+
+        let line: Vec<&Note> = melody(right);
+        for (i, note) in line.iter().enumerate() {
+            if config.key.contains(note.pitch) {
+                continue;
+            }
+            let next: &Note = line.get(i + 1).expect("una nota cromática al final");
+            assert!(config.key.contains(next.pitch));
+            assert_eq!(note.pitch.abs_diff(next.pitch), 1, "{:?}", config);
+        }
+    }
+}
+
+#[test]
+fn melody_moves_by_allowed_steps_and_ends_on_the_tonic() {
+    for case in 0..CASES {
+        let config: SightReadingConfig = random_config(case);
+        let piece: Piece = generate_piece(&config);
+        let scale: Vec<u8> = config.key.pitches_in(0, 127);
+        let degrees: Vec<usize> = melody(hands(&piece).0)
+            .iter()
+            .filter_map(|note| scale.iter().position(|&p| p == note.pitch))
+            .collect();
+
+        let (last, steps) = degrees.split_last().expect("melodía vacía");
+        assert_eq!(config.key.degree_of(scale[*last]), Some(0), "{:?}", config);
+        for pair in steps.windows(2) {
+            let step: u8 = pair[0].abs_diff(pair[1]) as u8;
+            assert!(
+                config.intervals.contains(&step),
+                "salto de {} grados con {:?}",
+                step,
+                config
+            );
+        }
+    }
+}
+
+#[test]
+fn generated_pieces_pass_validation_and_load_into_the_game() {
+    for case in 0..CASES / 10 {
+        let config: SightReadingConfig = random_config(case);
+        let piece: Piece = generate_piece(&config);
+        assert!(!has_errors(&validate_piece("sight-reading", &piece)));
+
+        let (left, right): (Partiture, Partiture) =
+            generate_partitures(&config, &CustomSettings::default(), &PlaybackClock::new())
+                .expect("el ejercicio no se pudo cargar");
+        assert_eq!(right.notes.len(), hands(&piece).0.len());
+        assert_eq!(left.notes.len(), hands(&piece).1.len());
+        assert!(right.time <= config.bars as f32 * config.bar_seconds() + EPSILON);
+        let metadata = right.metadata.as_ref().expect("sin metadatos");
+        assert_eq!(
+            metadata.time_signature,
+            (config.meter.beats, config.meter.beat_unit)
+        );
+    }
+}
+
+#[test]
+fn key_names_round_trip() {
+    for tonic in 0..12 {
+        for minor in [false, true] {
+            let key: Key = Key::new(tonic, minor);
+            assert_eq!(Key::parse(&key.to_string()), Some(key));
+        }
+    }
+    assert_eq!(Key::parse("Bb major"), Some(Key::new(10, false)));
+    assert_eq!(Key::parse("A minor"), Some(Key::new(9, true)));
+    assert_eq!(Key::parse("H dorian"), None);
+}
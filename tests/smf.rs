@@ -0,0 +1,210 @@
+use octarust::{
+    midi::smf::{MidiNote, MidiTrack, TICKS_PER_QUARTER, take_to_smf, write_smf},
+    models::{
+        partiture::PieceMetadata,
+        take::{InputSource, PlayedNote, Take},
+    },
+};
+
+// 4/4 con la negra de medio segundo: 500000 microsegundos por negra y 960 ticks por segundo
+fn metadata() -> PieceMetadata {
+    PieceMetadata {
+        time_signature: (4, 4),
+        base_note_value: 0.5,
+        sections: Vec::new(),
+    }
+}
+
+fn midi_note(start: f32, duration: f32, pitch: u8, velocity: u8) -> MidiNote {
+    MidiNote {
+        start,
+        duration,
+        pitch,
+        velocity,
+    }
+}
+
+fn track(notes: Vec<MidiNote>) -> MidiTrack {
+    MidiTrack {
+        name: "Right hand".to_string(),
+        channel: 0,
+        notes,
+        pedal: Vec::new(),
+    }
+}
+
+fn read_vlq(bytes: &[u8], at: &mut usize) -> u32 {
+    let mut value: u32 = 0;
+    loop {
+        let byte: u8 = bytes[*at];
+        *at += 1;
+        value = (value << 7) | (byte & 0x7F) as u32;
+        if byte & 0x80 == 0 {
+            return value;
+        }
+    }
+}
+
+// Eventos de cada pista con su tick absoluto; el escritor no usa running status
+fn events(bytes: &[u8]) -> Vec<Vec<(u32, Vec<u8>)>> {
+    let mut tracks: Vec<Vec<(u32, Vec<u8>)>> = Vec::new();
+    let mut at: usize = 14;
+    while at < bytes.len() {
+        assert_eq!(&bytes[at..at + 4], b"MTrk");
+        let len: usize = u32::from_be_bytes(bytes[at + 4..at + 8].try_into().unwrap()) as usize;
+        let body: &[u8] = &bytes[at + 8..at + 8 + len];
+        at += 8 + len;
+
+        let mut track: Vec<(u32, Vec<u8>)> = Vec::new();
+        let mut tick: u32 = 0;
+        let mut i: usize = 0;
+        while i < body.len() {
+            tick += read_vlq(body, &mut i);
+            let start: usize = i;
+            if body[i] == 0xFF {
+                i += 2;
+                let data: usize = read_vlq(body, &mut i) as usize;
+                i += data;
+            } else {
+                i += 3;
+            }
+            track.push((tick, body[start..i].to_vec()));
+        }
+        tracks.push(track);
+    }
+    tracks
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle)
+}
+
+#[test]
+fn header_is_format_1_with_one_track_per_hand() {
+    let bytes: Vec<u8> = write_smf(&[track(Vec::new()), track(Vec::new())], &metadata());
+
+    assert_eq!(&bytes[0..4], b"MThd");
+    assert_eq!(&bytes[4..8], &6u32.to_be_bytes());
+    assert_eq!(&bytes[8..10], &1u16.to_be_bytes());
+    assert_eq!(&bytes[10..12], &2u16.to_be_bytes());
+    assert_eq!(&bytes[12..14], &TICKS_PER_QUARTER.to_be_bytes());
+    assert_eq!(events(&bytes).len(), 2);
+}
+
+#[test]
+fn tempo_and_time_signature_go_in_the_first_track() {
+    let metadata: PieceMetadata = PieceMetadata {
+        time_signature: (3, 8),
+        base_note_value: 0.25,
+        sections: Vec::new(),
+    };
+    let tracks: Vec<Vec<(u32, Vec<u8>)>> = events(&write_smf(
+        &[track(Vec::new()), track(Vec::new())],
+        &metadata,
+    ));
+
+    // Corchea de 0.25 s: negra de 0.5 s, 500000 = 0x07A120 microsegundos
+    let first: Vec<Vec<u8>> = tracks[0].iter().map(|(_, e)| e.clone()).collect();
+    assert!(first.contains(&vec![0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20]));
+    // 3/8: el denominador como potencia de dos
+    assert!(first.contains(&vec![0xFF, 0x58, 0x04, 3, 3, 24, 8]));
+    assert!(
+        !tracks[1]
+            .iter()
+            .any(|(_, e)| e[..2] == [0xFF, 0x51] || e[..2] == [0xFF, 0x58])
+    );
+    // Todas las pistas acaban con el fin de pista
+    for track in &tracks {
+        assert_eq!(track.last().unwrap().1, vec![0xFF, 0x2F, 0x00]);
+    }
+}
+
+#[test]
+fn delta_times_are_variable_length_quantities() {
+    // Un segundo son 960 ticks (0x3C0) y veinte segundos 19200 (0x4B00)
+    let bytes: Vec<u8> = write_smf(
+        &[track(vec![
+            midi_note(1.0, 0.5, 60, 80),
+            midi_note(20.0, 0.5, 62, 80),
+        ])],
+        &metadata(),
+    );
+
+    assert!(contains(&bytes, &[0x87, 0x40, 0x90, 60, 80]));
+    // Del note-off de la primera (1.5 s, tick 1440) al note-on de la segunda: 17760 = 0x4560
+    assert!(contains(&bytes, &[0x81, 0x8A, 0x60, 0x90, 62, 80]));
+
+    let ticks: Vec<(u32, Vec<u8>)> = events(&bytes).remove(0);
+    assert!(ticks.contains(&(960, vec![0x90, 60, 80])));
+    assert!(ticks.contains(&(19200, vec![0x90, 62, 80])));
+}
+
+#[test]
+fn note_off_goes_before_note_on_on_the_same_tick() {
+    // La misma tecla dos veces seguidas: el note-off de la primera no puede cortar la segunda
+    let tracks: Vec<Vec<(u32, Vec<u8>)>> = events(&write_smf(
+        &[track(vec![
+            midi_note(0.0, 0.5, 60, 80),
+            midi_note(0.5, 0.5, 60, 90),
+        ])],
+        &metadata(),
+    ));
+
+    let notes: Vec<(u32, Vec<u8>)> = tracks[0]
+        .iter()
+        .filter(|(_, e)| e[0] & 0xF0 == 0x80 || e[0] & 0xF0 == 0x90)
+        .cloned()
+        .collect();
+    assert_eq!(
+        notes,
+        vec![
+            (0, vec![0x90, 60, 80]),
+            (480, vec![0x80, 60, 0]),
+            (480, vec![0x90, 60, 90]),
+            (960, vec![0x80, 60, 0]),
+        ]
+    );
+}
+
+#[test]
+fn takes_keep_the_recorded_velocities() {
+    let take: Take = Take {
+        piece: "prueba".to_string(),
+        recorded_at: 0,
+        notes: vec![
+            PlayedNote {
+                pitch: 60,
+                on: 0.0,
+                off: 0.5,
+                velocity: 40,
+                source: InputSource::Midi,
+            },
+            PlayedNote {
+                pitch: 64,
+                on: 0.5,
+                off: 1.0,
+                velocity: 110,
+                source: InputSource::Keyboard,
+            },
+        ],
+    };
+
+    let tracks: Vec<Vec<(u32, Vec<u8>)>> = events(&take_to_smf(&take, &metadata()));
+    assert_eq!(tracks.len(), 1);
+    let notes: Vec<(u32, Vec<u8>)> = tracks[0]
+        .iter()
+        .filter(|(_, e)| e[0] != 0xFF)
+        .cloned()
+        .collect();
+    assert_eq!(
+        notes,
+        vec![
+            (0, vec![0x90, 60, 40]),
+            (480, vec![0x80, 60, 0]),
+            (480, vec![0x90, 64, 110]),
+            (960, vec![0x80, 64, 0]),
+        ]
+    );
+}